@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+
+//! Git credential helper backed by the password database.
+//!
+//! Enable it with `git config --global credential.helper mithril`. When the
+//! app is running with the IPC socket enabled, logins are looked up there;
+//! the helper registers on first use and must be approved in the app once.
+//! Otherwise the database named by `MITHRIL_DB` is opened directly, with the
+//! keyfile in `MITHRIL_KEYFILE` if set and the password asked for on the
+//! terminal.
+
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+
+use mithril_vault_lib::cli::DATABASE_ENV;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::git_credential::{self, CredentialStore};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::unlock_throttle::UnlockThrottleService;
+use mithril_vault_lib::utils::terminal::prompt_secret;
+
+const KEYFILE_ENV: &str = "MITHRIL_KEYFILE";
+
+fn main() -> ExitCode {
+    let operation = std::env::args().nth(1).unwrap_or_default();
+    let mut stdin = io::stdin().lock();
+
+    // `erase` and operations added to the protocol later need no database
+    if operation != "get" && operation != "store" {
+        let _ = io::copy(&mut stdin, &mut io::sink());
+        return ExitCode::SUCCESS;
+    }
+
+    match run(&operation, &mut stdin) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("git-credential-mithril: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(operation: &str, input: &mut impl BufRead) -> Result<(), AppError> {
+    let mut store = open_store()?;
+    git_credential::run(operation, input, &mut io::stdout().lock(), store.as_mut())
+}
+
+fn open_store() -> Result<Box<dyn CredentialStore>, AppError> {
+    #[cfg(unix)]
+    if let Some(token_file) = git_credential::token_path() {
+        let socket = mithril_vault_lib::services::ipc::socket_path();
+        if let Some(connection) = git_credential::connect_app(&socket, &token_file)? {
+            return Ok(Box::new(connection));
+        }
+    }
+
+    let path = std::env::var(DATABASE_ENV).map_err(|_| {
+        AppError::InvalidArguments(format!(
+            "The app is not reachable over IPC and {DATABASE_ENV} is not set"
+        ))
+    })?;
+    let keyfile = std::env::var(KEYFILE_ENV).ok();
+    let password = prompt_secret(&format!("Password for {path}"))?;
+
+    let kdbx = KdbxService::new().with_unlock_throttle(UnlockThrottleService::new_in_app_data()?);
+    match keyfile.as_deref() {
+        Some(keyfile) => kdbx.open_with_keyfile(&path, password.as_str(), keyfile)?,
+        None => kdbx.open(&path, password.as_str())?,
+    };
+    Ok(Box::new(kdbx))
+}
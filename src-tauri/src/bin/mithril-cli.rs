@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+
+//! Headless command-line client for the password database.
+//!
+//! Secrets are read from the terminal without echo, or one per line from
+//! stdin when it is not a terminal, so scripts can pipe them in. Errors go
+//! to stderr; the exit code is 2 for usage errors and 1 for anything else.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+
+use mithril_vault_lib::cli::{self, Prompt};
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::unlock_throttle::UnlockThrottleService;
+use mithril_vault_lib::utils::terminal::prompt_secret;
+use zeroize::Zeroize;
+
+/// Reads secrets from the terminal or from piped stdin.
+struct StdinPrompt {
+    interactive: bool,
+}
+
+impl Prompt for StdinPrompt {
+    fn secret(&mut self, message: &str) -> Result<SecureString, AppError> {
+        if self.interactive {
+            return prompt_secret(message);
+        }
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Err(AppError::InvalidArguments(format!(
+                "{message}: no input on stdin"
+            )));
+        }
+
+        let secret = SecureString::from(line.trim_end_matches(['\r', '\n']));
+        line.zeroize();
+        Ok(secret)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    let mut prompt = StdinPrompt {
+        interactive: io::stdin().is_terminal(),
+    };
+    let mut stdout = io::stdout().lock();
+
+    let result = UnlockThrottleService::new_in_app_data()
+        .map(|throttle| KdbxService::new().with_unlock_throttle(throttle))
+        .and_then(|kdbx| cli::run(args, kdbx, &mut prompt, &mut stdout));
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(error) => {
+            let _ = stdout.flush();
+            if json {
+                eprintln!(
+                    "{}",
+                    serde_json::json!({
+                        "error": error.to_string(),
+                        "code": error.code(),
+                        "details": error.details(),
+                    })
+                );
+            } else {
+                eprintln!("mithril-cli: {error}");
+            }
+            if matches!(error, AppError::InvalidArguments(_)) {
+                if !json {
+                    eprintln!("Run 'mithril-cli help' for usage.");
+                }
+                ExitCode::from(2)
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+
+//! Native messaging host for `KeePassXC-Browser` compatible extensions.
+//!
+//! The browser starts this binary and exchanges length-prefixed JSON with it
+//! over stdin/stdout. Each message is relayed to the running app, which does
+//! all decryption and database access; when the app is not running the
+//! extension gets a "not connected" error instead.
+
+use std::io::{self, Write};
+
+use mithril_vault_lib::services::browser::protocol::{
+    error_response, read_message, write_message, ERROR_NOT_CONNECTED,
+};
+
+fn main() {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    while let Ok(Some(message)) = read_message(&mut stdin) {
+        let response = relay(&message).unwrap_or_else(|_| not_connected(&message));
+        if write_message(&mut stdout, &response).is_err() {
+            break;
+        }
+    }
+    let _ = stdout.flush();
+}
+
+#[cfg(unix)]
+fn relay(message: &[u8]) -> io::Result<Vec<u8>> {
+    use mithril_vault_lib::services::browser::server::{request, socket_path};
+    request(&socket_path(), message)
+}
+
+#[cfg(not(unix))]
+fn relay(_message: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Browser integration requires Unix sockets",
+    ))
+}
+
+fn not_connected(message: &[u8]) -> Vec<u8> {
+    let action = serde_json::from_slice::<serde_json::Value>(message)
+        .ok()
+        .and_then(|request| request["action"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    serde_json::to_vec(&error_response(&action, ERROR_NOT_CONNECTED)).unwrap_or_default()
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT
+
+//! Command-line argument parsing.
+//!
+//! Options are pulled out by name as each subcommand needs them; whatever
+//! is left over must be positional, so unknown options are reported instead
+//! of being taken for arguments. Everything after `--` is positional.
+
+use crate::dto::error::AppError;
+
+#[derive(Debug)]
+pub struct Args {
+    /// Arguments before `--`, minus the options taken so far
+    tokens: Vec<String>,
+    /// Arguments after `--`
+    trailing: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: impl IntoIterator<Item = String>) -> Self {
+        let mut tokens = Vec::new();
+        let mut trailing: Option<Vec<String>> = None;
+        for arg in args {
+            match &mut trailing {
+                Some(trailing) => trailing.push(arg),
+                None if arg == "--" => trailing = Some(Vec::new()),
+                None => tokens.push(arg),
+            }
+        }
+        Self {
+            tokens,
+            trailing: trailing.unwrap_or_default(),
+        }
+    }
+
+    /// Takes a boolean flag such as `--json`.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let long = format!("--{name}");
+        let before = self.tokens.len();
+        self.tokens.retain(|token| *token != long);
+        self.tokens.len() != before
+    }
+
+    /// Takes an option with a value, given as `--name value` or
+    /// `--name=value`. The last occurrence wins.
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, AppError> {
+        Ok(self.values(name)?.pop())
+    }
+
+    /// Takes every occurrence of a repeatable option.
+    pub fn values(&mut self, name: &str) -> Result<Vec<String>, AppError> {
+        let long = format!("--{name}");
+        let prefix = format!("--{name}=");
+        let mut values = Vec::new();
+        let mut index = 0;
+        while index < self.tokens.len() {
+            if self.tokens[index] == long {
+                if index + 1 >= self.tokens.len() {
+                    return Err(AppError::InvalidArguments(format!("{long} needs a value")));
+                }
+                values.push(self.tokens.remove(index + 1));
+                self.tokens.remove(index);
+            } else if let Some(value) = self.tokens[index].strip_prefix(&prefix) {
+                values.push(value.to_string());
+                self.tokens.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Takes a numeric option.
+    pub fn number<T: std::str::FromStr>(&mut self, name: &str) -> Result<Option<T>, AppError> {
+        self.value(name)?
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    AppError::InvalidArguments(format!("--{name} expects a number, got {value}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Takes the next positional argument, typically the subcommand.
+    pub fn next_positional(&mut self) -> Option<String> {
+        let index = self.tokens.iter().position(|token| !is_option(token))?;
+        Some(self.tokens.remove(index))
+    }
+
+    /// Returns the remaining positional arguments, failing on options that
+    /// no one asked for or on a count outside `min..=max`.
+    pub fn positionals(self, min: usize, max: usize) -> Result<Vec<String>, AppError> {
+        if let Some(unknown) = self.tokens.iter().find(|token| is_option(token)) {
+            return Err(AppError::InvalidArguments(format!(
+                "Unknown option: {unknown}"
+            )));
+        }
+        if !self.trailing.is_empty() {
+            return Err(AppError::InvalidArguments(
+                "Unexpected arguments after --".to_string(),
+            ));
+        }
+        if self.tokens.len() < min {
+            return Err(AppError::InvalidArguments("Missing arguments".to_string()));
+        }
+        if self.tokens.len() > max {
+            return Err(AppError::InvalidArguments(format!(
+                "Unexpected argument: {}",
+                self.tokens[max]
+            )));
+        }
+        Ok(self.tokens)
+    }
+
+    /// Returns the arguments after `--`, failing on any other argument that
+    /// is left.
+    pub fn trailing(self) -> Result<Vec<String>, AppError> {
+        if let Some(unexpected) = self.tokens.first() {
+            return Err(AppError::InvalidArguments(format!(
+                "Unexpected argument: {unexpected}"
+            )));
+        }
+        Ok(self.trailing)
+    }
+}
+
+fn is_option(token: &str) -> bool {
+    token.starts_with("--") && token.len() > 2
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Args {
+        Args::new(list.iter().map(|arg| (*arg).to_string()))
+    }
+
+    #[test]
+    fn test_options_anywhere_and_positionals_in_order() {
+        let mut args = args(&[
+            "--json",
+            "show",
+            "--field=URL",
+            "Email/Mail",
+            "--db",
+            "a.kdbx",
+        ]);
+        assert!(args.flag("json"));
+        assert_eq!(args.value("db").expect("db"), Some("a.kdbx".to_string()));
+        assert_eq!(args.next_positional().as_deref(), Some("show"));
+        assert_eq!(args.value("field").expect("field"), Some("URL".to_string()));
+        assert!(!args.flag("show-password"));
+        assert_eq!(
+            args.positionals(1, 1).expect("positionals"),
+            vec!["Email/Mail".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_repeated_values_and_trailing_command() {
+        let mut args = args(&["--tag", "a", "--tag=b", "--", "cargo", "--tag", "c"]);
+        assert_eq!(args.values("tag").expect("tags"), vec!["a", "b"]);
+        assert_eq!(
+            args.trailing().expect("trailing"),
+            vec!["cargo", "--tag", "c"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_options_and_missing_values() {
+        assert!(args(&["ls", "--bogus"]).positionals(0, 1).is_err());
+        assert!(args(&["--length"]).number::<usize>("length").is_err());
+        assert!(args(&["--length", "ten"])
+            .number::<usize>("length")
+            .is_err());
+        assert!(args(&["a", "b"]).positionals(0, 1).is_err());
+        assert!(args(&[]).positionals(1, 1).is_err());
+    }
+}
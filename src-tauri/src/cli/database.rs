@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: MIT
+
+//! Database subcommands: `export`, `import`, `db-info`, `lock-status` and
+//! `force-unlock`.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::args::Args;
+use super::{exactly, Cli};
+use crate::domain::secure::SecureString;
+use crate::dto::database::{
+    DatabaseConfigDto, DatabaseCreationOptions, DatabaseInfo, KdbImportSource, KdfSettings,
+};
+use crate::dto::error::AppError;
+use crate::dto::export::{ExportField, ExportFormat, ExportOptions};
+use crate::dto::lock::LockStatusDto;
+use crate::services::file_lock::FileLockService;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DatabaseDetails {
+    #[serde(flatten)]
+    info: DatabaseInfo,
+    config: DatabaseConfigDto,
+    group_count: usize,
+    entry_count: usize,
+}
+
+impl Cli<'_> {
+    pub(super) fn export(&mut self, mut args: Args) -> Result<(), AppError> {
+        let format = args.value("format")?;
+        let fields = args.value("fields")?;
+        let group = args.value("group")?;
+        let include_group_path = args.flag("group-path");
+        let include_protected = args.flag("include-protected");
+        let acknowledge_plaintext = args.flag("acknowledge-plaintext");
+        let [path] = exactly(args.positionals(1, 1)?)?;
+
+        let format = match format.as_deref() {
+            Some("csv") => ExportFormat::Csv,
+            Some("json") => ExportFormat::Json,
+            Some(other) => {
+                return Err(AppError::InvalidArguments(format!(
+                    "Unknown export format: {other}"
+                )))
+            }
+            None if has_extension(&path, "json") => ExportFormat::Json,
+            None => ExportFormat::Csv,
+        };
+        let fields = fields
+            .map(|list| list.split(',').map(export_field).collect())
+            .unwrap_or_default();
+        self.open()?;
+
+        let group_id = match group {
+            Some(group) => Some(self.kdbx.resolve_group(&group)?.id),
+            None => None,
+        };
+        let result = self.kdbx.export_entries(
+            &path,
+            &ExportOptions {
+                format,
+                fields,
+                group_id,
+                include_group_path,
+                include_protected,
+                acknowledge_plaintext,
+            },
+        )?;
+
+        let text = format!("Exported {} entries to {}", result.entry_count, result.path);
+        self.print(&result, &text)
+    }
+
+    pub(super) fn import(&mut self, mut args: Args) -> Result<(), AppError> {
+        let source_keyfile = args.value("source-keyfile")?;
+        let name = args.value("name")?;
+        let [source, target] = exactly(args.positionals(2, 2)?)?;
+
+        // Keyfile-only sources are unlocked with an empty password
+        let source_password = self.prompt.secret(&format!("Password for {source}"))?;
+        let source_password =
+            (!(source_password.is_empty() && source_keyfile.is_some())).then_some(source_password);
+        let password = if self.no_password {
+            None
+        } else {
+            Some(self.prompt.secret(&format!("Password for {target}"))?)
+        };
+        let name = name.unwrap_or_else(|| {
+            Path::new(&target).file_stem().map_or_else(
+                || "Imported".to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        });
+
+        let result = self.kdbx.import_kdb(
+            &KdbImportSource {
+                path: source,
+                password: source_password,
+                keyfile_path: source_keyfile,
+            },
+            &target,
+            password.as_ref().map(SecureString::as_str),
+            self.keyfile.as_deref(),
+            &name,
+            &DatabaseCreationOptions::default(),
+        )?;
+
+        let text = format!(
+            "Imported {} groups and {} entries into {}",
+            result.group_count, result.entry_count, result.database.path
+        );
+        self.print(&result, &text)
+    }
+
+    pub(super) fn database_info(&mut self, args: Args) -> Result<(), AppError> {
+        args.positionals(0, 0)?;
+        self.open()?;
+
+        let groups = self.kdbx.list_groups()?;
+        let details = DatabaseDetails {
+            info: self.kdbx.get_info()?,
+            config: self.kdbx.get_config()?,
+            // The root itself is not counted
+            group_count: groups.iter().map(count_groups).sum::<usize>() - groups.len(),
+            entry_count: self.kdbx.list_entries(None)?.len(),
+        };
+
+        let text = format!(
+            "Name: {}\nPath: {}\nVersion: {}\nCipher: {:?}\nKDF: {}\nGroups: {}\nEntries: {}\n",
+            details.info.name,
+            details.info.path,
+            details.config.version,
+            details.config.outer_cipher,
+            describe_kdf(&details.config.kdf),
+            details.group_count,
+            details.entry_count,
+        );
+        self.print(&details, &text)
+    }
+
+    pub(super) fn lock_status(&mut self, args: Args) -> Result<(), AppError> {
+        args.positionals(0, 0)?;
+        let status: LockStatusDto =
+            FileLockService::check_lock_status(self.database_path()?)?.into();
+
+        let text = describe_lock(&status);
+        self.print(&status, &text)
+    }
+
+    /// Removes the lock file. A lock held by a running process is only
+    /// removed with `--yes`, since writing from two places corrupts data.
+    pub(super) fn force_unlock(&mut self, mut args: Args) -> Result<(), AppError> {
+        let confirmed = args.flag("yes");
+        args.positionals(0, 0)?;
+        let path = self.database_path()?.to_string();
+
+        let status: LockStatusDto = FileLockService::check_lock_status(&path)?.into();
+        if let LockStatusDto::LockedByOtherProcess { info } = &status {
+            if !confirmed {
+                return Err(AppError::DatabaseLocked(
+                    format!(
+                        "{}; pass --yes to remove the lock anyway",
+                        describe_lock(&status)
+                    ),
+                    Some(Box::new(info.clone())),
+                ));
+            }
+        }
+        FileLockService::force_unlock(&path)?;
+
+        self.print(
+            &serde_json::json!({ "path": path, "removed": !matches!(status, LockStatusDto::Available) }),
+            &format!("Unlocked {path}"),
+        )
+    }
+}
+
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Export column for a name from `--fields`; unknown names are custom
+/// fields.
+fn export_field(name: &str) -> ExportField {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "title" => ExportField::Title,
+        "username" => ExportField::Username,
+        "password" => ExportField::Password,
+        "url" => ExportField::Url,
+        "notes" => ExportField::Notes,
+        "tags" => ExportField::Tags,
+        "created" => ExportField::CreatedAt,
+        "modified" => ExportField::ModifiedAt,
+        _ => ExportField::Custom(name.trim().to_string()),
+    }
+}
+
+fn count_groups(group: &crate::dto::group::Group) -> usize {
+    1 + group.children.iter().map(count_groups).sum::<usize>()
+}
+
+fn describe_kdf(kdf: &KdfSettings) -> String {
+    match kdf {
+        KdfSettings::AesKdf { rounds } => format!("AES-KDF, {rounds} rounds"),
+        KdfSettings::Argon2d {
+            memory,
+            iterations,
+            parallelism,
+        } => format!(
+            "Argon2d, {} MiB, {iterations} iterations, {parallelism} lanes",
+            memory / (1024 * 1024)
+        ),
+        KdfSettings::Argon2id {
+            memory,
+            iterations,
+            parallelism,
+        } => format!(
+            "Argon2id, {} MiB, {iterations} iterations, {parallelism} lanes",
+            memory / (1024 * 1024)
+        ),
+    }
+}
+
+fn describe_lock(status: &LockStatusDto) -> String {
+    match status {
+        LockStatusDto::Available => "Not locked".to_string(),
+        LockStatusDto::LockedByCurrentProcess => "Locked by this process".to_string(),
+        LockStatusDto::LockedByOtherProcess { info } => format!(
+            "Locked by {} (PID {}) on {} since {}",
+            info.application, info.pid, info.hostname, info.opened_at
+        ),
+        LockStatusDto::StaleLock { info } => format!(
+            "Stale lock left by {} (PID {}) on {} since {}",
+            info.application, info.pid, info.hostname, info.opened_at
+        ),
+    }
+}
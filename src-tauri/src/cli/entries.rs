@@ -0,0 +1,395 @@
+// SPDX-License-Identifier: MIT
+
+//! Entry subcommands: `ls`, `show`, `add`, `edit`, `rm`, `mv` and `search`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use super::args::Args;
+use super::{exactly, generator_options, Cli};
+use crate::domain::secure::SecureString;
+use crate::dto::entry::{CreateEntryData, Entry, UpdateEntryData};
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use crate::dto::group::Group;
+use crate::utils::password_generator::generate_password;
+
+/// A line of `ls` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListItem {
+    kind: &'static str,
+    id: String,
+    name: String,
+    path: String,
+}
+
+/// An entry found by `search`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EntrySummary {
+    id: String,
+    path: String,
+    title: String,
+    username: String,
+    url: Option<String>,
+}
+
+/// An entry as printed by `show`. Secrets are only present on request.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShownEntry<'a> {
+    path: String,
+    #[serde(flatten)]
+    entry: &'a Entry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    protected_fields: BTreeMap<String, String>,
+}
+
+/// Values given with the entry options of `add` and `edit`.
+struct EntryOptions {
+    username: Option<String>,
+    url: Option<String>,
+    notes: Option<String>,
+    tags: Option<Vec<String>>,
+    fields: BTreeMap<String, String>,
+    password: PasswordChoice,
+}
+
+enum PasswordChoice {
+    Keep,
+    Prompt,
+    Generate(PasswordGeneratorOptions),
+}
+
+impl Cli<'_> {
+    pub(super) fn list(&mut self, mut args: Args) -> Result<(), AppError> {
+        let recursive = args.flag("recursive");
+        let group = args.positionals(0, 1)?.pop().unwrap_or_default();
+        self.open()?;
+
+        let group = self.kdbx.resolve_group(&group)?;
+        let base = self.kdbx.group_path(&group.id)?;
+        let mut items = Vec::new();
+        self.collect_items(&group, &base, recursive, &mut items)?;
+
+        let mut text = String::new();
+        for item in &items {
+            let relative = item.path[base.len()..].trim_start_matches('/');
+            let suffix = if item.kind == "group" { "/" } else { "" };
+            let _ = writeln!(text, "{relative}{suffix}");
+        }
+        self.print(&items, &text)
+    }
+
+    fn collect_items(
+        &self,
+        group: &Group,
+        path: &str,
+        recursive: bool,
+        items: &mut Vec<ListItem>,
+    ) -> Result<(), AppError> {
+        for child in &group.children {
+            let child_path = join_path(path, &child.name);
+            items.push(ListItem {
+                kind: "group",
+                id: child.id.clone(),
+                name: child.name.clone(),
+                path: child_path.clone(),
+            });
+            if recursive {
+                self.collect_items(child, &child_path, recursive, items)?;
+            }
+        }
+        for entry in self.kdbx.list_entries(Some(&group.id))? {
+            items.push(ListItem {
+                kind: "entry",
+                path: join_path(path, &entry.title),
+                id: entry.id,
+                name: entry.title,
+            });
+        }
+        Ok(())
+    }
+
+    pub(super) fn show(&mut self, mut args: Args) -> Result<(), AppError> {
+        let show_protected = args.flag("show-protected");
+        let field = args.value("field")?;
+        let [reference] = exactly(args.positionals(1, 1)?)?;
+        self.open()?;
+
+        let entry = self.kdbx.resolve_entry(&reference)?;
+        if let Some(field) = field {
+            let value = SecureString::from(self.kdbx.get_entry_field(&entry.id, &field)?);
+            return self.print(
+                &serde_json::json!({ "field": field, "value": value.as_str() }),
+                value.as_str(),
+            );
+        }
+
+        let mut shown = ShownEntry {
+            path: self.entry_path(&entry)?,
+            entry: &entry,
+            password: None,
+            protected_fields: BTreeMap::new(),
+        };
+        if show_protected {
+            shown.password = Some(self.kdbx.get_entry_password(&entry.id)?);
+            for meta in entry
+                .custom_field_meta
+                .iter()
+                .filter(|meta| meta.is_protected)
+            {
+                let field = self
+                    .kdbx
+                    .get_entry_protected_custom_field(&entry.id, &meta.key)?;
+                shown.protected_fields.insert(field.key, field.value);
+            }
+        }
+
+        let text = format_entry(&shown);
+        self.print(&shown, &text)
+    }
+
+    pub(super) fn add(&mut self, mut args: Args) -> Result<(), AppError> {
+        let options = entry_options(&mut args)?;
+        let [path] = exactly(args.positionals(1, 1)?)?;
+        let (group_path, title) = path.rsplit_once('/').unwrap_or(("", path.as_str()));
+        if title.is_empty() {
+            return Err(AppError::InvalidArguments(
+                "The path must end with the entry title".to_string(),
+            ));
+        }
+        self.open()?;
+
+        let group = self.kdbx.resolve_group(group_path)?;
+        let password = self.entry_password(&options.password)?;
+        let entry = self.kdbx.create_entry(
+            &group.id,
+            CreateEntryData {
+                title: title.to_string(),
+                username: options.username.unwrap_or_default(),
+                password: password.unwrap_or_default(),
+                url: options.url,
+                notes: options.notes,
+                icon_id: None,
+                tags: options.tags,
+                custom_fields: (!options.fields.is_empty()).then_some(options.fields),
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )?;
+        self.kdbx.save()?;
+
+        let text = format!("Added {}", self.entry_path(&entry)?);
+        self.print(&entry, &text)
+    }
+
+    pub(super) fn edit(&mut self, mut args: Args) -> Result<(), AppError> {
+        let title = args.value("title")?;
+        let options = entry_options(&mut args)?;
+        let [reference] = exactly(args.positionals(1, 1)?)?;
+        self.open()?;
+
+        let entry = self.kdbx.resolve_entry(&reference)?;
+        let password = self.entry_password(&options.password)?;
+        // Custom fields are replaced as a whole, so the others are kept here
+        let (custom_fields, protected_custom_fields) = if options.fields.is_empty() {
+            (None, None)
+        } else {
+            let mut custom_fields = entry.custom_fields.clone();
+            let mut protected = BTreeMap::new();
+            for meta in entry
+                .custom_field_meta
+                .iter()
+                .filter(|meta| meta.is_protected)
+            {
+                if !options.fields.contains_key(&meta.key) {
+                    let field = self
+                        .kdbx
+                        .get_entry_protected_custom_field(&entry.id, &meta.key)?;
+                    protected.insert(field.key, SecureString::from(field.value));
+                }
+            }
+            custom_fields.extend(options.fields);
+            (Some(custom_fields), Some(protected))
+        };
+
+        let updated = self.kdbx.update_entry(
+            &entry.id,
+            UpdateEntryData {
+                title,
+                username: options.username,
+                password,
+                url: options.url,
+                notes: options.notes,
+                icon_id: None,
+                tags: options.tags,
+                custom_fields,
+                protected_custom_fields,
+                expires: None,
+                expiry_time: None,
+                auto_type: None,
+            },
+        )?;
+        self.kdbx.save()?;
+
+        let text = format!("Updated {}", self.entry_path(&updated)?);
+        self.print(&updated, &text)
+    }
+
+    pub(super) fn remove(&mut self, mut args: Args) -> Result<(), AppError> {
+        let permanent = args.flag("permanent");
+        let [reference] = exactly(args.positionals(1, 1)?)?;
+        self.open()?;
+
+        let entry = self.kdbx.resolve_entry(&reference)?;
+        let path = self.entry_path(&entry)?;
+        self.kdbx.delete_entry(&entry.id, permanent)?;
+        self.kdbx.save()?;
+
+        self.print(
+            &serde_json::json!({ "id": entry.id, "path": path, "permanent": permanent }),
+            &format!("Removed {path}"),
+        )
+    }
+
+    pub(super) fn move_entry(&mut self, args: Args) -> Result<(), AppError> {
+        let [reference, group] = exactly(args.positionals(2, 2)?)?;
+        self.open()?;
+
+        let entry = self.kdbx.resolve_entry(&reference)?;
+        let group = self.kdbx.resolve_group(&group)?;
+        let moved = self.kdbx.move_entry(&entry.id, &group.id)?;
+        self.kdbx.save()?;
+
+        let text = format!("Moved to {}", self.entry_path(&moved)?);
+        self.print(&moved, &text)
+    }
+
+    pub(super) fn search(&mut self, args: Args) -> Result<(), AppError> {
+        let [query] = exactly(args.positionals(1, 1)?)?;
+        self.open()?;
+
+        let mut found = Vec::new();
+        for entry in self.kdbx.search_entries(&query, None)? {
+            found.push(EntrySummary {
+                path: self.entry_path(&entry)?,
+                id: entry.id,
+                title: entry.title,
+                username: entry.username,
+                url: entry.url,
+            });
+        }
+
+        let mut text = String::new();
+        for entry in &found {
+            let _ = writeln!(text, "{}", entry.path);
+        }
+        self.print(&found, &text)
+    }
+
+    /// Path of an entry below the root, ending in its title.
+    fn entry_path(&self, entry: &Entry) -> Result<String, AppError> {
+        Ok(join_path(
+            &self.kdbx.group_path(&entry.group_id)?,
+            &entry.title,
+        ))
+    }
+
+    fn entry_password(
+        &mut self,
+        choice: &PasswordChoice,
+    ) -> Result<Option<SecureString>, AppError> {
+        match choice {
+            PasswordChoice::Keep => Ok(None),
+            PasswordChoice::Prompt => self.prompt.secret("Password for the entry").map(Some),
+            PasswordChoice::Generate(options) => {
+                Ok(Some(SecureString::from(generate_password(options)?)))
+            }
+        }
+    }
+}
+
+fn entry_options(args: &mut Args) -> Result<EntryOptions, AppError> {
+    let mut fields = BTreeMap::new();
+    for field in args.values("field")? {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            AppError::InvalidArguments(format!("--field expects KEY=VALUE, got {field}"))
+        })?;
+        fields.insert(key.to_string(), value.to_string());
+    }
+    let tags = args.values("tag")?;
+
+    let prompt = args.flag("password-prompt");
+    let generate = args.flag("generate");
+    let generator = generator_options(args)?;
+    let password = match (prompt, generate) {
+        (true, true) => {
+            return Err(AppError::InvalidArguments(
+                "--generate and --password-prompt cannot be combined".to_string(),
+            ))
+        }
+        (true, false) => PasswordChoice::Prompt,
+        (false, true) => PasswordChoice::Generate(generator),
+        (false, false) => PasswordChoice::Keep,
+    };
+
+    Ok(EntryOptions {
+        username: args.value("username")?,
+        url: args.value("url")?,
+        notes: args.value("notes")?,
+        tags: (!tags.is_empty()).then_some(tags),
+        fields,
+        password,
+    })
+}
+
+fn join_path(parent: &str, name: &str) -> String {
+    format!("{}/{name}", parent.trim_end_matches('/'))
+}
+
+fn format_entry(shown: &ShownEntry<'_>) -> String {
+    let entry = shown.entry;
+    let mut text = String::new();
+    let _ = writeln!(text, "Title: {}", entry.title);
+    let _ = writeln!(text, "Path: {}", shown.path);
+    let _ = writeln!(text, "UUID: {}", entry.id);
+    let _ = writeln!(text, "Username: {}", entry.username);
+    let _ = writeln!(
+        text,
+        "Password: {}",
+        shown.password.as_deref().unwrap_or("PROTECTED")
+    );
+    if let Some(url) = &entry.url {
+        let _ = writeln!(text, "URL: {url}");
+    }
+    if let Some(notes) = &entry.notes {
+        let _ = writeln!(text, "Notes: {notes}");
+    }
+    if !entry.tags.is_empty() {
+        let _ = writeln!(text, "Tags: {}", entry.tags.join(", "));
+    }
+    for meta in &entry.custom_field_meta {
+        let value = if meta.is_protected {
+            shown
+                .protected_fields
+                .get(&meta.key)
+                .map_or("PROTECTED", String::as_str)
+        } else {
+            entry
+                .custom_fields
+                .get(&meta.key)
+                .map_or("", String::as_str)
+        };
+        let _ = writeln!(text, "{}: {value}", meta.key);
+    }
+    if let Some(expiry_time) = &entry.expiry_time {
+        let _ = writeln!(text, "Expires: {expiry_time}");
+    }
+    text
+}
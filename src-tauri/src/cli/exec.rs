@@ -0,0 +1,446 @@
+// SPDX-License-Identifier: MIT
+
+//! The `run` subcommand: starts a program with secrets from the database in
+//! its environment.
+//!
+//! `--env NAME=ref:ENTRY/FIELD` sets a variable to a field of an entry, and
+//! `--file NAME=ref:ENTRY/FIELD` writes the field to a private temporary
+//! file and sets the variable to the file's path, for programs that read
+//! secrets from files. Other values are passed on unchanged.
+//!
+//! The database is closed before the program starts. The resolved secrets
+//! are masked in the program's stdout and stderr, and the temporary files
+//! are removed when it exits, including after Ctrl-C, SIGTERM or SIGHUP.
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use super::args::Args;
+use super::Cli;
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+
+const REF_PREFIX: &str = "ref:";
+const MASK: &[u8] = b"*****";
+/// Shorter secrets are not masked: hiding every occurrence of a one or
+/// two character string garbles the output without protecting anything.
+const MIN_MASKED_LENGTH: usize = 4;
+
+impl Cli<'_> {
+    pub(super) fn run_command(&mut self, mut args: Args) -> Result<u8, AppError> {
+        let env = args.values("env")?;
+        let files = args.values("file")?;
+        let command = args.trailing()?;
+        let Some((program, program_args)) = command.split_first() else {
+            return Err(AppError::InvalidArguments(
+                "No command given after --".to_string(),
+            ));
+        };
+        let env = env
+            .iter()
+            .map(|assignment| split_assignment(assignment))
+            .collect::<Result<Vec<_>, _>>()?;
+        let files = files
+            .iter()
+            .map(|assignment| split_assignment(assignment))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let needs_database = env
+            .iter()
+            .chain(&files)
+            .any(|(_, value)| value.starts_with(REF_PREFIX));
+        if needs_database {
+            self.open()?;
+        }
+        let mut secrets = Vec::new();
+        let mut variables = Vec::new();
+        for (name, value) in env {
+            variables.push((name, self.resolve_value(value, &mut secrets)?));
+        }
+        // Held until the secret files are removed, so a signal cannot end
+        // this process before that
+        let signals = HoldSignals::new();
+        let mut secret_files = SecretFiles::default();
+        for (name, value) in files {
+            let value = self.resolve_value(value, &mut secrets)?;
+            let path = secret_files.write(name, &value)?;
+            variables.push((name, SecureString::from(path.to_string_lossy().as_ref())));
+        }
+        if needs_database {
+            self.kdbx.close()?;
+        }
+
+        if let Some(signal) = signals.pending() {
+            return Ok(128u8.saturating_add(signal));
+        }
+
+        let mut command = Command::new(program);
+        command
+            .args(program_args)
+            .envs(variables.iter().map(|(name, value)| (name, value.as_str())))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        signals.restore_in_child(&mut command);
+        let mut child = command
+            .spawn()
+            .map_err(|e| AppError::Io(format!("Cannot run {program}: {e}")))?;
+        signals.forward_to(child.id());
+
+        let masker = Masker::new(&secrets);
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let out = &mut *self.out;
+        std::thread::scope(|scope| {
+            if let Some(stderr) = stderr {
+                scope.spawn(|| masker.copy(stderr, &mut io::stderr()));
+            }
+            if let Some(stdout) = stdout {
+                masker.copy(stdout, out);
+            }
+        });
+        let status = child.wait()?;
+
+        Ok(exit_code(status))
+    }
+
+    /// Resolves `ref:ENTRY/FIELD`, remembering the value for masking, or
+    /// returns other values as they are.
+    fn resolve_value(
+        &self,
+        value: &str,
+        secrets: &mut Vec<SecureString>,
+    ) -> Result<SecureString, AppError> {
+        let Some(reference) = value.strip_prefix(REF_PREFIX) else {
+            return Ok(SecureString::from(value));
+        };
+        let (entry, field) = reference.rsplit_once('/').ok_or_else(|| {
+            AppError::InvalidArguments(format!("Expected ref:ENTRY/FIELD, got {value}"))
+        })?;
+
+        let entry = self.kdbx.resolve_entry(entry)?;
+        let resolved = SecureString::from(self.kdbx.get_entry_field(&entry.id, field)?);
+        secrets.push(resolved.clone());
+        Ok(resolved)
+    }
+}
+
+/// Splits `NAME=VALUE`.
+fn split_assignment(assignment: &str) -> Result<(&str, &str), AppError> {
+    assignment
+        .split_once('=')
+        .filter(|(name, _)| !name.is_empty() && !name.contains('\0'))
+        .ok_or_else(|| AppError::InvalidArguments(format!("Expected NAME=VALUE, got {assignment}")))
+}
+
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> u8 {
+    use std::os::unix::process::ExitStatusExt;
+    // Shells report death by signal as 128 + the signal number
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .map_or(1, |code| u8::try_from(code & 0xff).unwrap_or(1))
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> u8 {
+    status
+        .code()
+        .map_or(1, |code| u8::try_from(code & 0xff).unwrap_or(1))
+}
+
+/// Replaces secrets in a byte stream with a mask.
+struct Masker {
+    /// Longest first, so a secret containing another is masked whole
+    secrets: Vec<Vec<u8>>,
+}
+
+impl Masker {
+    fn new(secrets: &[SecureString]) -> Self {
+        let mut secrets: Vec<Vec<u8>> = secrets
+            .iter()
+            .filter(|secret| secret.len() >= MIN_MASKED_LENGTH)
+            .map(|secret| secret.as_str().as_bytes().to_vec())
+            .collect();
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+        Self { secrets }
+    }
+
+    /// Copies `reader` to `writer` until the end of input. Output that may
+    /// be the start of a secret is held back until the next read decides.
+    /// After a write error the input is still drained, so the program does
+    /// not block on a full pipe.
+    fn copy(&self, mut reader: impl Read, writer: &mut dyn Write) {
+        let mut pending = Vec::new();
+        let mut chunk = [0u8; 8192];
+        let mut writable = true;
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            pending.extend_from_slice(&chunk[..read]);
+            let (masked, consumed) = self.mask(&pending, false);
+            pending.drain(..consumed);
+            writable = writable
+                && writer
+                    .write_all(&masked)
+                    .and_then(|()| writer.flush())
+                    .is_ok();
+        }
+        let (masked, _) = self.mask(&pending, true);
+        if writable {
+            let _ = writer.write_all(&masked).and_then(|()| writer.flush());
+        }
+    }
+
+    /// Masks `buffer` and returns the output with the number of bytes it
+    /// covers. Unless `at_end`, a tail that is the start of a secret is
+    /// left for later.
+    fn mask(&self, buffer: &[u8], at_end: bool) -> (Vec<u8>, usize) {
+        let mut output = Vec::with_capacity(buffer.len());
+        let mut index = 0;
+        while index < buffer.len() {
+            let rest = &buffer[index..];
+            // Wait while a longer secret may still match here
+            let undecided = self
+                .secrets
+                .iter()
+                .any(|secret| secret.len() > rest.len() && secret.starts_with(rest));
+            if undecided && !at_end {
+                break;
+            }
+            if let Some(secret) = self.secrets.iter().find(|secret| rest.starts_with(secret)) {
+                output.extend_from_slice(MASK);
+                index += secret.len();
+            } else {
+                output.push(buffer[index]);
+                index += 1;
+            }
+        }
+        (output, index)
+    }
+}
+
+/// Private directory for `--file` secrets, removed on drop.
+#[derive(Default)]
+struct SecretFiles {
+    dir: Option<PathBuf>,
+}
+
+impl SecretFiles {
+    fn write(&mut self, name: &str, value: &SecureString) -> Result<PathBuf, AppError> {
+        let dir = match self.dir.take() {
+            Some(dir) => dir,
+            None => create_private_dir()?,
+        };
+        let dir = self.dir.insert(dir);
+
+        let file_name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = dir.join(file_name);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&path)?.write_all(value.as_str().as_bytes())?;
+        Ok(path)
+    }
+}
+
+fn create_private_dir() -> Result<PathBuf, AppError> {
+    let dir = std::env::temp_dir().join(format!("mithril-run-{}", uuid::Uuid::new_v4()));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+impl Drop for SecretFiles {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Signals that would end this process before it removes the secret files.
+#[cfg(unix)]
+const HELD_SIGNALS: [libc::c_int; 4] = [libc::SIGINT, libc::SIGQUIT, libc::SIGTERM, libc::SIGHUP];
+
+/// The running program, or 0 before it is started.
+#[cfg(unix)]
+static CHILD_PID: AtomicI32 = AtomicI32::new(0);
+/// The last held signal that arrived before the program was started.
+#[cfg(unix)]
+static PENDING_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+extern "C" fn on_held_signal(signal: libc::c_int) {
+    let pid = CHILD_PID.load(Ordering::SeqCst);
+    if pid == 0 {
+        PENDING_SIGNAL.store(signal, Ordering::SeqCst);
+    } else if signal == libc::SIGTERM || signal == libc::SIGHUP {
+        // The terminal sends Ctrl-C and Ctrl-\ to the program itself, but
+        // SIGTERM and SIGHUP may have been meant for this process only.
+        // SAFETY: `kill` is async-signal-safe
+        unsafe {
+            libc::kill(pid, signal);
+        }
+    }
+}
+
+/// Keeps Ctrl-C, Ctrl-\, SIGTERM and SIGHUP from ending this process while
+/// alive. Before the program starts they are remembered, afterwards
+/// SIGTERM and SIGHUP are passed on to it, so this process always waits
+/// for the program and cleans up.
+struct HoldSignals {
+    #[cfg(unix)]
+    previous: [libc::sighandler_t; 4],
+}
+
+// The methods take `self` so they are only called while the signals are held
+#[allow(clippy::unused_self)]
+impl HoldSignals {
+    #[cfg(unix)]
+    #[allow(unsafe_code)]
+    fn new() -> Self {
+        CHILD_PID.store(0, Ordering::SeqCst);
+        PENDING_SIGNAL.store(0, Ordering::SeqCst);
+        // SAFETY: the handler only touches atomics and calls `kill`; the
+        // previous dispositions are restored on drop
+        let previous = HELD_SIGNALS.map(|signal| unsafe {
+            libc::signal(signal, on_held_signal as *const () as libc::sighandler_t)
+        });
+        Self { previous }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns a held signal that arrived before the program was started.
+    #[cfg(unix)]
+    fn pending(&self) -> Option<u8> {
+        u8::try_from(PENDING_SIGNAL.load(Ordering::SeqCst))
+            .ok()
+            .filter(|signal| *signal != 0)
+    }
+
+    #[cfg(not(unix))]
+    fn pending(&self) -> Option<u8> {
+        None
+    }
+
+    /// Starts `command` with the dispositions this process had before, so
+    /// the program can be interrupted as usual.
+    #[cfg(unix)]
+    #[allow(unsafe_code)]
+    fn restore_in_child(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+        let previous = self.previous;
+        // SAFETY: `signal` is async-signal-safe and the closure allocates
+        // nothing
+        unsafe {
+            command.pre_exec(move || {
+                for (signal, handler) in HELD_SIGNALS.into_iter().zip(previous) {
+                    libc::signal(signal, handler);
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn restore_in_child(&self, _command: &mut Command) {}
+
+    /// Passes signals on to the started program, including one that
+    /// arrived while it was being started.
+    #[cfg(unix)]
+    #[allow(unsafe_code)]
+    fn forward_to(&self, pid: u32) {
+        let Ok(pid) = i32::try_from(pid) else {
+            return;
+        };
+        CHILD_PID.store(pid, Ordering::SeqCst);
+        let pending = PENDING_SIGNAL.swap(0, Ordering::SeqCst);
+        if pending != 0 {
+            // SAFETY: sends a signal to the program started by this process
+            unsafe {
+                libc::kill(pid, pending);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn forward_to(&self, _pid: u32) {}
+}
+
+#[cfg(unix)]
+impl Drop for HoldSignals {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: restores the dispositions returned by `signal` in `new`
+        unsafe {
+            for (signal, handler) in HELD_SIGNALS.into_iter().zip(self.previous) {
+                libc::signal(signal, handler);
+            }
+        }
+        CHILD_PID.store(0, Ordering::SeqCst);
+        PENDING_SIGNAL.store(0, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads one small chunk at a time, so secrets are split across reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.0.len().min(3).min(buf.len());
+            buf[..count].copy_from_slice(&self.0[..count]);
+            self.0 = &self.0[count..];
+            Ok(count)
+        }
+    }
+
+    fn masked(secrets: &[&str], input: &str) -> String {
+        let secrets: Vec<SecureString> = secrets.iter().map(|s| SecureString::from(*s)).collect();
+        let mut output = Vec::new();
+        Masker::new(&secrets).copy(Trickle(input.as_bytes()), &mut output);
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    #[test]
+    fn test_masks_secrets_split_across_reads() {
+        assert_eq!(
+            masked(&["s3cret-value"], "a s3cret-value b s3cret-valu"),
+            "a ***** b s3cret-valu"
+        );
+        assert_eq!(
+            masked(&["abcd", "abcdef"], "xabcdefy abcd"),
+            "x*****y *****"
+        );
+        assert_eq!(masked(&["abc"], "abc"), "abc");
+    }
+}
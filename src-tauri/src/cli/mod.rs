@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: MIT
+
+//! Headless command-line interface over the library services, for scripts
+//! and CI. The `mithril-cli` binary only supplies the arguments, a
+//! [`Prompt`] for secrets and stdout.
+//!
+//! Every invocation opens the database given with `--db` (or the
+//! `MITHRIL_DB` environment variable), runs one subcommand and saves if it
+//! changed anything. The file lock is honored, so the database cannot be
+//! changed from here while the app has it open. Groups and entries are
+//! addressed by path or UUID (see `services::kdbx::paths`).
+//!
+//! With `--json`, results are printed as JSON documents on stdout.
+
+pub mod args;
+mod database;
+mod entries;
+mod exec;
+mod render;
+
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use crate::services::kdbx::KdbxService;
+use crate::utils::password_generator::generate_password;
+use args::Args;
+
+/// Environment variable naming the database when `--db` is not given.
+pub const DATABASE_ENV: &str = "MITHRIL_DB";
+
+pub const USAGE: &str = "\
+Usage: mithril-cli [--db PATH] [--keyfile PATH] [--no-password] [--json] COMMAND [ARGS]
+
+Commands:
+  ls [GROUP] [--recursive]               List groups and entries
+  show ENTRY [--show-protected] [--field NAME]
+                                         Show an entry, or print one field
+  add PATH [ENTRY OPTIONS]               Create an entry; PATH is GROUP/TITLE
+  edit ENTRY [--title TITLE] [ENTRY OPTIONS]
+                                         Change an entry
+  rm ENTRY [--permanent]                 Move an entry to the recycle bin
+  mv ENTRY GROUP                         Move an entry to another group
+  search QUERY                           Find entries by title, username, URL,
+                                         notes or tags
+  generate [GENERATOR OPTIONS]           Print a random password
+  export FILE [--format csv|json] [--fields LIST] [--group GROUP]
+         [--group-path] [--include-protected --acknowledge-plaintext]
+                                         Write entries to a plaintext file
+  import SOURCE.kdb TARGET.kdbx [--source-keyfile PATH] [--name NAME]
+                                         Convert a KeePass 1.x database
+  db-info                                Show database details
+  lock-status                            Show who holds the database lock
+  force-unlock [--yes]                   Remove a stale database lock
+  run [--env NAME=VALUE] [--file NAME=VALUE] -- PROGRAM [ARGS]
+                                         Run a program with secrets in its
+                                         environment; VALUE may be
+                                         ref:ENTRY/FIELD
+  render TEMPLATE OUTPUT                 Fill {{ vault \"ENTRY\" \"FIELD\" }} tags
+                                         and write OUTPUT readable only by
+                                         the owner
+
+Entry options:
+  --username NAME  --url URL  --notes TEXT  --tag TAG (repeatable)
+  --field KEY=VALUE (repeatable)  --password-prompt  --generate
+
+Generator options:
+  --length N  --no-uppercase  --no-lowercase  --no-numbers  --no-symbols
+  --exclude-ambiguous  --exclude CHARS
+
+ENTRY and GROUP are paths below the root (Email/Work/Mail) or UUIDs.
+Passwords are read from the terminal, or one per line from stdin.
+";
+
+/// Source of secrets: the database password and entry passwords.
+pub trait Prompt {
+    /// Reads one secret. `message` says what is asked for.
+    fn secret(&mut self, message: &str) -> Result<SecureString, AppError>;
+}
+
+/// State of one invocation.
+pub struct Cli<'a> {
+    kdbx: KdbxService,
+    prompt: &'a mut dyn Prompt,
+    out: &'a mut dyn Write,
+    json: bool,
+    database: Option<String>,
+    keyfile: Option<String>,
+    no_password: bool,
+}
+
+/// Runs the command line `args` (without the program name) and returns the
+/// exit code, which is only non-zero for `run`. The database is opened
+/// through `kdbx`.
+pub fn run(
+    args: impl IntoIterator<Item = String>,
+    kdbx: KdbxService,
+    prompt: &mut dyn Prompt,
+    out: &mut dyn Write,
+) -> Result<u8, AppError> {
+    let mut args = Args::new(args);
+    let json = args.flag("json");
+    let database = args
+        .value("db")?
+        .or_else(|| std::env::var(DATABASE_ENV).ok());
+    let keyfile = args.value("keyfile")?;
+    let no_password = args.flag("no-password");
+    if no_password && keyfile.is_none() {
+        return Err(AppError::InvalidArguments(
+            "--no-password requires --keyfile".to_string(),
+        ));
+    }
+
+    let mut cli = Cli {
+        kdbx,
+        prompt,
+        out,
+        json,
+        database,
+        keyfile,
+        no_password,
+    };
+
+    let Some(command) = args.next_positional() else {
+        cli.print_text(USAGE)?;
+        return Ok(0);
+    };
+    match command.as_str() {
+        "ls" => cli.list(args),
+        "show" => cli.show(args),
+        "add" => cli.add(args),
+        "edit" => cli.edit(args),
+        "rm" => cli.remove(args),
+        "mv" => cli.move_entry(args),
+        "search" => cli.search(args),
+        "generate" => cli.generate(args),
+        "export" => cli.export(args),
+        "import" => cli.import(args),
+        "db-info" => cli.database_info(args),
+        "lock-status" => cli.lock_status(args),
+        "force-unlock" => cli.force_unlock(args),
+        "render" => cli.render(args),
+        "run" => return cli.run_command(args),
+        "help" => cli.print_text(USAGE),
+        other => Err(AppError::InvalidArguments(format!(
+            "Unknown command: {other}"
+        ))),
+    }?;
+    Ok(0)
+}
+
+impl Cli<'_> {
+    fn database_path(&self) -> Result<&str, AppError> {
+        self.database.as_deref().ok_or_else(|| {
+            AppError::InvalidArguments(format!("No database given; use --db or {DATABASE_ENV}"))
+        })
+    }
+
+    /// Opens the database with the password from the prompt and the
+    /// keyfile, if any.
+    fn open(&mut self) -> Result<(), AppError> {
+        let path = self.database_path()?.to_string();
+        if self.no_password {
+            let keyfile = self.keyfile.as_deref().unwrap_or_default();
+            self.kdbx.open_with_keyfile_only(&path, keyfile)?;
+            return Ok(());
+        }
+
+        let password = self.prompt.secret(&format!("Password for {path}"))?;
+        match self.keyfile.as_deref() {
+            Some(keyfile) => self
+                .kdbx
+                .open_with_keyfile(&path, password.as_str(), keyfile)?,
+            None => self.kdbx.open(&path, password.as_str())?,
+        };
+        Ok(())
+    }
+
+    /// Prints `value` as JSON, or `text` otherwise.
+    fn print<T: Serialize>(&mut self, value: &T, text: &str) -> Result<(), AppError> {
+        if self.json {
+            let document =
+                serde_json::to_string_pretty(value).map_err(|e| AppError::Io(e.to_string()))?;
+            writeln!(self.out, "{document}")?;
+            Ok(())
+        } else {
+            self.print_text(text)
+        }
+    }
+
+    fn print_text(&mut self, text: &str) -> Result<(), AppError> {
+        write!(self.out, "{text}")?;
+        if !text.is_empty() && !text.ends_with('\n') {
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+
+    fn generate(&mut self, mut args: Args) -> Result<(), AppError> {
+        let options = generator_options(&mut args)?;
+        args.positionals(0, 0)?;
+
+        let password = SecureString::from(generate_password(&options)?);
+        self.print(
+            &serde_json::json!({ "password": password.as_str() }),
+            password.as_str(),
+        )
+    }
+}
+
+/// Password generator settings from the generator options.
+fn generator_options(args: &mut Args) -> Result<PasswordGeneratorOptions, AppError> {
+    let defaults = PasswordGeneratorOptions::default();
+    Ok(PasswordGeneratorOptions {
+        length: args.number("length")?.unwrap_or(defaults.length),
+        uppercase: !args.flag("no-uppercase"),
+        lowercase: !args.flag("no-lowercase"),
+        numbers: !args.flag("no-numbers"),
+        symbols: !args.flag("no-symbols"),
+        exclude_ambiguous: args.flag("exclude-ambiguous"),
+        exclude_chars: args.value("exclude")?,
+    })
+}
+
+/// Unpacks positionals whose count `Args::positionals` already checked.
+fn exactly<const N: usize>(values: Vec<String>) -> Result<[String; N], AppError> {
+    values
+        .try_into()
+        .map_err(|_| AppError::InvalidArguments("Wrong number of arguments".to_string()))
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+
+//! The `render` subcommand: fills a config file template with values from
+//! the database (see `services::kdbx::template`).
+//!
+//! The output is written atomically and readable only by the owner, so a
+//! failed render never leaves a partial file behind.
+
+use std::io::Write;
+
+use super::args::Args;
+use super::{exactly, Cli};
+use crate::dto::error::AppError;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+
+impl Cli<'_> {
+    pub(super) fn render(&mut self, args: Args) -> Result<(), AppError> {
+        let [template, output] = exactly(args.positionals(2, 2)?)?;
+        let source = std::fs::read_to_string(&template)
+            .map_err(|e| AppError::Io(format!("Cannot read {template}: {e}")))?;
+        self.open()?;
+
+        let rendered = self.kdbx.render_template(&source)?;
+        atomic_write(&output, &AtomicWriteOptions::default(), |file| {
+            Ok(file.write_all(rendered.as_str().as_bytes())?)
+        })?;
+
+        self.print(
+            &serde_json::json!({ "template": template, "output": output }),
+            &format!("Rendered {output}"),
+        )
+    }
+}
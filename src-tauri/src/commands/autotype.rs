@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::autotype::{AutoTypeSequence, KeyAction};
+use crate::dto::error::AppError;
+use crate::services::kdbx::KdbxService;
+use crate::utils::autotype;
+use std::sync::Arc;
+use tauri::State;
+
+/// Returns the effective auto-type sequence for an entry as key actions,
+/// using the association matching `window_title` if one is given.
+#[tauri::command]
+pub async fn get_auto_type_sequence(
+    entry_id: String,
+    window_title: Option<String>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<AutoTypeSequence, AppError> {
+    state.get_auto_type_sequence(&entry_id, window_title.as_deref())
+}
+
+/// Parses an auto-type sequence, e.g. to validate it while editing.
+#[tauri::command]
+pub async fn parse_auto_type_sequence(sequence: String) -> Result<Vec<KeyAction>, AppError> {
+    autotype::parse_sequence(&sequence)
+}
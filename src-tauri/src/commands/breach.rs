@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::breach::BreachReport;
+use crate::dto::error::AppError;
+use crate::services::breach;
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Checks all passwords against a local HIBP SHA-1 list or binary index.
+/// Nothing is sent over the network.
+#[tauri::command]
+pub async fn check_breached_passwords(
+    index_path: String,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<BreachReport, AppError> {
+    state.check_breached_passwords(&index_path)
+}
+
+/// Converts the HIBP "ordered by hash" text file into a compact binary index.
+/// Returns the number of hashes written.
+#[tauri::command]
+pub async fn build_breach_index(source_path: String, index_path: String) -> Result<u64, AppError> {
+    breach::build_index(&source_path, &index_path)
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::browser::BrowserAssociation;
+use crate::dto::error::AppError;
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Lists the browser extensions associated with the open database.
+#[tauri::command]
+pub async fn list_browser_associations(
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Vec<BrowserAssociation>, AppError> {
+    state.list_browser_associations()
+}
+
+/// Revokes a browser extension's access to the open database.
+#[tauri::command]
+pub async fn remove_browser_association(
+    id: String,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<(), AppError> {
+    state.remove_browser_association(&id)
+}
@@ -1,29 +1,82 @@
 // SPDX-License-Identifier: MIT
 
+use crate::domain::secure::SecureString;
 use crate::dto::database::{
-    DatabaseConfigDto, DatabaseCreationOptions, DatabaseHeaderInfo, DatabaseInfo,
+    DatabaseConfigDto, DatabaseCreationOptions, DatabaseHeaderInfo, DatabaseInfo, KdbImportResult,
+    KdbImportSource, SaveResult,
 };
 use crate::dto::error::AppError;
 use crate::dto::lock::LockStatusDto;
 use crate::services::file_lock::FileLockService;
 use crate::services::kdbx::KdbxService;
+use crate::services::quick_unlock::QuickUnlockService;
+use crate::services::ssh_agent::SshAgentService;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
-/// Opens a database with a password.
+/// Event emitted after unlocking a database that has expired entries or
+/// entries expiring within [`EXPIRY_WARNING_DAYS`]. Payload: `ExpirySummary`.
+pub const EXPIRY_EVENT: &str = "entries-expiring";
+
+/// Look-ahead window for [`EXPIRY_EVENT`].
+pub const EXPIRY_WARNING_DAYS: u32 = 7;
+
+/// Emits [`EXPIRY_EVENT`] if the just-opened database has expiring entries.
+/// Failures are ignored; the notification must not fail the unlock.
+fn notify_expiring_entries<R: Runtime>(app: &AppHandle<R>, state: &KdbxService) {
+    if let Ok(Some(summary)) = state.expiry_summary(EXPIRY_WARNING_DAYS) {
+        let _ = app.emit(EXPIRY_EVENT, summary);
+    }
+}
+
+/// Adds the keys of entries set to load on open to the SSH agent. Keys that
+/// fail to load are reported by the agent status instead.
+fn load_ssh_agent_keys<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(agent) = app.try_state::<Arc<SshAgentService>>() {
+        let _ = agent.load_database_keys();
+    }
+}
+
+/// Removes the keys of a closed or locked database from the SSH agent.
+fn clear_ssh_agent<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(agent) = app.try_state::<Arc<SshAgentService>>() {
+        agent.clear();
+    }
+}
+
+/// Opens a database with a password. Repeated wrong passwords fail with
+/// [`AppError::UnlockThrottled`] until the backoff delay has passed.
 #[tauri::command]
-pub async fn open_database(
+pub async fn open_database<R: Runtime>(
     path: String,
     password: String,
+    app: AppHandle<R>,
     state: State<'_, Arc<KdbxService>>,
 ) -> Result<DatabaseInfo, AppError> {
-    state.open(&path, &password)
+    let info = state.open(&path, &password)?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
 }
 
-/// Closes the currently open database.
+/// Closes the currently open database. The SSH agent keys and quick unlock
+/// data are cleared afterwards, even if closing fails.
 #[tauri::command]
-pub async fn close_database(state: State<'_, Arc<KdbxService>>) -> Result<(), AppError> {
-    state.close()
+pub async fn close_database<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<(), AppError> {
+    let result = state.close();
+    clear_ssh_agent(&app);
+    // A PIN only ever unlocks the database it was set for. The PIN stops
+    // working even if wiping the sealed key fails, so that failure
+    // is only logged.
+    if let Some(quick_unlock) = app.try_state::<Arc<QuickUnlockService>>() {
+        if let Err(err) = quick_unlock.disable() {
+            log::warn!("Failed to clear quick unlock data: {err}");
+        }
+    }
+    result
 }
 
 /// Create a new KDBX4 database
@@ -52,55 +105,130 @@ pub async fn create_database(
     )
 }
 
+/// Import a legacy KDB (.kdb) database into a new KDBX4 database
+///
+/// # Parameters
+/// - `source`: Path and credentials of the .kdb file
+/// - `path`: File path where the new database will be saved
+/// - `name`: Database name (also used as root group name)
+/// - `password`: Optional password for the new database (required if no keyfile)
+/// - `keyfile_path`: Optional keyfile for the new database
+/// - `options`: Optional creation options (KDF settings, description)
+#[tauri::command]
+pub async fn import_kdb_database(
+    source: KdbImportSource,
+    path: String,
+    name: String,
+    password: Option<String>,
+    keyfile_path: Option<String>,
+    options: Option<DatabaseCreationOptions>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<KdbImportResult, AppError> {
+    state.import_kdb(
+        &source,
+        &path,
+        password.as_deref(),
+        keyfile_path.as_deref(),
+        &name,
+        &options.unwrap_or_default(),
+    )
+}
+
 /// Saves the open database.
 #[tauri::command]
-pub async fn save_database(state: State<'_, Arc<KdbxService>>) -> Result<(), AppError> {
+pub async fn save_database(state: State<'_, Arc<KdbxService>>) -> Result<SaveResult, AppError> {
     state.save()
 }
 
 /// Opens a database with password and keyfile.
 #[tauri::command]
-pub async fn open_database_with_keyfile(
+pub async fn open_database_with_keyfile<R: Runtime>(
     path: String,
     password: String,
     keyfile_path: String,
+    app: AppHandle<R>,
     state: State<'_, Arc<KdbxService>>,
 ) -> Result<DatabaseInfo, AppError> {
-    state.open_with_keyfile(&path, &password, &keyfile_path)
+    let info = state.open_with_keyfile(&path, &password, &keyfile_path)?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
 }
 
 /// Opens a database using only a keyfile.
 #[tauri::command]
-pub async fn open_database_with_keyfile_only(
+pub async fn open_database_with_keyfile_only<R: Runtime>(
     path: String,
     keyfile_path: String,
+    app: AppHandle<R>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<DatabaseInfo, AppError> {
+    let info = state.open_with_keyfile_only(&path, &keyfile_path)?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
+}
+
+/// Opens a database read-only, without taking the file lock. Offered when
+/// another process holds the lock; every change is refused.
+#[tauri::command]
+pub async fn open_database_read_only<R: Runtime>(
+    path: String,
+    password: Option<String>,
+    keyfile_path: Option<String>,
+    app: AppHandle<R>,
     state: State<'_, Arc<KdbxService>>,
 ) -> Result<DatabaseInfo, AppError> {
-    state.open_with_keyfile_only(&path, &keyfile_path)
+    let info = state.open_read_only(&path, password.as_deref(), keyfile_path.as_deref())?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
 }
 
-/// Locks the database session (clears decrypted data but maintains file lock).
+/// Locks the database session: the decrypted data is dropped, the file lock
+/// is kept. Unsaved changes are kept encrypted in memory, not written to the
+/// file, and restored on unlock.
 ///
 /// Note: This is for session locking (UI lock), not file locking.
 /// File locking is automatic when opening/closing databases.
 #[tauri::command]
-pub async fn lock_database() -> Result<(), AppError> {
-    // TODO: Implement session locking (clear decrypted data, keep file lock)
-    Err(AppError::NotImplemented(
-        "lock_database (session lock)".into(),
-    ))
+pub async fn lock_database<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<(), AppError> {
+    let result = state.lock();
+    clear_ssh_agent(&app);
+    result
 }
 
-/// Unlocks the database session with a password.
+/// Unlocks the locked database session with its password. The keyfile it
+/// was opened with is read again; keyfile-only databases pass no password.
 ///
 /// Note: This is for session unlocking (UI unlock), not file unlocking.
 #[tauri::command]
-pub async fn unlock_database(password: String) -> Result<(), AppError> {
-    let _ = password;
-    // TODO: Implement session unlocking (re-decrypt with password)
-    Err(AppError::NotImplemented(
-        "unlock_database (session unlock)".into(),
-    ))
+pub async fn unlock_database<R: Runtime>(
+    password: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<DatabaseInfo, AppError> {
+    let info = state.unlock(password.as_deref())?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
+}
+
+/// Unlocks the locked database session with the quick unlock PIN.
+#[tauri::command]
+pub async fn unlock_database_with_pin<R: Runtime>(
+    pin: SecureString,
+    app: AppHandle<R>,
+    state: State<'_, Arc<KdbxService>>,
+    quick_unlock: State<'_, Arc<QuickUnlockService>>,
+) -> Result<DatabaseInfo, AppError> {
+    let info = quick_unlock.unlock(pin.as_str())?;
+    notify_expiring_entries(&app, &state);
+    load_ssh_agent_keys(&app);
+    Ok(info)
 }
 
 /// Gets the lock status for a database file without opening it.
@@ -146,13 +274,14 @@ pub async fn get_database_config(
 }
 
 /// Gets info about the currently open database, or returns None if no database is open.
+/// A locked database is reported with `is_locked` set.
 #[tauri::command]
 pub async fn get_database_info(
     state: State<'_, Arc<KdbxService>>,
 ) -> Result<Option<DatabaseInfo>, AppError> {
     match state.get_info() {
         Ok(info) => Ok(Some(info)),
-        Err(AppError::DatabaseNotOpen) => Ok(None),
+        Err(AppError::DatabaseNotOpen) => state.locked_info(),
         Err(e) => Err(e),
     }
 }
@@ -1,5 +1,11 @@
-use crate::dto::entry::{CreateEntryData, CustomFieldValue, Entry, UpdateEntryData};
+use crate::commands::database::EXPIRY_WARNING_DAYS;
+use crate::dto::entry::{
+    CreateEntryData, CreateReferenceEntryData, CustomFieldValue, Entry, ExpiringEntry,
+    UpdateEntryData,
+};
 use crate::dto::error::AppError;
+use crate::dto::placeholder::PlaceholderOptions;
+use crate::dto::url_match::{UrlMatch, UrlMatchOptions};
 use crate::services::kdbx::KdbxService;
 use std::sync::Arc;
 use tauri::State;
@@ -48,6 +54,17 @@ pub async fn create_entry(
     state.create_entry(&group_id, data)
 }
 
+/// Creates an entry whose selected fields are `{REF:...}` references to
+/// another entry, so both always share e.g. the same password.
+#[tauri::command]
+pub async fn create_reference_entry(
+    group_id: String,
+    data: CreateReferenceEntryData,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Entry, AppError> {
+    state.create_reference_entry(&group_id, &data)
+}
+
 /// Updates an existing entry.
 #[tauri::command]
 pub async fn update_entry(
@@ -58,10 +75,14 @@ pub async fn update_entry(
     state.update_entry(&id, data)
 }
 
-/// Deletes an entry by ID.
+/// Deletes an entry by ID (moves to recycle bin unless `permanent` is true).
 #[tauri::command]
-pub async fn delete_entry(id: String, state: State<'_, Arc<KdbxService>>) -> Result<(), AppError> {
-    state.delete_entry(&id)
+pub async fn delete_entry(
+    id: String,
+    permanent: Option<bool>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<(), AppError> {
+    state.delete_entry(&id, permanent.unwrap_or(false))
 }
 
 /// Moves an entry to another group.
@@ -73,3 +94,37 @@ pub async fn move_entry(
 ) -> Result<Entry, AppError> {
     state.move_entry(&id, &target_group_id)
 }
+
+/// Lists entries that have expired or expire within `within_days` days
+/// (defaults to the unlock notification window).
+#[tauri::command]
+pub async fn list_expiring_entries(
+    within_days: Option<u32>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Vec<ExpiringEntry>, AppError> {
+    state.list_expiring_entries(within_days.unwrap_or(EXPIRY_WARNING_DAYS))
+}
+
+/// Expands `KeePass` placeholders such as `{USERNAME}` or `{URL:HOST}` in
+/// `text` against an entry. Always runs in safe mode, so the webview cannot
+/// start processes through `{CMD:...}` or replace passwords through
+/// `{NEWPASSWORD}`; those placeholders are left unexpanded.
+#[tauri::command]
+pub async fn expand_placeholders(
+    entry_id: String,
+    text: String,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<String, AppError> {
+    state.expand_placeholders(&entry_id, &text, &PlaceholderOptions::default())
+}
+
+/// Finds entries whose URL matches `url` for autofill, exact host matches
+/// first. By default only the registrable domain has to match.
+#[tauri::command]
+pub async fn find_entries_for_url(
+    url: String,
+    options: Option<UrlMatchOptions>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Vec<UrlMatch>, AppError> {
+    state.find_entries_for_url(&url, &options.unwrap_or_default())
+}
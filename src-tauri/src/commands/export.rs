@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::database::DatabaseCreationOptions;
+use crate::dto::error::AppError;
+use crate::dto::export::{ExportOptions, ExportResult, GroupExportResult};
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Exports entries of the open database to a CSV or JSON file.
+/// Protected values require both `includeProtected` and `acknowledgePlaintext`.
+#[tauri::command]
+pub async fn export_entries(
+    path: String,
+    options: ExportOptions,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<ExportResult, AppError> {
+    state.export_entries(&path, &options)
+}
+
+/// Writes a group subtree to a new KDBX4 database with its own credentials.
+///
+/// # Parameters
+/// - `group_id`: Group to export, including all subgroups
+/// - `path`: File path of the new database
+/// - `password`: Optional password (required if no keyfile)
+/// - `keyfile_path`: Optional path to keyfile for authentication
+/// - `options`: Optional creation options (KDF settings, description)
+#[tauri::command]
+pub async fn export_group(
+    group_id: String,
+    path: String,
+    password: Option<String>,
+    keyfile_path: Option<String>,
+    options: Option<DatabaseCreationOptions>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<GroupExportResult, AppError> {
+    state.export_group(
+        &group_id,
+        &path,
+        password.as_deref(),
+        keyfile_path.as_deref(),
+        &options.unwrap_or_default(),
+    )
+}
@@ -1,54 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::dto::error::AppError;
-use serde::{Deserialize, Serialize};
-
-#[allow(clippy::struct_excessive_bools)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PasswordGeneratorOptions {
-    pub length: usize,
-    pub uppercase: bool,
-    pub lowercase: bool,
-    pub numbers: bool,
-    pub symbols: bool,
-    pub exclude_ambiguous: bool,
-    pub exclude_chars: Option<String>,
-}
-
-impl Default for PasswordGeneratorOptions {
-    fn default() -> Self {
-        Self {
-            length: 20,
-            uppercase: true,
-            lowercase: true,
-            numbers: true,
-            symbols: true,
-            exclude_ambiguous: false,
-            exclude_chars: None,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PassphraseGeneratorOptions {
-    pub word_count: usize,
-    pub separator: String,
-    pub capitalize: bool,
-    pub include_number: bool,
-}
-
-impl Default for PassphraseGeneratorOptions {
-    fn default() -> Self {
-        Self {
-            word_count: 4,
-            separator: "-".into(),
-            capitalize: true,
-            include_number: true,
-        }
-    }
-}
+use crate::dto::generator::{PassphraseGeneratorOptions, PasswordGeneratorOptions};
 
 /// TODO: Generates a random password (not yet implemented).
 #[tauri::command]
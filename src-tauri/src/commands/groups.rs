@@ -40,15 +40,16 @@ pub async fn update_group(
     state.update_group(&id, data)
 }
 
-/// Deletes a group (moves to recycle bin).
-/// Frontend sends just `id`, so `recursive` defaults to false.
+/// Deletes a group (moves to recycle bin unless `permanent` is true).
+/// `recursive` and `permanent` default to false when omitted.
 #[tauri::command]
 pub async fn delete_group(
     id: String,
     recursive: Option<bool>,
+    permanent: Option<bool>,
     state: State<'_, Arc<KdbxService>>,
 ) -> Result<(), AppError> {
-    state.delete_group(&id, recursive.unwrap_or(false), false)
+    state.delete_group(&id, recursive.unwrap_or(false), permanent.unwrap_or(false))
 }
 
 /// Moves a group to a new parent.
@@ -73,6 +74,7 @@ pub async fn rename_group(
         UpdateGroupData {
             name: Some(name),
             icon: None,
+            auto_type: None,
         },
     )
 }
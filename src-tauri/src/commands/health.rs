@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::error::AppError;
+use crate::dto::health::{HealthReport, HealthReportOptions};
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Builds the password health report for the open database.
+/// Omitted options default to a 365-day maximum password age.
+#[tauri::command]
+pub async fn get_health_report(
+    options: Option<HealthReportOptions>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<HealthReport, AppError> {
+    state.health_report(&options.unwrap_or_default())
+}
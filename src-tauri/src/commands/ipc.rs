@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::error::AppError;
+use crate::dto::ipc::{IpcAuditRecord, IpcClient, IpcScopes, IpcStatus};
+use crate::services::ipc::IpcService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Event emitted when a client registers on the IPC socket and waits for
+/// approval. The payload is the pending [`IpcClient`].
+pub const IPC_CLIENT_EVENT: &str = "ipc-client-pending";
+
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+
+/// Returns whether the IPC socket is enabled and the registered clients.
+#[tauri::command]
+pub async fn get_ipc_status(state: State<'_, Arc<IpcService>>) -> Result<IpcStatus, AppError> {
+    state.status()
+}
+
+/// Enables or disables the IPC socket.
+#[tauri::command]
+pub async fn set_ipc_enabled(
+    enabled: bool,
+    state: State<'_, Arc<IpcService>>,
+) -> Result<IpcStatus, AppError> {
+    state.set_enabled(enabled)
+}
+
+/// Approves a pending client with the given scopes, or updates the scopes of
+/// an approved one.
+#[tauri::command]
+pub async fn approve_ipc_client(
+    client_id: String,
+    scopes: IpcScopes,
+    state: State<'_, Arc<IpcService>>,
+) -> Result<IpcClient, AppError> {
+    state.approve_client(&client_id, scopes)
+}
+
+/// Removes a client and invalidates its token.
+#[tauri::command]
+pub async fn revoke_ipc_client(
+    client_id: String,
+    state: State<'_, Arc<IpcService>>,
+) -> Result<(), AppError> {
+    state.revoke_client(&client_id)
+}
+
+/// Returns the most recent IPC requests, oldest first.
+#[tauri::command]
+pub async fn get_ipc_audit_log(
+    limit: Option<usize>,
+    state: State<'_, Arc<IpcService>>,
+) -> Result<Vec<IpcAuditRecord>, AppError> {
+    state.audit_log(limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+}
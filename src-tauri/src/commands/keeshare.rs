@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::error::AppError;
+use crate::dto::keeshare::{ShareInfo, ShareSettings, ShareSyncResult};
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Event emitted when the share watcher imported changed containers. The
+/// payload is the list of [`ShareSyncResult`]s.
+pub const SHARES_IMPORTED_EVENT: &str = "shares-imported";
+
+/// Sets or removes (`settings: null`) the share configuration of a group.
+#[tauri::command]
+pub async fn set_group_share(
+    group_id: String,
+    settings: Option<ShareSettings>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<(), AppError> {
+    state.set_group_share(&group_id, settings.as_ref())
+}
+
+/// Lists the shared groups of the open database.
+#[tauri::command]
+pub async fn list_shares(state: State<'_, Arc<KdbxService>>) -> Result<Vec<ShareInfo>, AppError> {
+    state.list_shares()
+}
+
+/// Returns the public key other databases trust to import this database's
+/// signed containers.
+#[tauri::command]
+pub async fn get_share_public_key(state: State<'_, Arc<KdbxService>>) -> Result<String, AppError> {
+    state.share_public_key()
+}
+
+/// Imports changed containers and re-exports shared groups.
+/// Containers that change on disk are imported by the share watcher.
+#[tauri::command]
+pub async fn sync_shares(
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Vec<ShareSyncResult>, AppError> {
+    state.sync_shares()
+}
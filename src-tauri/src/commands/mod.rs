@@ -1,19 +1,42 @@
 // SPDX-License-Identifier: MIT
 
+pub mod autotype;
+pub mod breach;
+pub mod browser;
 pub mod database;
 pub mod entries;
+pub mod export;
 pub mod generator;
 pub mod groups;
+pub mod health;
+pub mod ipc;
+pub mod keeshare;
+pub mod quick_unlock;
+pub mod recycle_bin;
+pub mod secret_service;
 pub mod secure_storage;
 pub mod settings;
+pub mod ssh_agent;
 
+pub use autotype::*;
+pub use breach::*;
+pub use browser::*;
 pub use database::{
     close_database, create_database, force_unlock_database, get_database_config, get_database_info,
-    get_lock_status, inspect_database, lock_database, open_database, open_database_with_keyfile,
-    open_database_with_keyfile_only, save_database, unlock_database,
+    get_lock_status, import_kdb_database, inspect_database, lock_database, open_database,
+    open_database_read_only, open_database_with_keyfile, open_database_with_keyfile_only,
+    save_database, unlock_database, unlock_database_with_pin,
 };
 pub use entries::*;
+pub use export::*;
 pub use generator::*;
 pub use groups::*;
+pub use health::*;
+pub use ipc::*;
+pub use keeshare::*;
+pub use quick_unlock::*;
+pub use recycle_bin::*;
+pub use secret_service::*;
 pub use secure_storage::*;
 pub use settings::*;
+pub use ssh_agent::*;
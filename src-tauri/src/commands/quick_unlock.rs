@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::quick_unlock::QuickUnlockStatus;
+use crate::services::quick_unlock::{QuickUnlockService, MAX_TTL_SECS};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::State;
+
+/// Sets a PIN that unlocks the open database after it is locked, until the
+/// app exits, `ttl_secs` pass or too many wrong PINs are entered.
+/// `ttl_secs` may be at most [`MAX_TTL_SECS`].
+#[tauri::command]
+pub async fn enable_quick_unlock(
+    pin: SecureString,
+    ttl_secs: Option<u64>,
+    state: State<'_, Arc<QuickUnlockService>>,
+) -> Result<QuickUnlockStatus, AppError> {
+    let ttl = match ttl_secs {
+        Some(secs) if secs > MAX_TTL_SECS => {
+            return Err(AppError::InvalidArguments(format!(
+                "ttl_secs must be at most {MAX_TTL_SECS}"
+            )));
+        }
+        Some(secs) => Duration::from_secs(secs),
+        None => QuickUnlockService::default_ttl(),
+    };
+    state.enable(pin.as_str(), ttl)?;
+    state.status()
+}
+
+/// Wipes the quick unlock PIN.
+#[tauri::command]
+pub async fn disable_quick_unlock(
+    state: State<'_, Arc<QuickUnlockService>>,
+) -> Result<(), AppError> {
+    state.disable()
+}
+
+/// Reports whether the locked database can be unlocked with a PIN.
+#[tauri::command]
+pub async fn get_quick_unlock_status(
+    state: State<'_, Arc<QuickUnlockService>>,
+) -> Result<QuickUnlockStatus, AppError> {
+    state.status()
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::entry::Entry;
+use crate::dto::error::AppError;
+use crate::dto::group::Group;
+use crate::dto::recycle_bin::{RecycleBinItem, RecycleBinSettings};
+use crate::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Lists the entries and groups directly inside the recycle bin.
+#[tauri::command]
+pub async fn list_recycle_bin(
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Vec<RecycleBinItem>, AppError> {
+    state.list_recycle_bin()
+}
+
+/// Restores an entry from the recycle bin into `target_group_id`, or into
+/// the group it was deleted from.
+#[tauri::command]
+pub async fn restore_entry(
+    id: String,
+    target_group_id: Option<String>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Entry, AppError> {
+    state.restore_entry(&id, target_group_id.as_deref())
+}
+
+/// Restores a group from the recycle bin into `target_group_id`, or into
+/// the group it was deleted from.
+#[tauri::command]
+pub async fn restore_group(
+    id: String,
+    target_group_id: Option<String>,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<Group, AppError> {
+    state.restore_group(&id, target_group_id.as_deref())
+}
+
+/// Permanently deletes the recycle bin contents.
+/// Returns the number of removed entries and groups.
+#[tauri::command]
+pub async fn empty_recycle_bin(state: State<'_, Arc<KdbxService>>) -> Result<usize, AppError> {
+    state.empty_recycle_bin()
+}
+
+/// Returns the recycle bin settings of the open database.
+#[tauri::command]
+pub async fn get_recycle_bin_settings(
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<RecycleBinSettings, AppError> {
+    state.get_recycle_bin_settings()
+}
+
+/// Updates the recycle bin settings of the open database.
+#[tauri::command]
+pub async fn update_recycle_bin_settings(
+    settings: RecycleBinSettings,
+    state: State<'_, Arc<KdbxService>>,
+) -> Result<RecycleBinSettings, AppError> {
+    state.update_recycle_bin_settings(&settings)
+}
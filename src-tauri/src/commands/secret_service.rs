@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::error::AppError;
+use crate::dto::secret_service::SecretServiceStatus;
+use crate::services::kdbx::KdbxService;
+use crate::services::secret_service::SecretService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Returns whether the app serves as the system keyring, the designated
+/// group and the approved clients.
+#[tauri::command]
+pub async fn get_secret_service_status(
+    state: State<'_, Arc<SecretService>>,
+) -> Result<SecretServiceStatus, AppError> {
+    state.status()
+}
+
+/// Designates the group holding Secret Service items, or clears the
+/// designation.
+#[tauri::command]
+pub async fn set_secret_service_group(
+    group_id: Option<String>,
+    kdbx: State<'_, Arc<KdbxService>>,
+    state: State<'_, Arc<SecretService>>,
+) -> Result<SecretServiceStatus, AppError> {
+    kdbx.set_secret_service_group(group_id.as_deref())?;
+    state.status()
+}
+
+/// Revokes the approval of a Secret Service client.
+#[tauri::command]
+pub async fn revoke_secret_service_client(
+    client: String,
+    state: State<'_, Arc<SecretService>>,
+) -> Result<(), AppError> {
+    state.revoke_client(&client)
+}
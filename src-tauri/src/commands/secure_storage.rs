@@ -33,8 +33,8 @@ pub async fn has_session_key(
 
 // Note: load_session_key is intentionally NOT exposed as a command.
 // Session keys should remain in the Rust boundary and never be sent to the frontend.
-// Use has_session_key to check presence, and unlock_database_with_session (when implemented)
-// to use the stored key for database operations.
+// Use has_session_key to check presence. Quick unlock keeps its PIN-sealed composite key in
+// the same store and unlocks through unlock_database_with_pin.
 
 /// Clears the stored session key.
 #[tauri::command]
@@ -14,6 +14,7 @@ pub struct RecentDatabase {
     pub last_opened: String,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
@@ -24,6 +25,14 @@ pub struct AppSettings {
     pub start_minimized: bool,
     pub theme: String,
     pub recent_databases: Vec<RecentDatabase>,
+    /// Serves SSH keys from the open database on the agent socket. Off by
+    /// default; applied at startup.
+    #[serde(default)]
+    pub ssh_agent_enabled: bool,
+    /// Offers the open database as the system keyring over D-Bus. Off by
+    /// default; applied at startup.
+    #[serde(default)]
+    pub secret_service_enabled: bool,
 }
 
 impl Default for AppSettings {
@@ -36,6 +45,8 @@ impl Default for AppSettings {
             start_minimized: false,
             theme: "system".into(),
             recent_databases: Vec::new(),
+            ssh_agent_enabled: false,
+            secret_service_enabled: false,
         }
     }
 }
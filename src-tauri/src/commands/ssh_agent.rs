@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+
+use crate::dto::error::AppError;
+use crate::dto::ssh_agent::{SshAgentKey, SshAgentStatus};
+use crate::services::ssh_agent::SshAgentService;
+use std::sync::Arc;
+use tauri::State;
+
+/// Returns the agent socket path, the loaded keys and keys that failed to
+/// load.
+#[tauri::command]
+pub async fn get_ssh_agent_status(
+    state: State<'_, Arc<SshAgentService>>,
+) -> Result<SshAgentStatus, AppError> {
+    state.status()
+}
+
+/// Adds the SSH key of an entry to the agent.
+#[tauri::command]
+pub async fn add_ssh_agent_key(
+    entry_id: String,
+    state: State<'_, Arc<SshAgentService>>,
+) -> Result<SshAgentKey, AppError> {
+    state.add_key(&entry_id)
+}
+
+/// Removes the SSH key of an entry from the agent.
+#[tauri::command]
+pub async fn remove_ssh_agent_key(
+    entry_id: String,
+    state: State<'_, Arc<SshAgentService>>,
+) -> Result<(), AppError> {
+    state.remove_key(&entry_id)
+}
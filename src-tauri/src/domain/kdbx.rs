@@ -3,6 +3,7 @@ use keepass::Database;
 
 use super::secure::SecureString;
 use crate::services::file_lock::FileLock;
+use crate::services::kdbx::key::CompositeKey;
 
 pub struct OpenDatabase {
     pub db: Database,
@@ -10,10 +11,30 @@ pub struct OpenDatabase {
     pub is_modified: bool,
     pub password: Option<SecureString>,
     pub keyfile_path: Option<String>,
+    /// Set after a quick unlock, which restores the composite key but not
+    /// the password. Saving uses it instead of `password`.
+    pub composite_key: Option<CompositeKey>,
     pub version: String,
     /// File lock preventing concurrent access to the database.
     /// This is dropped when the database is closed, releasing the lock.
     pub file_lock: Option<FileLock>,
+    /// Opened without the file lock; every change is refused.
+    pub read_only: bool,
+}
+
+/// A database locked during a session: the decrypted contents are gone, but
+/// the file lock is kept so no other process can take over the file.
+pub struct LockedDatabase {
+    pub path: String,
+    pub name: String,
+    pub root_group_id: String,
+    pub keyfile_path: Option<String>,
+    pub version: String,
+    pub file_lock: Option<FileLock>,
+    pub read_only: bool,
+    /// Changes not yet saved when the database was locked, encrypted as
+    /// KDBX under the database key.
+    pub unsaved: Option<Vec<u8>>,
 }
 
 /// Formats a database version for display.
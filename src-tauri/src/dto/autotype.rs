@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for auto-type configuration and parsed key sequences.
+
+use serde::{Deserialize, Serialize};
+
+/// Sequence typed for a specific target window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTypeAssociation {
+    /// Window title pattern; `*` matches any run of characters
+    pub window: String,
+    /// Sequence for this window; falls back to the default sequence if unset
+    pub sequence: Option<String>,
+}
+
+/// Auto-type settings stored on an entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryAutoType {
+    pub enabled: bool,
+    /// Inherited from the group when unset
+    pub default_sequence: Option<String>,
+    pub associations: Vec<AutoTypeAssociation>,
+}
+
+impl Default for EntryAutoType {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_sequence: None,
+            associations: Vec::new(),
+        }
+    }
+}
+
+/// Auto-type settings stored on a group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAutoType {
+    /// Inherited from the parent group when unset
+    pub enabled: Option<bool>,
+    /// Inherited from the parent group when unset
+    pub default_sequence: Option<String>,
+}
+
+/// Modifier keys held while a key is pressed.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl KeyModifiers {
+    pub fn is_empty(self) -> bool {
+        self == Self::default()
+    }
+
+    /// Combines two sets of modifiers.
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            shift: self.shift || other.shift,
+            ctrl: self.ctrl || other.ctrl,
+            alt: self.alt || other.alt,
+            meta: self.meta || other.meta,
+        }
+    }
+}
+
+/// A key that can be pressed by auto-type.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "name", content = "value")]
+pub enum Key {
+    Tab,
+    Enter,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    Break,
+    Help,
+    Apps,
+    Win,
+    LeftWin,
+    RightWin,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// Numeric keypad digit (0-9)
+    Numpad(u8),
+    /// Function key (F1-F16)
+    Function(u8),
+    /// A printable character, used when it is combined with modifiers
+    Char(char),
+    /// Platform virtual key code from `{VKEY n}`
+    VirtualKey(u16),
+}
+
+/// A single step of a parsed auto-type sequence.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum KeyAction {
+    /// Literal text to type
+    Text { text: String },
+    /// A key press with modifiers held
+    Key { key: Key, modifiers: KeyModifiers },
+    /// A field placeholder such as `{USERNAME}`, resolved by the caller
+    Placeholder { name: String },
+    /// Pause once for the given time
+    Delay { milliseconds: u32 },
+    /// Change the pause between subsequent keystrokes
+    DefaultDelay { milliseconds: u32 },
+    /// Select and clear the contents of the focused field
+    ClearField,
+}
+
+/// The effective auto-type sequence for an entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTypeSequence {
+    pub sequence: String,
+    pub actions: Vec<KeyAction>,
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for the offline breached-password check.
+
+use serde::{Deserialize, Serialize};
+
+/// An entry whose password appears in the breach list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryBreach {
+    pub entry_id: String,
+    pub title: String,
+    /// Number of times the password was seen in breaches
+    pub count: u32,
+}
+
+/// Result of checking all passwords against a local breach list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreachReport {
+    /// Number of entries with a password that were looked up
+    pub checked_count: usize,
+    pub breached: Vec<EntryBreach>,
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for browser extension integration.
+
+use crate::domain::secure::SecureString;
+use serde::{Deserialize, Serialize};
+
+/// A browser extension associated with the open database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserAssociation {
+    /// Name chosen when the extension was associated
+    pub id: String,
+    /// Base64 identification key presented by the extension
+    pub key: String,
+    pub created_at: Option<String>,
+}
+
+/// A login offered to a browser extension.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserLogin {
+    pub entry_id: String,
+    pub title: String,
+    pub username: String,
+    pub password: SecureString,
+    pub group: String,
+}
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MIT
 
+use crate::domain::secure::SecureString;
+use crate::dto::keeshare::ShareSyncResult;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,10 +11,20 @@ pub struct DatabaseInfo {
     pub path: String,
     pub is_modified: bool,
     pub is_locked: bool,
+    pub is_read_only: bool,
     pub root_group_id: String,
     pub version: String,
 }
 
+/// Outcome of a successful save.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveResult {
+    /// Shares whose container could not be written. The database itself
+    /// was saved.
+    pub share_failures: Vec<ShareSyncResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DatabaseStats {
@@ -153,3 +165,31 @@ pub struct DatabaseConfigDto {
     /// Key derivation function settings
     pub kdf: KdfSettings,
 }
+
+/// Location and credentials of a legacy KDB (.kdb) database to import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdbImportSource {
+    /// Path to the .kdb file
+    pub path: String,
+    /// Password of the .kdb file
+    pub password: Option<SecureString>,
+    /// Keyfile of the .kdb file
+    pub keyfile_path: Option<String>,
+}
+
+/// Result of importing a legacy KDB database into a new KDBX4 database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdbImportResult {
+    /// The newly created database, which is now open
+    pub database: DatabaseInfo,
+    /// Number of imported groups
+    pub group_count: usize,
+    /// Number of imported entries (meta-streams excluded)
+    pub entry_count: usize,
+    /// Number of imported attachments
+    pub attachment_count: usize,
+    /// Names of meta-streams without a KDBX equivalent that were dropped
+    pub skipped_meta_streams: Vec<String>,
+}
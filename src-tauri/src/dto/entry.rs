@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::domain::secure::SecureString;
+use crate::dto::autotype::EntryAutoType;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -34,6 +35,13 @@ pub struct Entry {
     pub created_at: String,
     pub modified_at: String,
     pub accessed_at: String,
+    pub expires: bool,
+    /// Expiry time; only set when `expires` is true
+    pub expiry_time: Option<String>,
+    /// Fields whose stored value contains a `{REF:...}` reference; the values
+    /// above are resolved
+    pub reference_fields: Vec<String>,
+    pub auto_type: EntryAutoType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +66,8 @@ pub struct CreateEntryData {
     pub tags: Option<Vec<String>>,
     pub custom_fields: Option<BTreeMap<String, String>>,
     pub protected_custom_fields: Option<BTreeMap<String, SecureString>>,
+    /// Enables expiry at this time (RFC 3339 or `YYYY-MM-DD HH:MM:SS` UTC)
+    pub expiry_time: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,4 +82,55 @@ pub struct UpdateEntryData {
     pub tags: Option<Vec<String>>,
     pub custom_fields: Option<BTreeMap<String, String>>,
     pub protected_custom_fields: Option<BTreeMap<String, SecureString>>,
+    /// `Some(false)` turns expiry off; the stored time is kept
+    pub expires: Option<bool>,
+    /// Sets the expiry time and turns expiry on unless `expires` is `Some(false)`
+    pub expiry_time: Option<String>,
+    /// Replaces the entry's auto-type settings
+    pub auto_type: Option<EntryAutoType>,
+}
+
+/// Standard field that can reference another entry.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceField {
+    Title,
+    Username,
+    Password,
+    Url,
+    Notes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReferenceEntryData {
+    pub source_entry_id: String,
+    /// Fields stored as references to the source entry
+    pub fields: Vec<ReferenceField>,
+    /// Title of the new entry; defaults to a title reference if selected,
+    /// otherwise to a copy of the source title
+    pub title: Option<String>,
+}
+
+/// An entry that has expired or expires soon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiringEntry {
+    pub id: String,
+    pub group_id: String,
+    pub title: String,
+    pub username: String,
+    pub expiry_time: String,
+    pub expired: bool,
+}
+
+/// Payload of the event emitted after unlocking when entries have expired or
+/// expire soon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpirySummary {
+    pub within_days: u32,
+    pub expired_count: usize,
+    pub expiring_count: usize,
+    pub entries: Vec<ExpiringEntry>,
 }
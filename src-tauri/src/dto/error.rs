@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MIT
 
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::dto::lock::LockFileInfoDto;
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database not open")]
@@ -83,11 +86,27 @@ pub enum AppError {
     #[error("Secure storage error: {0}")]
     SecureStorage(String),
 
+    #[error("Quick unlock is not available")]
+    QuickUnlockUnavailable,
+
+    #[error("PIN must be at least {0} characters")]
+    PinTooShort(usize),
+
+    #[error("Incorrect PIN, {0} attempts remaining")]
+    IncorrectPin(u32),
+
+    #[error("Too many failed unlock attempts, retry in {0} seconds")]
+    UnlockThrottled(u64),
+
     #[error("Lock error")]
     Lock,
 
+    /// Carries the lock holder when the lock file could be read.
     #[error("Database is locked: {0}")]
-    DatabaseLocked(String),
+    DatabaseLocked(String, Option<Box<LockFileInfoDto>>),
+
+    #[error("Database is open read-only")]
+    DatabaseReadOnly,
 
     #[error("File lock failed: {0}")]
     FileLockFailed(String),
@@ -100,14 +119,200 @@ pub enum AppError {
 
     #[error("Failed to sync file to disk: {0}")]
     SyncFailed(String),
+
+    #[error("Exporting protected values requires explicit acknowledgement")]
+    PlaintextExportNotAcknowledged,
+
+    #[error("KeeShare error: {0}")]
+    KeeShare(String),
+
+    #[error("Not in recycle bin: {0}")]
+    NotInRecycleBin(String),
+
+    #[error("Breach index error: {0}")]
+    BreachIndex(String),
+
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(String),
+
+    #[error("Field reference error: {0}")]
+    FieldReference(String),
+
+    #[error("Invalid generator options: {0}")]
+    InvalidGeneratorOptions(String),
+
+    #[error("Placeholder error: {0}")]
+    Placeholder(String),
+
+    #[error("Invalid auto-type sequence: {0}")]
+    InvalidAutoTypeSequence(String),
+
+    #[error("Auto-type disabled for entry: {0}")]
+    AutoTypeDisabled(String),
+
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Browser integration error: {0}")]
+    BrowserIntegration(String),
+
+    #[error("IPC client not found: {0}")]
+    IpcClientNotFound(String),
+
+    #[error("IPC error: {0}")]
+    Ipc(String),
+
+    #[error("Attachment not found: {0}")]
+    AttachmentNotFound(String),
+
+    #[error("SSH agent error: {0}")]
+    SshAgent(String),
+
+    #[error("Secret Service error: {0}")]
+    SecretService(String),
+
+    #[error("Secret Service session not found: {0}")]
+    SecretSessionNotFound(String),
+
+    #[error("Secret Service access denied for {0}")]
+    SecretServiceAccessDenied(String),
+
+    #[error("Path matches more than one item: {0}")]
+    AmbiguousPath(String),
+
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+
+    #[error("Template error: {0}")]
+    Template(String),
+}
+
+/// Machine-readable context for an [`AppError`], serialized as its
+/// `details` field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ErrorDetails {
+    #[serde(rename_all = "camelCase")]
+    Entry { entry_id: String },
+    #[serde(rename_all = "camelCase")]
+    Group { group_id: String },
+    #[serde(rename_all = "camelCase")]
+    LockHolder { holder: LockFileInfoDto },
+    #[serde(rename_all = "camelCase")]
+    Retry { retry_after_secs: u64 },
+    #[serde(rename_all = "camelCase")]
+    PinAttempts { attempts_remaining: u32 },
+    #[serde(rename_all = "camelCase")]
+    PinLength { min_length: usize },
+}
+
+impl AppError {
+    /// Returns the stable identifier of the error kind. Codes never change
+    /// once released; the message may.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::DatabaseNotOpen => "databaseNotOpen",
+            Self::DatabaseAlreadyOpen => "databaseAlreadyOpen",
+            Self::InvalidPassword => "invalidPassword",
+            Self::EntryNotFound(..) => "entryNotFound",
+            Self::CustomFieldNotFound(..) => "customFieldNotFound",
+            Self::CustomFieldNotProtected(..) => "customFieldNotProtected",
+            Self::GroupNotFound(..) => "groupNotFound",
+            Self::CannotDeleteRootGroup => "cannotDeleteRootGroup",
+            Self::CannotMoveRootGroup => "cannotMoveRootGroup",
+            Self::CircularReference => "circularReference",
+            Self::GroupNotEmpty(..) => "groupNotEmpty",
+            Self::InvalidPath(..) => "invalidPath",
+            Self::Io(..) => "io",
+            Self::Crypto(..) => "crypto",
+            Self::Kdbx(..) => "kdbx",
+            Self::InvalidKdbxFile => "invalidKdbxFile",
+            Self::UnsupportedKdbxVersion(..) => "unsupportedKdbxVersion",
+            Self::HeaderIntegrityError => "headerIntegrityError",
+            Self::UnsupportedCipher(..) => "unsupportedCipher",
+            Self::UnsupportedKdf(..) => "unsupportedKdf",
+            Self::HeaderParseError(..) => "headerParseError",
+            Self::KeyfileNotFound => "keyfileNotFound",
+            Self::KeyfileInvalid => "keyfileInvalid",
+            Self::NoCredentials => "noCredentials",
+            Self::Keychain(..) => "keychain",
+            Self::SecureStorage(..) => "secureStorage",
+            Self::QuickUnlockUnavailable => "quickUnlockUnavailable",
+            Self::PinTooShort(..) => "pinTooShort",
+            Self::IncorrectPin(..) => "incorrectPin",
+            Self::UnlockThrottled(..) => "unlockThrottled",
+            Self::Lock => "lock",
+            Self::DatabaseLocked(..) => "databaseLocked",
+            Self::DatabaseReadOnly => "databaseReadOnly",
+            Self::FileLockFailed(..) => "fileLockFailed",
+            Self::NotImplemented(..) => "notImplemented",
+            Self::AtomicWrite(..) => "atomicWrite",
+            Self::SyncFailed(..) => "syncFailed",
+            Self::PlaintextExportNotAcknowledged => "plaintextExportNotAcknowledged",
+            Self::KeeShare(..) => "keeshare",
+            Self::NotInRecycleBin(..) => "notInRecycleBin",
+            Self::BreachIndex(..) => "breachIndex",
+            Self::InvalidTimestamp(..) => "invalidTimestamp",
+            Self::FieldReference(..) => "fieldReference",
+            Self::InvalidGeneratorOptions(..) => "invalidGeneratorOptions",
+            Self::Placeholder(..) => "placeholder",
+            Self::InvalidAutoTypeSequence(..) => "invalidAutoTypeSequence",
+            Self::AutoTypeDisabled(..) => "autoTypeDisabled",
+            Self::InvalidUrl(..) => "invalidUrl",
+            Self::BrowserIntegration(..) => "browserIntegration",
+            Self::IpcClientNotFound(..) => "ipcClientNotFound",
+            Self::Ipc(..) => "ipc",
+            Self::AttachmentNotFound(..) => "attachmentNotFound",
+            Self::SshAgent(..) => "sshAgent",
+            Self::SecretService(..) => "secretService",
+            Self::SecretSessionNotFound(..) => "secretSessionNotFound",
+            Self::SecretServiceAccessDenied(..) => "secretServiceAccessDenied",
+            Self::AmbiguousPath(..) => "ambiguousPath",
+            Self::InvalidArguments(..) => "invalidArguments",
+            Self::Template(..) => "template",
+        }
+    }
+
+    /// Returns the typed context of the error, if it has any beyond the
+    /// message.
+    pub fn details(&self) -> Option<ErrorDetails> {
+        match self {
+            Self::EntryNotFound(id) => Some(ErrorDetails::Entry {
+                entry_id: id.clone(),
+            }),
+            Self::GroupNotFound(id) | Self::GroupNotEmpty(id) => Some(ErrorDetails::Group {
+                group_id: id.clone(),
+            }),
+            Self::DatabaseLocked(_, Some(holder)) => Some(ErrorDetails::LockHolder {
+                holder: (**holder).clone(),
+            }),
+            Self::UnlockThrottled(secs) => Some(ErrorDetails::Retry {
+                retry_after_secs: *secs,
+            }),
+            Self::IncorrectPin(remaining) => Some(ErrorDetails::PinAttempts {
+                attempts_remaining: *remaining,
+            }),
+            Self::PinTooShort(length) => Some(ErrorDetails::PinLength {
+                min_length: *length,
+            }),
+            _ => None,
+        }
+    }
 }
 
+/// Errors cross the IPC boundary as `{ code, message, details }`. Until the
+/// next release the frontend wrapper also keeps `String(error)` yielding
+/// the message, for callers that still match on it.
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
     }
 }
 
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Output format of an entry export.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// A column of an entry export.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "field", content = "key", rename_all = "camelCase")]
+pub enum ExportField {
+    Title,
+    Username,
+    Password,
+    Url,
+    Notes,
+    Tags,
+    CreatedAt,
+    ModifiedAt,
+    /// A custom field, identified by its key
+    Custom(String),
+}
+
+impl ExportField {
+    /// Fields exported when the caller does not select any.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::Title,
+            Self::Username,
+            Self::Password,
+            Self::Url,
+            Self::Notes,
+        ]
+    }
+
+    /// Column header used in the exported file.
+    pub fn column_name(&self) -> &str {
+        match self {
+            Self::Title => "Title",
+            Self::Username => "Username",
+            Self::Password => "Password",
+            Self::Url => "URL",
+            Self::Notes => "Notes",
+            Self::Tags => "Tags",
+            Self::CreatedAt => "Created",
+            Self::ModifiedAt => "Modified",
+            Self::Custom(key) => key,
+        }
+    }
+}
+
+/// Options for exporting entries to a plaintext file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Columns to export, in order (default: title, username, password, URL, notes)
+    #[serde(default)]
+    pub fields: Vec<ExportField>,
+    /// Only export entries of this group and its subgroups
+    pub group_id: Option<String>,
+    /// Prepend a column with the entry's group path (e.g. "Root/Email")
+    #[serde(default)]
+    pub include_group_path: bool,
+    /// Write passwords and protected custom fields; left empty otherwise
+    #[serde(default)]
+    pub include_protected: bool,
+    /// Required together with `include_protected`: the caller confirmed that
+    /// secrets will be written unencrypted
+    #[serde(default)]
+    pub acknowledge_plaintext: bool,
+}
+
+/// Summary of a finished export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub path: String,
+    pub entry_count: usize,
+    pub includes_protected: bool,
+}
+
+/// Summary of a group subtree written to a standalone database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupExportResult {
+    pub path: String,
+    pub group_count: usize,
+    pub entry_count: usize,
+    pub attachment_count: usize,
+    pub custom_icon_count: usize,
+}
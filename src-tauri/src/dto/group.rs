@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT
 
+use crate::dto::autotype::GroupAutoType;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +11,7 @@ pub struct Group {
     pub name: String,
     pub icon: Option<String>,
     pub children: Vec<Group>,
+    pub auto_type: GroupAutoType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,4 +27,6 @@ pub struct CreateGroupData {
 pub struct UpdateGroupData {
     pub name: Option<String>,
     pub icon: Option<String>,
+    /// Replaces the group's auto-type settings
+    pub auto_type: Option<GroupAutoType>,
 }
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for the password health report.
+
+use serde::{Deserialize, Serialize};
+
+/// Password quality bands based on estimated entropy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum PasswordQuality {
+    /// Below 40 bits
+    Poor,
+    /// 40 to 65 bits
+    Weak,
+    /// 65 to 100 bits
+    Good,
+    /// 100 bits or more
+    Excellent,
+}
+
+impl PasswordQuality {
+    pub fn from_entropy(bits: f64) -> Self {
+        if bits < 40.0 {
+            Self::Poor
+        } else if bits < 65.0 {
+            Self::Weak
+        } else if bits < 100.0 {
+            Self::Good
+        } else {
+            Self::Excellent
+        }
+    }
+}
+
+/// A problem found with an entry's password.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthIssue {
+    Empty,
+    Weak,
+    Reused,
+    Old,
+    Expired,
+}
+
+/// Options for [`crate::services::kdbx::KdbxService::health_report`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReportOptions {
+    /// Passwords unchanged for longer than this are reported as old
+    pub max_password_age_days: u32,
+}
+
+impl Default for HealthReportOptions {
+    fn default() -> Self {
+        Self {
+            max_password_age_days: 365,
+        }
+    }
+}
+
+/// Health of a single entry's password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryHealth {
+    pub entry_id: String,
+    pub group_id: String,
+    pub title: String,
+    /// Estimated entropy in bits
+    pub entropy: f64,
+    pub quality: PasswordQuality,
+    /// When the current password was set, as far as history shows
+    pub password_changed_at: Option<String>,
+    /// Number of other entries using the same password
+    pub reuse_count: usize,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// Entries sharing the same password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReusedPasswordGroup {
+    pub entry_ids: Vec<String>,
+}
+
+/// Password health of all entries outside the recycle bin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub entries: Vec<EntryHealth>,
+    pub reused: Vec<ReusedPasswordGroup>,
+    /// Entries flagged to be excluded from reports
+    pub excluded_entry_ids: Vec<String>,
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for the local IPC socket used by scripts and other apps.
+
+use serde::{Deserialize, Serialize};
+
+/// What an IPC client may access.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcScopes {
+    /// Groups the client may read, including their subgroups; empty denies
+    /// every request
+    pub allowed_groups: Vec<String>,
+    /// Rejects requests that would change the database
+    pub read_only: bool,
+}
+
+/// A client that registered with the IPC socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcClient {
+    pub id: String,
+    /// Name the client gave when registering
+    pub name: String,
+    /// Tokens of unapproved clients are rejected
+    pub approved: bool,
+    pub scopes: IpcScopes,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+/// Whether the IPC socket is enabled and who may use it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcStatus {
+    pub enabled: bool,
+    /// Set while the socket is listening
+    pub socket_path: Option<String>,
+    pub clients: Vec<IpcClient>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IpcAuditOutcome {
+    Allowed,
+    /// Rejected for missing authentication or scope
+    Denied,
+    Failed,
+}
+
+/// One request received on the IPC socket. Field values are never logged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcAuditRecord {
+    pub timestamp: String,
+    pub client_id: Option<String>,
+    pub client_name: Option<String>,
+    pub method: String,
+    /// Entry ID or search text the request was about
+    pub target: Option<String>,
+    pub outcome: IpcAuditOutcome,
+    pub error: Option<String>,
+}
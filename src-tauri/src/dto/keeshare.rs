@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT
+
+use crate::domain::secure::SecureString;
+use serde::{Deserialize, Serialize};
+
+/// Direction in which a shared group is kept in step with its container.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShareMode {
+    /// Container contents are merged into the group
+    Import,
+    /// The group is written to the container on save
+    Export,
+    /// Both import and export
+    Synchronize,
+}
+
+impl ShareMode {
+    pub fn imports(self) -> bool {
+        matches!(self, Self::Import | Self::Synchronize)
+    }
+
+    pub fn exports(self) -> bool {
+        matches!(self, Self::Export | Self::Synchronize)
+    }
+}
+
+/// Share settings of a group, as stored in its `KeeShare/Reference` custom data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareSettings {
+    pub mode: ShareMode,
+    /// Container path; relative paths are resolved against the database directory.
+    /// A `.kdbx.share` extension selects a signed container.
+    pub path: String,
+    /// Password of the container database
+    pub password: Option<SecureString>,
+    /// Keep the subgroup structure when importing (default: true)
+    #[serde(default = "default_keep_groups")]
+    pub keep_groups: bool,
+    /// PEM public keys whose signed containers are imported, in addition to
+    /// this database's own key
+    #[serde(default)]
+    pub trusted_signers: Vec<String>,
+    /// Import containers that carry no signature
+    #[serde(default)]
+    pub allow_unsigned: bool,
+}
+
+fn default_keep_groups() -> bool {
+    true
+}
+
+/// Share settings of a group without the container password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareInfo {
+    pub group_id: String,
+    pub mode: ShareMode,
+    pub path: String,
+    pub is_signed: bool,
+    pub keep_groups: bool,
+    pub trusted_signers: Vec<String>,
+    pub allow_unsigned: bool,
+}
+
+/// Signature state of an imported container.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShareSignature {
+    /// Plain `.kdbx` container
+    Unsigned,
+    /// Signed by this database's own share key or a trusted signer
+    Verified,
+    /// Signed by a key the share does not trust, or tampered with
+    Unverified,
+}
+
+/// Outcome of a share import or export.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShareSyncStatus {
+    Imported,
+    Exported,
+    /// The container does not exist yet
+    Skipped,
+    Failed,
+}
+
+/// Result of synchronizing one shared group with its container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareSyncResult {
+    pub group_id: String,
+    pub path: String,
+    pub status: ShareSyncStatus,
+    pub signature: Option<ShareSignature>,
+    pub message: Option<String>,
+}
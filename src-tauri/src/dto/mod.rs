@@ -1,13 +1,41 @@
 // SPDX-License-Identifier: MIT
 
+pub mod autotype;
+pub mod breach;
+pub mod browser;
 pub mod database;
 pub mod entry;
 pub mod error;
+pub mod export;
+pub mod generator;
 pub mod group;
+pub mod health;
+pub mod ipc;
+pub mod keeshare;
 pub mod lock;
+pub mod placeholder;
+pub mod quick_unlock;
+pub mod recycle_bin;
+pub mod secret_service;
+pub mod ssh_agent;
+pub mod url_match;
 
+pub use autotype::*;
+pub use breach::*;
+pub use browser::*;
 pub use database::*;
 pub use entry::*;
 pub use error::*;
+pub use export::*;
+pub use generator::*;
 pub use group::*;
+pub use health::*;
+pub use ipc::*;
+pub use keeshare::*;
 pub use lock::*;
+pub use placeholder::*;
+pub use quick_unlock::*;
+pub use recycle_bin::*;
+pub use secret_service::*;
+pub use ssh_agent::*;
+pub use url_match::*;
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for placeholder expansion.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceholderOptions {
+    /// Leaves side-effecting placeholders (`{NEWPASSWORD}`, `{CMD:...}`)
+    /// unexpanded
+    pub safe_mode: bool,
+}
+
+impl Default for PlaceholderOptions {
+    fn default() -> Self {
+        Self { safe_mode: true }
+    }
+}
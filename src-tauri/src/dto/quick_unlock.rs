@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for unlocking a locked database with a PIN.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether the locked database can be unlocked with a PIN.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickUnlockStatus {
+    /// A PIN is set and has neither expired nor been wiped
+    pub enabled: bool,
+    /// The database the PIN unlocks
+    pub path: Option<String>,
+    /// Wrong PINs allowed before the PIN is wiped
+    pub attempts_remaining: u32,
+    /// Seconds until the PIN expires
+    pub expires_in_secs: Option<u64>,
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for recycle bin management.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether a recycle bin item is an entry or a group.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RecycleBinItemKind {
+    Entry,
+    Group,
+}
+
+/// An entry or group directly inside the recycle bin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecycleBinItem {
+    pub id: String,
+    pub kind: RecycleBinItemKind,
+    /// Entry title or group name
+    pub name: String,
+    /// Group the item was deleted from, if recorded
+    pub previous_parent_id: Option<String>,
+    /// When the item was moved into the bin
+    pub deleted_at: Option<String>,
+    /// Number of entries inside a deleted group (0 for entries)
+    pub entry_count: usize,
+}
+
+/// Recycle bin settings stored in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecycleBinSettings {
+    /// When false, deleted entries and groups are removed permanently
+    pub enabled: bool,
+    /// Items older than this many days are permanently deleted on save.
+    /// `None` keeps items until the bin is emptied.
+    pub retention_days: Option<u32>,
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for the Freedesktop Secret Service provider.
+
+use crate::domain::secure::SecureString;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// An entry of the Secret Service group as seen by D-Bus clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretItem {
+    pub id: String,
+    /// The entry title
+    pub label: String,
+    /// Lookup attributes, stored as the entry's unprotected custom fields
+    pub attributes: BTreeMap<String, String>,
+    pub content_type: String,
+    /// Unix timestamps in seconds
+    pub created: u64,
+    pub modified: u64,
+}
+
+/// The collection backing the Secret Service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretCollection {
+    /// Group ID; unset until a group is designated or the first item is
+    /// stored
+    pub group_id: Option<String>,
+    pub label: String,
+    pub created: u64,
+    pub modified: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretServiceStatus {
+    /// The app owns `org.freedesktop.secrets` on the session bus
+    pub active: bool,
+    /// Designated group of the open database
+    pub group_id: Option<String>,
+    pub group_name: Option<String>,
+    /// Executables allowed to use the Secret Service until the app quits
+    pub approved_clients: Vec<String>,
+}
+
+/// Changes to a Secret Service item; unset fields are left alone.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSecretItem {
+    pub label: Option<String>,
+    /// Replaces all attributes
+    pub attributes: Option<BTreeMap<String, String>>,
+    pub secret: Option<SecureString>,
+    pub content_type: Option<String>,
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for the built-in SSH agent.
+
+use serde::{Deserialize, Serialize};
+
+/// A key the SSH agent currently offers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshAgentKey {
+    pub entry_id: String,
+    pub title: String,
+    /// Key comment, or the entry title when the key has none
+    pub comment: String,
+    /// SSH algorithm name, e.g. `ssh-ed25519`
+    pub algorithm: String,
+    /// `SHA256:` fingerprint as printed by `ssh-add -l`
+    pub fingerprint: String,
+    /// Every signature has to be confirmed by the user
+    pub confirm: bool,
+    /// When the key is removed again, if it has a lifetime
+    pub expires_at: Option<String>,
+}
+
+/// An entry whose key could not be added to the agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshKeyLoadError {
+    pub entry_id: String,
+    pub title: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshAgentStatus {
+    /// Value for `SSH_AUTH_SOCK`; unset when the agent is not listening
+    pub socket_path: Option<String>,
+    pub keys: Vec<SshAgentKey>,
+    /// Keys that failed to load when the database was opened
+    pub load_errors: Vec<SshKeyLoadError>,
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT
+
+//! DTOs for finding entries that match a site URL.
+
+use serde::{Deserialize, Serialize};
+
+/// Extra URL parts that must match besides the domain.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlMatchOptions {
+    /// Require the same scheme, e.g. do not offer `http` entries on `https`
+    pub match_scheme: bool,
+    /// Require the same port, with default ports filled in
+    pub match_port: bool,
+    /// Require the entry URL's path to be a prefix of the site's path
+    pub match_path: bool,
+}
+
+/// How closely an entry URL matches the site, best first.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum UrlMatchKind {
+    /// Same host name
+    ExactHost,
+    /// Different host with the same registrable domain
+    Domain,
+}
+
+/// An entry with a URL that matches the site.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlMatch {
+    pub id: String,
+    pub group_id: String,
+    pub title: String,
+    pub username: String,
+    /// The entry URL that gave the best match
+    pub matched_url: String,
+    pub match_kind: UrlMatchKind,
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT
 
+pub mod cli;
 pub mod commands;
 pub mod domain;
 pub mod dto;
@@ -8,21 +9,35 @@ pub mod utils;
 
 use crate::dto::error::AppError;
 use commands::{
-    add_recent_database, calculate_password_strength, clear_recent_databases, clear_session_key,
-    close_database, create_database, create_entry, create_group, delete_entry, delete_group,
-    force_unlock_database, generate_passphrase, generate_password, get_database_config,
-    get_database_info, get_entry, get_entry_password, get_entry_protected_custom_field, get_group,
-    get_keyfile_for_database, get_lock_status, get_settings, has_session_key, inspect_database,
-    list_entries, list_groups, lock_database, move_entry, move_group, open_database,
-    open_database_with_keyfile, open_database_with_keyfile_only, remove_recent_database,
-    rename_group, save_database, store_session_key, unlock_database, update_entry, update_group,
-    update_settings,
+    add_recent_database, add_ssh_agent_key, approve_ipc_client, build_breach_index,
+    calculate_password_strength, check_breached_passwords, clear_recent_databases,
+    clear_session_key, close_database, create_database, create_entry, create_group,
+    create_reference_entry, delete_entry, delete_group, disable_quick_unlock, empty_recycle_bin,
+    enable_quick_unlock, expand_placeholders, export_entries, export_group, find_entries_for_url,
+    force_unlock_database, generate_passphrase, generate_password, get_auto_type_sequence,
+    get_database_config, get_database_info, get_entry, get_entry_password,
+    get_entry_protected_custom_field, get_group, get_health_report, get_ipc_audit_log,
+    get_ipc_status, get_keyfile_for_database, get_lock_status, get_quick_unlock_status,
+    get_recycle_bin_settings, get_secret_service_status, get_settings, get_share_public_key,
+    get_ssh_agent_status, has_session_key, import_kdb_database, inspect_database,
+    list_browser_associations, list_entries, list_expiring_entries, list_groups, list_recycle_bin,
+    list_shares, lock_database, move_entry, move_group, open_database, open_database_read_only,
+    open_database_with_keyfile, open_database_with_keyfile_only, parse_auto_type_sequence,
+    remove_browser_association, remove_recent_database, remove_ssh_agent_key, rename_group,
+    restore_entry, restore_group, revoke_ipc_client, revoke_secret_service_client, save_database,
+    set_group_share, set_ipc_enabled, set_secret_service_group, store_session_key, sync_shares,
+    unlock_database, unlock_database_with_pin, update_entry, update_group,
+    update_recycle_bin_settings, update_settings,
 };
+use services::ipc::IpcService;
 use services::kdbx::KdbxService;
+use services::quick_unlock::QuickUnlockService;
 use services::secure_storage::SecureStorageService;
 use services::settings::SettingsService;
+use services::share_watcher::ShareWatcher;
+use services::unlock_throttle::UnlockThrottleService;
 use std::sync::Arc;
-use tauri::{Manager, Runtime};
+use tauri::{Emitter, Manager, Runtime};
 
 #[doc(hidden)]
 pub fn build_app<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
@@ -34,11 +49,14 @@ pub fn build_app<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
             open_database,
             open_database_with_keyfile,
             open_database_with_keyfile_only,
+            open_database_read_only,
             close_database,
             create_database,
+            import_kdb_database,
             save_database,
             lock_database,
             unlock_database,
+            unlock_database_with_pin,
             get_lock_status,
             force_unlock_database,
             inspect_database,
@@ -49,9 +67,15 @@ pub fn build_app<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
             get_entry_password,
             get_entry_protected_custom_field,
             create_entry,
+            create_reference_entry,
             update_entry,
             delete_entry,
             move_entry,
+            list_expiring_entries,
+            expand_placeholders,
+            find_entries_for_url,
+            export_entries,
+            export_group,
             list_groups,
             get_group,
             create_group,
@@ -59,6 +83,34 @@ pub fn build_app<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
             delete_group,
             move_group,
             rename_group,
+            set_group_share,
+            list_shares,
+            get_share_public_key,
+            sync_shares,
+            list_recycle_bin,
+            restore_entry,
+            restore_group,
+            empty_recycle_bin,
+            get_recycle_bin_settings,
+            update_recycle_bin_settings,
+            get_health_report,
+            check_breached_passwords,
+            build_breach_index,
+            list_browser_associations,
+            remove_browser_association,
+            get_ipc_status,
+            set_ipc_enabled,
+            approve_ipc_client,
+            revoke_ipc_client,
+            get_ipc_audit_log,
+            get_ssh_agent_status,
+            add_ssh_agent_key,
+            remove_ssh_agent_key,
+            get_secret_service_status,
+            set_secret_service_group,
+            revoke_secret_service_client,
+            get_auto_type_sequence,
+            parse_auto_type_sequence,
             generate_password,
             generate_passphrase,
             calculate_password_strength,
@@ -71,23 +123,169 @@ pub fn build_app<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
             store_session_key,
             has_session_key,
             clear_session_key,
+            enable_quick_unlock,
+            disable_quick_unlock,
+            get_quick_unlock_status,
         ])
 }
 
 #[doc(hidden)]
 pub fn register_services<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<(), AppError> {
-    let secure_storage = SecureStorageService::new(app)?;
-    app.manage(Arc::new(secure_storage));
+    let secure_storage = Arc::new(SecureStorageService::new(app)?);
+    app.manage(Arc::clone(&secure_storage));
 
-    let kdbx_service = KdbxService::new();
-    app.manage(Arc::new(kdbx_service));
+    let data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let kdbx_service =
+        Arc::new(KdbxService::new().with_unlock_throttle(UnlockThrottleService::new(&data_dir)?));
+    app.manage(Arc::clone(&kdbx_service));
+    app.manage(Arc::new(QuickUnlockService::new(
+        Arc::clone(&kdbx_service),
+        secure_storage,
+    )));
 
     let settings_service = SettingsService::new(app)?;
+    let settings = settings_service.get_settings()?;
     app.manage(Arc::new(settings_service));
 
+    let handle = app.clone();
+    let ipc_service = IpcService::new(
+        Arc::clone(&kdbx_service),
+        &data_dir,
+        services::ipc::socket_path(),
+    )?
+    .with_registration_listener(move |client| {
+        let _ = handle.emit(commands::IPC_CLIENT_EVENT, client);
+    });
+    let ipc_service = Arc::new(ipc_service);
+    if let Err(err) = ipc_service.apply_enabled() {
+        log::warn!("IPC socket disabled: {err}");
+    }
+    app.manage(ipc_service);
+
+    let handle = app.clone();
+    let watcher = ShareWatcher::new(Arc::clone(&kdbx_service));
+    if let Err(err) = watcher.start(move |results| {
+        let _ = handle.emit(commands::SHARES_IMPORTED_EVENT, results);
+    }) {
+        log::warn!("Share watcher disabled: {err}");
+    }
+
+    #[cfg(unix)]
+    start_ssh_agent(app, Arc::clone(&kdbx_service), settings.ssh_agent_enabled);
+
+    start_secret_service(
+        app,
+        Arc::clone(&kdbx_service),
+        settings.secret_service_enabled,
+    );
+
+    #[cfg(unix)]
+    start_browser_integration(app, kdbx_service);
+
     Ok(())
 }
 
+/// Shows a blocking Allow/Deny dialog for a client asking to use the open
+/// database. Called from service threads, never from the main thread.
+fn confirm_access<R: Runtime>(app: &tauri::AppHandle<R>, title: &str, message: String) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    app.dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Deny".to_string(),
+        ))
+        .blocking_show()
+}
+
+/// Serves browser extensions through the native messaging host. Failing to
+/// bind the socket only disables browser integration.
+#[cfg(unix)]
+fn start_browser_integration<R: Runtime>(app: &tauri::AppHandle<R>, kdbx: Arc<KdbxService>) {
+    use services::browser::{server, BrowserService};
+
+    let handle = app.clone();
+    let browser_service = BrowserService::new(kdbx, move |id| {
+        confirm_access(
+            &handle,
+            "Browser Integration",
+            format!(
+                "A browser extension wants to connect to this database as \"{id}\". \
+                 Allow it to read and save logins?"
+            ),
+        )
+    });
+
+    let path = server::socket_path();
+    if let Err(err) = server::start(Arc::new(browser_service), &path) {
+        log::warn!("Browser integration disabled: {err}");
+    }
+}
+
+/// Serves SSH keys from the open database on the agent socket when enabled
+/// in the settings. Failing to bind the socket only disables the agent.
+#[cfg(unix)]
+fn start_ssh_agent<R: Runtime>(app: &tauri::AppHandle<R>, kdbx: Arc<KdbxService>, enabled: bool) {
+    use services::ssh_agent::{server, socket_path, SshAgentService};
+
+    let handle = app.clone();
+    let agent = Arc::new(SshAgentService::new(kdbx, socket_path(), move |key| {
+        confirm_access(
+            &handle,
+            "SSH Agent",
+            format!("Allow an SSH client to use the key {key}?"),
+        )
+    }));
+    if enabled {
+        if let Err(err) = server::start(Arc::clone(&agent)) {
+            log::warn!("SSH agent disabled: {err}");
+        }
+    }
+    app.manage(agent);
+}
+
+/// Offers the open database as the system keyring over D-Bus when enabled
+/// in the settings. Another keyring owning the bus name only disables the
+/// provider.
+fn start_secret_service<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    kdbx: Arc<KdbxService>,
+    enabled: bool,
+) {
+    use services::secret_service::SecretService;
+
+    let handle = app.clone();
+    let service = Arc::new(SecretService::new(kdbx, move |client| {
+        confirm_access(
+            &handle,
+            "Secret Service",
+            format!(
+                "{client} wants to use the Secret Service to store and read secrets \
+                 in this database. Allow it until MithrilVault quits?"
+            ),
+        )
+    }));
+
+    #[cfg(target_os = "linux")]
+    if enabled {
+        match services::secret_service::dbus::start(&service, None) {
+            Ok(connection) => {
+                app.manage(connection);
+            }
+            Err(err) => log::warn!("Secret Service disabled: {err}"),
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = enabled;
+    app.manage(service);
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 #[allow(clippy::expect_used)]
 /// Runs the Tauri application.
@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: MIT
+
+//! Offline lookups in a local copy of the Have I Been Pwned password list.
+//!
+//! Two file formats are supported:
+//! - the SHA-1 "ordered by hash" text download (`HASH:COUNT` per line)
+//! - a compact binary index built from it with [`build_index`]: an 8-byte
+//!   magic followed by sorted 24-byte records (20-byte hash, little-endian
+//!   `u32` count)
+//!
+//! Both are searched with a binary search over the file, so nothing is held
+//! in memory and no hash ever leaves the machine.
+
+use crate::dto::error::AppError;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// Magic bytes at the start of a binary index file.
+const INDEX_MAGIC: &[u8; 8] = b"MVHIBP1\0";
+
+const HASH_LEN: usize = 20;
+const RECORD_LEN: u64 = 24;
+
+pub type Sha1Hash = [u8; HASH_LEN];
+
+/// SHA-1 of a password, as used by the breach list.
+pub fn password_hash(password: &str) -> Sha1Hash {
+    Sha1::digest(password.as_bytes()).into()
+}
+
+enum Format {
+    Text { len: u64 },
+    Binary { records: u64 },
+}
+
+/// An opened breach list in either supported format.
+pub struct BreachIndex {
+    reader: BufReader<File>,
+    format: Format,
+}
+
+impl BreachIndex {
+    /// Opens a text hash list or binary index, detected by its magic bytes.
+    pub fn open(path: &str) -> Result<Self, AppError> {
+        let mut file = File::open(path)
+            .map_err(|e| AppError::BreachIndex(format!("Cannot open {path}: {e}")))?;
+        let len = file.metadata()?.len();
+
+        let mut magic = [0u8; INDEX_MAGIC.len()];
+        let is_binary = len >= magic.len() as u64
+            && file.read_exact(&mut magic).is_ok()
+            && &magic == INDEX_MAGIC;
+
+        let format = if is_binary {
+            let body = len - INDEX_MAGIC.len() as u64;
+            if !body.is_multiple_of(RECORD_LEN) {
+                return Err(AppError::BreachIndex(format!(
+                    "Truncated binary index: {path}"
+                )));
+            }
+            Format::Binary {
+                records: body / RECORD_LEN,
+            }
+        } else {
+            Format::Text { len }
+        };
+
+        Ok(Self {
+            reader: BufReader::new(file),
+            format,
+        })
+    }
+
+    /// Returns how often `hash` appears in breaches, or `None` if it is not
+    /// listed.
+    pub fn lookup(&mut self, hash: &Sha1Hash) -> Result<Option<u32>, AppError> {
+        match self.format {
+            Format::Binary { records } => self.lookup_binary(records, hash),
+            Format::Text { len } => self.lookup_text(len, hash),
+        }
+    }
+
+    fn lookup_binary(&mut self, records: u64, hash: &Sha1Hash) -> Result<Option<u32>, AppError> {
+        let mut lo = 0;
+        let mut hi = records;
+        let mut record = [0u8; HASH_LEN + 4];
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            self.reader
+                .seek(SeekFrom::Start(INDEX_MAGIC.len() as u64 + mid * RECORD_LEN))?;
+            self.reader.read_exact(&mut record)?;
+
+            match record[..HASH_LEN].cmp(hash) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => {
+                    let count = record[HASH_LEN..]
+                        .try_into()
+                        .map(u32::from_le_bytes)
+                        .map_err(|_| AppError::BreachIndex("Corrupt record".into()))?;
+                    return Ok(Some(count));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Binary search over byte offsets. `lo` is always the start of a line
+    /// and no matching line starts at or after `hi`.
+    fn lookup_text(&mut self, len: u64, hash: &Sha1Hash) -> Result<Option<u32>, AppError> {
+        let mut lo = 0;
+        let mut hi = len;
+        let mut line = Vec::new();
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let line_start = self.next_line_start(mid)?;
+            if line_start >= hi {
+                hi = mid;
+                continue;
+            }
+
+            line.clear();
+            self.reader.seek(SeekFrom::Start(line_start))?;
+            let read = self.reader.read_until(b'\n', &mut line)?;
+            let (line_hash, count) = parse_line(&line)?;
+
+            match line_hash.cmp(hash) {
+                Ordering::Less => lo = line_start + read as u64,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Ok(Some(count)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Offset of the first line starting at or after `offset`.
+    fn next_line_start(&mut self, offset: u64) -> Result<u64, AppError> {
+        if offset == 0 {
+            return Ok(0);
+        }
+        self.reader.seek(SeekFrom::Start(offset - 1))?;
+        let mut skipped = Vec::new();
+        let read = self.reader.read_until(b'\n', &mut skipped)?;
+        Ok(offset - 1 + read as u64)
+    }
+}
+
+/// Converts the text hash list at `source_path` into a binary index at
+/// `index_path`. Returns the number of hashes written.
+///
+/// The source must be sorted by hash; the conversion fails otherwise since
+/// lookups rely on the order.
+pub fn build_index(source_path: &str, index_path: &str) -> Result<u64, AppError> {
+    let source = File::open(source_path)
+        .map_err(|e| AppError::BreachIndex(format!("Cannot open {source_path}: {e}")))?;
+    let mut reader = BufReader::new(source);
+    let mut count = 0;
+
+    atomic_write(index_path, &AtomicWriteOptions::default(), |file| {
+        let mut writer = BufWriter::new(file);
+        writer.write_all(INDEX_MAGIC)?;
+
+        let mut previous: Option<Sha1Hash> = None;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+
+            let (hash, occurrences) = parse_line(&line)?;
+            if previous.is_some_and(|p| p >= hash) {
+                return Err(AppError::BreachIndex(format!(
+                    "Hash list is not sorted by hash at line {}",
+                    count + 1
+                )));
+            }
+            writer.write_all(&hash)?;
+            writer.write_all(&occurrences.to_le_bytes())?;
+            previous = Some(hash);
+            count += 1;
+        }
+
+        writer.flush()?;
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+/// Parses a `HASH:COUNT` line, ignoring surrounding whitespace.
+fn parse_line(line: &[u8]) -> Result<(Sha1Hash, u32), AppError> {
+    let invalid = || {
+        AppError::BreachIndex(format!(
+            "Invalid hash list line: {}",
+            String::from_utf8_lossy(line).trim()
+        ))
+    };
+
+    let line = std::str::from_utf8(line).map_err(|_| invalid())?.trim();
+    let (hex, count) = line.split_once(':').ok_or_else(invalid)?;
+    if hex.len() != HASH_LEN * 2 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut hash = [0u8; HASH_LEN];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    // Counts beyond u32 don't occur in practice; saturate rather than fail
+    let count = count
+        .parse::<u64>()
+        .map_err(|_| invalid())?
+        .try_into()
+        .unwrap_or(u32::MAX);
+
+    Ok((hash, count))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+
+    fn hex(hash: &Sha1Hash) -> String {
+        hash.iter().fold(String::new(), |mut out, byte| {
+            let _ = write!(out, "{byte:02X}");
+            out
+        })
+    }
+
+    /// Writes a sorted hash list containing the given passwords and some
+    /// filler hashes. Returns the temp dir and file path.
+    fn write_hash_list(passwords: &[(&str, u32)]) -> (tempfile::TempDir, String) {
+        let mut lines: Vec<(Sha1Hash, u32)> = passwords
+            .iter()
+            .map(|(password, count)| (password_hash(password), *count))
+            .collect();
+        lines.extend((0..200).map(|i| (password_hash(&format!("filler-{i}")), i + 1)));
+        lines.sort_unstable();
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("pwned.txt");
+        let content = lines.iter().fold(String::new(), |mut out, (hash, count)| {
+            let _ = write!(out, "{}:{count}\r\n", hex(hash));
+            out
+        });
+        std::fs::write(&path, content).expect("write list");
+        (dir, path.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_password_hash_matches_known_value() {
+        assert_eq!(
+            hex(&password_hash("password")),
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+        );
+    }
+
+    #[test]
+    fn test_lookup_in_text_list() {
+        let (_dir, path) = write_hash_list(&[("password", 42), ("hunter2", 7)]);
+        let mut index = BreachIndex::open(&path).expect("open list");
+
+        assert_eq!(
+            index.lookup(&password_hash("password")).expect("lookup"),
+            Some(42)
+        );
+        assert_eq!(
+            index.lookup(&password_hash("hunter2")).expect("lookup"),
+            Some(7)
+        );
+        assert_eq!(
+            index.lookup(&password_hash("filler-0")).expect("lookup"),
+            Some(1)
+        );
+        assert_eq!(
+            index.lookup(&password_hash("not listed")).expect("lookup"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lookup_first_and_last_lines() {
+        let (_dir, path) = write_hash_list(&[]);
+        let content = std::fs::read_to_string(&path).expect("read list");
+        let mut index = BreachIndex::open(&path).expect("open list");
+
+        for line in [content.lines().next(), content.lines().last()] {
+            let (hash, count) = parse_line(line.expect("line").as_bytes()).expect("parse");
+            assert_eq!(index.lookup(&hash).expect("lookup"), Some(count));
+        }
+        assert_eq!(index.lookup(&[0u8; HASH_LEN]).expect("lookup"), None);
+        assert_eq!(index.lookup(&[0xFF; HASH_LEN]).expect("lookup"), None);
+    }
+
+    #[test]
+    fn test_build_index_and_lookup() {
+        let (dir, path) = write_hash_list(&[("password", 42)]);
+        let index_path = dir.path().join("pwned.bin").to_string_lossy().into_owned();
+
+        assert_eq!(build_index(&path, &index_path).expect("build index"), 201);
+        let mut index = BreachIndex::open(&index_path).expect("open index");
+        assert!(matches!(index.format, Format::Binary { records: 201 }));
+        assert_eq!(
+            index.lookup(&password_hash("password")).expect("lookup"),
+            Some(42)
+        );
+        assert_eq!(
+            index.lookup(&password_hash("not listed")).expect("lookup"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_build_index_rejects_unsorted_list() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("pwned.txt");
+        let index_path = dir.path().join("pwned.bin");
+        std::fs::write(
+            &path,
+            format!(
+                "{}:1\n{}:1\n",
+                "F".repeat(HASH_LEN * 2),
+                "0".repeat(HASH_LEN * 2)
+            ),
+        )
+        .expect("write list");
+
+        let result = build_index(&path.to_string_lossy(), &index_path.to_string_lossy());
+        assert!(matches!(result, Err(AppError::BreachIndex(_))));
+        assert!(!index_path.exists());
+    }
+
+    #[test]
+    fn test_parse_line_rejects_garbage() {
+        assert!(parse_line(b"not a hash").is_err());
+        assert!(parse_line(b"ABC:1").is_err());
+        assert!(parse_line(format!("{}:x", "0".repeat(40)).as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: MIT
+
+//! Browser extension integration over the `KeePassXC-Browser` protocol.
+//!
+//! Extensions talk to the `mithril-native-host` binary through native
+//! messaging; the host relays each message to the app over a local socket
+//! (see [`server`]) where [`BrowserService`] answers it.
+//!
+//! Apart from the initial key exchange, messages are encrypted with `NaCl`
+//! `crypto_box` between the extension's session key and a key generated for
+//! the session. An extension proves it was associated with the database by
+//! presenting an identification key that the user approved earlier; those
+//! keys live in the database's custom data so they travel with the file.
+
+pub mod protocol;
+#[cfg(unix)]
+pub mod server;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use crypto_box::aead::{Aead, OsRng};
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use crate::services::kdbx::KdbxService;
+use crate::utils::password_generator::generate_password;
+use protocol::{
+    error_response, increment_nonce, ERROR_ACTION_DENIED, ERROR_ASSOCIATION_FAILED,
+    ERROR_CANNOT_DECRYPT_MESSAGE, ERROR_CANNOT_ENCRYPT_MESSAGE,
+    ERROR_CLIENT_PUBLIC_KEY_NOT_RECEIVED, ERROR_DATABASE_NOT_OPENED,
+    ERROR_ENCRYPTION_KEY_UNRECOGNIZED, ERROR_INCORRECT_ACTION, ERROR_NO_LOGINS_FOUND,
+    ERROR_NO_URL_PROVIDED, PROTOCOL_VERSION,
+};
+
+const NONCE_SIZE: usize = 24;
+
+/// Asks the user whether a new extension may be associated under the given
+/// name. Called from the socket thread, so it may block.
+pub type AssociationApprover = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Keys and verified associations for one extension connection.
+struct ClientSession {
+    client_key: PublicKey,
+    crypto: Arc<SalsaBox>,
+    /// Associations the client proved during this session
+    verified_ids: HashSet<String>,
+}
+
+/// The result of one encrypted action: extra reply fields or an error code.
+type ActionResult = Result<Map<String, Value>, u32>;
+
+pub struct BrowserService {
+    kdbx: Arc<KdbxService>,
+    approve_association: AssociationApprover,
+    sessions: Mutex<HashMap<String, ClientSession>>,
+}
+
+impl BrowserService {
+    /// Creates a browser service answering from `kdbx`. New associations are
+    /// only stored if `approve_association` returns true.
+    pub fn new(
+        kdbx: Arc<KdbxService>,
+        approve_association: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            kdbx,
+            approve_association: Box::new(approve_association),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Answers a raw JSON message from the native messaging host.
+    pub fn handle_message(&self, message: &[u8]) -> Vec<u8> {
+        let response = match serde_json::from_slice::<Value>(message) {
+            Ok(request) => self.handle_request(&request),
+            Err(_) => error_response("", ERROR_INCORRECT_ACTION),
+        };
+        serde_json::to_vec(&response).unwrap_or_default()
+    }
+
+    /// Answers a decoded request from a browser extension.
+    pub fn handle_request(&self, request: &Value) -> Value {
+        let action = request["action"].as_str().unwrap_or_default();
+        let Some(client_id) = request["clientID"].as_str() else {
+            return error_response(action, ERROR_INCORRECT_ACTION);
+        };
+
+        if action == "change-public-keys" {
+            return self
+                .change_public_keys(client_id, request)
+                .unwrap_or_else(|code| error_response(action, code));
+        }
+        self.handle_encrypted(action, client_id, request)
+            .unwrap_or_else(|code| error_response(action, code))
+    }
+
+    /// Starts a session: stores the client's public key and replies with a
+    /// freshly generated key for this session.
+    fn change_public_keys(&self, client_id: &str, request: &Value) -> Result<Value, u32> {
+        let client_key = decode_field(request, "publicKey")
+            .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+            .ok_or(ERROR_CLIENT_PUBLIC_KEY_NOT_RECEIVED)?;
+        let nonce = decode_nonce(request).ok_or(ERROR_CANNOT_DECRYPT_MESSAGE)?;
+
+        let secret_key = SecretKey::generate(&mut OsRng);
+        let public_key = secret_key.public_key();
+        let session = ClientSession {
+            crypto: Arc::new(SalsaBox::new(&client_key, &secret_key)),
+            client_key,
+            verified_ids: HashSet::new(),
+        };
+        self.sessions
+            .lock()
+            .map_err(|_| ERROR_ACTION_DENIED)?
+            .insert(client_id.to_string(), session);
+
+        Ok(json!({
+            "action": "change-public-keys",
+            "version": PROTOCOL_VERSION,
+            "publicKey": BASE64.encode(public_key.as_bytes()),
+            "nonce": BASE64.encode(increment_nonce(&nonce)),
+            "success": "true",
+        }))
+    }
+
+    fn handle_encrypted(
+        &self,
+        action: &str,
+        client_id: &str,
+        request: &Value,
+    ) -> Result<Value, u32> {
+        let crypto = self
+            .sessions
+            .lock()
+            .map_err(|_| ERROR_ACTION_DENIED)?
+            .get(client_id)
+            .map(|session| Arc::clone(&session.crypto))
+            .ok_or(ERROR_ENCRYPTION_KEY_UNRECOGNIZED)?;
+
+        let nonce = decode_nonce(request).ok_or(ERROR_CANNOT_DECRYPT_MESSAGE)?;
+        let message = decode_field(request, "message")
+            .and_then(|ciphertext| {
+                crypto
+                    .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                    .ok()
+            })
+            .and_then(|plaintext| serde_json::from_slice::<Value>(&plaintext).ok())
+            .ok_or(ERROR_CANNOT_DECRYPT_MESSAGE)?;
+        if message["action"].as_str() != Some(action) {
+            return Err(ERROR_INCORRECT_ACTION);
+        }
+
+        let mut reply = match action {
+            "get-databasehash" => self.database_hash_reply(),
+            "associate" => self.associate(client_id, &message),
+            "test-associate" => self.test_associate(client_id, &message),
+            "get-logins" => self.get_logins(client_id, &message),
+            "set-login" => self.set_login(client_id, &message),
+            "generate-password" => generate_password_reply(),
+            _ => Err(ERROR_INCORRECT_ACTION),
+        }?;
+
+        let reply_nonce = increment_nonce(&nonce);
+        reply.insert("version".into(), PROTOCOL_VERSION.into());
+        reply.insert("success".into(), "true".into());
+        reply.insert("nonce".into(), BASE64.encode(&reply_nonce).into());
+        let plaintext = serde_json::to_vec(&reply).map_err(|_| ERROR_CANNOT_ENCRYPT_MESSAGE)?;
+        let ciphertext = crypto
+            .encrypt(Nonce::from_slice(&reply_nonce), plaintext.as_slice())
+            .map_err(|_| ERROR_CANNOT_ENCRYPT_MESSAGE)?;
+
+        Ok(json!({
+            "action": action,
+            "message": BASE64.encode(ciphertext),
+            "nonce": BASE64.encode(&reply_nonce),
+        }))
+    }
+
+    fn database_hash_reply(&self) -> ActionResult {
+        let hash = self.kdbx.database_hash().map_err(|err| error_code(&err))?;
+        Ok(Map::from_iter([("hash".to_string(), hash.into())]))
+    }
+
+    /// Associates a new extension after the user approves it. The request
+    /// carries the session key, which must match the one from the key
+    /// exchange, and the long-lived identification key to store.
+    fn associate(&self, client_id: &str, message: &Value) -> ActionResult {
+        let session_key = decode_field(message, "key").ok_or(ERROR_ASSOCIATION_FAILED)?;
+        let id_key = message["idKey"]
+            .as_str()
+            .filter(|key| !key.is_empty())
+            .ok_or(ERROR_ASSOCIATION_FAILED)?;
+        if !self.client_key_matches(client_id, &session_key)? {
+            return Err(ERROR_ASSOCIATION_FAILED);
+        }
+        let mut reply = self.database_hash_reply()?;
+
+        let id = format!(
+            "Browser {}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        if !(self.approve_association)(&id) {
+            return Err(ERROR_ACTION_DENIED);
+        }
+        self.kdbx
+            .add_browser_association(&id, id_key)
+            .map_err(|err| error_code(&err))?;
+        self.mark_verified(client_id, &id)?;
+
+        reply.insert("id".into(), id.into());
+        Ok(reply)
+    }
+
+    fn test_associate(&self, client_id: &str, message: &Value) -> ActionResult {
+        let id = message["id"].as_str().unwrap_or_default();
+        let key = message["key"].as_str().unwrap_or_default();
+        let mut reply = self.database_hash_reply()?;
+        if !self.verify_association(client_id, id, key)? {
+            return Err(ERROR_ASSOCIATION_FAILED);
+        }
+
+        reply.insert("id".into(), id.into());
+        Ok(reply)
+    }
+
+    fn get_logins(&self, client_id: &str, message: &Value) -> ActionResult {
+        let url = message["url"]
+            .as_str()
+            .filter(|url| !url.is_empty())
+            .ok_or(ERROR_NO_URL_PROVIDED)?;
+        let mut reply = self.database_hash_reply()?;
+
+        let mut verified = None;
+        for key in message["keys"].as_array().into_iter().flatten() {
+            let id = key["id"].as_str().unwrap_or_default();
+            if self.verify_association(client_id, id, key["key"].as_str().unwrap_or_default())? {
+                verified = Some(id.to_string());
+                break;
+            }
+        }
+        let id = verified.ok_or(ERROR_ASSOCIATION_FAILED)?;
+
+        let logins = self
+            .kdbx
+            .browser_logins(url)
+            .map_err(|err| error_code(&err))?;
+        if logins.is_empty() {
+            return Err(ERROR_NO_LOGINS_FOUND);
+        }
+        let entries: Vec<Value> = logins
+            .into_iter()
+            .map(|login| {
+                json!({
+                    "uuid": simple_uuid(&login.entry_id),
+                    "name": login.title,
+                    "login": login.username,
+                    "password": login.password.as_str(),
+                    "group": login.group,
+                    "totp": "",
+                    "expired": "false",
+                    "stringFields": [],
+                })
+            })
+            .collect();
+
+        reply.insert("id".into(), id.into());
+        reply.insert("count".into(), entries.len().into());
+        reply.insert("entries".into(), entries.into());
+        Ok(reply)
+    }
+
+    /// Saves a login submitted in the browser. Only clients that proved an
+    /// association during this session may write to the database.
+    fn set_login(&self, client_id: &str, message: &Value) -> ActionResult {
+        let id = message["id"].as_str().unwrap_or_default();
+        if !self.is_verified(client_id, id)? {
+            return Err(ERROR_ASSOCIATION_FAILED);
+        }
+        let url = message["url"]
+            .as_str()
+            .filter(|url| !url.is_empty())
+            .ok_or(ERROR_NO_URL_PROVIDED)?;
+        let entry_id = message["uuid"].as_str().and_then(hyphenated_uuid);
+        let group_id = message["groupUuid"].as_str().and_then(hyphenated_uuid);
+
+        self.kdbx
+            .save_browser_login(
+                entry_id.as_deref(),
+                group_id.as_deref(),
+                url,
+                message["login"].as_str().unwrap_or_default(),
+                SecureString::from(message["password"].as_str().unwrap_or_default()),
+            )
+            .map_err(|err| error_code(&err))?;
+
+        let mut reply = self.database_hash_reply()?;
+        reply.insert("count".into(), Value::Null);
+        reply.insert("entries".into(), Value::Null);
+        reply.insert("error".into(), "".into());
+        Ok(reply)
+    }
+
+    fn client_key_matches(&self, client_id: &str, key: &[u8]) -> Result<bool, u32> {
+        let sessions = self.sessions.lock().map_err(|_| ERROR_ACTION_DENIED)?;
+        Ok(sessions
+            .get(client_id)
+            .is_some_and(|session| session.client_key.as_bytes().as_slice() == key))
+    }
+
+    /// Checks an identification key against the stored association and
+    /// remembers the association for the session if it matches.
+    fn verify_association(&self, client_id: &str, id: &str, key: &str) -> Result<bool, u32> {
+        if id.is_empty() || key.is_empty() {
+            return Ok(false);
+        }
+        let stored = self
+            .kdbx
+            .browser_association_key(id)
+            .map_err(|err| error_code(&err))?;
+        if stored.as_deref() != Some(key) {
+            return Ok(false);
+        }
+        self.mark_verified(client_id, id)?;
+        Ok(true)
+    }
+
+    fn mark_verified(&self, client_id: &str, id: &str) -> Result<(), u32> {
+        let mut sessions = self.sessions.lock().map_err(|_| ERROR_ACTION_DENIED)?;
+        if let Some(session) = sessions.get_mut(client_id) {
+            session.verified_ids.insert(id.to_string());
+        }
+        Ok(())
+    }
+
+    fn is_verified(&self, client_id: &str, id: &str) -> Result<bool, u32> {
+        let sessions = self.sessions.lock().map_err(|_| ERROR_ACTION_DENIED)?;
+        Ok(sessions
+            .get(client_id)
+            .is_some_and(|session| session.verified_ids.contains(id)))
+    }
+}
+
+/// Generates a password with the default generator options. Both the
+/// current `password` field and the legacy `entries` list are filled in.
+fn generate_password_reply() -> ActionResult {
+    let password =
+        generate_password(&PasswordGeneratorOptions::default()).map_err(|_| ERROR_ACTION_DENIED)?;
+    Ok(Map::from_iter([
+        (
+            "entries".to_string(),
+            json!([{ "login": "", "password": password }]),
+        ),
+        ("password".to_string(), password.into()),
+    ]))
+}
+
+fn error_code(err: &AppError) -> u32 {
+    match err {
+        AppError::DatabaseNotOpen => ERROR_DATABASE_NOT_OPENED,
+        AppError::InvalidUrl(_) => ERROR_NO_URL_PROVIDED,
+        _ => ERROR_ACTION_DENIED,
+    }
+}
+
+fn decode_field(value: &Value, field: &str) -> Option<Vec<u8>> {
+    BASE64.decode(value[field].as_str()?).ok()
+}
+
+fn decode_nonce(request: &Value) -> Option<Vec<u8>> {
+    decode_field(request, "nonce").filter(|nonce| nonce.len() == NONCE_SIZE)
+}
+
+/// The extension identifies entries by UUID without hyphens.
+fn simple_uuid(id: &str) -> String {
+    uuid::Uuid::parse_str(id).map_or_else(|_| id.to_string(), |uuid| uuid.simple().to_string())
+}
+
+fn hyphenated_uuid(id: &str) -> Option<String> {
+    uuid::Uuid::parse_str(id).ok().map(|uuid| uuid.to_string())
+}
@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT
+
+//! Wire format shared by the native messaging host and the app.
+//!
+//! Browsers frame native messages as a 32-bit length in native byte order
+//! followed by that many bytes of UTF-8 JSON. The host relays frames to the
+//! app's socket unchanged, so both hops use the same framing.
+
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+
+/// Largest message accepted in either direction. Chrome caps messages to the
+/// host at 4 GiB but replies at 1 MiB, which is plenty for login lists.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Protocol version reported to the extension.
+pub const PROTOCOL_VERSION: &str = "2.7.0";
+
+pub const ERROR_DATABASE_NOT_OPENED: u32 = 1;
+pub const ERROR_CLIENT_PUBLIC_KEY_NOT_RECEIVED: u32 = 3;
+pub const ERROR_CANNOT_DECRYPT_MESSAGE: u32 = 4;
+pub const ERROR_NOT_CONNECTED: u32 = 5;
+pub const ERROR_ACTION_DENIED: u32 = 6;
+pub const ERROR_CANNOT_ENCRYPT_MESSAGE: u32 = 7;
+pub const ERROR_ASSOCIATION_FAILED: u32 = 8;
+pub const ERROR_ENCRYPTION_KEY_UNRECOGNIZED: u32 = 10;
+pub const ERROR_INCORRECT_ACTION: u32 = 12;
+pub const ERROR_NO_URL_PROVIDED: u32 = 14;
+pub const ERROR_NO_LOGINS_FOUND: u32 = 15;
+
+/// Reads one length-prefixed message. Returns `None` at end of stream.
+pub fn read_message(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Message of {length} bytes exceeds the size limit"),
+        ));
+    }
+
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Writes one length-prefixed message and flushes the writer.
+pub fn write_message(writer: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(message.len())
+        .ok()
+        .filter(|_| message.len() <= MAX_MESSAGE_SIZE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Message is too large"))?;
+    writer.write_all(&length.to_ne_bytes())?;
+    writer.write_all(message)?;
+    writer.flush()
+}
+
+/// An unencrypted error reply in the format the extension expects.
+pub fn error_response(action: &str, code: u32) -> Value {
+    json!({
+        "action": action,
+        "errorCode": code.to_string(),
+        "error": error_message(code),
+    })
+}
+
+fn error_message(code: u32) -> &'static str {
+    match code {
+        ERROR_DATABASE_NOT_OPENED => "Database not opened",
+        ERROR_CLIENT_PUBLIC_KEY_NOT_RECEIVED => "Client public key not received",
+        ERROR_CANNOT_DECRYPT_MESSAGE => "Cannot decrypt message",
+        ERROR_NOT_CONNECTED => "Timeout or cannot connect to Mithril Vault",
+        ERROR_ACTION_DENIED => "Action cancelled or denied",
+        ERROR_CANNOT_ENCRYPT_MESSAGE => "Message encryption failed",
+        ERROR_ASSOCIATION_FAILED => "Association failed",
+        ERROR_ENCRYPTION_KEY_UNRECOGNIZED => "Encryption key is not recognized",
+        ERROR_INCORRECT_ACTION => "Incorrect action",
+        ERROR_NO_URL_PROVIDED => "No URL provided",
+        ERROR_NO_LOGINS_FOUND => "No logins found",
+        _ => "Unknown error",
+    }
+}
+
+/// Increments a nonce as a little-endian integer, as libsodium's
+/// `sodium_increment` does. Replies carry the request nonce plus one.
+pub fn increment_nonce(nonce: &[u8]) -> Vec<u8> {
+    let mut next = nonce.to_vec();
+    for byte in &mut next {
+        let (value, overflow) = byte.overflowing_add(1);
+        *byte = value;
+        if !overflow {
+            break;
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, b"{\"action\":\"ping\"}").expect("write");
+        write_message(&mut buffer, b"{}").expect("write");
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(
+            read_message(&mut reader).expect("read").as_deref(),
+            Some(&b"{\"action\":\"ping\"}"[..])
+        );
+        assert_eq!(
+            read_message(&mut reader).expect("read").as_deref(),
+            Some(&b"{}"[..])
+        );
+        assert!(read_message(&mut reader).expect("read").is_none());
+    }
+
+    #[test]
+    fn test_oversized_messages_are_rejected() {
+        let length = u32::try_from(MAX_MESSAGE_SIZE + 1).expect("length");
+        let mut reader = &length.to_ne_bytes()[..];
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_increment_nonce_carries() {
+        assert_eq!(increment_nonce(&[0, 0, 0]), vec![1, 0, 0]);
+        assert_eq!(increment_nonce(&[255, 255, 0]), vec![0, 0, 1]);
+        assert_eq!(increment_nonce(&[255, 255]), vec![0, 0]);
+    }
+}
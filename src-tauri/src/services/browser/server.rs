@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT
+
+//! Local socket the native messaging host forwards extension messages to.
+//!
+//! The socket lives in a directory only the current user can enter (see
+//! [`crate::utils::local_socket`]), so the file permissions are the access
+//! control; everything past that is authenticated by the browser protocol
+//! itself.
+
+use std::io;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use super::protocol::{read_message, write_message};
+use super::BrowserService;
+use crate::dto::error::AppError;
+use crate::utils::local_socket::{bind, socket_dir};
+
+const SOCKET_NAME: &str = "browser.sock";
+
+/// Path of the socket the native messaging host connects to.
+pub fn socket_path() -> PathBuf {
+    socket_dir().join(SOCKET_NAME)
+}
+
+/// Listens on `path` and answers each connection on its own thread.
+pub fn start(service: Arc<BrowserService>, path: &Path) -> Result<(), AppError> {
+    let listener = bind(path)?;
+    thread::Builder::new()
+        .name("browser-socket".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let service = Arc::clone(&service);
+                thread::spawn(move || serve(&service, stream));
+            }
+        })?;
+    Ok(())
+}
+
+fn serve(service: &BrowserService, mut stream: UnixStream) {
+    while let Ok(Some(message)) = read_message(&mut stream) {
+        let response = service.handle_message(&message);
+        if write_message(&mut stream, &response).is_err() {
+            break;
+        }
+    }
+}
+
+/// Sends one message to the app and waits for the reply.
+pub fn request(path: &Path, message: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(path)?;
+    write_message(&mut stream, message)?;
+    read_message(&mut stream)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed"))
+}
@@ -144,13 +144,16 @@ impl FileLockService {
                 return Err(AppError::DatabaseAlreadyOpen);
             }
             LockStatus::LockedByOtherProcess(info) => {
-                return Err(AppError::DatabaseLocked(format!(
-                    "Database is locked by {} (PID: {}) on {} since {}",
-                    info.application,
-                    info.pid,
-                    info.hostname,
-                    info.opened_at.format("%Y-%m-%d %H:%M:%S UTC")
-                )));
+                return Err(AppError::DatabaseLocked(
+                    format!(
+                        "Database is locked by {} (PID: {}) on {} since {}",
+                        info.application,
+                        info.pid,
+                        info.hostname,
+                        info.opened_at.format("%Y-%m-%d %H:%M:%S UTC")
+                    ),
+                    Some(Box::new(info.into())),
+                ));
             }
             LockStatus::StaleLock(_info) => {
                 // Clean up stale lock and continue
@@ -181,6 +184,7 @@ impl FileLockService {
             if e.kind() == std::io::ErrorKind::WouldBlock {
                 return Err(AppError::DatabaseLocked(
                     "Database is locked by another process".to_string(),
+                    None,
                 ));
             }
             return Err(AppError::FileLockFailed(format!(
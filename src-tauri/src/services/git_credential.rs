@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: MIT
+
+//! Git credential helper protocol, for the `git-credential-mithril` binary.
+//!
+//! Git writes `key=value` lines to the helper's stdin, ending with a blank
+//! line, and names the operation as the first argument:
+//!
+//! - `get`: the helper answers with `username` and `password` of the best
+//!   entry whose URL matches `protocol://host/path`, or with nothing
+//! - `store`: the login worked; the entry for that host and username gets the
+//!   password, or a new entry is created
+//! - `erase`: the login was rejected. Entries are left alone, since git also
+//!   erases after transient failures and the vault is the source of truth.
+//!
+//! Entries come either from the running app over the IPC socket, or from a
+//! database opened directly by the helper.
+//!
+//! When the app isn't running, the helper asks for the master password on
+//! the terminal rather than reusing the session key cached by
+//! `SecureStorageService`. That store is encrypted under a random key the app
+//! generates at startup and keeps only in memory, so no other process can
+//! read it, and handing the key out would defeat the point of the store.
+//! With the app running, the IPC path covers the cached-session case.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+use zeroize::Zeroize;
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::url_match::{UrlMatch, UrlMatchOptions};
+use crate::services::kdbx::KdbxService;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+
+/// Name the helper registers with on the IPC socket.
+pub const CLIENT_NAME: &str = "git-credential-mithril";
+
+const TOKEN_FILE: &str = "git-credential-token";
+
+/// The attributes of a credential request that the helper uses. Others,
+/// such as `capability[]`, are ignored.
+#[derive(Debug, Default)]
+pub struct Credential {
+    pub protocol: Option<String>,
+    pub host: Option<String>,
+    /// Only sent when `credential.useHttpPath` is set
+    pub path: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<SecureString>,
+}
+
+impl Credential {
+    /// Reads attributes up to a blank line or the end of input. A `url`
+    /// attribute is split into its parts.
+    pub fn read(input: &mut impl BufRead) -> Result<Self, AppError> {
+        let mut credential = Self::default();
+        let mut line = String::new();
+        loop {
+            line.zeroize();
+            if input.read_line(&mut line)? == 0 {
+                break;
+            }
+            let attribute = line.trim_end_matches(['\r', '\n']);
+            if attribute.is_empty() {
+                break;
+            }
+            let (key, value) = attribute.split_once('=').ok_or_else(|| {
+                AppError::InvalidArguments("Credential attribute without '='".to_string())
+            })?;
+            let value = value.to_string();
+            match key {
+                "protocol" => credential.protocol = Some(value),
+                "host" => credential.host = Some(value),
+                "path" => credential.path = Some(value),
+                "username" => credential.username = Some(value),
+                "password" => credential.password = Some(SecureString::from(value)),
+                "url" => credential.apply_url(&value)?,
+                _ => {}
+            }
+        }
+        line.zeroize();
+        Ok(credential)
+    }
+
+    fn apply_url(&mut self, value: &str) -> Result<(), AppError> {
+        let url = url::Url::parse(value).map_err(|_| AppError::InvalidUrl(value.to_string()))?;
+        self.protocol = Some(url.scheme().to_string());
+        self.host = url.host_str().map(|host| match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        });
+        let path = url.path().trim_start_matches('/');
+        self.path = (!path.is_empty()).then(|| path.to_string());
+        if !url.username().is_empty() {
+            self.username = Some(url.username().to_string());
+        }
+        Ok(())
+    }
+
+    /// The URL entries are matched against, or `None` without a host.
+    pub fn url(&self) -> Option<String> {
+        let host = self.host.as_deref()?;
+        let protocol = self.protocol.as_deref().unwrap_or("https");
+        Some(match self.path.as_deref() {
+            Some(path) => format!("{protocol}://{host}/{path}"),
+            None => format!("{protocol}://{host}"),
+        })
+    }
+
+    /// Writes the username and password, as expected in a `get` answer.
+    pub fn write(&self, output: &mut impl Write) -> Result<(), AppError> {
+        if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            writeln!(output, "username={username}")?;
+        }
+        if let Some(password) = &self.password {
+            writeln!(output, "password={}", password.as_str())?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// Where credentials are looked up and saved.
+pub trait CredentialStore {
+    /// Entries matching `url`, best first.
+    fn find(&mut self, url: &str, options: &UrlMatchOptions) -> Result<Vec<UrlMatch>, AppError>;
+
+    fn password(&mut self, entry_id: &str) -> Result<SecureString, AppError>;
+
+    /// Saves a working login for `url`.
+    fn store(&mut self, url: &str, username: &str, password: &SecureString)
+        -> Result<(), AppError>;
+}
+
+/// A database opened by the helper itself. Stored logins are saved to disk
+/// right away.
+impl CredentialStore for KdbxService {
+    fn find(&mut self, url: &str, options: &UrlMatchOptions) -> Result<Vec<UrlMatch>, AppError> {
+        self.find_entries_for_url(url, options)
+    }
+
+    fn password(&mut self, entry_id: &str) -> Result<SecureString, AppError> {
+        self.get_entry_password(entry_id).map(SecureString::from)
+    }
+
+    fn store(
+        &mut self,
+        url: &str,
+        username: &str,
+        password: &SecureString,
+    ) -> Result<(), AppError> {
+        self.store_url_credential(url, username, password, None)?;
+        self.save()?;
+        Ok(())
+    }
+}
+
+/// The running app, reached over an authenticated IPC connection.
+#[cfg(unix)]
+impl CredentialStore for crate::services::ipc::client::IpcConnection {
+    fn find(&mut self, url: &str, options: &UrlMatchOptions) -> Result<Vec<UrlMatch>, AppError> {
+        serde_json::from_value(self.call("findByUrl", &json!({ "url": url, "options": options }))?)
+            .map_err(|e| AppError::Ipc(e.to_string()))
+    }
+
+    fn password(&mut self, entry_id: &str) -> Result<SecureString, AppError> {
+        let mut result = self.call(
+            "getField",
+            &json!({ "entryId": entry_id, "field": "Password" }),
+        )?;
+        match result["value"].take() {
+            serde_json::Value::String(value) => Ok(SecureString::from(value)),
+            _ => Err(AppError::Ipc("Missing field value".to_string())),
+        }
+    }
+
+    fn store(
+        &mut self,
+        url: &str,
+        username: &str,
+        password: &SecureString,
+    ) -> Result<(), AppError> {
+        self.call(
+            "storeCredential",
+            &json!({ "url": url, "username": username, "password": password.as_str() }),
+        )
+        .map(|_| ())
+    }
+}
+
+/// Runs one helper operation on the attributes read from `input`. Unknown
+/// operations are ignored, as the protocol requires.
+pub fn run(
+    operation: &str,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    store: &mut dyn CredentialStore,
+) -> Result<(), AppError> {
+    let request = Credential::read(input)?;
+    match operation {
+        "get" => {
+            if let Some(found) = get(store, &request)? {
+                found.write(output)?;
+            }
+            Ok(())
+        }
+        "store" => save(store, &request),
+        _ => Ok(()),
+    }
+}
+
+/// Finds the login for a request. A username in the request must match the
+/// entry's.
+pub fn get(
+    store: &mut dyn CredentialStore,
+    request: &Credential,
+) -> Result<Option<Credential>, AppError> {
+    let Some(url) = request.url() else {
+        return Ok(None);
+    };
+    let options = UrlMatchOptions {
+        match_path: request.path.is_some(),
+        ..UrlMatchOptions::default()
+    };
+
+    let mut candidates = store.find(&url, &options)?;
+    if options.match_path {
+        // Among entries for the same host, the longest URL is the most
+        // specific path
+        candidates.sort_by_key(|candidate| {
+            (
+                candidate.match_kind,
+                std::cmp::Reverse(candidate.matched_url.trim_end_matches('/').len()),
+            )
+        });
+    }
+    let best = candidates.into_iter().find(|candidate| {
+        request
+            .username
+            .as_deref()
+            .is_none_or(|username| candidate.username == username)
+    });
+    let Some(best) = best else {
+        return Ok(None);
+    };
+
+    Ok(Some(Credential {
+        password: Some(store.password(&best.id)?),
+        username: Some(best.username),
+        ..Credential::default()
+    }))
+}
+
+/// Saves the login from a `store` request. Requests without a username or
+/// password are ignored.
+pub fn save(store: &mut dyn CredentialStore, request: &Credential) -> Result<(), AppError> {
+    let (Some(url), Some(username), Some(password)) = (
+        request.url(),
+        request.username.as_deref(),
+        request.password.as_ref(),
+    ) else {
+        return Ok(());
+    };
+    store.store(&url, username, password)
+}
+
+/// File holding the helper's IPC token:
+/// `$XDG_CONFIG_HOME/mithril-vault/git-credential-token`, falling back to
+/// `~/.config`.
+pub fn token_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("mithril-vault").join(TOKEN_FILE))
+}
+
+/// Connects to the running app and authenticates with the token in
+/// `token_file`. Returns `None` when no app is listening on `socket`.
+///
+/// Without a token the helper registers first and stores the new token. The
+/// user must approve the client in the app before it gets any answers, so
+/// this first attempt fails with a hint.
+#[cfg(unix)]
+pub fn connect_app(
+    socket: &Path,
+    token_file: &Path,
+) -> Result<Option<crate::services::ipc::client::IpcConnection>, AppError> {
+    let Ok(mut connection) = crate::services::ipc::client::IpcConnection::connect(socket) else {
+        return Ok(None);
+    };
+
+    let token = match std::fs::read_to_string(token_file) {
+        Ok(token) => SecureString::from(token.trim()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let mut registered = connection.call("register", &json!({ "name": CLIENT_NAME }))?;
+            let token = match registered["token"].take() {
+                serde_json::Value::String(token) => SecureString::from(token),
+                _ => return Err(AppError::Ipc("Missing token".to_string())),
+            };
+            save_token(token_file, &token)?;
+            return Err(AppError::Ipc(format!(
+                "Registered as {CLIENT_NAME}; approve it in the app, then retry"
+            )));
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    connection.authenticate(token.as_str())?;
+    Ok(Some(connection))
+}
+
+fn save_token(token_file: &Path, token: &SecureString) -> Result<(), AppError> {
+    if let Some(parent) = token_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic_write(
+        &token_file.to_string_lossy(),
+        &AtomicWriteOptions::default(),
+        |file| Ok(file.write_all(token.as_str().as_bytes())?),
+    )
+}
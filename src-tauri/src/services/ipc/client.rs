@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT
+
+//! Client side of the IPC socket, for the command-line helpers that talk to
+//! the running app.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::dto::error::AppError;
+
+/// One connection to the IPC socket. Authentication applies to the
+/// connection, so it is kept open for all requests of a session.
+pub struct IpcConnection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    next_id: u64,
+}
+
+impl IpcConnection {
+    /// Connects to the socket; fails when the app is not running or IPC is
+    /// disabled.
+    pub fn connect(path: &Path) -> Result<Self, AppError> {
+        let writer = UnixStream::connect(path)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self {
+            reader,
+            writer,
+            next_id: 1,
+        })
+    }
+
+    /// Sends one request and returns its result. JSON-RPC errors become
+    /// [`AppError::Ipc`] with the server's message.
+    pub fn call(&mut self, method: &str, params: &Value) -> Result<Value, AppError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = serde_json::to_vec(
+            &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        )
+        .map_err(|e| AppError::Ipc(e.to_string()))?;
+        request.push(b'\n');
+        self.writer.write_all(&request)?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(AppError::Ipc("Connection closed".to_string()));
+        }
+        let mut response: Value =
+            serde_json::from_str(&line).map_err(|e| AppError::Ipc(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            let message = error["message"].as_str().unwrap_or("Request failed");
+            return Err(AppError::Ipc(message.to_string()));
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Authenticates the connection with a client token.
+    pub fn authenticate(&mut self, token: &str) -> Result<(), AppError> {
+        self.call("authenticate", &json!({ "token": token }))
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,635 @@
+// SPDX-License-Identifier: MIT
+
+//! JSON-RPC over a local socket for scripts and other apps.
+//!
+//! A client first calls `register` with a display name and receives a
+//! token. The token is useless until the user approves the client in the UI
+//! and picks the groups it may access; after that the client calls
+//! `authenticate` once per connection and may use the narrow data surface:
+//!
+//! - `search {query}`: entry summaries matching the text
+//! - `getField {entryId, field}`: one field value, references resolved
+//! - `generatePassword {options?, entryId?}`: a new password, optionally
+//!   stored as the entry's password
+//! - `findByUrl {url, options?}`: entries whose URLs match a site, best
+//!   match first
+//! - `storeCredential {url, username, password}`: saves a login for a site,
+//!   updating the entry for that host and username or creating one
+//!
+//! Registrations waiting for approval are capped and expire after an hour.
+//! Only SHA-256 hashes of tokens are persisted. Every request is appended to
+//! an audit log without any field values.
+
+#[cfg(unix)]
+pub mod client;
+#[cfg(unix)]
+pub mod server;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::domain::secure::SecureString;
+use crate::dto::entry::UpdateEntryData;
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use crate::dto::ipc::{IpcAuditOutcome, IpcAuditRecord, IpcClient, IpcScopes, IpcStatus};
+use crate::dto::url_match::UrlMatchOptions;
+use crate::services::kdbx::KdbxService;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+use crate::utils::local_socket::socket_dir;
+use crate::utils::password_generator::generate_password;
+
+const STATE_FILE: &str = "ipc.json";
+const AUDIT_FILE: &str = "ipc-audit.jsonl";
+const SOCKET_NAME: &str = "ipc.sock";
+
+/// Most clients that may wait for approval at once; registering is
+/// unauthenticated, so anything on the machine could otherwise fill the list.
+const MAX_PENDING_CLIENTS: usize = 8;
+
+/// How long a registration may wait for approval before it is dropped.
+const PENDING_CLIENT_TTL: chrono::Duration = chrono::Duration::hours(1);
+
+/// JSON-RPC error codes; the ones above -32000 are specific to this server.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const APP_ERROR: i64 = -32000;
+pub const UNAUTHORIZED: i64 = -32001;
+pub const FORBIDDEN: i64 = -32002;
+
+/// Called when a client registers, so the UI can ask for approval.
+pub type RegistrationListener = Box<dyn Fn(&IpcClient) + Send + Sync>;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredClient {
+    #[serde(flatten)]
+    client: IpcClient,
+    token_hash: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IpcState {
+    enabled: bool,
+    clients: Vec<StoredClient>,
+}
+
+/// Per-connection state: which client authenticated on it.
+#[derive(Debug, Default)]
+pub struct IpcSession {
+    client_id: Option<String>,
+}
+
+/// A JSON-RPC error with the details for the audit log.
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn outcome(&self) -> IpcAuditOutcome {
+        match self.code {
+            UNAUTHORIZED | FORBIDDEN => IpcAuditOutcome::Denied,
+            _ => IpcAuditOutcome::Failed,
+        }
+    }
+}
+
+impl From<AppError> for RpcError {
+    fn from(err: AppError) -> Self {
+        Self::new(APP_ERROR, err.to_string())
+    }
+}
+
+/// Path of the IPC socket, next to the browser integration socket.
+pub fn socket_path() -> PathBuf {
+    socket_dir().join(SOCKET_NAME)
+}
+
+pub struct IpcService {
+    kdbx: Arc<KdbxService>,
+    state: Mutex<IpcState>,
+    state_path: PathBuf,
+    audit_path: PathBuf,
+    socket_path: PathBuf,
+    /// Whether the socket is currently listening
+    listening: AtomicBool,
+    on_register: Option<RegistrationListener>,
+}
+
+impl IpcService {
+    /// Creates the service with its state and audit log in `data_dir`. The
+    /// socket is not opened until [`IpcService::apply_enabled`] is called.
+    pub fn new(
+        kdbx: Arc<KdbxService>,
+        data_dir: &Path,
+        socket_path: PathBuf,
+    ) -> Result<Self, AppError> {
+        fs::create_dir_all(data_dir)?;
+        let state_path = data_dir.join(STATE_FILE);
+        let state = load_state(&state_path);
+
+        Ok(Self {
+            kdbx,
+            state: Mutex::new(state),
+            state_path,
+            audit_path: data_dir.join(AUDIT_FILE),
+            socket_path,
+            listening: AtomicBool::new(false),
+            on_register: None,
+        })
+    }
+
+    /// Sets the callback run when a new client registers.
+    #[must_use]
+    pub fn with_registration_listener(
+        mut self,
+        listener: impl Fn(&IpcClient) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_register = Some(Box::new(listener));
+        self
+    }
+
+    pub fn status(&self) -> Result<IpcStatus, AppError> {
+        let state = self.state.lock().map_err(|_| AppError::Lock)?;
+        Ok(IpcStatus {
+            enabled: state.enabled,
+            socket_path: self
+                .listening
+                .load(Ordering::SeqCst)
+                .then(|| self.socket_path.to_string_lossy().into_owned()),
+            clients: state.clients.iter().map(|c| c.client.clone()).collect(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().is_ok_and(|state| state.enabled)
+    }
+
+    /// Turns the socket on or off and remembers the choice.
+    pub fn set_enabled(self: &Arc<Self>, enabled: bool) -> Result<IpcStatus, AppError> {
+        {
+            let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+            state.enabled = enabled;
+            self.save(&state)?;
+        }
+        self.apply_enabled()?;
+        self.status()
+    }
+
+    /// Starts or stops listening to match the stored setting.
+    pub fn apply_enabled(self: &Arc<Self>) -> Result<(), AppError> {
+        #[cfg(unix)]
+        {
+            if self.is_enabled() {
+                if !self.listening.swap(true, Ordering::SeqCst) {
+                    if let Err(err) = server::start(Arc::clone(self)) {
+                        self.listening.store(false, Ordering::SeqCst);
+                        return Err(err);
+                    }
+                }
+            } else if self.listening.swap(false, Ordering::SeqCst) {
+                server::stop(&self.socket_path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Approves a registered client, or changes an approved client's scopes.
+    /// At least one group must be allowed.
+    pub fn approve_client(
+        &self,
+        client_id: &str,
+        scopes: IpcScopes,
+    ) -> Result<IpcClient, AppError> {
+        if scopes.allowed_groups.is_empty() {
+            return Err(AppError::Ipc(
+                "Choose at least one group the client may access".into(),
+            ));
+        }
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        let stored = state
+            .clients
+            .iter_mut()
+            .find(|c| c.client.id == client_id)
+            .ok_or_else(|| AppError::IpcClientNotFound(client_id.to_string()))?;
+        stored.client.approved = true;
+        stored.client.scopes = scopes;
+        let client = stored.client.clone();
+        self.save(&state)?;
+        Ok(client)
+    }
+
+    /// Forgets a client; its token stops working immediately.
+    pub fn revoke_client(&self, client_id: &str) -> Result<(), AppError> {
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        let count = state.clients.len();
+        state.clients.retain(|c| c.client.id != client_id);
+        if state.clients.len() == count {
+            return Err(AppError::IpcClientNotFound(client_id.to_string()));
+        }
+        self.save(&state)
+    }
+
+    /// The most recent audit records, oldest first.
+    pub fn audit_log(&self, limit: usize) -> Result<Vec<IpcAuditRecord>, AppError> {
+        let file = match fs::File::open(&self.audit_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let records: Vec<IpcAuditRecord> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        let skip = records.len().saturating_sub(limit);
+        Ok(records.into_iter().skip(skip).collect())
+    }
+
+    /// Answers one JSON-RPC request received on `session`'s connection.
+    pub fn handle_request(&self, session: &mut IpcSession, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request["method"]
+            .as_str()
+            .filter(|_| request["jsonrpc"] == "2.0")
+        else {
+            return error_response(&id, &RpcError::new(INVALID_REQUEST, "Invalid request"));
+        };
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let result = if self.is_enabled() {
+            self.dispatch(session, method, &params)
+        } else {
+            Err(RpcError::new(UNAUTHORIZED, "IPC access is disabled"))
+        };
+        self.audit(session, method, &params, result.as_ref().err());
+
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(&id, &err),
+        }
+    }
+
+    /// Answers a line of JSON from the socket.
+    pub fn handle_line(&self, session: &mut IpcSession, line: &str) -> Value {
+        match serde_json::from_str::<Value>(line) {
+            Ok(request) => self.handle_request(session, &request),
+            Err(_) => error_response(&Value::Null, &RpcError::new(PARSE_ERROR, "Parse error")),
+        }
+    }
+
+    fn dispatch(
+        &self,
+        session: &mut IpcSession,
+        method: &str,
+        params: &Value,
+    ) -> Result<Value, RpcError> {
+        match method {
+            "register" => self.register(params),
+            "authenticate" => self.authenticate(session, params),
+            "search" | "getField" | "generatePassword" | "findByUrl" | "storeCredential" => {
+                let client = self.session_client(session)?;
+                match method {
+                    "search" => self.search(&client.scopes, params),
+                    "getField" => self.get_field(&client.scopes, params),
+                    "findByUrl" => self.find_by_url(&client.scopes, params),
+                    "storeCredential" => self.store_credential(&client.scopes, params),
+                    _ => self.generate_password(&client.scopes, params),
+                }
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method: {method}"),
+            )),
+        }
+    }
+
+    fn register(&self, params: &Value) -> Result<Value, RpcError> {
+        let name = string_param(params, "name")?.trim();
+        if name.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "name must not be empty"));
+        }
+
+        let mut token = [0u8; 32];
+        OsRng.fill_bytes(&mut token);
+        let token = to_hex(&token);
+        let client = IpcClient {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            approved: false,
+            scopes: IpcScopes {
+                allowed_groups: Vec::new(),
+                read_only: true,
+            },
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_used_at: None,
+        };
+        {
+            let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+            let now = chrono::Utc::now();
+            state
+                .clients
+                .retain(|c| c.client.approved || !is_stale_registration(&c.client, now));
+            let pending = state.clients.iter().filter(|c| !c.client.approved).count();
+            if pending >= MAX_PENDING_CLIENTS {
+                return Err(RpcError::new(
+                    FORBIDDEN,
+                    "Too many clients are awaiting approval",
+                ));
+            }
+            state.clients.push(StoredClient {
+                client: client.clone(),
+                token_hash: token_hash(&token),
+            });
+            self.save(&state)?;
+        }
+        if let Some(listener) = &self.on_register {
+            listener(&client);
+        }
+
+        Ok(json!({ "clientId": client.id, "token": token }))
+    }
+
+    fn authenticate(&self, session: &mut IpcSession, params: &Value) -> Result<Value, RpcError> {
+        let hash = token_hash(string_param(params, "token")?);
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        let stored = state
+            .clients
+            .iter_mut()
+            .find(|c| c.token_hash == hash)
+            .ok_or_else(|| RpcError::new(UNAUTHORIZED, "Unknown token"))?;
+        if !stored.client.approved {
+            return Err(RpcError::new(UNAUTHORIZED, "Client is awaiting approval"));
+        }
+
+        stored.client.last_used_at = Some(chrono::Utc::now().to_rfc3339());
+        session.client_id = Some(stored.client.id.clone());
+        let client = stored.client.clone();
+        self.save(&state)?;
+
+        Ok(json!({
+            "clientId": client.id,
+            "name": client.name,
+            "scopes": client.scopes,
+        }))
+    }
+
+    /// The approved client behind a session, looked up on every request so
+    /// revocations and scope changes apply to open connections. Clients
+    /// without allowed groups may do nothing.
+    fn session_client(&self, session: &IpcSession) -> Result<IpcClient, RpcError> {
+        let client_id = session
+            .client_id
+            .as_deref()
+            .ok_or_else(|| RpcError::new(UNAUTHORIZED, "Not authenticated"))?;
+        let client = self
+            .client(client_id)
+            .filter(|client| client.approved)
+            .ok_or_else(|| RpcError::new(UNAUTHORIZED, "Client access was revoked"))?;
+        if client.scopes.allowed_groups.is_empty() {
+            return Err(RpcError::new(FORBIDDEN, "Client has no allowed groups"));
+        }
+        Ok(client)
+    }
+
+    fn search(&self, scopes: &IpcScopes, params: &Value) -> Result<Value, RpcError> {
+        let query = string_param(params, "query")?;
+        let entries = self
+            .kdbx
+            .search_entries(query, Some(&scopes.allowed_groups))?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                json!({
+                    "id": entry.id,
+                    "groupId": entry.group_id,
+                    "title": entry.title,
+                    "username": entry.username,
+                    "url": entry.url,
+                })
+            })
+            .collect())
+    }
+
+    fn get_field(&self, scopes: &IpcScopes, params: &Value) -> Result<Value, RpcError> {
+        let entry_id = string_param(params, "entryId")?;
+        let field = string_param(params, "field")?;
+        self.check_entry_scope(scopes, entry_id)?;
+
+        let value = self.kdbx.get_entry_field(entry_id, field)?;
+        Ok(json!({ "value": value }))
+    }
+
+    fn generate_password(&self, scopes: &IpcScopes, params: &Value) -> Result<Value, RpcError> {
+        let options: PasswordGeneratorOptions = match params.get("options") {
+            Some(options) if !options.is_null() => serde_json::from_value(options.clone())
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?,
+            _ => PasswordGeneratorOptions::default(),
+        };
+        let entry_id = params.get("entryId").and_then(Value::as_str);
+        if let Some(entry_id) = entry_id {
+            if scopes.read_only {
+                return Err(RpcError::new(FORBIDDEN, "Client is read-only"));
+            }
+            self.check_entry_scope(scopes, entry_id)?;
+        }
+
+        let password = generate_password(&options)?;
+        if let Some(entry_id) = entry_id {
+            self.kdbx.update_entry(
+                entry_id,
+                UpdateEntryData {
+                    title: None,
+                    username: None,
+                    password: Some(SecureString::from(password.as_str())),
+                    url: None,
+                    notes: None,
+                    icon_id: None,
+                    tags: None,
+                    custom_fields: None,
+                    protected_custom_fields: None,
+                    expires: None,
+                    expiry_time: None,
+                    auto_type: None,
+                },
+            )?;
+        }
+
+        Ok(json!({ "password": password }))
+    }
+
+    fn find_by_url(&self, scopes: &IpcScopes, params: &Value) -> Result<Value, RpcError> {
+        let url = string_param(params, "url")?;
+        let options: UrlMatchOptions = match params.get("options") {
+            Some(options) if !options.is_null() => serde_json::from_value(options.clone())
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?,
+            _ => UrlMatchOptions::default(),
+        };
+        let mut matches = Vec::new();
+        for candidate in self.kdbx.find_entries_for_url(url, &options)? {
+            if self
+                .kdbx
+                .entry_in_groups(&candidate.id, &scopes.allowed_groups)?
+            {
+                matches.push(candidate);
+            }
+        }
+
+        serde_json::to_value(matches).map_err(|e| RpcError::new(APP_ERROR, e.to_string()))
+    }
+
+    fn store_credential(&self, scopes: &IpcScopes, params: &Value) -> Result<Value, RpcError> {
+        let url = string_param(params, "url")?;
+        let username = string_param(params, "username")?;
+        let password = SecureString::from(string_param(params, "password")?);
+        if scopes.read_only {
+            return Err(RpcError::new(FORBIDDEN, "Client is read-only"));
+        }
+
+        let entry = self.kdbx.store_url_credential(
+            url,
+            username,
+            &password,
+            Some(&scopes.allowed_groups),
+        )?;
+        Ok(json!({ "entryId": entry.id }))
+    }
+
+    fn check_entry_scope(&self, scopes: &IpcScopes, entry_id: &str) -> Result<(), RpcError> {
+        if self
+            .kdbx
+            .entry_in_groups(entry_id, &scopes.allowed_groups)?
+        {
+            return Ok(());
+        }
+        Err(RpcError::new(
+            FORBIDDEN,
+            "Entry is outside the client's allowed groups",
+        ))
+    }
+
+    fn client(&self, client_id: &str) -> Option<IpcClient> {
+        let state = self.state.lock().ok()?;
+        state
+            .clients
+            .iter()
+            .find(|c| c.client.id == client_id)
+            .map(|c| c.client.clone())
+    }
+
+    fn save(&self, state: &IpcState) -> Result<(), AppError> {
+        let content = serde_json::to_vec_pretty(state).map_err(|e| AppError::Io(e.to_string()))?;
+        atomic_write(
+            &self.state_path.to_string_lossy(),
+            &AtomicWriteOptions::default(),
+            |file| Ok(file.write_all(&content)?),
+        )
+    }
+
+    /// Appends a request to the audit log. Logging failures are ignored
+    /// rather than failing the request.
+    fn audit(&self, session: &IpcSession, method: &str, params: &Value, error: Option<&RpcError>) {
+        let client = session.client_id.as_deref().and_then(|id| self.client(id));
+        let target = ["entryId", "query", "url", "name"]
+            .iter()
+            .find_map(|key| params.get(key).and_then(Value::as_str))
+            .map(str::to_string);
+        let record = IpcAuditRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            client_id: client.as_ref().map(|c| c.id.clone()),
+            client_name: client.map(|c| c.name),
+            method: method.to_string(),
+            target,
+            outcome: error.map_or(IpcAuditOutcome::Allowed, RpcError::outcome),
+            error: error.map(|e| e.message.clone()),
+        };
+
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            return;
+        };
+        line.push('\n');
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        if let Ok(mut file) = options.open(&self.audit_path) {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+/// Reads the stored state. A file that can't be read or parsed is moved
+/// aside so it isn't overwritten, and IPC starts from defaults (disabled, no
+/// clients) rather than keeping the app from starting.
+fn load_state(path: &Path) -> IpcState {
+    let error = match fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(state) => return state,
+            Err(err) => err.to_string(),
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return IpcState::default(),
+        Err(err) => err.to_string(),
+    };
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let backup_path = path.with_file_name(format!("{STATE_FILE}.bad-{timestamp}"));
+    log::warn!(
+        "Invalid {STATE_FILE} ({error}); moved to {}",
+        backup_path.display()
+    );
+    let _ = fs::rename(path, backup_path);
+    IpcState::default()
+}
+
+/// Whether an unapproved client registered longer ago than the approval
+/// window allows.
+fn is_stale_registration(client: &IpcClient, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&client.created_at).map_or(true, |created| {
+        now - created.with_timezone(&chrono::Utc) > PENDING_CLIENT_TTL
+    })
+}
+
+fn string_param<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    params[key]
+        .as_str()
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing string parameter: {key}")))
+}
+
+fn error_response(id: &Value, err: &RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn token_hash(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
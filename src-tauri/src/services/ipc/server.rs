@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+
+//! Unix socket transport for the IPC service: one JSON-RPC request per line,
+//! answered with one response per line.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+use super::{IpcService, IpcSession};
+use crate::dto::error::AppError;
+use crate::utils::local_socket::bind;
+
+/// Longest request line accepted; longer lines close the connection.
+const MAX_LINE_LENGTH: u64 = 64 * 1024;
+
+/// Listens on the service's socket until [`stop`] is called.
+pub fn start(service: Arc<IpcService>) -> Result<(), AppError> {
+    let listener = bind(&service.socket_path)?;
+    thread::Builder::new()
+        .name("ipc-socket".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if !service.listening.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let service = Arc::clone(&service);
+                thread::spawn(move || serve(&service, stream));
+            }
+        })?;
+    Ok(())
+}
+
+/// Stops the accept loop and removes the socket. Open connections are left
+/// to finish, but every request on them fails while IPC is disabled.
+pub fn stop(path: &Path) {
+    // Wake the accept loop so it sees the cleared flag
+    let _ = UnixStream::connect(path);
+    let _ = std::fs::remove_file(path);
+}
+
+fn serve(service: &IpcService, stream: UnixStream) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    let mut session = IpcSession::default();
+
+    loop {
+        let mut line = String::new();
+        match reader.by_ref().take(MAX_LINE_LENGTH).read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if !line.ends_with('\n') && line.len() as u64 >= MAX_LINE_LENGTH => break,
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = service.handle_line(&mut session, line.trim());
+        let Ok(mut bytes) = serde_json::to_vec(&response) else {
+            break;
+        };
+        bytes.push(b'\n');
+        if writer.write_all(&bytes).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Entry attachment storage.
+//!
+//! The `keepass` crate keeps the KDBX4 inner-header binary pool
+//! (`Database::header_attachments`) but discards the `<Binary>` references
+//! inside entries. Attachments written by the app are therefore linked
+//! from the entry's `CustomData`: one item per attachment, keyed by
+//! [`ATTACHMENT_KEY_PREFIX`] plus the file name, whose value is the index of
+//! the content in the binary pool.
+
+use keepass::db::{CustomDataItem, Entry as KeepassEntry, HeaderAttachment, Times, Value};
+
+use super::entries::{find_entry_by_id_mut, find_entry_by_id_ref};
+use super::health::collect_entries;
+use super::mapping::recycle_bin_id;
+use super::{writable, KdbxService};
+use crate::dto::error::AppError;
+
+/// `CustomData` key prefix linking an entry to a binary pool item.
+pub(crate) const ATTACHMENT_KEY_PREFIX: &str = "MithrilVault.Attachment/";
+
+/// Inner header flag marking a binary as protected in memory.
+const PROTECTED_FLAG: u8 = 0x01;
+
+impl KdbxService {
+    /// Attaches `content` to an entry under `name`, replacing an existing
+    /// attachment of that name. The content is protected in memory.
+    pub fn add_entry_attachment(
+        &self,
+        entry_id: &str,
+        name: &str,
+        content: Vec<u8>,
+    ) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let db = &mut open_db.db;
+        let (entry, _) = find_entry_by_id_mut(&mut db.root, entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+        add_attachment(&mut db.header_attachments, entry, name, content, true);
+        entry.times.set_last_modification(Times::now());
+        open_db.is_modified = true;
+        Ok(())
+    }
+
+    /// Names of an entry's attachments, sorted.
+    pub fn list_entry_attachments(&self, entry_id: &str) -> Result<Vec<String>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let entry = find_entry_by_id_ref(&open_db.db.root, entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+        Ok(attachment_links(entry)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Content of the attachment `name` of an entry.
+    pub fn get_entry_attachment(&self, entry_id: &str, name: &str) -> Result<Vec<u8>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let entry = find_entry_by_id_ref(&open_db.db.root, entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+        attachment_links(entry)
+            .into_iter()
+            .find(|(link, _)| link == name)
+            .and_then(|(_, index)| open_db.db.header_attachments.get(index))
+            .map(|item| item.content.clone())
+            .ok_or_else(|| AppError::AttachmentNotFound(name.to_string()))
+    }
+
+    /// IDs of the entries outside the recycle bin that have an attachment
+    /// called `name`.
+    pub fn find_entries_with_attachment(&self, name: &str) -> Result<Vec<String>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut entries = Vec::new();
+        collect_entries(&open_db.db.root, bin_id.as_deref(), &mut entries);
+        Ok(entries
+            .into_iter()
+            .filter(|(_, entry)| attachment_links(entry).iter().any(|(link, _)| link == name))
+            .map(|(_, entry)| entry.uuid.to_string())
+            .collect())
+    }
+}
+
+/// Adds `content` to the binary pool (reusing an identical item if present)
+/// and links it to `entry` under `name`.
+pub(crate) fn add_attachment(
+    pool: &mut Vec<HeaderAttachment>,
+    entry: &mut KeepassEntry,
+    name: &str,
+    content: Vec<u8>,
+    protected: bool,
+) {
+    let flags = if protected { PROTECTED_FLAG } else { 0 };
+    let index = if let Some(existing) = pool
+        .iter()
+        .position(|item| item.content == content && item.flags == flags)
+    {
+        existing
+    } else {
+        pool.push(HeaderAttachment { flags, content });
+        pool.len() - 1
+    };
+
+    entry.custom_data.items.insert(
+        format!("{ATTACHMENT_KEY_PREFIX}{name}"),
+        CustomDataItem {
+            value: Some(Value::Unprotected(index.to_string())),
+            last_modification_time: Some(Times::now()),
+        },
+    );
+}
+
+/// Copies the attachments linked to `entry` from the `source` pool into the
+/// `target` pool and rewrites the links to match. Dangling links are dropped.
+/// Returns the number of attachments copied.
+pub(crate) fn copy_attachments(
+    source: &[HeaderAttachment],
+    target: &mut Vec<HeaderAttachment>,
+    entry: &mut KeepassEntry,
+) -> usize {
+    let links = attachment_links(entry);
+    entry
+        .custom_data
+        .items
+        .retain(|key, _| !key.starts_with(ATTACHMENT_KEY_PREFIX));
+
+    let mut copied = 0;
+    for (name, index) in links {
+        if let Some(item) = source.get(index) {
+            let protected = item.flags & PROTECTED_FLAG != 0;
+            add_attachment(target, entry, &name, item.content.clone(), protected);
+            copied += 1;
+        }
+    }
+    copied
+}
+
+/// Returns the `(name, pool index)` links of `entry`, sorted by name.
+fn attachment_links(entry: &KeepassEntry) -> Vec<(String, usize)> {
+    let mut links: Vec<(String, usize)> = entry
+        .custom_data
+        .items
+        .iter()
+        .filter_map(|(key, item)| {
+            let name = key.strip_prefix(ATTACHMENT_KEY_PREFIX)?;
+            match item.value.as_ref()? {
+                Value::Unprotected(index) => Some((name.to_string(), index.parse().ok()?)),
+                _ => None,
+            }
+        })
+        .collect();
+
+    links.sort();
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linked_index(entry: &KeepassEntry, name: &str) -> Option<String> {
+        match entry
+            .custom_data
+            .items
+            .get(&format!("{ATTACHMENT_KEY_PREFIX}{name}"))?
+            .value
+            .as_ref()?
+        {
+            Value::Unprotected(index) => Some(index.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_add_attachment_links_pool_item() {
+        let mut pool = Vec::new();
+        let mut entry = KeepassEntry::new();
+
+        add_attachment(&mut pool, &mut entry, "notes.txt", b"hello".to_vec(), false);
+
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool[0].content, b"hello");
+        assert_eq!(pool[0].flags, 0);
+        assert_eq!(linked_index(&entry, "notes.txt").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_identical_content_shares_pool_item() {
+        let mut pool = Vec::new();
+        let mut first = KeepassEntry::new();
+        let mut second = KeepassEntry::new();
+
+        add_attachment(&mut pool, &mut first, "a.bin", vec![1, 2, 3], true);
+        add_attachment(&mut pool, &mut second, "b.bin", vec![1, 2, 3], true);
+
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool[0].flags, PROTECTED_FLAG);
+        assert_eq!(linked_index(&second, "b.bin").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_copy_attachments_relinks_into_target_pool() {
+        let mut source = Vec::new();
+        let mut entry = KeepassEntry::new();
+        add_attachment(&mut source, &mut entry, "unused.bin", vec![9], false);
+        add_attachment(&mut source, &mut entry, "key.pem", vec![1, 2], true);
+        entry.custom_data.items.insert(
+            format!("{ATTACHMENT_KEY_PREFIX}missing.bin"),
+            CustomDataItem {
+                value: Some(Value::Unprotected("7".to_string())),
+                last_modification_time: None,
+            },
+        );
+        entry
+            .custom_data
+            .items
+            .remove(&format!("{ATTACHMENT_KEY_PREFIX}unused.bin"));
+
+        let mut target = Vec::new();
+        let copied = copy_attachments(&source, &mut target, &mut entry);
+
+        assert_eq!(copied, 1);
+        assert_eq!(target.len(), 1);
+        assert_eq!(target[0].content, vec![1, 2]);
+        assert_eq!(target[0].flags, PROTECTED_FLAG);
+        assert_eq!(linked_index(&entry, "key.pem").as_deref(), Some("0"));
+        assert!(linked_index(&entry, "missing.bin").is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::dto::autotype::{AutoTypeAssociation, AutoTypeSequence, EntryAutoType, GroupAutoType};
+use crate::dto::error::AppError;
+use crate::utils::autotype::{parse_sequence, DEFAULT_SEQUENCE};
+use keepass::db::{
+    AutoType, AutoTypeAssociation as KeepassAssociation, Entry as KeepassEntry,
+    Group as KeepassGroup, Node,
+};
+
+use super::entries::find_entry_by_id_ref;
+use super::KdbxService;
+
+impl KdbxService {
+    /// Returns the auto-type sequence for an entry, parsed into key actions.
+    ///
+    /// The sequence comes from the first association matching
+    /// `window_title`, then the entry's default sequence, then the nearest
+    /// group that defines one. Fails if auto-type is disabled for the entry
+    /// or its nearest group with an explicit setting.
+    pub fn get_auto_type_sequence(
+        &self,
+        entry_id: &str,
+        window_title: Option<&str>,
+    ) -> Result<AutoTypeSequence, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root = &open_db.db.root;
+        let entry = find_entry_by_id_ref(root, entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+        let groups = entry_groups(root, entry_id).unwrap_or_default();
+        let group_settings: Vec<GroupAutoType> =
+            groups.iter().rev().map(|g| group_auto_type(g)).collect();
+        let auto_type = entry_auto_type(entry);
+
+        let group_enabled = group_settings.iter().find_map(|g| g.enabled);
+        if !auto_type.enabled || group_enabled == Some(false) {
+            return Err(AppError::AutoTypeDisabled(entry_id.to_string()));
+        }
+
+        let association = window_title.and_then(|title| {
+            auto_type
+                .associations
+                .iter()
+                .find(|a| window_matches(&a.window, title))
+        });
+        let sequence = association
+            .and_then(|a| a.sequence.clone())
+            .or(auto_type.default_sequence)
+            .or_else(|| group_settings.into_iter().find_map(|g| g.default_sequence))
+            .unwrap_or_else(|| DEFAULT_SEQUENCE.to_string());
+
+        let actions = parse_sequence(&sequence)?;
+        Ok(AutoTypeSequence { sequence, actions })
+    }
+}
+
+pub(crate) fn entry_auto_type(entry: &KeepassEntry) -> EntryAutoType {
+    let Some(auto_type) = &entry.autotype else {
+        return EntryAutoType::default();
+    };
+    EntryAutoType {
+        enabled: auto_type.enabled,
+        default_sequence: non_empty(auto_type.sequence.as_deref()),
+        associations: auto_type
+            .associations
+            .iter()
+            .map(|a| AutoTypeAssociation {
+                window: a.window.clone().unwrap_or_default(),
+                sequence: non_empty(a.sequence.as_deref()),
+            })
+            .collect(),
+    }
+}
+
+/// Checks that every sequence in `auto_type` parses.
+pub(crate) fn validate_entry_auto_type(auto_type: &EntryAutoType) -> Result<(), AppError> {
+    let sequences = auto_type
+        .associations
+        .iter()
+        .filter_map(|a| a.sequence.as_deref())
+        .chain(auto_type.default_sequence.as_deref());
+    for sequence in sequences {
+        parse_sequence(sequence)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn set_entry_auto_type(entry: &mut KeepassEntry, auto_type: EntryAutoType) {
+    entry.autotype = Some(AutoType {
+        enabled: auto_type.enabled,
+        sequence: non_empty(auto_type.default_sequence.as_deref()),
+        associations: auto_type
+            .associations
+            .into_iter()
+            .map(|a| KeepassAssociation {
+                window: Some(a.window),
+                sequence: non_empty(a.sequence.as_deref()),
+            })
+            .collect(),
+    });
+}
+
+pub(crate) fn group_auto_type(group: &KeepassGroup) -> GroupAutoType {
+    // KeePass stores "true", "false" or "null" (inherit)
+    let enabled = match group.enable_autotype.as_deref() {
+        Some(value) if value.eq_ignore_ascii_case("true") => Some(true),
+        Some(value) if value.eq_ignore_ascii_case("false") => Some(false),
+        _ => None,
+    };
+    GroupAutoType {
+        enabled,
+        default_sequence: non_empty(group.default_autotype_sequence.as_deref()),
+    }
+}
+
+/// Validates and stores a group's auto-type settings.
+pub(crate) fn set_group_auto_type(
+    group: &mut KeepassGroup,
+    auto_type: GroupAutoType,
+) -> Result<(), AppError> {
+    if let Some(sequence) = &auto_type.default_sequence {
+        parse_sequence(sequence)?;
+    }
+    group.enable_autotype = auto_type.enabled.map(|enabled| enabled.to_string());
+    group.default_autotype_sequence = auto_type
+        .default_sequence
+        .filter(|sequence| !sequence.trim().is_empty());
+    Ok(())
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .map(std::string::ToString::to_string)
+}
+
+/// Matches a window title against an association pattern, where `*` matches
+/// any run of characters. Comparison is case-insensitive.
+fn window_matches(pattern: &str, title: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let title = title.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = title.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Groups from the root down to the group holding the entry.
+fn entry_groups<'a>(group: &'a KeepassGroup, entry_id: &str) -> Option<Vec<&'a KeepassGroup>> {
+    for node in &group.children {
+        match node {
+            Node::Entry(entry) if entry.uuid.to_string() == entry_id => {
+                return Some(vec![group]);
+            }
+            Node::Group(child) => {
+                if let Some(mut groups) = entry_groups(child, entry_id) {
+                    groups.insert(0, group);
+                    return Some(groups);
+                }
+            }
+            Node::Entry(_) => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_matches_wildcards() {
+        assert!(window_matches(
+            "Login - Mozilla Firefox",
+            "login - mozilla firefox"
+        ));
+        assert!(window_matches("*Firefox", "Example - Mozilla Firefox"));
+        assert!(window_matches("Example*", "Example - Mozilla Firefox"));
+        assert!(window_matches(
+            "*Mail*Firefox",
+            "Inbox - Mail - Mozilla Firefox"
+        ));
+        assert!(window_matches("*", "anything"));
+        assert!(!window_matches("*Chrome", "Example - Mozilla Firefox"));
+        assert!(!window_matches("Example", "Example - Mozilla Firefox"));
+        assert!(!window_matches("a*a", "a"));
+    }
+
+    #[test]
+    fn test_group_enable_values() {
+        let mut group = KeepassGroup::new("Group");
+        assert_eq!(group_auto_type(&group).enabled, None);
+
+        group.enable_autotype = Some("null".to_string());
+        assert_eq!(group_auto_type(&group).enabled, None);
+
+        set_group_auto_type(
+            &mut group,
+            GroupAutoType {
+                enabled: Some(false),
+                default_sequence: Some("{PASSWORD}{ENTER}".to_string()),
+            },
+        )
+        .expect("set auto-type");
+        assert_eq!(group.enable_autotype.as_deref(), Some("false"));
+        assert_eq!(group_auto_type(&group).enabled, Some(false));
+    }
+}
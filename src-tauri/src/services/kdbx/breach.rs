@@ -0,0 +1,60 @@
+use crate::dto::breach::{BreachReport, EntryBreach};
+use crate::dto::error::AppError;
+use crate::services::breach::{password_hash, BreachIndex, Sha1Hash};
+use std::cmp::Reverse;
+use zeroize::Zeroizing;
+
+use super::health::collect_entries;
+use super::mapping::recycle_bin_id;
+use super::KdbxService;
+
+impl KdbxService {
+    /// Looks up the password of every entry outside the recycle bin in a
+    /// local HIBP hash list or binary index at `index_path`.
+    ///
+    /// Passwords are hashed while the database is locked; the (slow) file
+    /// search runs after the lock is released.
+    pub fn check_breached_passwords(&self, index_path: &str) -> Result<BreachReport, AppError> {
+        let hashed = self.hash_passwords()?;
+        let mut index = BreachIndex::open(index_path)?;
+
+        let mut breached = Vec::new();
+        for (entry_id, title, hash) in &hashed {
+            if let Some(count) = index.lookup(hash)? {
+                breached.push(EntryBreach {
+                    entry_id: entry_id.clone(),
+                    title: title.clone(),
+                    count,
+                });
+            }
+        }
+        breached.sort_by_key(|breach| Reverse(breach.count));
+
+        Ok(BreachReport {
+            checked_count: hashed.len(),
+            breached,
+        })
+    }
+
+    /// SHA-1 of each non-empty password, with the entry ID and title.
+    fn hash_passwords(&self) -> Result<Vec<(String, String, Zeroizing<Sha1Hash>)>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut entries = Vec::new();
+        collect_entries(&open_db.db.root, bin_id.as_deref(), &mut entries);
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(_, entry)| {
+                let password = entry.get_password().filter(|p| !p.is_empty())?;
+                Some((
+                    entry.uuid.to_string(),
+                    entry.get_title().unwrap_or_default().to_string(),
+                    Zeroizing::new(password_hash(password)),
+                ))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,207 @@
+use crate::domain::secure::SecureString;
+use crate::dto::browser::{BrowserAssociation, BrowserLogin};
+use crate::dto::entry::{CreateEntryData, Entry, UpdateEntryData};
+use crate::dto::error::AppError;
+use crate::dto::url_match::UrlMatchOptions;
+use keepass::db::{CustomDataItem, Times, Value};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+
+use super::entries::find_entry_by_id_ref;
+use super::mapping::{find_group_by_id, find_group_by_name};
+use super::references::resolve_field;
+use super::{writable, KdbxService};
+
+/// Prefix of the database `CustomData` keys holding association keys. Shared
+/// with `KeePassXC` so associations survive switching between the two.
+const ASSOCIATION_PREFIX: &str = "KPXC_BROWSER_";
+
+/// Group that receives logins saved from the browser without a target group.
+const BROWSER_GROUP_NAME: &str = "KeePassXC-Browser Passwords";
+
+impl KdbxService {
+    /// Hex SHA-256 of the root group UUID, which browser extensions use to
+    /// tell databases apart.
+    pub fn database_hash(&self) -> Result<String, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root_uuid = open_db.db.root.uuid.simple().to_string();
+        let digest = Sha256::digest(root_uuid.as_bytes());
+        Ok(digest.iter().fold(String::new(), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        }))
+    }
+
+    /// Lists the browser extensions associated with the open database.
+    pub fn list_browser_associations(&self) -> Result<Vec<BrowserAssociation>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let mut associations: Vec<BrowserAssociation> = open_db
+            .db
+            .meta
+            .custom_data
+            .items
+            .iter()
+            .filter_map(|(key, item)| {
+                let id = key.strip_prefix(ASSOCIATION_PREFIX)?;
+                let Some(Value::Unprotected(value)) = &item.value else {
+                    return None;
+                };
+                Some(BrowserAssociation {
+                    id: id.to_string(),
+                    key: value.clone(),
+                    created_at: item.last_modification_time.map(|time| time.to_string()),
+                })
+            })
+            .collect();
+        associations.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(associations)
+    }
+
+    /// Returns the identification key stored for association `id`.
+    pub fn browser_association_key(&self, id: &str) -> Result<Option<String>, AppError> {
+        Ok(self
+            .list_browser_associations()?
+            .into_iter()
+            .find(|association| association.id == id)
+            .map(|association| association.key))
+    }
+
+    /// Stores an association key in the database metadata, replacing any
+    /// association with the same ID.
+    pub fn add_browser_association(&self, id: &str, key: &str) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        open_db.db.meta.custom_data.items.insert(
+            format!("{ASSOCIATION_PREFIX}{id}"),
+            CustomDataItem {
+                value: Some(Value::Unprotected(key.to_string())),
+                last_modification_time: Some(Times::now()),
+            },
+        );
+        open_db.is_modified = true;
+
+        Ok(())
+    }
+
+    /// Removes a browser association so the extension has to associate again.
+    pub fn remove_browser_association(&self, id: &str) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        open_db
+            .db
+            .meta
+            .custom_data
+            .items
+            .remove(&format!("{ASSOCIATION_PREFIX}{id}"))
+            .ok_or_else(|| {
+                AppError::BrowserIntegration(format!("No browser association named {id}"))
+            })?;
+        open_db.is_modified = true;
+
+        Ok(())
+    }
+
+    /// Logins for a site, best matches first, with references resolved.
+    pub fn browser_logins(&self, url: &str) -> Result<Vec<BrowserLogin>, AppError> {
+        let matches = self.find_entries_for_url(url, &UrlMatchOptions::default())?;
+
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        let root = &open_db.db.root;
+
+        Ok(matches
+            .into_iter()
+            .filter_map(|found| {
+                let entry = find_entry_by_id_ref(root, &found.id)?;
+                Some(BrowserLogin {
+                    password: SecureString::new(
+                        resolve_field(root, entry, "Password").unwrap_or_default(),
+                    ),
+                    group: find_group_by_id(root, &found.group_id)
+                        .map(|group| group.name.clone())
+                        .unwrap_or_default(),
+                    entry_id: found.id,
+                    title: found.title,
+                    username: found.username,
+                })
+            })
+            .collect())
+    }
+
+    /// Saves a login submitted in the browser. Updates the username and
+    /// password of `entry_id` if given, otherwise creates an entry titled
+    /// after the site in `group_id` or the browser passwords group.
+    pub fn save_browser_login(
+        &self,
+        entry_id: Option<&str>,
+        group_id: Option<&str>,
+        url: &str,
+        username: &str,
+        password: SecureString,
+    ) -> Result<Entry, AppError> {
+        if let Some(id) = entry_id {
+            return self.update_entry(
+                id,
+                UpdateEntryData {
+                    title: None,
+                    username: Some(username.to_string()),
+                    password: Some(password),
+                    url: None,
+                    notes: None,
+                    icon_id: None,
+                    tags: None,
+                    custom_fields: None,
+                    protected_custom_fields: None,
+                    expires: None,
+                    expiry_time: None,
+                    auto_type: None,
+                },
+            );
+        }
+
+        let group_id = match group_id {
+            Some(id) => id.to_string(),
+            None => self.browser_group_id()?,
+        };
+        let title = url::Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string());
+
+        self.create_entry(
+            &group_id,
+            CreateEntryData {
+                title,
+                username: username.to_string(),
+                password,
+                url: Some(url.to_string()),
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+    }
+
+    /// Returns the group for logins saved from the browser, creating it
+    /// below the root if needed.
+    fn browser_group_id(&self) -> Result<String, AppError> {
+        {
+            let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+            let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+            if let Some(group) = find_group_by_name(&open_db.db.root, BROWSER_GROUP_NAME) {
+                return Ok(group.uuid.to_string());
+            }
+        }
+        Ok(self.create_group(None, BROWSER_GROUP_NAME, None)?.id)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domain::kdbx::OpenDatabase;
+use crate::domain::kdbx::{format_database_version, OpenDatabase};
 use crate::domain::secure::SecureString;
 use crate::dto::database::{DatabaseCreationOptions, DatabaseInfo};
 use crate::dto::error::AppError;
@@ -38,7 +38,7 @@ impl KdbxService {
     ) -> Result<DatabaseInfo, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
 
-        if db_lock.is_some() {
+        if db_lock.is_some() || self.is_locked()? {
             return Err(AppError::DatabaseAlreadyOpen);
         }
 
@@ -46,27 +46,7 @@ impl KdbxService {
             return Err(AppError::NoCredentials);
         }
 
-        let config = DatabaseConfig {
-            version: DatabaseVersion::KDB4(0),
-            outer_cipher_config: OuterCipherConfig::AES256,
-            compression_config: CompressionConfig::GZip,
-            inner_cipher_config: InnerCipherConfig::ChaCha20,
-            kdf_config: KdfConfig::Argon2id {
-                iterations: options.iterations(),
-                memory: options.memory_bytes(),
-                parallelism: options.parallelism(),
-                version: argon2::Version::Version13,
-            },
-            public_custom_data: None,
-        };
-
-        let mut db = Database::new(config);
-        db.root.name = name.to_string();
-        db.meta.database_name = Some(name.to_string());
-        db.meta.generator = Some(String::from("MithrilVault"));
-        if let Some(description) = &options.description {
-            db.meta.database_description = Some(description.clone());
-        }
+        let mut db = new_database(name, options);
 
         if options.create_default_groups {
             for group_name in DEFAULT_GROUP_NAMES {
@@ -75,44 +55,94 @@ impl KdbxService {
             }
         }
 
-        let root_group_id = db.root.uuid.to_string();
-        let password_owned = password.map(String::from);
-        let keyfile_path_owned = keyfile_path.map(String::from);
+        open_new_database(&mut db_lock, db, path, password, keyfile_path)
+    }
+}
 
-        atomic_write(
-            path,
-            &AtomicWriteOptions {
-                preserve_permissions: false,
-            },
-            |file| {
-                let key =
-                    build_database_key(password_owned.as_deref(), keyfile_path_owned.as_deref())?;
-                db.save(file, key)
-                    .map_err(|e| AppError::Kdbx(e.to_string()))
-            },
-        )?;
-
-        // Acquire file lock after database file is created
-        let file_lock = FileLockService::try_acquire_lock(path)?;
-        let version = String::from("KDBX 4.0");
-
-        *db_lock = Some(OpenDatabase {
-            db,
-            path: path.to_string(),
-            is_modified: false,
-            password: password.map(SecureString::from),
-            keyfile_path: keyfile_path.map(String::from),
-            version: version.clone(),
-            file_lock: Some(file_lock),
-        });
-
-        Ok(DatabaseInfo {
-            name: name.to_string(),
-            path: path.to_string(),
-            is_modified: false,
-            is_locked: false,
-            root_group_id,
-            version,
-        })
+/// Builds an empty KDBX 4.0 database using the application's default
+/// cipher suite and the KDF parameters from `options`.
+pub(crate) fn new_database(name: &str, options: &DatabaseCreationOptions) -> Database {
+    let config = DatabaseConfig {
+        version: DatabaseVersion::KDB4(0),
+        outer_cipher_config: OuterCipherConfig::AES256,
+        compression_config: CompressionConfig::GZip,
+        inner_cipher_config: InnerCipherConfig::ChaCha20,
+        kdf_config: KdfConfig::Argon2id {
+            iterations: options.iterations(),
+            memory: options.memory_bytes(),
+            parallelism: options.parallelism(),
+            version: argon2::Version::Version13,
+        },
+        public_custom_data: None,
+    };
+
+    let mut db = Database::new(config);
+    db.root.name = name.to_string();
+    db.meta.database_name = Some(name.to_string());
+    db.meta.generator = Some(String::from("MithrilVault"));
+    if let Some(description) = &options.description {
+        db.meta.database_description = Some(description.clone());
     }
+
+    db
+}
+
+/// Writes a database that is not (yet) the open one to `path`.
+pub(crate) fn write_new_database(
+    db: &Database,
+    path: &str,
+    password: Option<&str>,
+    keyfile_path: Option<&str>,
+) -> Result<(), AppError> {
+    atomic_write(
+        path,
+        &AtomicWriteOptions {
+            preserve_permissions: false,
+        },
+        |file| {
+            let key = build_database_key(password, keyfile_path)?;
+            db.save(file, key)
+                .map_err(|e| AppError::Kdbx(e.to_string()))
+        },
+    )
+}
+
+/// Writes a freshly built database to `path`, acquires its file lock and
+/// installs it as the open database.
+pub(crate) fn open_new_database(
+    slot: &mut Option<OpenDatabase>,
+    db: Database,
+    path: &str,
+    password: Option<&str>,
+    keyfile_path: Option<&str>,
+) -> Result<DatabaseInfo, AppError> {
+    let name = db.root.name.clone();
+    let root_group_id = db.root.uuid.to_string();
+    write_new_database(&db, path, password, keyfile_path)?;
+
+    // Acquire file lock after database file is created
+    let file_lock = FileLockService::try_acquire_lock(path)?;
+    let version = format_database_version(&db.config.version);
+
+    *slot = Some(OpenDatabase {
+        db,
+        path: path.to_string(),
+        is_modified: false,
+        password: password.map(SecureString::from),
+        keyfile_path: keyfile_path.map(String::from),
+        composite_key: None,
+        version: version.clone(),
+        file_lock: Some(file_lock),
+        read_only: false,
+    });
+
+    Ok(DatabaseInfo {
+        name,
+        path: path.to_string(),
+        is_modified: false,
+        is_locked: false,
+        is_read_only: false,
+        root_group_id,
+        version,
+    })
 }
@@ -3,11 +3,18 @@ use crate::dto::error::AppError;
 use keepass::db::{Entry as KeepassEntry, Node, Times, Value};
 use secstr::SecStr;
 
+use super::autotype::{set_entry_auto_type, validate_entry_auto_type};
 use super::mapping::{
-    apply_custom_fields, convert_entry, ensure_recycle_bin, find_group_by_id, find_group_by_id_mut,
-    is_standard_entry_field, replace_custom_fields,
+    apply_custom_fields, apply_expiry, convert_entry, ensure_recycle_bin, find_entry_parent_id,
+    find_group_by_id, find_group_by_id_mut, is_standard_entry_field, recycle_bin_id,
+    replace_custom_fields,
 };
-use super::KdbxService;
+use super::recycle_bin::{
+    entry_in_recycle_bin, record_deleted_nodes, record_previous_parent, recycle_bin_enabled,
+    PREVIOUS_PARENT_KEY,
+};
+use super::references::{convert_entry_resolved, resolve_field};
+use super::{writable, KdbxService};
 
 impl KdbxService {
     /// Lists entries, optionally filtered by group.
@@ -20,9 +27,10 @@ impl KdbxService {
         if let Some(gid) = group_id {
             let group = find_group_by_id(&open_db.db.root, gid)
                 .ok_or_else(|| AppError::GroupNotFound(gid.to_string()))?;
-            collect_entries_from_group(group, &mut entries);
+            collect_entries_from_group(&open_db.db.root, group, &mut entries);
         } else {
-            collect_all_entries(&open_db.db.root, &mut entries);
+            let root = &open_db.db.root;
+            collect_all_entries(root, root, &mut entries);
         }
 
         Ok(entries)
@@ -33,19 +41,18 @@ impl KdbxService {
         let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
         let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
 
-        find_entry_by_id(&open_db.db.root, id)
-            .ok_or_else(|| AppError::EntryNotFound(id.to_string()))
+        let root = &open_db.db.root;
+        find_entry_by_id(root, root, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))
     }
 
-    /// Fetches an entry password.
+    /// Fetches an entry password, with field references resolved.
     pub fn get_entry_password(&self, id: &str) -> Result<String, AppError> {
         let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
         let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
 
-        match find_entry_password(&open_db.db.root, id) {
-            PasswordSearchResult::Found(password) => Ok(password),
-            PasswordSearchResult::NotFound => Err(AppError::EntryNotFound(id.to_string())),
-        }
+        let entry = find_entry_by_id_ref(&open_db.db.root, id)
+            .ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
+        resolve_field(&open_db.db.root, entry, "Password")
     }
 
     /// Fetches a protected custom field value.
@@ -81,12 +88,13 @@ impl KdbxService {
     /// Creates a new entry in a group.
     pub fn create_entry(&self, group_id: &str, data: CreateEntryData) -> Result<Entry, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
         let group = find_group_by_id_mut(&mut open_db.db.root, group_id)
             .ok_or_else(|| AppError::GroupNotFound(group_id.to_string()))?;
 
         let mut entry = KeepassEntry::new();
+        apply_expiry(&mut entry.times, None, data.expiry_time.as_deref())?;
         entry
             .fields
             .insert("Title".to_string(), Value::Unprotected(data.title));
@@ -130,11 +138,16 @@ impl KdbxService {
     /// Updates an existing entry.
     pub fn update_entry(&self, id: &str, data: UpdateEntryData) -> Result<Entry, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
-        let (entry, group_id) = find_entry_by_id_mut(&mut open_db.db.root, id)
+        let (entry, _) = find_entry_by_id_mut(&mut open_db.db.root, id)
             .ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
 
+        // Validated first so invalid input leaves the entry untouched
+        if let Some(auto_type) = &data.auto_type {
+            validate_entry_auto_type(auto_type)?;
+        }
+        apply_expiry(&mut entry.times, data.expires, data.expiry_time.as_deref())?;
         if let Some(title) = data.title {
             entry
                 .fields
@@ -174,23 +187,41 @@ impl KdbxService {
                 data.protected_custom_fields.as_ref(),
             );
         }
+        if let Some(auto_type) = data.auto_type {
+            set_entry_auto_type(entry, auto_type);
+        }
 
         entry.times.set_last_modification(Times::now());
         open_db.is_modified = true;
 
-        Ok(convert_entry(entry, &group_id))
+        let root = &open_db.db.root;
+        find_entry_by_id(root, root, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))
     }
 
     /// Deletes an entry by moving it to recycle bin.
-    pub fn delete_entry(&self, id: &str) -> Result<(), AppError> {
+    /// The entry is deleted permanently if `permanent` is true, the recycle bin
+    /// is disabled or the entry is already in the bin.
+    pub fn delete_entry(&self, id: &str, permanent: bool) -> Result<(), AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
+        let parent_id = find_entry_parent_id(&open_db.db.root, id)
+            .ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
+        let permanent =
+            permanent || !recycle_bin_enabled(&open_db.db) || entry_in_recycle_bin(&open_db.db, id);
         let mut entry = {
             let root = &mut open_db.db.root;
             remove_entry_by_id(root, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))?
         };
 
+        if permanent {
+            record_deleted_nodes(&mut open_db.db, vec![Node::Entry(entry)], Times::now());
+            open_db.is_modified = true;
+            return Ok(());
+        }
+
+        record_previous_parent(&mut entry.custom_data, &parent_id);
+
         let recycle_bin_id = ensure_recycle_bin(&mut open_db.db);
         let recycle_bin = find_group_by_id_mut(&mut open_db.db.root, &recycle_bin_id)
             .ok_or_else(|| AppError::GroupNotFound(recycle_bin_id.clone()))?;
@@ -207,13 +238,22 @@ impl KdbxService {
     /// Moves an entry to another group.
     pub fn move_entry(&self, id: &str, target_group_id: &str) -> Result<Entry, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
+        let parent_id = find_entry_parent_id(&open_db.db.root, id)
+            .ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
         let mut entry = {
             let root = &mut open_db.db.root;
             remove_entry_by_id(root, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))?
         };
 
+        // Remember where the entry came from when it is dragged into the bin
+        if recycle_bin_id(&open_db.db).as_deref() == Some(target_group_id) {
+            record_previous_parent(&mut entry.custom_data, &parent_id);
+        } else {
+            entry.custom_data.items.remove(PREVIOUS_PARENT_KEY);
+        }
+
         let target_group = find_group_by_id_mut(&mut open_db.db.root, target_group_id)
             .ok_or_else(|| AppError::GroupNotFound(target_group_id.to_string()))?;
 
@@ -229,16 +269,20 @@ impl KdbxService {
     }
 }
 
-fn find_entry_by_id(group: &keepass::db::Group, id: &str) -> Option<Entry> {
+fn find_entry_by_id(
+    root: &keepass::db::Group,
+    group: &keepass::db::Group,
+    id: &str,
+) -> Option<Entry> {
     for node in &group.children {
         match node {
             Node::Entry(entry) => {
                 if entry.uuid.to_string() == id {
-                    return Some(convert_entry(entry, &group.uuid.to_string()));
+                    return Some(convert_entry_resolved(root, entry, &group.uuid.to_string()));
                 }
             }
             Node::Group(child) => {
-                if let Some(found) = find_entry_by_id(child, id) {
+                if let Some(found) = find_entry_by_id(root, child, id) {
                     return Some(found);
                 }
             }
@@ -247,7 +291,10 @@ fn find_entry_by_id(group: &keepass::db::Group, id: &str) -> Option<Entry> {
     None
 }
 
-fn find_entry_by_id_ref<'a>(group: &'a keepass::db::Group, id: &str) -> Option<&'a KeepassEntry> {
+pub(crate) fn find_entry_by_id_ref<'a>(
+    group: &'a keepass::db::Group,
+    id: &str,
+) -> Option<&'a KeepassEntry> {
     for node in &group.children {
         match node {
             Node::Entry(entry) => {
@@ -265,7 +312,7 @@ fn find_entry_by_id_ref<'a>(group: &'a keepass::db::Group, id: &str) -> Option<&
     None
 }
 
-fn find_entry_by_id_mut<'a>(
+pub(crate) fn find_entry_by_id_mut<'a>(
     group: &'a mut keepass::db::Group,
     id: &str,
 ) -> Option<(&'a mut KeepassEntry, String)> {
@@ -289,57 +336,38 @@ fn find_entry_by_id_mut<'a>(
     None
 }
 
-enum PasswordSearchResult {
-    NotFound,
-    Found(String),
-}
-
-fn find_entry_password(group: &keepass::db::Group, id: &str) -> PasswordSearchResult {
-    for node in &group.children {
-        match node {
-            Node::Entry(entry) => {
-                if entry.uuid.to_string() == id {
-                    let password = entry
-                        .get_password()
-                        .map(std::string::ToString::to_string)
-                        .unwrap_or_default();
-                    return PasswordSearchResult::Found(password);
-                }
-            }
-            Node::Group(child) => {
-                if let PasswordSearchResult::Found(pw) = find_entry_password(child, id) {
-                    return PasswordSearchResult::Found(pw);
-                }
-            }
-        }
-    }
-    PasswordSearchResult::NotFound
-}
-
-fn collect_entries_from_group(group: &keepass::db::Group, entries: &mut Vec<Entry>) {
+fn collect_entries_from_group(
+    root: &keepass::db::Group,
+    group: &keepass::db::Group,
+    entries: &mut Vec<Entry>,
+) {
     let group_id = group.uuid.to_string();
     for node in &group.children {
         if let Node::Entry(entry) = node {
-            entries.push(convert_entry(entry, &group_id));
+            entries.push(convert_entry_resolved(root, entry, &group_id));
         }
     }
 }
 
-fn collect_all_entries(group: &keepass::db::Group, entries: &mut Vec<Entry>) {
+pub(crate) fn collect_all_entries(
+    root: &keepass::db::Group,
+    group: &keepass::db::Group,
+    entries: &mut Vec<Entry>,
+) {
     let group_id = group.uuid.to_string();
     for node in &group.children {
         match node {
             Node::Entry(entry) => {
-                entries.push(convert_entry(entry, &group_id));
+                entries.push(convert_entry_resolved(root, entry, &group_id));
             }
             Node::Group(child) => {
-                collect_all_entries(child, entries);
+                collect_all_entries(root, child, entries);
             }
         }
     }
 }
 
-fn remove_entry_by_id(group: &mut keepass::db::Group, id: &str) -> Option<KeepassEntry> {
+pub(crate) fn remove_entry_by_id(group: &mut keepass::db::Group, id: &str) -> Option<KeepassEntry> {
     let mut index = 0;
     while index < group.children.len() {
         match &mut group.children[index] {
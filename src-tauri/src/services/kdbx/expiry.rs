@@ -0,0 +1,67 @@
+use crate::dto::entry::{ExpiringEntry, ExpirySummary};
+use crate::dto::error::AppError;
+use chrono::TimeDelta;
+use keepass::db::Times;
+
+use super::health::collect_entries;
+use super::mapping::{is_expired, recycle_bin_id};
+use super::KdbxService;
+
+impl KdbxService {
+    /// Lists entries outside the recycle bin that have expired or expire
+    /// within `within_days` days, soonest first. A window reaching past the
+    /// latest representable date lists every entry with an expiry time.
+    pub fn list_expiring_entries(&self, within_days: u32) -> Result<Vec<ExpiringEntry>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut candidates = Vec::new();
+        collect_entries(&open_db.db.root, bin_id.as_deref(), &mut candidates);
+
+        let now = Times::now();
+        let horizon = TimeDelta::try_days(i64::from(within_days))
+            .and_then(|window| now.checked_add_signed(window));
+
+        let mut expiring: Vec<_> = candidates
+            .into_iter()
+            .filter(|(_, entry)| entry.times.expires)
+            .filter_map(|(group_id, entry)| {
+                let expiry = *entry.times.get_expiry()?;
+                horizon.is_none_or(|horizon| expiry <= horizon).then(|| {
+                    (
+                        expiry,
+                        ExpiringEntry {
+                            id: entry.uuid.to_string(),
+                            group_id,
+                            title: entry.get_title().unwrap_or_default().to_string(),
+                            username: entry.get_username().unwrap_or_default().to_string(),
+                            expiry_time: expiry.to_string(),
+                            expired: is_expired(&entry.times, now),
+                        },
+                    )
+                })
+            })
+            .collect();
+        expiring.sort_by_key(|(expiry, _)| *expiry);
+
+        Ok(expiring.into_iter().map(|(_, entry)| entry).collect())
+    }
+
+    /// Summarizes expired and soon-expiring entries, or returns `None` when
+    /// there are none.
+    pub fn expiry_summary(&self, within_days: u32) -> Result<Option<ExpirySummary>, AppError> {
+        let entries = self.list_expiring_entries(within_days)?;
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let expired_count = entries.iter().filter(|entry| entry.expired).count();
+        Ok(Some(ExpirySummary {
+            within_days,
+            expired_count,
+            expiring_count: entries.len() - expired_count,
+            entries,
+        }))
+    }
+}
@@ -0,0 +1,436 @@
+use crate::dto::database::DatabaseCreationOptions;
+use crate::dto::entry::Entry;
+use crate::dto::error::AppError;
+use crate::dto::export::{
+    ExportField, ExportFormat, ExportOptions, ExportResult, GroupExportResult,
+};
+use crate::dto::group::Group;
+use crate::services::kdbx::attachments::copy_attachments;
+use crate::services::kdbx::create::{new_database, write_new_database};
+use crate::services::kdbx::entries::{collect_all_entries, find_entry_by_id_ref};
+use crate::services::kdbx::mapping::{convert_group, find_group_by_id};
+use crate::services::kdbx::references::resolve_field;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+use keepass::db::{
+    Entry as KeepassEntry, Group as KeepassGroup, HeaderAttachment, History, Node, Value,
+};
+use keepass::Database;
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use super::KdbxService;
+
+const GROUP_COLUMN: &str = "Group";
+
+/// JSON key holding the custom fields of an entry, and prefix of their CSV
+/// columns, so they can't collide with the standard columns.
+const CUSTOM_FIELDS_KEY: &str = "Fields";
+
+impl KdbxService {
+    /// Exports entries of the open database to a plaintext CSV or JSON file.
+    ///
+    /// Protected values are only written when `include_protected` and
+    /// `acknowledge_plaintext` are both set. The file is created with
+    /// owner-only permissions. In JSON, custom fields are nested under a
+    /// `Fields` object and in CSV their columns are named `Fields/<key>`, so
+    /// they can't clash with the standard columns. The entries are read
+    /// under one database lock, so the file is a consistent snapshot.
+    pub fn export_entries(
+        &self,
+        path: &str,
+        options: &ExportOptions,
+    ) -> Result<ExportResult, AppError> {
+        if options.include_protected && !options.acknowledge_plaintext {
+            return Err(AppError::PlaintextExportNotAcknowledged);
+        }
+
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        if Path::new(&open_db.path) == Path::new(path) {
+            return Err(AppError::InvalidPath(
+                "Export target must differ from the open database".to_string(),
+            ));
+        }
+        let root = &open_db.db.root;
+
+        let groups = convert_group(root, None);
+        let mut group_paths = HashMap::new();
+        collect_group_paths(&groups, "", &mut group_paths);
+
+        let mut entries = Vec::new();
+        collect_all_entries(root, root, &mut entries);
+        if let Some(group_id) = options.group_id.as_deref() {
+            let subtree_root = find_group(&groups, group_id)
+                .ok_or_else(|| AppError::GroupNotFound(group_id.to_string()))?;
+            let mut subtree = HashMap::new();
+            collect_group_paths(subtree_root, "", &mut subtree);
+            entries.retain(|entry| subtree.contains_key(entry.group_id.as_str()));
+        }
+
+        let mut fields = if options.fields.is_empty() {
+            ExportField::defaults()
+        } else {
+            options.fields.clone()
+        };
+        // A column selected twice would be written twice
+        let mut seen = HashSet::new();
+        fields.retain(|field| seen.insert(field.clone()));
+
+        let mut columns: Vec<&str> = Vec::with_capacity(fields.len() + 1);
+        if options.include_group_path {
+            columns.push(GROUP_COLUMN);
+        }
+        columns.extend(fields.iter().map(ExportField::column_name));
+
+        let mut rows = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            let mut row = Vec::with_capacity(columns.len());
+            if options.include_group_path {
+                let group_path = group_paths.get(entry.group_id.as_str()).cloned();
+                row.push(Zeroizing::new(group_path.unwrap_or_default()));
+            }
+            for field in &fields {
+                row.push(export_cell(root, entry, field, options.include_protected)?);
+            }
+            rows.push(row);
+        }
+        drop(db_lock);
+
+        let mut custom = vec![false; columns.len() - fields.len()];
+        custom.extend(
+            fields
+                .iter()
+                .map(|field| matches!(field, ExportField::Custom(_))),
+        );
+        atomic_write(
+            path,
+            &AtomicWriteOptions {
+                preserve_permissions: false,
+            },
+            |file| match options.format {
+                ExportFormat::Csv => write_csv(file, &columns, &custom, &rows),
+                ExportFormat::Json => write_json(file, &columns, &custom, &rows),
+            },
+        )?;
+
+        Ok(ExportResult {
+            path: path.to_string(),
+            entry_count: rows.len(),
+            includes_protected: options.include_protected,
+        })
+    }
+
+    /// Writes a group and everything below it (entries, history,
+    /// attachments and the custom icons they use) to a new KDBX4 database
+    /// protected by its own credentials. The open database is not modified.
+    pub fn export_group(
+        &self,
+        group_id: &str,
+        path: &str,
+        password: Option<&str>,
+        keyfile_path: Option<&str>,
+        options: &DatabaseCreationOptions,
+    ) -> Result<GroupExportResult, AppError> {
+        if password.is_none() && keyfile_path.is_none() {
+            return Err(AppError::NoCredentials);
+        }
+
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        if Path::new(&open_db.path) == Path::new(path) {
+            return Err(AppError::InvalidPath(
+                "Export target must differ from the open database".to_string(),
+            ));
+        }
+
+        let group = find_group_by_id(&open_db.db.root, group_id)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.to_string()))?;
+
+        let (db, counts) = subtree_database(&open_db.db, group, options);
+        write_new_database(&db, path, password, keyfile_path)?;
+
+        Ok(GroupExportResult {
+            path: path.to_string(),
+            group_count: counts.groups,
+            entry_count: counts.entries,
+            attachment_count: counts.attachments,
+            custom_icon_count: counts.custom_icons,
+        })
+    }
+}
+
+/// Number of items copied into a subtree database.
+#[derive(Debug, Default)]
+pub(crate) struct SubtreeCounts {
+    pub groups: usize,
+    pub entries: usize,
+    pub attachments: usize,
+    pub custom_icons: usize,
+}
+
+/// Builds a new database whose root is a copy of `group`, including entry
+/// history, attachments and the custom icons referenced by the subtree.
+pub(crate) fn subtree_database(
+    source: &Database,
+    group: &KeepassGroup,
+    options: &DatabaseCreationOptions,
+) -> (Database, SubtreeCounts) {
+    let mut db = new_database(&group.name, options);
+    let mut subtree = group.clone();
+    let mut counts = SubtreeCounts::default();
+    let mut icon_uuids = HashSet::new();
+    relink_group(
+        &mut subtree,
+        &source.header_attachments,
+        &mut db.header_attachments,
+        &mut icon_uuids,
+        &mut counts,
+    );
+
+    db.meta.custom_icons.icons = source
+        .meta
+        .custom_icons
+        .icons
+        .iter()
+        .filter(|icon| icon_uuids.contains(&icon.uuid))
+        .cloned()
+        .collect();
+    counts.custom_icons = db.meta.custom_icons.icons.len();
+
+    // The exported group becomes the root of the new database
+    db.root = subtree;
+    (db, counts)
+}
+
+/// Moves the attachments of every entry below `group` into `target_pool`,
+/// records the custom icons in use and counts groups and entries.
+fn relink_group(
+    group: &mut KeepassGroup,
+    source_pool: &[HeaderAttachment],
+    target_pool: &mut Vec<HeaderAttachment>,
+    icon_uuids: &mut HashSet<Uuid>,
+    counts: &mut SubtreeCounts,
+) {
+    counts.groups += 1;
+    icon_uuids.extend(group.custom_icon_uuid);
+
+    for node in &mut group.children {
+        match node {
+            Node::Group(child) => {
+                relink_group(child, source_pool, target_pool, icon_uuids, counts);
+            }
+            Node::Entry(entry) => {
+                counts.entries += 1;
+                counts.attachments += relink_entry(entry, source_pool, target_pool, icon_uuids);
+
+                if let Some(history) = entry.history.take() {
+                    let mut relinked = History::default();
+                    for mut old in history.get_entries().iter().rev().cloned() {
+                        relink_entry(&mut old, source_pool, target_pool, icon_uuids);
+                        relinked.add_entry(old);
+                    }
+                    entry.history = Some(relinked);
+                }
+            }
+        }
+    }
+}
+
+fn relink_entry(
+    entry: &mut KeepassEntry,
+    source_pool: &[HeaderAttachment],
+    target_pool: &mut Vec<HeaderAttachment>,
+    icon_uuids: &mut HashSet<Uuid>,
+) -> usize {
+    icon_uuids.extend(entry.custom_icon_uuid);
+    copy_attachments(source_pool, target_pool, entry)
+}
+
+/// The value of `field` for `entry`. Protected values are only read when
+/// `include_protected` is set.
+fn export_cell(
+    root: &KeepassGroup,
+    entry: &Entry,
+    field: &ExportField,
+    include_protected: bool,
+) -> Result<Zeroizing<String>, AppError> {
+    let value = match field {
+        ExportField::Title => entry.title.clone(),
+        ExportField::Username => entry.username.clone(),
+        ExportField::Password if include_protected => {
+            resolve_field(root, raw_entry(root, &entry.id)?, "Password")?
+        }
+        ExportField::Password => String::new(),
+        ExportField::Url => entry.url.clone().unwrap_or_default(),
+        ExportField::Notes => entry.notes.clone().unwrap_or_default(),
+        ExportField::Tags => entry.tags.join(","),
+        ExportField::CreatedAt => entry.created_at.clone(),
+        ExportField::ModifiedAt => entry.modified_at.clone(),
+        ExportField::Custom(key) => {
+            let is_protected = entry
+                .custom_field_meta
+                .iter()
+                .any(|meta| meta.key == *key && meta.is_protected);
+            if !is_protected {
+                entry.custom_fields.get(key).cloned().unwrap_or_default()
+            } else if include_protected {
+                match raw_entry(root, &entry.id)?.fields.get(key) {
+                    Some(Value::Protected(secret)) => {
+                        String::from_utf8_lossy(secret.unsecure()).into_owned()
+                    }
+                    _ => String::new(),
+                }
+            } else {
+                String::new()
+            }
+        }
+    };
+
+    Ok(Zeroizing::new(value))
+}
+
+fn raw_entry<'a>(root: &'a KeepassGroup, id: &str) -> Result<&'a KeepassEntry, AppError> {
+    find_entry_by_id_ref(root, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))
+}
+
+fn collect_group_paths<'a>(
+    group: &'a Group,
+    parent_path: &str,
+    paths: &mut HashMap<&'a str, String>,
+) {
+    let path = if parent_path.is_empty() {
+        group.name.clone()
+    } else {
+        format!("{parent_path}/{}", group.name)
+    };
+    for child in &group.children {
+        collect_group_paths(child, &path, paths);
+    }
+    paths.insert(group.id.as_str(), path);
+}
+
+fn find_group<'a>(group: &'a Group, id: &str) -> Option<&'a Group> {
+    if group.id == id {
+        return Some(group);
+    }
+    group
+        .children
+        .iter()
+        .find_map(|child| find_group(child, id))
+}
+
+fn write_csv(
+    file: &mut File,
+    columns: &[&str],
+    custom: &[bool],
+    rows: &[Vec<Zeroizing<String>>],
+) -> Result<(), AppError> {
+    let mut writer = csv::Writer::from_writer(file);
+    let header = columns.iter().zip(custom).map(|(column, is_custom)| {
+        if *is_custom {
+            format!("{CUSTOM_FIELDS_KEY}/{column}")
+        } else {
+            (*column).to_string()
+        }
+    });
+    writer
+        .write_record(header)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    for row in rows {
+        writer
+            .write_record(row.iter().map(|cell| cell.as_bytes()))
+            .map_err(|e| AppError::Io(e.to_string()))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_json(
+    file: &mut File,
+    columns: &[&str],
+    custom: &[bool],
+    rows: &[Vec<Zeroizing<String>>],
+) -> Result<(), AppError> {
+    serde_json::to_writer_pretty(
+        file,
+        &JsonRows {
+            columns,
+            custom,
+            rows,
+        },
+    )
+    .map_err(|e| AppError::Io(e.to_string()))
+}
+
+/// Serializes rows as an array of objects without copying the cell values.
+struct JsonRows<'a> {
+    columns: &'a [&'a str],
+    /// Marks the columns that are custom fields
+    custom: &'a [bool],
+    rows: &'a [Vec<Zeroizing<String>>],
+}
+
+impl Serialize for JsonRows<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.rows.len()))?;
+        for row in self.rows {
+            seq.serialize_element(&JsonRow {
+                columns: self.columns,
+                custom: self.custom,
+                row,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+/// One entry: the standard columns, then its custom fields nested under
+/// [`CUSTOM_FIELDS_KEY`].
+struct JsonRow<'a> {
+    columns: &'a [&'a str],
+    custom: &'a [bool],
+    row: &'a [Zeroizing<String>],
+}
+
+impl JsonRow<'_> {
+    /// Cells that are (or are not) custom fields, with their column names.
+    fn cells(&self, custom: bool) -> impl Iterator<Item = (&str, &str)> {
+        self.columns
+            .iter()
+            .zip(self.custom)
+            .zip(self.row)
+            .filter(move |((_, is_custom), _)| **is_custom == custom)
+            .map(|((column, _), cell)| (*column, cell.as_str()))
+    }
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (column, cell) in self.cells(false) {
+            map.serialize_entry(column, cell)?;
+        }
+        if self.custom.contains(&true) {
+            map.serialize_entry(CUSTOM_FIELDS_KEY, &CustomFields(self))?;
+        }
+        map.end()
+    }
+}
+
+struct CustomFields<'a>(&'a JsonRow<'a>);
+
+impl Serialize for CustomFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (key, cell) in self.0.cells(true) {
+            map.serialize_entry(key, cell)?;
+        }
+        map.end()
+    }
+}
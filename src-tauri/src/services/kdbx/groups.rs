@@ -1,12 +1,17 @@
 use crate::dto::error::AppError;
 use crate::dto::group::{Group, UpdateGroupData};
-use keepass::db::{Group as KeepassGroup, Times};
+use keepass::db::{Group as KeepassGroup, Node, Times};
 
+use super::autotype::set_group_auto_type;
 use super::mapping::{
     convert_group, ensure_recycle_bin, find_group_by_id, find_group_by_id_mut,
-    find_parent_group_id, group_has_children, is_ancestor_of, remove_group_by_id,
+    find_parent_group_id, group_has_children, is_ancestor_of, recycle_bin_id, remove_group_by_id,
 };
-use super::KdbxService;
+use super::recycle_bin::{
+    group_in_recycle_bin, record_deleted_nodes, record_previous_parent, recycle_bin_enabled,
+    PREVIOUS_PARENT_KEY,
+};
+use super::{writable, KdbxService};
 
 impl KdbxService {
     /// Lists groups in a hierarchy.
@@ -36,7 +41,7 @@ impl KdbxService {
         icon: Option<u32>,
     ) -> Result<Group, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
         // Find the parent group (root if parent_id is None)
         let (parent, parent_uuid) = if let Some(pid) = parent_id {
@@ -65,7 +70,7 @@ impl KdbxService {
     /// Updates an existing group.
     pub fn update_group(&self, id: &str, data: UpdateGroupData) -> Result<Group, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
         // Find parent ID before mutating (for return value)
         let parent_id = find_parent_group_id(&open_db.db.root, id);
@@ -73,6 +78,10 @@ impl KdbxService {
         let group = find_group_by_id_mut(&mut open_db.db.root, id)
             .ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
 
+        // Applied first so an invalid sequence leaves the group untouched
+        if let Some(auto_type) = data.auto_type {
+            set_group_auto_type(group, auto_type)?;
+        }
         if let Some(name) = data.name {
             group.name = name;
         }
@@ -89,9 +98,12 @@ impl KdbxService {
     /// Deletes a group.
     /// If `recursive` is false and the group has children, returns an error.
     /// If `permanent` is true, the group is permanently deleted; otherwise moved to recycle bin.
+    /// Groups are also deleted permanently when the recycle bin is disabled or
+    /// the group is the bin or inside it. Permanent deletions are recorded in
+    /// the deleted objects list.
     pub fn delete_group(&self, id: &str, recursive: bool, permanent: bool) -> Result<(), AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
         // Cannot delete root group
         if open_db.db.root.uuid.to_string() == id {
@@ -108,12 +120,21 @@ impl KdbxService {
             }
         }
 
+        let permanent =
+            permanent || !recycle_bin_enabled(&open_db.db) || group_in_recycle_bin(&open_db.db, id);
+
         // Remove the group from its parent
+        let parent_id = find_parent_group_id(&open_db.db.root, id)
+            .ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
         let mut removed_group = remove_group_by_id(&mut open_db.db.root, id)
             .ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
 
         if permanent {
-            // Permanently deleted, nothing more to do
+            record_deleted_nodes(
+                &mut open_db.db,
+                vec![Node::Group(removed_group)],
+                Times::now(),
+            );
         } else {
             // Move to recycle bin
             let recycle_bin_id = ensure_recycle_bin(&mut open_db.db);
@@ -123,6 +144,7 @@ impl KdbxService {
             let now = Times::now();
             removed_group.times.set_last_modification(now);
             removed_group.times.set_location_changed(now);
+            record_previous_parent(&mut removed_group.custom_data, &parent_id);
             recycle_bin.add_child(removed_group);
         }
 
@@ -134,7 +156,7 @@ impl KdbxService {
     /// If `target_parent_id` is None, moves to root.
     pub fn move_group(&self, id: &str, target_parent_id: Option<&str>) -> Result<Group, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
         let root_id = open_db.db.root.uuid.to_string();
 
@@ -162,9 +184,19 @@ impl KdbxService {
         }
 
         // Remove the group from its current parent
+        let parent_id = find_parent_group_id(&open_db.db.root, id)
+            .ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
+        let to_recycle_bin = recycle_bin_id(&open_db.db).as_deref() == Some(target_id);
         let mut group = remove_group_by_id(&mut open_db.db.root, id)
             .ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
 
+        // Remember where the group came from when it is dragged into the bin
+        if to_recycle_bin {
+            record_previous_parent(&mut group.custom_data, &parent_id);
+        } else {
+            group.custom_data.items.remove(PREVIOUS_PARENT_KEY);
+        }
+
         // Update timestamps
         let now = Times::now();
         group.times.set_last_modification(now);
@@ -0,0 +1,185 @@
+use crate::dto::error::AppError;
+use crate::dto::health::{
+    EntryHealth, HealthIssue, HealthReport, HealthReportOptions, PasswordQuality,
+    ReusedPasswordGroup,
+};
+use crate::utils::password_strength::estimate_entropy;
+use chrono::{Duration, NaiveDateTime};
+use hmac::{Hmac, Mac};
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Node, Times, Value};
+use rand::RngCore;
+use sha2::Sha256;
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+use super::mapping::{is_expired, recycle_bin_id};
+use super::KdbxService;
+
+/// Entry custom data flag that other clients set to exclude an entry from
+/// health reports.
+const EXCLUDE_FROM_REPORTS_KEY: &str = "KnownBad";
+
+impl KdbxService {
+    /// Checks every entry outside the recycle bin for empty, weak, reused,
+    /// old and expired passwords.
+    ///
+    /// Reused passwords are matched by HMAC with a key that only lives for
+    /// the duration of the report; neither passwords nor hashes are returned.
+    pub fn health_report(&self, options: &HealthReportOptions) -> Result<HealthReport, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut candidates = Vec::new();
+        collect_entries(&open_db.db.root, bin_id.as_deref(), &mut candidates);
+
+        let mut hmac_key = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(hmac_key.as_mut());
+
+        let now = Times::now();
+        let max_age = Duration::days(i64::from(options.max_password_age_days));
+
+        let mut entries = Vec::new();
+        let mut excluded_entry_ids = Vec::new();
+        let mut by_hash: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+
+        for (group_id, entry) in candidates {
+            if is_excluded(entry) {
+                excluded_entry_ids.push(entry.uuid.to_string());
+                continue;
+            }
+
+            let password = entry.get_password().unwrap_or_default();
+            let entropy = estimate_entropy(password);
+            let quality = PasswordQuality::from_entropy(entropy);
+            let changed_at = password_changed_at(entry);
+
+            let mut issues = Vec::new();
+            if password.is_empty() {
+                issues.push(HealthIssue::Empty);
+            } else {
+                if quality <= PasswordQuality::Weak {
+                    issues.push(HealthIssue::Weak);
+                }
+                by_hash
+                    .entry(keyed_hash(hmac_key.as_ref(), password)?)
+                    .or_default()
+                    .push(entries.len());
+            }
+            if changed_at.is_some_and(|changed| now - changed > max_age) {
+                issues.push(HealthIssue::Old);
+            }
+            if is_expired(&entry.times, now) {
+                issues.push(HealthIssue::Expired);
+            }
+
+            entries.push(EntryHealth {
+                entry_id: entry.uuid.to_string(),
+                group_id,
+                title: entry.get_title().unwrap_or_default().to_string(),
+                entropy,
+                quality,
+                password_changed_at: changed_at.map(|t| t.to_string()),
+                reuse_count: 0,
+                issues,
+            });
+        }
+
+        let mut reused = Vec::new();
+        for indices in by_hash.into_values().filter(|indices| indices.len() > 1) {
+            for &index in &indices {
+                let entry = &mut entries[index];
+                entry.reuse_count = indices.len() - 1;
+                entry.issues.push(HealthIssue::Reused);
+            }
+            reused.push(ReusedPasswordGroup {
+                entry_ids: indices
+                    .iter()
+                    .map(|&index| entries[index].entry_id.clone())
+                    .collect(),
+            });
+        }
+        reused.sort_by(|a, b| {
+            b.entry_ids
+                .len()
+                .cmp(&a.entry_ids.len())
+                .then_with(|| a.entry_ids.cmp(&b.entry_ids))
+        });
+
+        Ok(HealthReport {
+            entries,
+            reused,
+            excluded_entry_ids,
+        })
+    }
+}
+
+/// Collects entries together with their group ID, skipping the recycle bin.
+pub(super) fn collect_entries<'a>(
+    group: &'a KeepassGroup,
+    bin_id: Option<&str>,
+    entries: &mut Vec<(String, &'a KeepassEntry)>,
+) {
+    let group_id = group.uuid.to_string();
+    if bin_id == Some(group_id.as_str()) {
+        return;
+    }
+
+    for node in &group.children {
+        match node {
+            Node::Entry(entry) => entries.push((group_id.clone(), entry)),
+            Node::Group(child) => collect_entries(child, bin_id, entries),
+        }
+    }
+}
+
+/// Entries are excluded with the KDBX `QualityCheck` flag or the custom
+/// data flag of other clients.
+fn is_excluded(entry: &KeepassEntry) -> bool {
+    entry.quality_check == Some(false)
+        || entry
+            .custom_data
+            .items
+            .get(EXCLUDE_FROM_REPORTS_KEY)
+            .and_then(|item| item.value.as_ref())
+            .is_some_and(
+                |value| matches!(value, Value::Unprotected(v) if v.eq_ignore_ascii_case("true")),
+            )
+}
+
+/// Walks the history from newest to oldest and returns the modification
+/// time of the oldest version that still has the current password.
+fn password_changed_at(entry: &KeepassEntry) -> Option<NaiveDateTime> {
+    let current = entry.get_password().unwrap_or_default();
+    let mut changed_at = entry.times.get_last_modification().copied();
+
+    let Some(history) = entry.history.as_ref() else {
+        return changed_at;
+    };
+    let mut versions: Vec<&KeepassEntry> = history.get_entries().iter().collect();
+    versions.sort_by(|a, b| {
+        b.times
+            .get_last_modification()
+            .cmp(&a.times.get_last_modification())
+    });
+
+    for version in versions {
+        if version.get_password().unwrap_or_default() != current {
+            break;
+        }
+        changed_at = version
+            .times
+            .get_last_modification()
+            .copied()
+            .or(changed_at);
+    }
+
+    changed_at
+}
+
+fn keyed_hash(key: &[u8], password: &str) -> Result<[u8; 32], AppError> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).map_err(|e| AppError::Crypto(e.to_string()))?;
+    mac.update(password.as_bytes());
+    Ok(mac.finalize().into_bytes().into())
+}
@@ -0,0 +1,72 @@
+use crate::dto::database::{DatabaseCreationOptions, KdbImportResult, KdbImportSource};
+use crate::dto::error::AppError;
+use crate::services::kdbx::create::{new_database, open_new_database};
+use crate::services::kdbx::kdb::read_kdb;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use super::KdbxService;
+
+impl KdbxService {
+    /// Converts a legacy KDB database into a new KDBX4 database at `path`
+    /// and opens it. The source file is left untouched.
+    pub fn import_kdb(
+        &self,
+        source: &KdbImportSource,
+        path: &str,
+        password: Option<&str>,
+        keyfile_path: Option<&str>,
+        name: &str,
+        options: &DatabaseCreationOptions,
+    ) -> Result<KdbImportResult, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+
+        if db_lock.is_some() || self.is_locked()? {
+            return Err(AppError::DatabaseAlreadyOpen);
+        }
+
+        if password.is_none() && keyfile_path.is_none() {
+            return Err(AppError::NoCredentials);
+        }
+
+        if source.password.is_none() && source.keyfile_path.is_none() {
+            return Err(AppError::NoCredentials);
+        }
+
+        if Path::new(&source.path) == Path::new(path) {
+            return Err(AppError::InvalidPath(
+                "Import target must differ from the source file".to_string(),
+            ));
+        }
+
+        let data = std::fs::read(&source.path)
+            .map_err(|e| AppError::InvalidPath(format!("{}: {e}", source.path)))?;
+        let keyfile = source
+            .keyfile_path
+            .as_deref()
+            .map(|kf| {
+                std::fs::read(kf)
+                    .map(Zeroizing::new)
+                    .map_err(|_| AppError::KeyfileNotFound)
+            })
+            .transpose()?;
+
+        let mut db = new_database(name, options);
+        let stats = read_kdb(
+            &data,
+            source.password.as_deref(),
+            keyfile.as_ref().map(|kf| kf.as_slice()),
+            &mut db,
+        )?;
+
+        let database = open_new_database(&mut db_lock, db, path, password, keyfile_path)?;
+
+        Ok(KdbImportResult {
+            database,
+            group_count: stats.group_count,
+            entry_count: stats.entry_count,
+            attachment_count: stats.attachment_count,
+            skipped_meta_streams: stats.skipped_meta_streams,
+        })
+    }
+}
@@ -0,0 +1,727 @@
+//! Reader for legacy KDB (`.kdb`) databases.
+//!
+//! The `keepass` crate can open KDB files, but it drops UUIDs, timestamps,
+//! icons, meta-streams and attachments. The importer therefore decrypts and
+//! parses the format itself and maps the result onto a KDBX4 [`Database`].
+
+use crate::dto::error::AppError;
+use aes::Aes256;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncrypt, KeyInit, KeyIvInit};
+use chrono::{NaiveDate, NaiveDateTime};
+use keepass::db::{Color, Entry, Group, Icon, Node, Value};
+use keepass::Database;
+use secstr::SecStr;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use super::attachments::add_attachment;
+
+const KDB_SIGNATURE_1: u32 = 0x9AA2_D903;
+const KDB_SIGNATURE_2: u32 = 0xB54B_FB65;
+const KDB_VERSION: u32 = 0x0003_0004;
+const KDB_VERSION_MASK: u32 = 0xFFFF_FF00;
+const HEADER_SIZE: usize = 124;
+
+const FLAG_RIJNDAEL: u32 = 0x02;
+const FLAG_ARCFOUR: u32 = 0x04;
+const FLAG_TWOFISH: u32 = 0x08;
+
+const FIELD_END: u16 = 0xFFFF;
+
+/// Expiry date the KDB format uses to mean "never expires".
+const NEVER_EXPIRES: (i32, u32, u32) = (2999, 12, 28);
+
+const META_STREAM_TITLE: &str = "Meta-Info";
+const META_STREAM_USERNAME: &str = "SYSTEM";
+const META_STREAM_URL: &str = "$";
+const META_STREAM_BINARY_DESC: &str = "bin-stream";
+
+const META_CUSTOM_ICONS: &str = "KPX_CUSTOM_ICONS_4";
+const META_GROUP_TREE_STATE: &str = "KPX_GROUP_TREE_STATE";
+const META_DEFAULT_USERNAME: &str = "Default User Name";
+const META_DATABASE_COLOR: &str = "Database Color";
+
+/// Counts reported after a KDB file has been mapped into a database.
+#[derive(Debug, Default)]
+pub(crate) struct KdbImportStats {
+    pub group_count: usize,
+    pub entry_count: usize,
+    pub attachment_count: usize,
+    pub skipped_meta_streams: Vec<String>,
+}
+
+struct KdbHeader {
+    flags: u32,
+    master_seed: [u8; 16],
+    encryption_iv: [u8; 16],
+    num_groups: u32,
+    num_entries: u32,
+    contents_hash: [u8; 32],
+    transform_seed: [u8; 32],
+    transform_rounds: u32,
+}
+
+struct KdbGroup {
+    id: u32,
+    level: u16,
+    group: Group,
+}
+
+struct KdbEntry {
+    group_id: u32,
+    image_id: u32,
+    entry: Entry,
+    binary_desc: String,
+    binary_data: Vec<u8>,
+}
+
+impl KdbEntry {
+    fn field(&self, key: &str) -> &str {
+        self.entry.get(key).unwrap_or_default()
+    }
+
+    /// Mirrors `_IsMetaStream` from the reference KDB implementation.
+    fn is_meta_stream(&self) -> bool {
+        !self.binary_data.is_empty()
+            && !self.field("Notes").is_empty()
+            && self.binary_desc == META_STREAM_BINARY_DESC
+            && self.field("Title") == META_STREAM_TITLE
+            && self.field("UserName") == META_STREAM_USERNAME
+            && self.field("URL") == META_STREAM_URL
+            && self.image_id == 0
+    }
+}
+
+/// Decrypts a KDB file and adds its groups, entries, attachments and
+/// meta-stream settings to `db`. Top-level KDB groups become children of
+/// `db.root`.
+pub(crate) fn read_kdb(
+    data: &[u8],
+    password: Option<&str>,
+    keyfile: Option<&[u8]>,
+    db: &mut Database,
+) -> Result<KdbImportStats, AppError> {
+    let header = parse_header(data)?;
+    let payload = decrypt_payload(&header, &data[HEADER_SIZE..], password, keyfile)?;
+
+    let mut reader = Reader::new(&payload);
+    let mut groups = Vec::new();
+    for _ in 0..header.num_groups {
+        groups.push(read_group(&mut reader)?);
+    }
+    let mut entries = Vec::new();
+    for _ in 0..header.num_entries {
+        entries.push(read_entry(&mut reader)?);
+    }
+
+    let mut stats = KdbImportStats {
+        group_count: groups.len(),
+        ..KdbImportStats::default()
+    };
+
+    let (meta_streams, entries): (Vec<KdbEntry>, Vec<KdbEntry>) =
+        entries.into_iter().partition(KdbEntry::is_meta_stream);
+
+    let mut group_icons = HashMap::new();
+    let mut entry_icons = HashMap::new();
+    let mut expanded = HashMap::new();
+    for stream in meta_streams {
+        let name = stream.field("Notes").to_string();
+        let data = stream.binary_data.as_slice();
+        match name.as_str() {
+            META_CUSTOM_ICONS => {
+                read_custom_icons(data, db, &mut group_icons, &mut entry_icons)?;
+            }
+            META_GROUP_TREE_STATE => read_group_tree_state(data, &mut expanded)?,
+            META_DEFAULT_USERNAME => {
+                db.meta.default_username = Some(read_c_string(data));
+            }
+            META_DATABASE_COLOR if data.len() >= 3 => {
+                // COLORREF, stored as 0x00BBGGRR
+                db.meta.color = Some(Color {
+                    r: data[0],
+                    g: data[1],
+                    b: data[2],
+                });
+            }
+            _ => stats.skipped_meta_streams.push(name),
+        }
+    }
+
+    let mut entries_by_group: HashMap<u32, Vec<Entry>> = HashMap::new();
+    for kdb_entry in entries {
+        if !groups.iter().any(|g| g.id == kdb_entry.group_id) {
+            return Err(AppError::Kdbx(format!(
+                "KDB entry references unknown group {}",
+                kdb_entry.group_id
+            )));
+        }
+
+        let mut entry = kdb_entry.entry;
+        if let Some(icon_uuid) = entry_icons.get(&entry.uuid) {
+            entry.custom_icon_uuid = Some(*icon_uuid);
+        }
+        if !kdb_entry.binary_data.is_empty() || !kdb_entry.binary_desc.is_empty() {
+            let name = if kdb_entry.binary_desc.is_empty() {
+                String::from("attachment")
+            } else {
+                kdb_entry.binary_desc
+            };
+            add_attachment(
+                &mut db.header_attachments,
+                &mut entry,
+                &name,
+                kdb_entry.binary_data,
+                false,
+            );
+            stats.attachment_count += 1;
+        }
+
+        stats.entry_count += 1;
+        entries_by_group
+            .entry(kdb_entry.group_id)
+            .or_default()
+            .push(entry);
+    }
+
+    for kdb_group in &mut groups {
+        if let Some(icon_uuid) = group_icons.get(&kdb_group.id) {
+            kdb_group.group.custom_icon_uuid = Some(*icon_uuid);
+        }
+        if let Some(is_expanded) = expanded.get(&kdb_group.id) {
+            kdb_group.group.is_expanded = *is_expanded;
+        }
+        if let Some(group_entries) = entries_by_group.remove(&kdb_group.id) {
+            kdb_group
+                .group
+                .children
+                .extend(group_entries.into_iter().map(Node::Entry));
+        }
+    }
+
+    for group in build_group_tree(groups)? {
+        db.root.add_child(group);
+    }
+
+    Ok(stats)
+}
+
+fn parse_header(data: &[u8]) -> Result<KdbHeader, AppError> {
+    if data.len() < HEADER_SIZE {
+        return Err(AppError::InvalidKdbxFile);
+    }
+
+    let mut reader = Reader::new(&data[..HEADER_SIZE]);
+    if reader.u32()? != KDB_SIGNATURE_1 || reader.u32()? != KDB_SIGNATURE_2 {
+        return Err(AppError::InvalidKdbxFile);
+    }
+
+    let flags = reader.u32()?;
+    let version = reader.u32()?;
+    if version & KDB_VERSION_MASK != KDB_VERSION & KDB_VERSION_MASK {
+        return Err(AppError::UnsupportedKdbxVersion(format!(
+            "KDB version {version:#010x}"
+        )));
+    }
+
+    Ok(KdbHeader {
+        flags,
+        master_seed: reader.array()?,
+        encryption_iv: reader.array()?,
+        num_groups: reader.u32()?,
+        num_entries: reader.u32()?,
+        contents_hash: reader.array()?,
+        transform_seed: reader.array()?,
+        transform_rounds: reader.u32()?,
+    })
+}
+
+/// Derives the master key and decrypts the payload. Non-ASCII passwords are
+/// tried as UTF-8 first and then as Latin-1, which older KDB
+/// writers used.
+fn decrypt_payload(
+    header: &KdbHeader,
+    ciphertext: &[u8],
+    password: Option<&str>,
+    keyfile: Option<&[u8]>,
+) -> Result<Zeroizing<Vec<u8>>, AppError> {
+    if header.flags & FLAG_RIJNDAEL == 0 && header.flags & FLAG_TWOFISH == 0 {
+        let cipher = if header.flags & FLAG_ARCFOUR == 0 {
+            format!("Unknown KDB cipher flags: {:#x}", header.flags)
+        } else {
+            String::from("ArcFour")
+        };
+        return Err(AppError::UnsupportedCipher(cipher));
+    }
+
+    let keyfile_key = keyfile.map(keyfile_key);
+    let mut candidates: Vec<Option<Zeroizing<Vec<u8>>>> = Vec::new();
+    match password {
+        Some(pw) => {
+            candidates.push(Some(Zeroizing::new(pw.as_bytes().to_vec())));
+            if !pw.is_ascii() {
+                if let Some(latin1) = encode_latin1(pw) {
+                    candidates.push(Some(latin1));
+                }
+            }
+        }
+        None => candidates.push(None),
+    }
+
+    for password_bytes in candidates {
+        let composite = composite_key(
+            password_bytes.as_ref().map(|pw| pw.as_slice()),
+            keyfile_key.as_ref(),
+        );
+        let transformed =
+            transform_key(&composite, &header.transform_seed, header.transform_rounds);
+        let master_key = Zeroizing::new(sha256(&[&header.master_seed, transformed.as_slice()]));
+
+        if let Some(payload) = decrypt_with_key(header, &master_key, ciphertext)? {
+            if sha256(&[payload.as_slice()]) == header.contents_hash {
+                return Ok(payload);
+            }
+        }
+    }
+
+    Err(AppError::InvalidPassword)
+}
+
+fn decrypt_with_key(
+    header: &KdbHeader,
+    key: &[u8; 32],
+    ciphertext: &[u8],
+) -> Result<Option<Zeroizing<Vec<u8>>>, AppError> {
+    let mut buffer = Zeroizing::new(ciphertext.to_vec());
+    let iv = &header.encryption_iv;
+
+    let plaintext_len = if header.flags & FLAG_RIJNDAEL != 0 {
+        cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+            .map_err(|e| AppError::Crypto(e.to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map(<[u8]>::len)
+    } else {
+        cbc::Decryptor::<twofish::Twofish>::new_from_slices(key, iv)
+            .map_err(|e| AppError::Crypto(e.to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map(<[u8]>::len)
+    };
+
+    // A padding error means the key was wrong
+    Ok(plaintext_len.ok().map(|len| {
+        buffer.truncate(len);
+        buffer
+    }))
+}
+
+/// KDB keyfiles are 32 raw bytes, 64 hex characters, or any other
+/// file whose SHA-256 hash is used.
+fn keyfile_key(data: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    if data.len() == 32 {
+        key.copy_from_slice(data);
+    } else if let Some(decoded) = decode_hex_key(data) {
+        *key = *decoded;
+    } else {
+        *key = sha256(&[data]);
+    }
+    key
+}
+
+fn decode_hex_key(data: &[u8]) -> Option<Zeroizing<[u8; 32]>> {
+    if data.len() != 64 {
+        return None;
+    }
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    for (byte, pair) in key.iter_mut().zip(data.chunks_exact(2)) {
+        let digits = std::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(digits, 16).ok()?;
+    }
+    Some(key)
+}
+
+fn composite_key(password: Option<&[u8]>, keyfile_key: Option<&Zeroizing<[u8; 32]>>) -> [u8; 32] {
+    match (password, keyfile_key) {
+        (Some(pw), Some(kf)) => sha256(&[&sha256(&[pw]), kf.as_slice()]),
+        (Some(pw), None) => sha256(&[pw]),
+        (None, Some(kf)) => **kf,
+        (None, None) => sha256(&[&[]]),
+    }
+}
+
+fn transform_key(composite: &[u8; 32], seed: &[u8; 32], rounds: u32) -> Zeroizing<[u8; 32]> {
+    let cipher = Aes256::new(seed.into());
+    let mut left = aes::Block::clone_from_slice(&composite[..16]);
+    let mut right = aes::Block::clone_from_slice(&composite[16..]);
+    for _ in 0..rounds {
+        cipher.encrypt_block(&mut left);
+        cipher.encrypt_block(&mut right);
+    }
+    Zeroizing::new(sha256(&[left.as_slice(), right.as_slice()]))
+}
+
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn encode_latin1(value: &str) -> Option<Zeroizing<Vec<u8>>> {
+    value
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).ok())
+        .collect::<Option<Vec<u8>>>()
+        .map(Zeroizing::new)
+}
+
+fn read_group(reader: &mut Reader<'_>) -> Result<KdbGroup, AppError> {
+    let mut id = None;
+    let mut level = 0;
+    let mut group = Group::new("");
+
+    loop {
+        let (field_type, value) = reader.field()?;
+        match field_type {
+            0x0001 => id = Some(read_u32_field(value)?),
+            0x0002 => group.name = read_c_string(value),
+            0x0003 => set_time(value, |t| group.times.set_creation(t)),
+            0x0004 => set_time(value, |t| group.times.set_last_modification(t)),
+            0x0005 => set_time(value, |t| group.times.set_last_access(t)),
+            0x0006 => set_expiry(value, &mut group.times),
+            0x0007 => group.icon_id = Some(read_u32_field(value)? as usize),
+            0x0008 => {
+                let bytes: [u8; 2] = value.try_into().map_err(|_| field_error(field_type))?;
+                level = u16::from_le_bytes(bytes);
+            }
+            FIELD_END => break,
+            // 0x0000 is a comment field; 0x0009 holds flags unused by KeePass
+            _ => {}
+        }
+    }
+
+    let id = id.ok_or_else(|| AppError::Kdbx("KDB group without an ID".to_string()))?;
+    Ok(KdbGroup { id, level, group })
+}
+
+fn read_entry(reader: &mut Reader<'_>) -> Result<KdbEntry, AppError> {
+    let mut group_id = None;
+    let mut image_id = 0;
+    let mut entry = Entry::new();
+    let mut binary_desc = String::new();
+    let mut binary_data = Vec::new();
+
+    loop {
+        let (field_type, value) = reader.field()?;
+        match field_type {
+            0x0001 => {
+                let bytes: [u8; 16] = value.try_into().map_err(|_| field_error(field_type))?;
+                entry.uuid = Uuid::from_bytes(bytes);
+            }
+            0x0002 => group_id = Some(read_u32_field(value)?),
+            0x0003 => image_id = read_u32_field(value)?,
+            0x0004 => insert_text(&mut entry, "Title", value),
+            0x0005 => insert_text(&mut entry, "URL", value),
+            0x0006 => insert_text(&mut entry, "UserName", value),
+            0x0007 => {
+                let password = Zeroizing::new(read_c_string(value));
+                entry.fields.insert(
+                    "Password".to_string(),
+                    Value::Protected(SecStr::from(password.as_str())),
+                );
+            }
+            0x0008 => insert_text(&mut entry, "Notes", value),
+            0x0009 => set_time(value, |t| entry.times.set_creation(t)),
+            0x000A => set_time(value, |t| entry.times.set_last_modification(t)),
+            0x000B => set_time(value, |t| entry.times.set_last_access(t)),
+            0x000C => set_expiry(value, &mut entry.times),
+            0x000D => binary_desc = read_c_string(value),
+            0x000E => binary_data = value.to_vec(),
+            FIELD_END => break,
+            _ => {}
+        }
+    }
+
+    entry.icon_id = Some(image_id as usize);
+    let group_id = group_id.ok_or_else(|| AppError::Kdbx("KDB entry without a group".into()))?;
+
+    Ok(KdbEntry {
+        group_id,
+        image_id,
+        entry,
+        binary_desc,
+        binary_data,
+    })
+}
+
+/// Rebuilds the group hierarchy from the pre-order list of (group, level).
+fn build_group_tree(groups: Vec<KdbGroup>) -> Result<Vec<Group>, AppError> {
+    let mut roots = Vec::new();
+    let mut branch: Vec<Group> = Vec::new();
+
+    for kdb_group in groups {
+        let level = usize::from(kdb_group.level);
+        if level > branch.len() {
+            return Err(AppError::Kdbx(format!(
+                "KDB group '{}' skips a level",
+                kdb_group.group.name
+            )));
+        }
+        collapse_branch(&mut branch, level, &mut roots);
+        branch.push(kdb_group.group);
+    }
+    collapse_branch(&mut branch, 0, &mut roots);
+
+    Ok(roots)
+}
+
+fn collapse_branch(branch: &mut Vec<Group>, level: usize, roots: &mut Vec<Group>) {
+    while branch.len() > level {
+        let Some(leaf) = branch.pop() else {
+            break;
+        };
+        match branch.last_mut() {
+            Some(parent) => parent.add_child(leaf),
+            None => roots.push(leaf),
+        }
+    }
+}
+
+fn read_custom_icons(
+    data: &[u8],
+    db: &mut Database,
+    group_icons: &mut HashMap<u32, Uuid>,
+    entry_icons: &mut HashMap<Uuid, Uuid>,
+) -> Result<(), AppError> {
+    let mut reader = Reader::new(data);
+    let num_icons = reader.u32()?;
+    let num_entries = reader.u32()?;
+    let num_groups = reader.u32()?;
+
+    let mut icon_uuids = Vec::new();
+    for _ in 0..num_icons {
+        let size = reader.u32()? as usize;
+        let icon = Icon {
+            uuid: Uuid::new_v4(),
+            data: reader.take(size)?.to_vec(),
+        };
+        icon_uuids.push(icon.uuid);
+        db.meta.custom_icons.icons.push(icon);
+    }
+    for _ in 0..num_entries {
+        let entry_uuid = Uuid::from_bytes(reader.array()?);
+        let index = reader.u32()? as usize;
+        if let Some(icon_uuid) = icon_uuids.get(index) {
+            entry_icons.insert(entry_uuid, *icon_uuid);
+        }
+    }
+    for _ in 0..num_groups {
+        let group_id = reader.u32()?;
+        let index = reader.u32()? as usize;
+        if let Some(icon_uuid) = icon_uuids.get(index) {
+            group_icons.insert(group_id, *icon_uuid);
+        }
+    }
+
+    Ok(())
+}
+
+fn read_group_tree_state(data: &[u8], expanded: &mut HashMap<u32, bool>) -> Result<(), AppError> {
+    let mut reader = Reader::new(data);
+    let count = reader.u32()?;
+    for _ in 0..count {
+        let group_id = reader.u32()?;
+        let [is_expanded] = reader.array::<1>()?;
+        expanded.insert(group_id, is_expanded != 0);
+    }
+    Ok(())
+}
+
+fn insert_text(entry: &mut Entry, key: &str, value: &[u8]) {
+    let text = read_c_string(value);
+    if !text.is_empty() {
+        entry
+            .fields
+            .insert(key.to_string(), Value::Unprotected(text));
+    }
+}
+
+fn read_c_string(value: &[u8]) -> String {
+    String::from_utf8_lossy(value)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn read_u32_field(value: &[u8]) -> Result<u32, AppError> {
+    let bytes: [u8; 4] = value
+        .try_into()
+        .map_err(|_| AppError::Kdbx(format!("Invalid KDB field length: {}", value.len())))?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn field_error(field_type: u16) -> AppError {
+    AppError::Kdbx(format!("Invalid length for KDB field {field_type:#06x}"))
+}
+
+fn set_time(value: &[u8], set: impl FnOnce(NaiveDateTime)) {
+    if let Some(time) = unpack_time(value) {
+        set(time);
+    }
+}
+
+fn set_expiry(value: &[u8], times: &mut keepass::db::Times) {
+    if let Some(time) = unpack_time(value) {
+        let (year, month, day) = NEVER_EXPIRES;
+        let never = NaiveDate::from_ymd_opt(year, month, day).is_some_and(|d| time.date() == d);
+        if !never {
+            times.set_expiry(time);
+            times.expires = true;
+        }
+    }
+}
+
+/// Decodes the packed 5-byte KDB date/time format.
+fn unpack_time(value: &[u8]) -> Option<NaiveDateTime> {
+    let [b0, b1, b2, b3, b4]: [u8; 5] = value.try_into().ok()?;
+    let (b0, b1, b2, b3, b4) = (
+        u32::from(b0),
+        u32::from(b1),
+        u32::from(b2),
+        u32::from(b3),
+        u32::from(b4),
+    );
+
+    let year = (b0 << 6) | (b1 >> 2);
+    let month = ((b1 & 0x03) << 2) | (b2 >> 6);
+    let day = (b2 >> 1) & 0x1F;
+    let hour = ((b2 & 0x01) << 4) | (b3 >> 4);
+    let minute = ((b3 & 0x0F) << 2) | (b4 >> 6);
+    let second = b4 & 0x3F;
+
+    NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, day)?
+        .and_hms_opt(hour, minute, second)
+}
+
+/// Little-endian cursor over KDB header and payload bytes.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AppError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| AppError::Kdbx("Unexpected end of KDB data".to_string()))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AppError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32, AppError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /// Reads one type-length-value field.
+    fn field(&mut self) -> Result<(u16, &'a [u8]), AppError> {
+        let field_type = u16::from_le_bytes(self.array()?);
+        let size = self.u32()? as usize;
+        Ok((field_type, self.take(size)?))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_time() {
+        // 2024-03-15 13:45:30
+        let packed = [0x1F, 0xA0, 0xDE, 0xDB, 0x5E];
+
+        let time = unpack_time(&packed).expect("valid time");
+        assert_eq!(time.to_string(), "2024-03-15 13:45:30");
+    }
+
+    #[test]
+    fn test_unpack_time_rejects_zero_date() {
+        assert!(unpack_time(&[0, 0, 0, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_keyfile_key_formats() {
+        let raw = [7u8; 32];
+        assert_eq!(*keyfile_key(&raw), raw);
+
+        let hex = "ab".repeat(32);
+        assert_eq!(*keyfile_key(hex.as_bytes()), [0xab; 32]);
+
+        let other = b"not a key";
+        assert_eq!(*keyfile_key(other), sha256(&[other]));
+    }
+
+    #[test]
+    fn test_build_group_tree_nests_by_level() {
+        let groups = vec![
+            KdbGroup {
+                id: 1,
+                level: 0,
+                group: Group::new("Internet"),
+            },
+            KdbGroup {
+                id: 2,
+                level: 1,
+                group: Group::new("Email"),
+            },
+            KdbGroup {
+                id: 3,
+                level: 0,
+                group: Group::new("Backup"),
+            },
+        ];
+
+        let roots = build_group_tree(groups).expect("valid tree");
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].name, "Internet");
+        assert_eq!(roots[0].groups()[0].name, "Email");
+        assert_eq!(roots[1].name, "Backup");
+    }
+
+    #[test]
+    fn test_build_group_tree_rejects_skipped_level() {
+        let groups = vec![KdbGroup {
+            id: 1,
+            level: 1,
+            group: Group::new("Orphan"),
+        }];
+
+        assert!(build_group_tree(groups).is_err());
+    }
+
+    #[test]
+    fn test_parse_header_rejects_wrong_signature() {
+        let data = [0u8; HEADER_SIZE];
+        assert!(matches!(
+            parse_header(&data),
+            Err(AppError::InvalidKdbxFile)
+        ));
+    }
+}
@@ -0,0 +1,938 @@
+//! Group sharing in the `KeeShare` container format.
+//!
+//! A shared group carries a `KeeShare/Reference` item in its custom data:
+//! base64-encoded XML naming the container file, its password and whether the
+//! group imports from and/or exports to it. Plain `.kdbx` containers are
+//! ordinary databases; `.kdbx.share` containers are zip archives holding the
+//! database and an RSA signature over it.
+//!
+//! Shares are imported when the database is opened or unlocked, and again
+//! by `services::share_watcher` whenever a container changes on disk.
+//!
+//! Imports merge by UUID and keep the newer version of each entry. Entries
+//! deleted from a container are kept locally. A signed container is only
+//! merged when its signature verifies against this database's own key or one
+//! of the share's trusted signers; unsigned containers only when the share
+//! allows them. Those trust settings live in a separate custom data item, as
+//! other `KeeShare` clients don't know them.
+
+use crate::domain::secure::SecureString;
+use crate::dto::database::DatabaseCreationOptions;
+use crate::dto::error::AppError;
+use crate::dto::keeshare::{
+    ShareInfo, ShareMode, ShareSettings, ShareSignature, ShareSyncResult, ShareSyncStatus,
+};
+use crate::services::kdbx::export::subtree_database;
+use crate::services::kdbx::key::build_database_key;
+use crate::services::kdbx::mapping::find_group_by_id_mut;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use keepass::config::KdfConfig;
+use keepass::db::{CustomDataItem, Group as KeepassGroup, Node, Times, Value};
+use keepass::Database;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use secstr::SecStr;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+
+use super::{writable, KdbxService};
+
+/// Group custom data key holding the share reference.
+pub(crate) const REFERENCE_KEY: &str = "KeeShare/Reference";
+/// Group custom data key holding the share's trust settings (JSON).
+const TRUST_KEY: &str = "MithrilVault.KeeShare/Trust";
+/// Meta custom data key holding this database's signing key (PKCS#8 PEM),
+/// stored as a protected value.
+const OWN_KEY: &str = "MithrilVault.KeeShare/OwnKey";
+
+const SIGNED_EXTENSION: &str = ".kdbx.share";
+const CONTAINER_FILE: &str = "container.share.kdbx";
+const SIGNATURE_FILE: &str = "container.share.signature";
+const SIGNATURE_PREFIX: &str = "rsa|";
+const SIGNING_KEY_BITS: usize = 2048;
+
+impl KdbxService {
+    /// Configures a group as shared group, or removes its share when
+    /// `settings` is `None`. Takes effect on the next save or sync.
+    pub fn set_group_share(
+        &self,
+        group_id: &str,
+        settings: Option<&ShareSettings>,
+    ) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        if let Some(settings) = settings {
+            if settings.path.trim().is_empty() {
+                return Err(AppError::InvalidPath(
+                    "Share container path must not be empty".to_string(),
+                ));
+            }
+            parse_signers(&settings.trusted_signers)?;
+        }
+
+        let group = find_group_by_id_mut(&mut open_db.db.root, group_id)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.to_string()))?;
+        write_reference(group, settings);
+        open_db.is_modified = true;
+        Ok(())
+    }
+
+    /// Lists the shared groups of the open database.
+    pub fn list_shares(&self) -> Result<Vec<ShareInfo>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        Ok(list_shares(&open_db.db.root))
+    }
+
+    /// Returns the public key (PEM) that signs this database's containers,
+    /// for other databases to add to their trusted signers. The key is
+    /// created if the database has none yet.
+    pub fn share_public_key(&self) -> Result<String, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let existing = signing_key(&db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?.db);
+
+        let key = if let Some(key) = existing {
+            key
+        } else {
+            let open_db = writable(&mut db_lock)?;
+            let key = create_signing_key(&mut open_db.db)?;
+            open_db.is_modified = true;
+            key
+        };
+        key.to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| AppError::Crypto(e.to_string()))
+    }
+
+    /// Merges the containers of all importing shares without writing any.
+    /// Used by the share watcher when a container changes on disk.
+    pub fn import_share_containers(&self) -> Result<Vec<ShareSyncResult>, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let (results, imported) = import_shares(&mut open_db.db, &open_db.path);
+        open_db.is_modified |= imported;
+        Ok(results)
+    }
+
+    /// Imports all importing shares, then writes all exporting shares.
+    /// Used on user request.
+    pub fn sync_shares(&self) -> Result<Vec<ShareSyncResult>, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let (mut results, imported) = import_shares(&mut open_db.db, &open_db.path);
+        open_db.is_modified |= imported;
+        open_db.is_modified |= ensure_signing_key(&mut open_db.db)?;
+        results.extend(export_shares(&open_db.db, &open_db.path));
+        Ok(results)
+    }
+}
+
+/// A decoded `KeeShare/Reference`.
+pub(crate) struct ShareReference {
+    pub mode: ShareMode,
+    pub path: String,
+    pub password: SecureString,
+    pub keep_groups: bool,
+    pub trust: ShareTrust,
+}
+
+/// Which containers of a share may be imported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShareTrust {
+    #[serde(default)]
+    pub trusted_signers: Vec<String>,
+    #[serde(default)]
+    pub allow_unsigned: bool,
+}
+
+impl ShareReference {
+    pub fn is_signed(&self) -> bool {
+        is_signed_path(&self.path)
+    }
+}
+
+/// Reads the share reference of `group`, if it has one.
+pub(crate) fn read_reference(group: &KeepassGroup) -> Option<Result<ShareReference, AppError>> {
+    let item = group.custom_data.items.get(REFERENCE_KEY)?;
+    let Some(Value::Unprotected(encoded)) = item.value.as_ref() else {
+        return Some(Err(AppError::KeeShare(
+            "Share reference is not a text value".to_string(),
+        )));
+    };
+    Some(decode_reference(encoded).and_then(|mut reference| {
+        reference.trust = read_trust(group)?;
+        Ok(reference)
+    }))
+}
+
+fn read_trust(group: &KeepassGroup) -> Result<ShareTrust, AppError> {
+    let Some(item) = group.custom_data.items.get(TRUST_KEY) else {
+        return Ok(ShareTrust::default());
+    };
+    match item.value.as_ref() {
+        Some(Value::Unprotected(json)) => serde_json::from_str(json)
+            .map_err(|_| AppError::KeeShare("Share trust settings are damaged".to_string())),
+        _ => Err(AppError::KeeShare(
+            "Share trust settings are not a text value".to_string(),
+        )),
+    }
+}
+
+/// Stores `settings` as the share reference of `group`, or removes the
+/// reference when `settings` is `None`.
+pub(crate) fn write_reference(group: &mut KeepassGroup, settings: Option<&ShareSettings>) {
+    let Some(settings) = settings else {
+        group.custom_data.items.remove(REFERENCE_KEY);
+        group.custom_data.items.remove(TRUST_KEY);
+        return;
+    };
+
+    let reference = ShareReference {
+        mode: settings.mode,
+        path: settings.path.clone(),
+        password: settings.password.clone().unwrap_or_default(),
+        keep_groups: settings.keep_groups,
+        trust: ShareTrust {
+            trusted_signers: settings.trusted_signers.clone(),
+            allow_unsigned: settings.allow_unsigned,
+        },
+    };
+    let xml = serialize_reference_xml(&reference, group.uuid);
+    group.custom_data.items.insert(
+        REFERENCE_KEY.to_string(),
+        CustomDataItem {
+            value: Some(Value::Unprotected(BASE64.encode(xml))),
+            last_modification_time: Some(Times::now()),
+        },
+    );
+
+    if reference.trust == ShareTrust::default() {
+        group.custom_data.items.remove(TRUST_KEY);
+    } else if let Ok(json) = serde_json::to_string(&reference.trust) {
+        group.custom_data.items.insert(
+            TRUST_KEY.to_string(),
+            CustomDataItem {
+                value: Some(Value::Unprotected(json)),
+                last_modification_time: Some(Times::now()),
+            },
+        );
+    }
+}
+
+/// Lists the shared groups below `root` (unreadable references are skipped).
+pub(crate) fn list_shares(root: &KeepassGroup) -> Vec<ShareInfo> {
+    shared_groups(root)
+        .into_iter()
+        .filter_map(|(uuid, reference)| {
+            let reference = reference.ok()?;
+            Some(ShareInfo {
+                group_id: uuid.to_string(),
+                mode: reference.mode,
+                is_signed: reference.is_signed(),
+                path: reference.path,
+                keep_groups: reference.keep_groups,
+                trusted_signers: reference.trust.trusted_signers,
+                allow_unsigned: reference.trust.allow_unsigned,
+            })
+        })
+        .collect()
+}
+
+/// Creates this database's signing key if a signed share is exported and
+/// none exists yet. Returns true if the database was changed.
+pub(crate) fn ensure_signing_key(db: &mut Database) -> Result<bool, AppError> {
+    if db.meta.custom_data.items.contains_key(OWN_KEY) {
+        return Ok(false);
+    }
+
+    let needs_key = shared_groups(&db.root)
+        .into_iter()
+        .filter_map(|(_, reference)| reference.ok())
+        .any(|reference| reference.mode.exports() && reference.is_signed());
+    if !needs_key {
+        return Ok(false);
+    }
+
+    create_signing_key(db)?;
+    Ok(true)
+}
+
+fn create_signing_key(db: &mut Database) -> Result<RsaPrivateKey, AppError> {
+    let key = RsaPrivateKey::new(&mut rand::rngs::OsRng, SIGNING_KEY_BITS)
+        .map_err(|e| AppError::Crypto(e.to_string()))?;
+    let pem = key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|e| AppError::Crypto(e.to_string()))?;
+    db.meta.custom_data.items.insert(
+        OWN_KEY.to_string(),
+        CustomDataItem {
+            value: Some(Value::Protected(SecStr::new(pem.as_bytes().to_vec()))),
+            last_modification_time: Some(Times::now()),
+        },
+    );
+    Ok(key)
+}
+
+/// Writes every exporting share of `db` to its container.
+pub(crate) fn export_shares(db: &Database, db_path: &str) -> Vec<ShareSyncResult> {
+    let mut results = Vec::new();
+
+    for (uuid, reference) in shared_groups(&db.root) {
+        let group_id = uuid.to_string();
+        let reference = match reference {
+            Ok(reference) if reference.mode.exports() => reference,
+            Ok(_) => continue,
+            Err(e) => {
+                results.push(failed(group_id, String::new(), &e));
+                continue;
+            }
+        };
+
+        let container = resolve_path(db_path, &reference.path);
+        let outcome = find_group(&db.root, uuid)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.clone()))
+            .and_then(|group| export_container(db, group, &reference, &container));
+
+        results.push(match outcome {
+            Ok(signature) => ShareSyncResult {
+                group_id,
+                path: container.to_string_lossy().to_string(),
+                status: ShareSyncStatus::Exported,
+                signature: Some(signature),
+                message: None,
+            },
+            Err(e) => failed(group_id, container.to_string_lossy().to_string(), &e),
+        });
+    }
+
+    results
+}
+
+/// Merges the containers of every importing share into `db`.
+/// Returns the per-share results and whether the database was changed.
+pub(crate) fn import_shares(db: &mut Database, db_path: &str) -> (Vec<ShareSyncResult>, bool) {
+    let own_key = signing_key(db).map(|key| key.to_public_key());
+    let mut results = Vec::new();
+    let mut changed = false;
+
+    for (uuid, reference) in shared_groups(&db.root) {
+        let group_id = uuid.to_string();
+        let reference = match reference {
+            Ok(reference) if reference.mode.imports() => reference,
+            Ok(_) => continue,
+            Err(e) => {
+                results.push(failed(group_id, String::new(), &e));
+                continue;
+            }
+        };
+
+        let container = resolve_path(db_path, &reference.path);
+        let path = container.to_string_lossy().to_string();
+        if !container.exists() {
+            results.push(ShareSyncResult {
+                group_id,
+                path,
+                status: ShareSyncStatus::Skipped,
+                signature: None,
+                message: Some("Container does not exist".to_string()),
+            });
+            continue;
+        }
+
+        let signers = parse_signers(&reference.trust.trusted_signers)
+            .map(|signers| own_key.iter().cloned().chain(signers).collect::<Vec<_>>());
+        let (kdbx, signature) =
+            match signers.and_then(|signers| read_container(&container, &reference, &signers)) {
+                Ok(read) => read,
+                Err(e) => {
+                    results.push(failed(group_id, path, &e));
+                    continue;
+                }
+            };
+        if let Err(e) = check_trust(&reference, signature) {
+            results.push(ShareSyncResult {
+                signature: Some(signature),
+                ..failed(group_id, path, &e)
+            });
+            continue;
+        }
+
+        let outcome = open_container(kdbx, &reference).and_then(|source| {
+            let target = find_group_by_id_mut(&mut db.root, &group_id)
+                .ok_or_else(|| AppError::GroupNotFound(group_id.clone()))?;
+            Ok(merge_share(target, source.root, reference.keep_groups))
+        });
+
+        results.push(match outcome {
+            Ok(merged) => {
+                changed |= merged;
+                ShareSyncResult {
+                    group_id,
+                    path,
+                    status: ShareSyncStatus::Imported,
+                    signature: Some(signature),
+                    message: None,
+                }
+            }
+            Err(e) => failed(group_id, path, &e),
+        });
+    }
+
+    (results, changed)
+}
+
+fn failed(group_id: String, path: String, error: &AppError) -> ShareSyncResult {
+    ShareSyncResult {
+        group_id,
+        path,
+        status: ShareSyncStatus::Failed,
+        signature: None,
+        message: Some(error.to_string()),
+    }
+}
+
+fn export_container(
+    db: &Database,
+    group: &KeepassGroup,
+    reference: &ShareReference,
+    container: &Path,
+) -> Result<ShareSignature, AppError> {
+    let (mut share, _) = subtree_database(db, group, &container_options(db));
+    strip_references(&mut share.root);
+
+    let key = build_database_key(Some(reference.password.as_str()), None)?;
+    let target = container.to_string_lossy();
+    let options = AtomicWriteOptions {
+        preserve_permissions: true,
+    };
+
+    if !reference.is_signed() {
+        atomic_write(&target, &options, |file| {
+            share
+                .save(file, key)
+                .map_err(|e| AppError::Kdbx(e.to_string()))
+        })?;
+        return Ok(ShareSignature::Unsigned);
+    }
+
+    let signing_key = signing_key(db)
+        .ok_or_else(|| AppError::KeeShare("No signing key for signed share".to_string()))?;
+    let mut kdbx = Vec::new();
+    share
+        .save(&mut kdbx, key)
+        .map_err(|e| AppError::Kdbx(e.to_string()))?;
+    let signature = SigningKey::<Sha256>::new(signing_key).sign(&kdbx);
+    let signature = format!("{SIGNATURE_PREFIX}{}", to_hex(&signature.to_bytes()));
+
+    atomic_write(&target, &options, |file| {
+        let mut zip = zip::ZipWriter::new(file);
+        let file_options = SimpleFileOptions::default();
+        zip.start_file(CONTAINER_FILE, file_options)
+            .map_err(|e| zip_error(&e))?;
+        zip.write_all(&kdbx)?;
+        zip.start_file(SIGNATURE_FILE, file_options)
+            .map_err(|e| zip_error(&e))?;
+        zip.write_all(signature.as_bytes())?;
+        zip.finish().map_err(|e| zip_error(&e))?;
+        Ok(())
+    })?;
+
+    Ok(ShareSignature::Verified)
+}
+
+/// Reads the database of a container and checks its signature against
+/// `signers`.
+fn read_container(
+    container: &Path,
+    reference: &ShareReference,
+    signers: &[RsaPublicKey],
+) -> Result<(Vec<u8>, ShareSignature), AppError> {
+    let data = std::fs::read(container)?;
+    if !reference.is_signed() {
+        return Ok((data, ShareSignature::Unsigned));
+    }
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| zip_error(&e))?;
+    let kdbx = read_zip_file(&mut archive, CONTAINER_FILE)?;
+    let signature = match read_zip_file(&mut archive, SIGNATURE_FILE) {
+        Ok(signature) => verify_signature(&kdbx, &signature, signers),
+        Err(_) => ShareSignature::Unsigned,
+    };
+    Ok((kdbx, signature))
+}
+
+/// Refuses containers that are not signed by a trusted key, and unsigned
+/// containers unless the share allows them.
+fn check_trust(reference: &ShareReference, signature: ShareSignature) -> Result<(), AppError> {
+    match signature {
+        ShareSignature::Verified => Ok(()),
+        ShareSignature::Unsigned if reference.trust.allow_unsigned => Ok(()),
+        ShareSignature::Unsigned => Err(AppError::KeeShare(
+            "Container is not signed and the share does not allow unsigned containers".to_string(),
+        )),
+        ShareSignature::Unverified => Err(AppError::KeeShare(
+            "Container is not signed by a trusted key".to_string(),
+        )),
+    }
+}
+
+fn open_container(kdbx: Vec<u8>, reference: &ShareReference) -> Result<Database, AppError> {
+    let key = build_database_key(Some(reference.password.as_str()), None)?;
+    Database::open(&mut Cursor::new(kdbx), key)
+        .map_err(|e| AppError::KeeShare(format!("Cannot open container: {e}")))
+}
+
+fn read_zip_file(
+    archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>,
+    name: &str,
+) -> Result<Vec<u8>, AppError> {
+    let mut file = archive.by_name(name).map_err(|e| zip_error(&e))?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn verify_signature(kdbx: &[u8], signature: &[u8], signers: &[RsaPublicKey]) -> ShareSignature {
+    let signature = std::str::from_utf8(signature)
+        .ok()
+        .and_then(|s| s.trim().strip_prefix(SIGNATURE_PREFIX))
+        .and_then(from_hex)
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok());
+    let verified = signature.is_some_and(|signature| {
+        signers.iter().any(|key| {
+            VerifyingKey::<Sha256>::new(key.clone())
+                .verify(kdbx, &signature)
+                .is_ok()
+        })
+    });
+
+    if verified {
+        ShareSignature::Verified
+    } else {
+        ShareSignature::Unverified
+    }
+}
+
+fn signing_key(db: &Database) -> Option<RsaPrivateKey> {
+    match db.meta.custom_data.items.get(OWN_KEY)?.value.as_ref()? {
+        Value::Protected(pem) => std::str::from_utf8(pem.unsecure())
+            .ok()
+            .and_then(|pem| RsaPrivateKey::from_pkcs8_pem(pem).ok()),
+        _ => None,
+    }
+}
+
+fn parse_signers(pems: &[String]) -> Result<Vec<RsaPublicKey>, AppError> {
+    pems.iter()
+        .map(|pem| {
+            RsaPublicKey::from_public_key_pem(pem.trim()).map_err(|_| {
+                AppError::KeeShare("Trusted signer is not a PEM public key".to_string())
+            })
+        })
+        .collect()
+}
+
+/// Containers use the KDF parameters of the database they are shared from.
+fn container_options(db: &Database) -> DatabaseCreationOptions {
+    match &db.config.kdf_config {
+        KdfConfig::Argon2 {
+            iterations,
+            memory,
+            parallelism,
+            ..
+        }
+        | KdfConfig::Argon2id {
+            iterations,
+            memory,
+            parallelism,
+            ..
+        } => DatabaseCreationOptions {
+            kdf_memory: Some(*memory),
+            kdf_iterations: Some(*iterations),
+            kdf_parallelism: Some(*parallelism),
+            ..DatabaseCreationOptions::default()
+        },
+        KdfConfig::Aes { .. } => DatabaseCreationOptions::default(),
+    }
+}
+
+fn strip_references(group: &mut KeepassGroup) {
+    group.custom_data.items.remove(REFERENCE_KEY);
+    for node in &mut group.children {
+        if let Node::Group(child) = node {
+            strip_references(child);
+        }
+    }
+}
+
+/// Merges a container's root into the shared group. Entries that are new or
+/// newer in the container replace local copies; returns true on any change.
+fn merge_share(target: &mut KeepassGroup, source: KeepassGroup, keep_groups: bool) -> bool {
+    let mut local = HashMap::new();
+    collect_modification_times(target, &mut local);
+
+    let mut incoming = HashMap::new();
+    collect_modification_times(&source, &mut incoming);
+
+    let winners: HashSet<Uuid> = incoming
+        .into_iter()
+        .filter(|(uuid, modified)| match local.get(uuid) {
+            Some(local_modified) => modified > local_modified,
+            None => true,
+        })
+        .map(|(uuid, _)| uuid)
+        .collect();
+
+    if winners.is_empty() && (!keep_groups || !has_new_groups(target, &source)) {
+        return false;
+    }
+
+    remove_entries(target, &winners);
+    let target_uuid = target.uuid;
+    place_children(target, target_uuid, source, keep_groups, &winners);
+    true
+}
+
+fn place_children(
+    share_root: &mut KeepassGroup,
+    parent_uuid: Uuid,
+    source: KeepassGroup,
+    keep_groups: bool,
+    winners: &HashSet<Uuid>,
+) {
+    for node in source.children {
+        match node {
+            Node::Entry(entry) => {
+                if winners.contains(&entry.uuid) {
+                    if let Some(parent) = find_group_mut(share_root, parent_uuid) {
+                        parent.children.push(Node::Entry(entry));
+                    }
+                }
+            }
+            Node::Group(mut group) if keep_groups => {
+                let uuid = group.uuid;
+                let children = std::mem::take(&mut group.children);
+                if find_group_mut(share_root, uuid).is_none() {
+                    if let Some(parent) = find_group_mut(share_root, parent_uuid) {
+                        parent.add_child(group.clone());
+                    }
+                }
+                group.children = children;
+                place_children(share_root, uuid, group, keep_groups, winners);
+            }
+            Node::Group(group) => {
+                place_children(share_root, parent_uuid, group, keep_groups, winners);
+            }
+        }
+    }
+}
+
+fn has_new_groups(target: &KeepassGroup, source: &KeepassGroup) -> bool {
+    source.children.iter().any(|node| match node {
+        Node::Group(group) => {
+            find_group(target, group.uuid).is_none() || has_new_groups(target, group)
+        }
+        Node::Entry(_) => false,
+    })
+}
+
+fn collect_modification_times(
+    group: &KeepassGroup,
+    times: &mut HashMap<Uuid, Option<chrono::NaiveDateTime>>,
+) {
+    for node in &group.children {
+        match node {
+            Node::Entry(entry) => {
+                times.insert(entry.uuid, entry.times.get_last_modification().copied());
+            }
+            Node::Group(child) => collect_modification_times(child, times),
+        }
+    }
+}
+
+fn remove_entries(group: &mut KeepassGroup, uuids: &HashSet<Uuid>) {
+    group.children.retain(|node| match node {
+        Node::Entry(entry) => !uuids.contains(&entry.uuid),
+        Node::Group(_) => true,
+    });
+    for node in &mut group.children {
+        if let Node::Group(child) = node {
+            remove_entries(child, uuids);
+        }
+    }
+}
+
+fn shared_groups(root: &KeepassGroup) -> Vec<(Uuid, Result<ShareReference, AppError>)> {
+    let mut shares = Vec::new();
+    collect_shared_groups(root, &mut shares);
+    shares
+}
+
+fn collect_shared_groups(
+    group: &KeepassGroup,
+    shares: &mut Vec<(Uuid, Result<ShareReference, AppError>)>,
+) {
+    if let Some(reference) = read_reference(group) {
+        shares.push((group.uuid, reference));
+    }
+    for node in &group.children {
+        if let Node::Group(child) = node {
+            collect_shared_groups(child, shares);
+        }
+    }
+}
+
+fn find_group(group: &KeepassGroup, uuid: Uuid) -> Option<&KeepassGroup> {
+    if group.uuid == uuid {
+        return Some(group);
+    }
+    group.children.iter().find_map(|node| match node {
+        Node::Group(child) => find_group(child, uuid),
+        Node::Entry(_) => None,
+    })
+}
+
+fn find_group_mut(group: &mut KeepassGroup, uuid: Uuid) -> Option<&mut KeepassGroup> {
+    if group.uuid == uuid {
+        return Some(group);
+    }
+    group.children.iter_mut().find_map(|node| match node {
+        Node::Group(child) => find_group_mut(child, uuid),
+        Node::Entry(_) => None,
+    })
+}
+
+/// Relative container paths are resolved against the database's directory.
+pub(crate) fn resolve_path(db_path: &str, share_path: &str) -> PathBuf {
+    let share_path = Path::new(share_path);
+    if share_path.is_absolute() {
+        return share_path.to_path_buf();
+    }
+    Path::new(db_path)
+        .parent()
+        .map_or_else(|| share_path.to_path_buf(), |dir| dir.join(share_path))
+}
+
+pub(crate) fn is_signed_path(path: &str) -> bool {
+    path.to_lowercase().ends_with(SIGNED_EXTENSION)
+}
+
+fn decode_reference(encoded: &str) -> Result<ShareReference, AppError> {
+    let xml = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| AppError::KeeShare("Share reference is not valid base64".to_string()))?;
+    parse_reference_xml(&xml)
+}
+
+fn parse_reference_xml(xml: &str) -> Result<ShareReference, AppError> {
+    let kind = element_text(xml, "Type").unwrap_or_default();
+    let imports = kind.contains("<Import");
+    let exports = kind.contains("<Export");
+    let mode = match (imports, exports) {
+        (true, true) => ShareMode::Synchronize,
+        (true, false) => ShareMode::Import,
+        (false, true) => ShareMode::Export,
+        (false, false) => {
+            return Err(AppError::KeeShare(
+                "Share reference has no type".to_string(),
+            ));
+        }
+    };
+
+    let path = element_text(xml, "Path")
+        .and_then(decode_base64_text)
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| AppError::KeeShare("Share reference has no path".to_string()))?;
+    let password = element_text(xml, "Password")
+        .and_then(decode_base64_text)
+        .unwrap_or_default();
+    let keep_groups = element_text(xml, "KeepGroups").is_none_or(|value| value.trim() == "True");
+
+    Ok(ShareReference {
+        mode,
+        path,
+        password: SecureString::from(password),
+        keep_groups,
+        trust: ShareTrust::default(),
+    })
+}
+
+fn serialize_reference_xml(reference: &ShareReference, group_uuid: Uuid) -> String {
+    let kind = match reference.mode {
+        ShareMode::Import => "<Import/>",
+        ShareMode::Export => "<Export/>",
+        ShareMode::Synchronize => "<Import/><Export/>",
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <KeeShare><Type>{kind}</Type><Group>{}</Group><Path>{}</Path>\
+         <Password>{}</Password><KeepGroups>{}</KeepGroups></KeeShare>",
+        BASE64.encode(group_uuid.as_bytes()),
+        BASE64.encode(reference.path.as_bytes()),
+        BASE64.encode(reference.password.as_str().as_bytes()),
+        if reference.keep_groups {
+            "True"
+        } else {
+            "False"
+        },
+    )
+}
+
+/// Returns the content between `<tag>` and `</tag>`. Empty elements
+/// (`<tag/>`) yield an empty string.
+fn element_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    if xml.contains(&format!("<{tag}/>")) {
+        return Some("");
+    }
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(&xml[start..end])
+}
+
+fn decode_base64_text(value: &str) -> Option<String> {
+    let bytes = BASE64.decode(value.trim()).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn zip_error(err: &zip::result::ZipError) -> AppError {
+    AppError::KeeShare(format!("Invalid share container: {err}"))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keepassxc_reference() {
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><KeeShare><Type><Import/><Export/></Type>\
+             <Group>{}</Group><Path>{}</Path><Password>{}</Password></KeeShare>",
+            BASE64.encode([0u8; 16]),
+            BASE64.encode("../team.kdbx.share"),
+            BASE64.encode("s3cret"),
+        );
+
+        let reference = decode_reference(&BASE64.encode(xml)).expect("valid reference");
+        assert_eq!(reference.mode, ShareMode::Synchronize);
+        assert_eq!(reference.path, "../team.kdbx.share");
+        assert_eq!(reference.password.as_str(), "s3cret");
+        assert!(reference.keep_groups);
+        assert!(reference.is_signed());
+    }
+
+    #[test]
+    fn test_reference_round_trip() {
+        let mut group = KeepassGroup::new("Team");
+        let settings = ShareSettings {
+            mode: ShareMode::Export,
+            path: "team.kdbx".to_string(),
+            password: Some(SecureString::from("pw")),
+            keep_groups: false,
+            trusted_signers: Vec::new(),
+            allow_unsigned: true,
+        };
+
+        write_reference(&mut group, Some(&settings));
+        let reference = read_reference(&group)
+            .expect("reference present")
+            .expect("reference valid");
+        assert_eq!(reference.mode, ShareMode::Export);
+        assert_eq!(reference.path, "team.kdbx");
+        assert_eq!(reference.password.as_str(), "pw");
+        assert!(!reference.keep_groups);
+        assert!(reference.trust.allow_unsigned);
+
+        write_reference(&mut group, None);
+        assert!(read_reference(&group).is_none());
+        assert!(group.custom_data.items.is_empty());
+    }
+
+    #[test]
+    fn test_reference_without_type_is_rejected() {
+        let xml = format!(
+            "<KeeShare><Type></Type><Path>{}</Path></KeeShare>",
+            BASE64.encode("team.kdbx")
+        );
+        assert!(parse_reference_xml(&xml).is_err());
+    }
+
+    #[test]
+    fn test_resolve_relative_path() {
+        let resolved = resolve_path("/vaults/main.kdbx", "shares/team.kdbx");
+        assert_eq!(resolved, PathBuf::from("/vaults/shares/team.kdbx"));
+        assert_eq!(
+            resolve_path("/vaults/main.kdbx", "/abs/team.kdbx"),
+            PathBuf::from("/abs/team.kdbx")
+        );
+    }
+
+    #[test]
+    fn test_signing_key_is_stored_protected() {
+        let mut db = crate::services::kdbx::create::new_database(
+            "Keys",
+            &DatabaseCreationOptions::default(),
+        );
+        let mut group = KeepassGroup::new("Team");
+        let settings = ShareSettings {
+            mode: ShareMode::Export,
+            path: "team.kdbx.share".to_string(),
+            password: None,
+            keep_groups: false,
+            trusted_signers: Vec::new(),
+            allow_unsigned: false,
+        };
+        write_reference(&mut group, Some(&settings));
+        db.root.children.push(Node::Group(group));
+
+        assert!(ensure_signing_key(&mut db).expect("create key"));
+        let item = &db.meta.custom_data.items[OWN_KEY];
+        assert!(matches!(item.value, Some(Value::Protected(_))));
+        assert!(signing_key(&db).is_some());
+        assert!(!ensure_signing_key(&mut db).expect("unchanged"));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = [0x00, 0x7f, 0xff, 0x10];
+        assert_eq!(from_hex(&to_hex(&bytes)).as_deref(), Some(&bytes[..]));
+        assert!(from_hex("abc").is_none());
+    }
+}
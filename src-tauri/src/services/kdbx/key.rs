@@ -1,6 +1,12 @@
+use crate::domain::kdbx::OpenDatabase;
+use crate::domain::secure::SecureString;
 use crate::dto::error::AppError;
+use base64::Engine as _;
 use keepass::DatabaseKey;
+use sha2::{Digest, Sha256};
 use std::fs::File;
+use xml::reader::{EventReader, XmlEvent};
+use zeroize::Zeroizing;
 
 /// Builds a database key from credentials.
 pub fn build_database_key(
@@ -23,3 +29,148 @@ pub fn build_database_key(
 
     Ok(key)
 }
+
+/// The secrets a database was unlocked with: its password and the contents
+/// of its keyfile. Unlike a keyfile path, this opens the database again even
+/// when the keyfile is no longer reachable.
+#[derive(Clone, Default)]
+pub struct DatabaseCredentials {
+    pub password: Option<SecureString>,
+    pub keyfile: Option<Zeroizing<Vec<u8>>>,
+}
+
+impl DatabaseCredentials {
+    /// Builds the database key.
+    pub fn to_key(&self) -> Result<DatabaseKey, AppError> {
+        let mut key = DatabaseKey::new();
+
+        if let Some(pw) = &self.password {
+            key = key.with_password(pw.as_str());
+        }
+
+        if let Some(keyfile) = &self.keyfile {
+            key = key
+                .with_keyfile(&mut keyfile.as_slice())
+                .map_err(|_| AppError::KeyfileInvalid)?;
+        }
+
+        Ok(key)
+    }
+}
+
+/// The key elements that are hashed into the composite key of a database:
+/// the SHA-256 of the password, then the key taken from the keyfile.
+///
+/// It opens the one database it was derived from, but the password cannot
+/// be recovered from it.
+#[derive(Clone)]
+pub struct CompositeKey(Zeroizing<Vec<u8>>);
+
+impl CompositeKey {
+    /// Derives the key elements from a password and keyfile contents.
+    pub fn derive(password: Option<&str>, keyfile: Option<&[u8]>) -> Result<Self, AppError> {
+        let mut elements = Zeroizing::new(Vec::new());
+        if let Some(pw) = password {
+            elements.extend_from_slice(&Sha256::digest(pw.as_bytes()));
+        }
+        if let Some(keyfile) = keyfile {
+            elements.extend_from_slice(&keyfile_key(keyfile));
+        }
+        if elements.is_empty() {
+            return Err(AppError::NoCredentials);
+        }
+        Ok(Self(elements))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(Zeroizing::new(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Builds the database key. keepass only takes a password and a
+    /// keyfile, so the elements are passed as the data of a version 2.0
+    /// XML keyfile; hashing that single element gives the same composite
+    /// key.
+    pub fn to_key(&self) -> Result<DatabaseKey, AppError> {
+        let keyfile = Zeroizing::new(format!(
+            "<KeyFile><Meta><Version>2.0</Version></Meta><Key><Data>{}</Data></Key></KeyFile>",
+            Zeroizing::new(hex::encode(self.as_bytes())).as_str()
+        ));
+        DatabaseKey::new()
+            .with_keyfile(&mut keyfile.as_bytes())
+            .map_err(|_| AppError::KeyfileInvalid)
+    }
+}
+
+/// Returns the key of the open database: the composite key after a quick
+/// unlock, otherwise its password and keyfile.
+pub(crate) fn open_database_key(open_db: &OpenDatabase) -> Result<DatabaseKey, AppError> {
+    if let Some(composite) = &open_db.composite_key {
+        return composite.to_key();
+    }
+    if open_db.password.is_none() && open_db.keyfile_path.is_none() {
+        return Err(AppError::NoCredentials);
+    }
+    build_database_key(
+        open_db.password.as_ref().map(SecureString::as_str),
+        open_db.keyfile_path.as_deref(),
+    )
+}
+
+/// Reads the key from keyfile contents the way keepass does: the data of an
+/// XML keyfile, a 32-byte binary key as is, and the SHA-256 of anything
+/// else.
+fn keyfile_key(contents: &[u8]) -> Zeroizing<Vec<u8>> {
+    if let Some(key) = xml_keyfile_key(contents) {
+        key
+    } else if contents.len() == 32 {
+        Zeroizing::new(contents.to_vec())
+    } else {
+        Zeroizing::new(Sha256::digest(contents).to_vec())
+    }
+}
+
+/// Version 2.0 keyfiles hold hex data, older ones base64. Data that does
+/// not decode is used as is.
+fn xml_keyfile_key(contents: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    let mut path = Vec::new();
+    let mut version = None;
+    let mut data = None;
+    for event in EventReader::new(contents) {
+        match event.ok()? {
+            XmlEvent::StartElement { name, .. } => path.push(name.local_name),
+            XmlEvent::EndElement { .. } => {
+                path.pop();
+            }
+            XmlEvent::Characters(text) => {
+                if path == ["KeyFile", "Meta", "Version"] {
+                    version = Some(text);
+                } else if path == ["KeyFile", "Key", "Data"] {
+                    data = Some(Zeroizing::new(text));
+                }
+            }
+            _ => {}
+        }
+    }
+    let data = data?;
+
+    let decoded = if version.as_deref() == Some("2.0") {
+        let digits: Zeroizing<String> = Zeroizing::new(
+            data.trim()
+                .chars()
+                .filter(|c| !matches!(c, ' ' | '\n' | '\r'))
+                .collect(),
+        );
+        hex::decode(digits.as_str()).ok()
+    } else {
+        base64::engine::general_purpose::STANDARD
+            .decode(data.as_bytes())
+            .ok()
+    };
+    Some(Zeroizing::new(
+        decoded.unwrap_or_else(|| data.as_bytes().to_vec()),
+    ))
+}
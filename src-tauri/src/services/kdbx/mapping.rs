@@ -1,11 +1,16 @@
 use crate::domain::secure::SecureString;
 use crate::dto::entry::{CustomFieldMeta, Entry};
+use crate::dto::error::AppError;
 use crate::dto::group::Group;
+use chrono::{DateTime, NaiveDateTime};
 use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Node, Times, Value};
 use keepass::Database;
 use secstr::SecStr;
 use std::collections::BTreeMap;
 
+use super::autotype::{entry_auto_type, group_auto_type};
+use super::references::has_reference;
+
 pub(crate) fn find_group_by_id<'a>(
     group: &'a keepass::db::Group,
     id: &str,
@@ -47,6 +52,19 @@ pub(crate) fn find_group_by_name<'a>(
 pub(crate) fn convert_entry(entry: &keepass::db::Entry, group_id: &str) -> Entry {
     let times = &entry.times;
     let (custom_fields, custom_field_meta) = collect_custom_fields(entry);
+    let mut reference_fields: Vec<String> = entry
+        .fields
+        .iter()
+        .filter(|(_, value)| match value {
+            Value::Unprotected(text) => has_reference(text),
+            Value::Protected(secret) => {
+                std::str::from_utf8(secret.unsecure()).is_ok_and(has_reference)
+            }
+            Value::Bytes(_) => false,
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    reference_fields.sort();
 
     Entry {
         id: entry.uuid.to_string(),
@@ -71,7 +89,48 @@ pub(crate) fn convert_entry(entry: &keepass::db::Entry, group_id: &str) -> Entry
             .get_last_access()
             .map(std::string::ToString::to_string)
             .unwrap_or_default(),
+        expires: times.expires,
+        expiry_time: times
+            .get_expiry()
+            .filter(|_| times.expires)
+            .map(std::string::ToString::to_string),
+        reference_fields,
+        auto_type: entry_auto_type(entry),
+    }
+}
+
+/// Whether `times` has expiry enabled and the expiry time is not after `now`.
+pub(crate) fn is_expired(times: &Times, now: NaiveDateTime) -> bool {
+    times.expires && times.get_expiry().is_some_and(|expiry| *expiry <= now)
+}
+
+/// Parses a timestamp from the frontend as UTC. Accepts RFC 3339 and the
+/// `YYYY-MM-DD HH:MM:SS` form used in entry DTOs (with or without a `T`).
+pub(crate) fn parse_timestamp(value: &str) -> Result<NaiveDateTime, AppError> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.naive_utc());
     }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| AppError::InvalidTimestamp(value.to_string()))
+}
+
+/// Applies expiry changes from create or update data to `times`.
+pub(crate) fn apply_expiry(
+    times: &mut Times,
+    expires: Option<bool>,
+    expiry_time: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(expiry_time) = expiry_time {
+        times.set_expiry(parse_timestamp(expiry_time)?);
+        times.expires = true;
+    }
+    if let Some(expires) = expires {
+        times.expires = expires;
+    }
+    Ok(())
 }
 
 pub(crate) fn convert_group(group: &keepass::db::Group, parent_id: Option<&str>) -> Group {
@@ -90,6 +149,7 @@ pub(crate) fn convert_group(group: &keepass::db::Group, parent_id: Option<&str>)
         name: group.name.clone(),
         icon: group.icon_id.map(|i| i.to_string()),
         children,
+        auto_type: group_auto_type(group),
     }
 }
 
@@ -220,6 +280,26 @@ pub(crate) fn find_parent_group_id(group: &KeepassGroup, target_id: &str) -> Opt
     None
 }
 
+/// Finds the group containing an entry by ID.
+pub(crate) fn find_entry_parent_id(group: &KeepassGroup, entry_id: &str) -> Option<String> {
+    for node in &group.children {
+        match node {
+            Node::Entry(entry) => {
+                if entry.uuid.to_string() == entry_id {
+                    return Some(group.uuid.to_string());
+                }
+            }
+            Node::Group(child) => {
+                if let Some(found) = find_entry_parent_id(child, entry_id) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+
+    None
+}
+
 /// Removes a group from its parent and returns it.
 pub(crate) fn remove_group_by_id(group: &mut KeepassGroup, id: &str) -> Option<KeepassGroup> {
     let mut index = 0;
@@ -279,6 +359,12 @@ fn contains_descendant(group: &KeepassGroup, descendant_id: &str) -> bool {
     false
 }
 
+/// Returns the UUID of the recycle bin if it exists, without creating one.
+pub(crate) fn recycle_bin_id(db: &Database) -> Option<String> {
+    let recycle_uuid = db.meta.recyclebin_uuid?.to_string();
+    find_group_by_id(&db.root, &recycle_uuid).map(|_| recycle_uuid)
+}
+
 /// Ensures a recycle bin exists and returns its UUID.
 pub(crate) fn ensure_recycle_bin(db: &mut Database) -> String {
     if let Some(recycle_uuid) = db.meta.recyclebin_uuid {
@@ -1,17 +1,43 @@
+pub mod attachments;
+pub mod autotype;
+pub mod breach;
+pub mod browser;
 pub mod create;
 pub mod entries;
+pub mod expiry;
+pub mod export;
 pub mod groups;
 pub mod header;
+pub mod health;
+pub mod import;
+pub mod kdb;
+pub mod keeshare;
 pub mod key;
 pub mod mapping;
 pub mod open;
+pub mod paths;
+pub mod placeholders;
+pub mod recycle_bin;
+pub mod references;
 pub mod save;
+pub mod search;
+pub mod secret_service;
+pub mod session;
+pub mod template;
+pub mod url_match;
 
-use crate::domain::kdbx::OpenDatabase;
+use crate::domain::kdbx::{LockedDatabase, OpenDatabase};
+use crate::dto::error::AppError;
+use crate::services::unlock_throttle::UnlockThrottleService;
 use std::sync::Mutex;
 
 pub struct KdbxService {
     database: Mutex<Option<OpenDatabase>>,
+    /// Set while the session is locked; `database` is then `None`. Always
+    /// taken after `database` when both are needed.
+    locked: Mutex<Option<LockedDatabase>>,
+    /// Backoff applied to every password and keyfile unlock, if set.
+    unlock_throttle: Option<UnlockThrottleService>,
 }
 
 impl KdbxService {
@@ -19,8 +45,40 @@ impl KdbxService {
     pub fn new() -> Self {
         Self {
             database: Mutex::new(None),
+            locked: Mutex::new(None),
+            unlock_throttle: None,
         }
     }
+
+    /// Makes every open and unlock go through `throttle`, so repeated wrong
+    /// passwords fail with [`AppError::UnlockThrottled`].
+    #[must_use]
+    pub fn with_unlock_throttle(mut self, throttle: UnlockThrottleService) -> Self {
+        self.unlock_throttle = Some(throttle);
+        self
+    }
+
+    /// Runs `unlock` for the database at `path` under the unlock throttle.
+    fn throttled<T>(
+        &self,
+        path: &str,
+        unlock: impl FnOnce() -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        match &self.unlock_throttle {
+            Some(throttle) => throttle.attempt(path, unlock),
+            None => unlock(),
+        }
+    }
+}
+
+/// Returns the open database for a change. Databases opened read-only
+/// refuse every change with [`AppError::DatabaseReadOnly`].
+fn writable(database: &mut Option<OpenDatabase>) -> Result<&mut OpenDatabase, AppError> {
+    let open_db = database.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+    if open_db.read_only {
+        return Err(AppError::DatabaseReadOnly);
+    }
+    Ok(open_db)
 }
 
 impl Default for KdbxService {
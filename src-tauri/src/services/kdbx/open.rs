@@ -3,6 +3,7 @@ use crate::domain::secure::SecureString;
 use crate::dto::database::DatabaseInfo;
 use crate::dto::error::AppError;
 use crate::services::file_lock::FileLockService;
+use crate::services::kdbx::keeshare::import_shares;
 use keepass::error::{
     BlockStreamError, CompressionConfigError, CryptographyError, DatabaseIntegrityError,
     DatabaseKeyError, DatabaseOpenError, InnerCipherConfigError, KdfConfigError,
@@ -18,7 +19,7 @@ impl KdbxService {
     pub fn open(&self, path: &str, password: &str) -> Result<DatabaseInfo, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
 
-        if db_lock.is_some() {
+        if db_lock.is_some() || self.is_locked()? {
             return Err(AppError::DatabaseAlreadyOpen);
         }
 
@@ -28,7 +29,11 @@ impl KdbxService {
         let mut file = File::open(path).map_err(|e| AppError::InvalidPath(e.to_string()))?;
 
         let key = DatabaseKey::new().with_password(password);
-        let db = Database::open(&mut file, key).map_err(map_open_error)?;
+        let mut db = self.throttled(path, || {
+            Database::open(&mut file, key).map_err(map_open_error)
+        })?;
+        // Share failures don't prevent opening; `sync_shares` reports them
+        let (_, imported) = import_shares(&mut db, path);
 
         let root_group_id = db.root.uuid.to_string();
         let name = db.root.name.clone();
@@ -37,18 +42,21 @@ impl KdbxService {
         *db_lock = Some(OpenDatabase {
             db,
             path: path.to_string(),
-            is_modified: false,
+            is_modified: imported,
             password: Some(SecureString::from(password)),
             keyfile_path: None,
+            composite_key: None,
             version: version.clone(),
             file_lock: Some(file_lock),
+            read_only: false,
         });
 
         Ok(DatabaseInfo {
             name,
             path: path.to_string(),
-            is_modified: false,
+            is_modified: imported,
             is_locked: false,
+            is_read_only: false,
             root_group_id,
             version,
         })
@@ -63,7 +71,7 @@ impl KdbxService {
     ) -> Result<DatabaseInfo, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
 
-        if db_lock.is_some() {
+        if db_lock.is_some() || self.is_locked()? {
             return Err(AppError::DatabaseAlreadyOpen);
         }
 
@@ -79,7 +87,10 @@ impl KdbxService {
             .with_keyfile(&mut keyfile)
             .map_err(|e| AppError::Kdbx(e.to_string()))?;
 
-        let db = Database::open(&mut file, key).map_err(map_open_error)?;
+        let mut db = self.throttled(path, || {
+            Database::open(&mut file, key).map_err(map_open_error)
+        })?;
+        let (_, imported) = import_shares(&mut db, path);
 
         let root_group_id = db.root.uuid.to_string();
         let name = db.root.name.clone();
@@ -88,18 +99,21 @@ impl KdbxService {
         *db_lock = Some(OpenDatabase {
             db,
             path: path.to_string(),
-            is_modified: false,
+            is_modified: imported,
             password: Some(SecureString::from(password)),
             keyfile_path: Some(keyfile_path.to_string()),
+            composite_key: None,
             version: version.clone(),
             file_lock: Some(file_lock),
+            read_only: false,
         });
 
         Ok(DatabaseInfo {
             name,
             path: path.to_string(),
-            is_modified: false,
+            is_modified: imported,
             is_locked: false,
+            is_read_only: false,
             root_group_id,
             version,
         })
@@ -113,7 +127,7 @@ impl KdbxService {
     ) -> Result<DatabaseInfo, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
 
-        if db_lock.is_some() {
+        if db_lock.is_some() || self.is_locked()? {
             return Err(AppError::DatabaseAlreadyOpen);
         }
 
@@ -127,7 +141,10 @@ impl KdbxService {
             .with_keyfile(&mut keyfile)
             .map_err(|_| AppError::KeyfileInvalid)?;
 
-        let db = Database::open(&mut file, key).map_err(map_open_error)?;
+        let mut db = self.throttled(path, || {
+            Database::open(&mut file, key).map_err(map_open_error)
+        })?;
+        let (_, imported) = import_shares(&mut db, path);
 
         let root_group_id = db.root.uuid.to_string();
         let name = db.root.name.clone();
@@ -136,11 +153,77 @@ impl KdbxService {
         *db_lock = Some(OpenDatabase {
             db,
             path: path.to_string(),
-            is_modified: false,
+            is_modified: imported,
             password: None,
             keyfile_path: Some(keyfile_path.to_string()),
+            composite_key: None,
             version: version.clone(),
             file_lock: Some(file_lock),
+            read_only: false,
+        });
+
+        Ok(DatabaseInfo {
+            name,
+            path: path.to_string(),
+            is_modified: imported,
+            is_locked: false,
+            is_read_only: false,
+            root_group_id,
+            version,
+        })
+    }
+
+    /// Opens a database read-only, with a password, a keyfile or both.
+    ///
+    /// No file lock is taken, so this works while another process holds
+    /// it. Every change to the database is refused with
+    /// [`AppError::DatabaseReadOnly`], and shares are not imported.
+    pub fn open_read_only(
+        &self,
+        path: &str,
+        password: Option<&str>,
+        keyfile_path: Option<&str>,
+    ) -> Result<DatabaseInfo, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+
+        if db_lock.is_some() || self.is_locked()? {
+            return Err(AppError::DatabaseAlreadyOpen);
+        }
+        if password.is_none() && keyfile_path.is_none() {
+            return Err(AppError::NoCredentials);
+        }
+
+        let mut file = File::open(path).map_err(|e| AppError::InvalidPath(e.to_string()))?;
+
+        let mut key = DatabaseKey::new();
+        if let Some(password) = password {
+            key = key.with_password(password);
+        }
+        if let Some(keyfile_path) = keyfile_path {
+            let mut keyfile = File::open(keyfile_path).map_err(|_| AppError::KeyfileNotFound)?;
+            key = key
+                .with_keyfile(&mut keyfile)
+                .map_err(|_| AppError::KeyfileInvalid)?;
+        }
+
+        let db = self.throttled(path, || {
+            Database::open(&mut file, key).map_err(map_open_error)
+        })?;
+
+        let root_group_id = db.root.uuid.to_string();
+        let name = db.root.name.clone();
+        let version = format_database_version(&db.config.version);
+
+        *db_lock = Some(OpenDatabase {
+            db,
+            path: path.to_string(),
+            is_modified: false,
+            password: password.map(SecureString::from),
+            keyfile_path: keyfile_path.map(String::from),
+            composite_key: None,
+            version: version.clone(),
+            file_lock: None,
+            read_only: true,
         });
 
         Ok(DatabaseInfo {
@@ -148,20 +231,23 @@ impl KdbxService {
             path: path.to_string(),
             is_modified: false,
             is_locked: false,
+            is_read_only: true,
             root_group_id,
             version,
         })
     }
 
-    /// Closes the active database.
+    /// Closes the active database, locked or not.
     pub fn close(&self) -> Result<(), AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let mut locked = self.locked.lock().map_err(|_| AppError::Lock)?;
 
-        if db_lock.is_none() {
+        if db_lock.is_none() && locked.is_none() {
             return Err(AppError::DatabaseNotOpen);
         }
 
         *db_lock = None;
+        *locked = None;
         Ok(())
     }
 
@@ -175,13 +261,14 @@ impl KdbxService {
             path: open_db.path.clone(),
             is_modified: open_db.is_modified,
             is_locked: false,
+            is_read_only: open_db.read_only,
             root_group_id: open_db.db.root.uuid.to_string(),
             version: open_db.version.clone(),
         })
     }
 }
 
-fn map_open_error(err: DatabaseOpenError) -> AppError {
+pub(crate) fn map_open_error(err: DatabaseOpenError) -> AppError {
     match err {
         // Authentication errors - incorrect credentials
         DatabaseOpenError::Key(DatabaseKeyError::IncorrectKey)
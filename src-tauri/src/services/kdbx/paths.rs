@@ -0,0 +1,145 @@
+//! Addressing groups and entries by path, for the CLI and other
+//! non-interactive callers.
+//!
+//! Paths are written relative to the root group, with `/` between group
+//! names and the entry title last (`Email/Work/Mail`); a leading `/` is
+//! allowed and the root itself is `/` or the empty path. Anything that
+//! parses as a UUID is looked up by ID first.
+
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Node};
+use uuid::Uuid;
+
+use super::entries::find_entry_by_id_ref;
+use super::mapping::{convert_group, find_entry_parent_id, find_group_by_id, find_parent_group_id};
+use super::references::convert_entry_resolved;
+use super::KdbxService;
+use crate::dto::entry::Entry;
+use crate::dto::error::AppError;
+use crate::dto::group::Group;
+
+impl KdbxService {
+    /// Finds an entry by UUID or path. A path matching several entries is
+    /// refused rather than picking one.
+    pub fn resolve_entry(&self, reference: &str) -> Result<Entry, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        let root = &open_db.db.root;
+
+        if let Some(id) = as_uuid(reference) {
+            if let Some(entry) = find_entry_by_id_ref(root, &id) {
+                let group_id = find_entry_parent_id(root, &id).unwrap_or_default();
+                return Ok(convert_entry_resolved(root, entry, &group_id));
+            }
+        }
+
+        let segments = split_path(reference);
+        let Some((title, group_segments)) = segments.split_last() else {
+            return Err(AppError::EntryNotFound(reference.to_string()));
+        };
+        let mut found: Vec<(&KeepassGroup, &KeepassEntry)> = Vec::new();
+        for group in groups_at(root, group_segments) {
+            for node in &group.children {
+                if let Node::Entry(entry) = node {
+                    if entry.get_title() == Some(*title) {
+                        found.push((group, entry));
+                    }
+                }
+            }
+        }
+
+        match found.as_slice() {
+            [(group, entry)] => Ok(convert_entry_resolved(root, entry, &group.uuid.to_string())),
+            [] => Err(AppError::EntryNotFound(reference.to_string())),
+            _ => Err(AppError::AmbiguousPath(reference.to_string())),
+        }
+    }
+
+    /// Finds a group by UUID or path. The group hierarchy is included as in
+    /// [`KdbxService::get_group`].
+    pub fn resolve_group(&self, reference: &str) -> Result<Group, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        let root = &open_db.db.root;
+
+        if let Some(id) = as_uuid(reference) {
+            if let Some(group) = find_group_by_id(root, &id) {
+                let parent_id = find_parent_group_id(root, &id);
+                return Ok(convert_group(group, parent_id.as_deref()));
+            }
+        }
+
+        let segments = split_path(reference);
+        match groups_at(root, &segments).as_slice() {
+            [group] => {
+                let id = group.uuid.to_string();
+                let parent_id = find_parent_group_id(root, &id);
+                Ok(convert_group(group, parent_id.as_deref()))
+            }
+            [] => Err(AppError::GroupNotFound(reference.to_string())),
+            _ => Err(AppError::AmbiguousPath(reference.to_string())),
+        }
+    }
+
+    /// Path of a group relative to the root, `/` for the root itself.
+    pub fn group_path(&self, group_id: &str) -> Result<String, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+        let root = &open_db.db.root;
+
+        let mut names = Vec::new();
+        if !collect_group_names(root, group_id, &mut names) {
+            return Err(AppError::GroupNotFound(group_id.to_string()));
+        }
+        Ok(format!("/{}", names.join("/")))
+    }
+}
+
+fn as_uuid(reference: &str) -> Option<String> {
+    Uuid::try_parse(reference.trim_matches('/'))
+        .ok()
+        .map(|uuid| uuid.to_string())
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// All groups reachable from `root` by following group names. Sibling
+/// groups may share a name, so there can be several.
+fn groups_at<'a>(root: &'a KeepassGroup, segments: &[&str]) -> Vec<&'a KeepassGroup> {
+    let mut groups = vec![root];
+    for segment in segments {
+        groups = groups
+            .into_iter()
+            .flat_map(|group| &group.children)
+            .filter_map(|node| match node {
+                Node::Group(child) if child.name == *segment => Some(child),
+                _ => None,
+            })
+            .collect();
+    }
+    groups
+}
+
+/// Pushes the names of the groups below `group` leading to `target_id`.
+fn collect_group_names<'a>(
+    group: &'a KeepassGroup,
+    target_id: &str,
+    names: &mut Vec<&'a str>,
+) -> bool {
+    if group.uuid.to_string() == target_id {
+        return true;
+    }
+    for node in &group.children {
+        if let Node::Group(child) = node {
+            names.push(&child.name);
+            if collect_group_names(child, target_id, names) {
+                return true;
+            }
+            names.pop();
+        }
+    }
+    false
+}
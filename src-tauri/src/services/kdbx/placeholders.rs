@@ -0,0 +1,523 @@
+//! `KeePass` placeholder expansion for entries.
+//!
+//! Supported placeholders (case-insensitive):
+//! - entry fields: `{TITLE}`, `{USERNAME}`, `{PASSWORD}`, `{URL}`, `{NOTES}`,
+//!   `{UUID}` and custom fields via `{S:Name}`
+//! - URL parts: `{URL:RMVSCM}`, `{URL:SCM}`, `{URL:HOST}`, `{URL:PORT}`,
+//!   `{URL:PATH}`, `{URL:QUERY}`, `{URL:USERINFO}`, `{URL:USERNAME}`,
+//!   `{URL:PASSWORD}`
+//! - context: `{GROUP}`, `{GROUP_PATH}`, `{DB_NAME}`, `{DB_PATH}`
+//! - date/time: `{DT_SIMPLE}`, `{DT_YEAR}` … `{DT_SECOND}` and the `DT_UTC_`
+//!   variants
+//! - environment: `{ENV:NAME}` and `{ENV_DIRSEP}`
+//! - `{REF:...}` field references and `{C:comment}` (removed)
+//! - side effects, disabled in safe mode: `{NEWPASSWORD}` (generates and
+//!   stores a new password) and `{CMD:/command/options/}` (runs a command and
+//!   inserts its output)
+//!
+//! Unknown placeholders such as auto-type keys (`{TAB}`, `{ENTER}`) are left
+//! untouched.
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use crate::dto::placeholder::PlaceholderOptions;
+use crate::utils::password_generator::generate_password;
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike, Utc};
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Node, Times, Value};
+use secstr::SecStr;
+use url::Url;
+
+use super::entries::{find_entry_by_id_mut, find_entry_by_id_ref};
+use super::references::{resolve_field, resolve_text};
+use super::{writable, KdbxService};
+
+/// Field values are expanded recursively up to this depth.
+const MAX_PLACEHOLDER_DEPTH: usize = 12;
+
+impl KdbxService {
+    /// Expands the placeholders in `text` against an entry.
+    ///
+    /// With `safe_mode` off, `{NEWPASSWORD}` stores the generated password in
+    /// the entry and `{CMD:...}` runs the given command. Unsafe expansion
+    /// needs a writable database and is refused before any command runs.
+    pub fn expand_placeholders(
+        &self,
+        entry_id: &str,
+        text: &str,
+        options: &PlaceholderOptions,
+    ) -> Result<String, AppError> {
+        if options.safe_mode {
+            let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+            let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+            let (result, _) = expand_entry(
+                &open_db.db.root,
+                entry_id,
+                text,
+                open_db.db.meta.database_name.clone().unwrap_or_default(),
+                open_db.path.clone(),
+                true,
+            )?;
+            return Ok(result);
+        }
+
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+        let (result, new_password) = expand_entry(
+            &open_db.db.root,
+            entry_id,
+            text,
+            open_db.db.meta.database_name.clone().unwrap_or_default(),
+            open_db.path.clone(),
+            false,
+        )?;
+
+        if let Some(password) = new_password {
+            let (entry, _) = find_entry_by_id_mut(&mut open_db.db.root, entry_id)
+                .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+            // Keep the old password recoverable from the entry's history.
+            let mut history = entry.history.take().unwrap_or_default();
+            history.add_entry(entry.clone());
+            entry.history = Some(history);
+            entry.fields.insert(
+                "Password".to_string(),
+                Value::Protected(SecStr::new(password.as_str().as_bytes().to_vec())),
+            );
+            entry.times.set_last_modification(Times::now());
+            open_db.is_modified = true;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Expands `text` against the entry `entry_id` in `root`. Returns the result
+/// and the password generated for `{NEWPASSWORD}`, if any.
+fn expand_entry(
+    root: &KeepassGroup,
+    entry_id: &str,
+    text: &str,
+    database_name: String,
+    database_path: String,
+    safe_mode: bool,
+) -> Result<(String, Option<SecureString>), AppError> {
+    let entry = find_entry_by_id_ref(root, entry_id)
+        .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+    let group_path = entry_group_path(root, entry_id).unwrap_or_default();
+
+    let mut expander = Expander {
+        root,
+        entry,
+        group_path,
+        database_name,
+        database_path,
+        safe_mode,
+        new_password: None,
+    };
+    let result = expander.expand(text, 0)?;
+    Ok((result, expander.new_password))
+}
+
+struct Expander<'a> {
+    root: &'a KeepassGroup,
+    entry: &'a KeepassEntry,
+    /// Group names from below the root down to the entry's group
+    group_path: Vec<&'a str>,
+    database_name: String,
+    database_path: String,
+    safe_mode: bool,
+    /// Password generated for `{NEWPASSWORD}`, reused for every occurrence
+    new_password: Option<SecureString>,
+}
+
+impl Expander<'_> {
+    fn expand(&mut self, text: &str, depth: usize) -> Result<String, AppError> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            let candidate = &rest[start..];
+
+            let Some((replacement, consumed)) = self.placeholder(candidate, depth)? else {
+                output.push('{');
+                rest = &candidate[1..];
+                continue;
+            };
+            output.push_str(&replacement);
+            rest = &candidate[consumed..];
+        }
+        output.push_str(rest);
+
+        Ok(output)
+    }
+
+    /// Expands the placeholder at the start of `text`. Returns the
+    /// replacement and the number of bytes consumed, or `None` if `text` does
+    /// not start with a known placeholder.
+    fn placeholder(
+        &mut self,
+        text: &str,
+        depth: usize,
+    ) -> Result<Option<(String, usize)>, AppError> {
+        if starts_with_ignore_case(text, "{CMD:") {
+            return self.command(text);
+        }
+
+        let Some(end) = text.find('}') else {
+            return Ok(None);
+        };
+        let inner = &text[1..end];
+        let consumed = end + 1;
+        let upper = inner.to_ascii_uppercase();
+
+        let replacement = match upper.as_str() {
+            "TITLE" => self.field("Title", depth)?,
+            "USERNAME" => self.field("UserName", depth)?,
+            "PASSWORD" => self.field("Password", depth)?,
+            "URL" => self.field("URL", depth)?,
+            "NOTES" => self.field("Notes", depth)?,
+            "UUID" => self.entry.uuid.simple().to_string().to_uppercase(),
+            "GROUP" => self
+                .group_path
+                .last()
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            "GROUP_PATH" => self.group_path.join("."),
+            "DB_NAME" => self.database_name.clone(),
+            "DB_PATH" => self.database_path.clone(),
+            "ENV_DIRSEP" => std::path::MAIN_SEPARATOR.to_string(),
+            "NEWPASSWORD" => {
+                if self.safe_mode {
+                    return Ok(None);
+                }
+                self.new_password()?
+            }
+            _ => {
+                if let Some(part) = upper.strip_prefix("URL:") {
+                    let url = self.field("URL", depth)?;
+                    url_part(&url, part).unwrap_or_default()
+                } else if let Some(name) = strip_prefix_ignore_case(inner, "S:") {
+                    self.custom_field(name, depth)?
+                } else if let Some(name) = strip_prefix_ignore_case(inner, "ENV:") {
+                    std::env::var(name).unwrap_or_default()
+                } else if strip_prefix_ignore_case(inner, "C:").is_some() {
+                    String::new()
+                } else if strip_prefix_ignore_case(inner, "REF:").is_some() {
+                    resolve_text(self.root, &text[..consumed])?
+                } else if let Some(format) = upper.strip_prefix("DT_UTC_") {
+                    match date_time(&Utc::now(), format) {
+                        Some(value) => value,
+                        None => return Ok(None),
+                    }
+                } else if let Some(format) = upper.strip_prefix("DT_") {
+                    match date_time(&Local::now(), format) {
+                        Some(value) => value,
+                        None => return Ok(None),
+                    }
+                } else {
+                    return Ok(None);
+                }
+            }
+        };
+
+        Ok(Some((replacement, consumed)))
+    }
+
+    /// Value of a standard field with references and nested placeholders
+    /// expanded.
+    fn field(&mut self, key: &str, depth: usize) -> Result<String, AppError> {
+        let value = resolve_field(self.root, self.entry, key)?;
+        self.expand_nested(&value, depth)
+    }
+
+    fn custom_field(&mut self, name: &str, depth: usize) -> Result<String, AppError> {
+        let key = if self.entry.fields.contains_key(name) {
+            Some(name)
+        } else {
+            self.entry
+                .fields
+                .keys()
+                .map(String::as_str)
+                .find(|key| key.eq_ignore_ascii_case(name))
+        };
+        match key {
+            Some(key) => {
+                let key = key.to_string();
+                self.field(&key, depth)
+            }
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Field values may contain placeholders themselves; beyond the depth
+    /// limit they are inserted as they are.
+    fn expand_nested(&mut self, value: &str, depth: usize) -> Result<String, AppError> {
+        if depth + 1 >= MAX_PLACEHOLDER_DEPTH || !value.contains('{') {
+            return Ok(value.to_string());
+        }
+        self.expand(value, depth + 1)
+    }
+
+    fn new_password(&mut self) -> Result<String, AppError> {
+        if self.new_password.is_none() {
+            let password = generate_password(&PasswordGeneratorOptions::default())?;
+            self.new_password = Some(SecureString::from(password));
+        }
+        Ok(self
+            .new_password
+            .as_ref()
+            .map(|password| password.as_str().to_string())
+            .unwrap_or_default())
+    }
+
+    /// `{CMD:/command/options/}`: any character may be used as delimiter.
+    /// Runs `command` without a shell and inserts its trimmed standard output.
+    fn command(&self, text: &str) -> Result<Option<(String, usize)>, AppError> {
+        const PREFIX_LEN: usize = "{CMD:".len();
+        let Some(delimiter) = text[PREFIX_LEN..].chars().next() else {
+            return Ok(None);
+        };
+        let body = &text[PREFIX_LEN + delimiter.len_utf8()..];
+        let Some((command, after)) = body.split_once(delimiter) else {
+            return Ok(None);
+        };
+        let Some((_options, after)) = after.split_once(delimiter) else {
+            return Ok(None);
+        };
+        if !after.starts_with('}') {
+            return Ok(None);
+        }
+        let consumed = text.len() - after.len() + 1;
+
+        if self.safe_mode {
+            return Ok(None);
+        }
+
+        let args = shlex::split(command)
+            .filter(|args| !args.is_empty())
+            .ok_or_else(|| AppError::Placeholder(format!("Invalid command: {command}")))?;
+        let output = std::process::Command::new(&args[0])
+            .args(&args[1..])
+            .output()
+            .map_err(|e| AppError::Placeholder(format!("Failed to run {}: {e}", args[0])))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(Some((
+            stdout.trim_end_matches(['\r', '\n']).to_string(),
+            consumed,
+        )))
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    starts_with_ignore_case(text, prefix).then(|| &text[prefix.len()..])
+}
+
+/// A component of `url` for `{URL:<part>}`, or `None` for unknown parts and
+/// URLs that cannot be parsed.
+fn url_part(url: &str, part: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let value = match part {
+        "RMVSCM" => url
+            .split_once(':')
+            .map(|(_, rest)| rest.trim_start_matches('/').to_string())?,
+        "SCM" => parsed.scheme().to_string(),
+        "HOST" => parsed.host_str().unwrap_or_default().to_string(),
+        "PORT" => parsed
+            .port_or_known_default()
+            .map(|port| port.to_string())
+            .unwrap_or_default(),
+        "PATH" => parsed.path().to_string(),
+        "QUERY" => parsed
+            .query()
+            .map(|query| format!("?{query}"))
+            .unwrap_or_default(),
+        "USERINFO" => match parsed.password() {
+            Some(password) => format!("{}:{password}", parsed.username()),
+            None => parsed.username().to_string(),
+        },
+        "USERNAME" => parsed.username().to_string(),
+        "PASSWORD" => parsed.password().unwrap_or_default().to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Formats `now` for a `{DT_<format>}` placeholder.
+fn date_time<Tz: TimeZone>(now: &DateTime<Tz>, format: &str) -> Option<String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let value = match format {
+        "SIMPLE" => now.format("%Y%m%d%H%M%S").to_string(),
+        "YEAR" => format!("{:04}", now.year()),
+        "MONTH" => format!("{:02}", now.month()),
+        "DAY" => format!("{:02}", now.day()),
+        "HOUR" => format!("{:02}", now.hour()),
+        "MINUTE" => format!("{:02}", now.minute()),
+        "SECOND" => format!("{:02}", now.second()),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Names of the groups from below the root down to the group holding the
+/// entry.
+fn entry_group_path<'a>(group: &'a KeepassGroup, entry_id: &str) -> Option<Vec<&'a str>> {
+    for node in &group.children {
+        match node {
+            Node::Entry(entry) if entry.uuid.to_string() == entry_id => {
+                return Some(Vec::new());
+            }
+            Node::Group(child) => {
+                if let Some(mut path) = entry_group_path(child, entry_id) {
+                    path.insert(0, child.name.as_str());
+                    return Some(path);
+                }
+            }
+            Node::Entry(_) => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn entry() -> KeepassEntry {
+        let mut entry = KeepassEntry::new();
+        for (key, value) in [
+            ("Title", "Mail"),
+            ("UserName", "alice"),
+            ("URL", "https://bob:pw@mail.example.com:8443/inbox?folder=1"),
+            ("Notes", "User {USERNAME}"),
+            ("Employee ID", "E-42"),
+        ] {
+            entry
+                .fields
+                .insert(key.into(), Value::Unprotected(value.into()));
+        }
+        entry.fields.insert(
+            "Password".into(),
+            Value::Protected(SecStr::new(b"s3cret".to_vec())),
+        );
+        entry
+    }
+
+    fn expand(entry: &KeepassEntry, text: &str, safe_mode: bool) -> Result<String, AppError> {
+        let root = KeepassGroup::new("Root");
+        let mut expander = Expander {
+            root: &root,
+            entry,
+            group_path: vec!["Internet", "Email"],
+            database_name: "Vault".into(),
+            database_path: "/tmp/vault.kdbx".into(),
+            safe_mode,
+            new_password: None,
+        };
+        expander.expand(text, 0)
+    }
+
+    #[test]
+    fn test_expands_entry_fields_case_insensitively() {
+        let entry = entry();
+        let expanded = expand(
+            &entry,
+            "{Title}|{USERNAME}|{password}|{S:employee id}",
+            true,
+        )
+        .expect("expand");
+        assert_eq!(expanded, "Mail|alice|s3cret|E-42");
+    }
+
+    #[test]
+    fn test_expands_nested_placeholders_in_fields() {
+        let entry = entry();
+        assert_eq!(
+            expand(&entry, "{NOTES}", true).expect("expand"),
+            "User alice"
+        );
+    }
+
+    #[test]
+    fn test_expands_url_parts() {
+        let entry = entry();
+        let expanded = expand(
+            &entry,
+            "{URL:SCM}|{URL:HOST}|{URL:PORT}|{URL:PATH}|{URL:QUERY}|{URL:USERINFO}",
+            true,
+        )
+        .expect("expand");
+        assert_eq!(
+            expanded,
+            "https|mail.example.com|8443|/inbox|?folder=1|bob:pw"
+        );
+        assert_eq!(
+            expand(&entry, "{URL:RMVSCM}", true).expect("expand"),
+            "bob:pw@mail.example.com:8443/inbox?folder=1"
+        );
+    }
+
+    #[test]
+    fn test_expands_context_and_environment() {
+        let entry = entry();
+        let expanded =
+            expand(&entry, "{GROUP}|{GROUP_PATH}|{DB_NAME}|{C:hidden}", true).expect("expand");
+        assert_eq!(expanded, "Email|Internet.Email|Vault|");
+
+        let path = std::env::var("PATH").unwrap_or_default();
+        assert_eq!(expand(&entry, "{ENV:PATH}", true).expect("expand"), path);
+    }
+
+    #[test]
+    fn test_expands_date_time() {
+        let entry = entry();
+        let simple = expand(&entry, "{DT_UTC_SIMPLE}", true).expect("expand");
+        assert_eq!(simple.len(), 14);
+        assert!(simple.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(
+            expand(&entry, "{DT_YEAR}", true).expect("expand"),
+            Local::now().year().to_string()
+        );
+    }
+
+    #[test]
+    fn test_unknown_placeholders_and_braces_are_kept() {
+        let entry = entry();
+        let expanded = expand(&entry, "{TAB}{ENTER} {USERNAME} {not closed", true).expect("expand");
+        assert_eq!(expanded, "{TAB}{ENTER} alice {not closed");
+    }
+
+    #[test]
+    fn test_safe_mode_disables_side_effects() {
+        let entry = entry();
+        let text = "{NEWPASSWORD}{CMD:/echo hi/W=0/}";
+        assert_eq!(expand(&entry, text, true).expect("expand"), text);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cmd_inserts_output_without_safe_mode() {
+        let entry = entry();
+        let expanded = expand(&entry, "[{CMD:|echo 'hi there'||}]", false).expect("expand");
+        assert_eq!(expanded, "[hi there]");
+    }
+
+    #[test]
+    fn test_new_password_is_reused_within_expansion() {
+        let entry = entry();
+        let expanded = expand(&entry, "{NEWPASSWORD} {NEWPASSWORD}", false).expect("expand");
+        let (first, second) = expanded.split_once(' ').expect("two passwords");
+        assert_eq!(first, second);
+        assert_eq!(first.len(), PasswordGeneratorOptions::default().length);
+    }
+}
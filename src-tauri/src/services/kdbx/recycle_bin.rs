@@ -0,0 +1,380 @@
+//! Recycle bin listing, restore, emptying and retention.
+//!
+//! keepass 0.8 neither reads nor writes the KDBX 4.1 `PreviousParentGroup`
+//! element, so the group an item was deleted from is kept in the item's
+//! custom data instead.
+
+use crate::dto::entry::Entry;
+use crate::dto::error::AppError;
+use crate::dto::group::Group;
+use crate::dto::recycle_bin::{RecycleBinItem, RecycleBinItemKind, RecycleBinSettings};
+use chrono::{NaiveDateTime, TimeDelta};
+use keepass::db::{
+    CustomData, CustomDataItem, DeletedObject, Group as KeepassGroup, Node, Times, Value,
+};
+use keepass::Database;
+
+use super::entries::{find_entry_by_id_ref, remove_entry_by_id};
+use super::mapping::{
+    convert_entry, convert_group, find_group_by_id, find_group_by_id_mut, is_ancestor_of,
+    recycle_bin_id, remove_group_by_id,
+};
+use super::{writable, KdbxService};
+
+/// Custom data key holding the UUID of the group an item was deleted from.
+pub(crate) const PREVIOUS_PARENT_KEY: &str = "MithrilVault.PreviousParentGroup";
+/// Meta custom data key holding the retention period in days.
+const RETENTION_DAYS_KEY: &str = "MithrilVault.RecycleBin/RetentionDays";
+/// Longest retention period that can be configured (about 100 years).
+const MAX_RETENTION_DAYS: u32 = 36_500;
+
+impl KdbxService {
+    /// Lists the entries and groups directly inside the recycle bin.
+    pub fn list_recycle_bin(&self) -> Result<Vec<RecycleBinItem>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let Some(bin) = recycle_bin_id(&open_db.db)
+            .and_then(|bin_id| find_group_by_id(&open_db.db.root, &bin_id))
+        else {
+            return Ok(Vec::new());
+        };
+
+        let items = bin
+            .children
+            .iter()
+            .map(|node| match node {
+                Node::Entry(entry) => RecycleBinItem {
+                    id: entry.uuid.to_string(),
+                    kind: RecycleBinItemKind::Entry,
+                    name: entry.get_title().unwrap_or_default().to_string(),
+                    previous_parent_id: previous_parent(&entry.custom_data),
+                    deleted_at: entry.times.get_location_changed().map(ToString::to_string),
+                    entry_count: 0,
+                },
+                Node::Group(group) => RecycleBinItem {
+                    id: group.uuid.to_string(),
+                    kind: RecycleBinItemKind::Group,
+                    name: group.name.clone(),
+                    previous_parent_id: previous_parent(&group.custom_data),
+                    deleted_at: group.times.get_location_changed().map(ToString::to_string),
+                    entry_count: count_entries(group),
+                },
+            })
+            .collect();
+
+        Ok(items)
+    }
+
+    /// Moves an entry out of the recycle bin into `target_group_id`. Without
+    /// a target it goes back to the group it was deleted from, or into the
+    /// root group if that group no longer exists or is in the recycle bin.
+    pub fn restore_entry(
+        &self,
+        id: &str,
+        target_group_id: Option<&str>,
+    ) -> Result<Entry, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let bin_id =
+            recycle_bin_id(&open_db.db).ok_or_else(|| AppError::NotInRecycleBin(id.to_string()))?;
+        if !entry_in_recycle_bin(&open_db.db, id) {
+            return Err(AppError::NotInRecycleBin(id.to_string()));
+        }
+        if let Some(target_id) = target_group_id {
+            check_restore_target(&open_db.db.root, &bin_id, target_id)?;
+        }
+
+        let bin = find_group_by_id_mut(&mut open_db.db.root, &bin_id)
+            .ok_or_else(|| AppError::GroupNotFound(bin_id.clone()))?;
+        let mut entry =
+            remove_entry_by_id(bin, id).ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
+
+        let target_id = target_group_id.map_or_else(
+            || previous_parent_or_root(&open_db.db.root, &bin_id, &entry.custom_data),
+            ToString::to_string,
+        );
+        entry.custom_data.items.remove(PREVIOUS_PARENT_KEY);
+        let now = Times::now();
+        entry.times.set_last_modification(now);
+        entry.times.set_location_changed(now);
+
+        let target = find_group_by_id_mut(&mut open_db.db.root, &target_id)
+            .ok_or_else(|| AppError::GroupNotFound(target_id.clone()))?;
+        let entry_model = convert_entry(&entry, &target_id);
+        target.add_child(entry);
+        open_db.is_modified = true;
+
+        Ok(entry_model)
+    }
+
+    /// Moves a group out of the recycle bin into `target_group_id`. Without
+    /// a target it goes back to the group it was deleted from, or into the
+    /// root group if that group no longer exists or is in the recycle bin.
+    pub fn restore_group(
+        &self,
+        id: &str,
+        target_group_id: Option<&str>,
+    ) -> Result<Group, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let bin_id =
+            recycle_bin_id(&open_db.db).ok_or_else(|| AppError::NotInRecycleBin(id.to_string()))?;
+        if bin_id == id || !is_ancestor_of(&open_db.db.root, &bin_id, id) {
+            return Err(AppError::NotInRecycleBin(id.to_string()));
+        }
+        if let Some(target_id) = target_group_id {
+            check_restore_target(&open_db.db.root, &bin_id, target_id)?;
+        }
+
+        let bin = find_group_by_id_mut(&mut open_db.db.root, &bin_id)
+            .ok_or_else(|| AppError::GroupNotFound(bin_id.clone()))?;
+        let mut group =
+            remove_group_by_id(bin, id).ok_or_else(|| AppError::GroupNotFound(id.to_string()))?;
+
+        let target_id = target_group_id.map_or_else(
+            || previous_parent_or_root(&open_db.db.root, &bin_id, &group.custom_data),
+            ToString::to_string,
+        );
+        group.custom_data.items.remove(PREVIOUS_PARENT_KEY);
+        let now = Times::now();
+        group.times.set_last_modification(now);
+        group.times.set_location_changed(now);
+
+        let target = find_group_by_id_mut(&mut open_db.db.root, &target_id)
+            .ok_or_else(|| AppError::GroupNotFound(target_id.clone()))?;
+        let group_model = convert_group(&group, Some(&target_id));
+        target.add_child(group);
+        open_db.is_modified = true;
+
+        Ok(group_model)
+    }
+
+    /// Permanently deletes everything in the recycle bin and records the
+    /// deletions for synchronization. Returns the number of removed items.
+    pub fn empty_recycle_bin(&self) -> Result<usize, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let Some(bin_id) = recycle_bin_id(&open_db.db) else {
+            return Ok(0);
+        };
+        let bin = find_group_by_id_mut(&mut open_db.db.root, &bin_id)
+            .ok_or_else(|| AppError::GroupNotFound(bin_id.clone()))?;
+
+        let removed = std::mem::take(&mut bin.children);
+        if removed.is_empty() {
+            return Ok(0);
+        }
+
+        let count = record_deleted_nodes(&mut open_db.db, removed, Times::now());
+        open_db.is_modified = true;
+        Ok(count)
+    }
+
+    /// Returns the recycle bin settings of the open database.
+    pub fn get_recycle_bin_settings(&self) -> Result<RecycleBinSettings, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        Ok(RecycleBinSettings {
+            enabled: recycle_bin_enabled(&open_db.db),
+            retention_days: retention_days(&open_db.db),
+        })
+    }
+
+    /// Updates the recycle bin settings of the open database.
+    pub fn update_recycle_bin_settings(
+        &self,
+        settings: &RecycleBinSettings,
+    ) -> Result<RecycleBinSettings, AppError> {
+        if settings
+            .retention_days
+            .is_some_and(|days| days > MAX_RETENTION_DAYS)
+        {
+            return Err(AppError::InvalidArguments(format!(
+                "retention period must be at most {MAX_RETENTION_DAYS} days"
+            )));
+        }
+
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let enabled = recycle_bin_enabled(&open_db.db);
+        let meta = &mut open_db.db.meta;
+        if enabled != settings.enabled {
+            meta.recyclebin_enabled = Some(settings.enabled);
+            meta.recyclebin_changed = Some(Times::now());
+        }
+
+        let items = &mut meta.custom_data.items;
+        match settings.retention_days {
+            Some(days) => {
+                items.insert(
+                    RETENTION_DAYS_KEY.to_string(),
+                    CustomDataItem {
+                        value: Some(Value::Unprotected(days.to_string())),
+                        last_modification_time: Some(Times::now()),
+                    },
+                );
+            }
+            None => {
+                items.remove(RETENTION_DAYS_KEY);
+            }
+        }
+        open_db.is_modified = true;
+
+        Ok(settings.clone())
+    }
+}
+
+/// Whether deleted items go to the recycle bin. Databases without the
+/// setting use the bin.
+pub(crate) fn recycle_bin_enabled(db: &Database) -> bool {
+    db.meta.recyclebin_enabled.unwrap_or(true)
+}
+
+/// Whether an entry is inside the recycle bin (at any depth).
+pub(crate) fn entry_in_recycle_bin(db: &Database, id: &str) -> bool {
+    recycle_bin_id(db)
+        .and_then(|bin_id| find_group_by_id(&db.root, &bin_id))
+        .is_some_and(|bin| find_entry_by_id_ref(bin, id).is_some())
+}
+
+/// Whether a group is the recycle bin or inside it.
+pub(crate) fn group_in_recycle_bin(db: &Database, id: &str) -> bool {
+    recycle_bin_id(db).is_some_and(|bin_id| is_ancestor_of(&db.root, &bin_id, id))
+}
+
+/// Records `parent_id` as the group an item is being moved out of.
+pub(crate) fn record_previous_parent(custom_data: &mut CustomData, parent_id: &str) {
+    custom_data.items.insert(
+        PREVIOUS_PARENT_KEY.to_string(),
+        CustomDataItem {
+            value: Some(Value::Unprotected(parent_id.to_string())),
+            last_modification_time: Some(Times::now()),
+        },
+    );
+}
+
+/// Permanently deletes recycle bin items that were moved in longer ago than
+/// the retention period. Returns the number of removed items. A period too
+/// long to subtract from the current time (only possible in a hand-edited
+/// file) purges nothing.
+pub(crate) fn purge_expired_items(db: &mut Database) -> usize {
+    let now = Times::now();
+    let Some(cutoff) = retention_days(db)
+        .and_then(|days| TimeDelta::try_days(i64::from(days)))
+        .and_then(|retention| now.checked_sub_signed(retention))
+    else {
+        return 0;
+    };
+    let Some(bin_id) = recycle_bin_id(db) else {
+        return 0;
+    };
+    let Some(bin) = find_group_by_id_mut(&mut db.root, &bin_id) else {
+        return 0;
+    };
+
+    let (expired, kept) = std::mem::take(&mut bin.children)
+        .into_iter()
+        .partition(|node| moved_in_before(node, cutoff));
+    bin.children = kept;
+
+    record_deleted_nodes(db, expired, now)
+}
+
+fn moved_in_before(node: &Node, cutoff: NaiveDateTime) -> bool {
+    let times = match node {
+        Node::Entry(entry) => &entry.times,
+        Node::Group(group) => &group.times,
+    };
+    times
+        .get_location_changed()
+        .is_some_and(|moved_in| *moved_in <= cutoff)
+}
+
+/// Adds every entry and group in `nodes` to the deleted objects list.
+pub(crate) fn record_deleted_nodes(
+    db: &mut Database,
+    nodes: Vec<Node>,
+    deletion_time: NaiveDateTime,
+) -> usize {
+    let mut count = 0;
+    let mut stack = nodes;
+    while let Some(node) = stack.pop() {
+        let uuid = match node {
+            Node::Entry(entry) => entry.uuid,
+            Node::Group(group) => {
+                stack.extend(group.children);
+                group.uuid
+            }
+        };
+        db.deleted_objects.objects.push(DeletedObject {
+            uuid,
+            deletion_time,
+        });
+        count += 1;
+    }
+    count
+}
+
+fn retention_days(db: &Database) -> Option<u32> {
+    match db
+        .meta
+        .custom_data
+        .items
+        .get(RETENTION_DAYS_KEY)?
+        .value
+        .as_ref()?
+    {
+        Value::Unprotected(days) => days.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn previous_parent(custom_data: &CustomData) -> Option<String> {
+    match custom_data.items.get(PREVIOUS_PARENT_KEY)?.value.as_ref()? {
+        Value::Unprotected(id) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+/// Picks the recorded previous parent if it still exists outside the bin,
+/// otherwise the root group.
+fn previous_parent_or_root(root: &KeepassGroup, bin_id: &str, custom_data: &CustomData) -> String {
+    previous_parent(custom_data)
+        .filter(|id| find_group_by_id(root, id).is_some())
+        .filter(|id| !is_ancestor_of(root, bin_id, id))
+        .unwrap_or_else(|| root.uuid.to_string())
+}
+
+/// Checks that a caller-chosen restore target exists outside the recycle bin.
+fn check_restore_target(
+    root: &KeepassGroup,
+    bin_id: &str,
+    target_id: &str,
+) -> Result<(), AppError> {
+    if find_group_by_id(root, target_id).is_none() {
+        return Err(AppError::GroupNotFound(target_id.to_string()));
+    }
+    if is_ancestor_of(root, bin_id, target_id) {
+        return Err(AppError::InvalidArguments(
+            "cannot restore into the recycle bin".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn count_entries(group: &KeepassGroup) -> usize {
+    group
+        .children
+        .iter()
+        .map(|node| match node {
+            Node::Entry(_) => 1,
+            Node::Group(child) => count_entries(child),
+        })
+        .sum()
+}
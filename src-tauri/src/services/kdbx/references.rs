@@ -0,0 +1,454 @@
+//! Resolution of `{REF:<field>@<search in>:<text>}` field references.
+//!
+//! Wanted fields: `T` title, `U` user name, `P` password, `A` URL, `N` notes
+//! and `I` UUID. Search types: the same letters plus `O` (other, i.e. custom
+//! fields). As in the `KeePass` resolver, text searches are case-insensitive
+//! substring matches, the first entry in tree order wins and references to
+//! missing entries are left as they are.
+
+use crate::dto::entry::{CreateReferenceEntryData, Entry, ReferenceField};
+use crate::dto::error::AppError;
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Node, Value};
+use secstr::SecStr;
+use uuid::Uuid;
+
+use super::entries::find_entry_by_id_ref;
+use super::mapping::{convert_entry, find_group_by_id_mut, is_standard_entry_field};
+use super::{writable, KdbxService};
+
+const REF_PREFIX: &str = "{REF:";
+
+/// Maximum reference nesting; deeper chains are reported as errors.
+const MAX_REFERENCE_DEPTH: usize = 12;
+
+impl KdbxService {
+    /// Creates an entry in `group_id` whose selected fields reference the
+    /// corresponding fields of another entry by UUID.
+    pub fn create_reference_entry(
+        &self,
+        group_id: &str,
+        data: &CreateReferenceEntryData,
+    ) -> Result<Entry, AppError> {
+        if data.fields.is_empty() {
+            return Err(AppError::FieldReference(
+                "No fields selected for reference".into(),
+            ));
+        }
+
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let source = find_entry_by_id_ref(&open_db.db.root, &data.source_entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(data.source_entry_id.clone()))?;
+        let source_title = resolve_field(&open_db.db.root, source, "Title").unwrap_or_default();
+        let source_ref = uuid_hex(&source.uuid);
+
+        let reference =
+            |field: ReferenceField| format!("{REF_PREFIX}{}@I:{source_ref}}}", field.code());
+
+        // An explicit title wins over a title reference
+        let title = match &data.title {
+            Some(title) => title.clone(),
+            None if data.fields.contains(&ReferenceField::Title) => {
+                reference(ReferenceField::Title)
+            }
+            None => source_title,
+        };
+        let mut entry = KeepassEntry::new();
+        entry
+            .fields
+            .insert("Title".to_string(), Value::Unprotected(title));
+        for &field in &data.fields {
+            let value = match field {
+                ReferenceField::Title => continue,
+                ReferenceField::Password => {
+                    Value::Protected(SecStr::new(reference(field).into_bytes()))
+                }
+                _ => Value::Unprotected(reference(field)),
+            };
+            entry.fields.insert(field.key().to_string(), value);
+        }
+
+        let group = find_group_by_id_mut(&mut open_db.db.root, group_id)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.to_string()))?;
+        let id = entry.uuid.to_string();
+        group.add_child(entry);
+        open_db.is_modified = true;
+
+        let entry = find_entry_by_id_ref(&open_db.db.root, &id)
+            .ok_or_else(|| AppError::EntryNotFound(id.clone()))?;
+        Ok(convert_entry_resolved(&open_db.db.root, entry, group_id))
+    }
+}
+
+impl ReferenceField {
+    fn code(self) -> char {
+        match self {
+            Self::Title => 'T',
+            Self::Username => 'U',
+            Self::Password => 'P',
+            Self::Url => 'A',
+            Self::Notes => 'N',
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Username => "UserName",
+            Self::Password => "Password",
+            Self::Url => "URL",
+            Self::Notes => "Notes",
+        }
+    }
+}
+
+/// Whether `value` contains a field reference.
+pub(crate) fn has_reference(value: &str) -> bool {
+    value.to_ascii_uppercase().contains(REF_PREFIX)
+}
+
+/// Converts an entry with references in its standard and unprotected custom
+/// fields resolved. Fields whose references cannot be resolved (cycles, too
+/// deep) keep their raw value.
+pub(crate) fn convert_entry_resolved(
+    root: &KeepassGroup,
+    entry: &KeepassEntry,
+    group_id: &str,
+) -> Entry {
+    let mut model = convert_entry(entry, group_id);
+    if model.reference_fields.is_empty() {
+        return model;
+    }
+
+    let resolve = |key: &str, raw: String| resolve_field(root, entry, key).unwrap_or(raw);
+    model.title = resolve("Title", model.title);
+    model.username = resolve("UserName", model.username);
+    model.url = model.url.map(|url| resolve("URL", url));
+    model.notes = model.notes.map(|notes| resolve("Notes", notes));
+    for (key, value) in &mut model.custom_fields {
+        *value = resolve(key, std::mem::take(value));
+    }
+    model
+}
+
+/// Returns the value of `key` on `entry` with all references resolved. A
+/// missing field resolves to an empty string.
+pub(crate) fn resolve_field(
+    root: &KeepassGroup,
+    entry: &KeepassEntry,
+    key: &str,
+) -> Result<String, AppError> {
+    let mut stack = Vec::new();
+    resolve_value(root, entry, key, &mut stack)
+}
+
+fn resolve_value(
+    root: &KeepassGroup,
+    entry: &KeepassEntry,
+    key: &str,
+    stack: &mut Vec<(Uuid, String)>,
+) -> Result<String, AppError> {
+    let value = entry.get(key).unwrap_or_default();
+    if !has_reference(value) {
+        return Ok(value.to_string());
+    }
+
+    if stack
+        .iter()
+        .any(|(uuid, k)| *uuid == entry.uuid && k == key)
+    {
+        return Err(AppError::FieldReference(format!(
+            "Reference cycle at {key} of entry {}",
+            entry.uuid
+        )));
+    }
+    if stack.len() >= MAX_REFERENCE_DEPTH {
+        return Err(AppError::FieldReference(format!(
+            "References nested deeper than {MAX_REFERENCE_DEPTH} levels"
+        )));
+    }
+    stack.push((entry.uuid, key.to_string()));
+    let resolved = resolve_references(root, value, stack)?;
+    stack.pop();
+
+    Ok(resolved)
+}
+
+/// Resolves the references in free text, e.g. an auto-type sequence.
+pub(crate) fn resolve_text(root: &KeepassGroup, text: &str) -> Result<String, AppError> {
+    resolve_references(root, text, &mut Vec::new())
+}
+
+fn resolve_references(
+    root: &KeepassGroup,
+    value: &str,
+    stack: &mut Vec<(Uuid, String)>,
+) -> Result<String, AppError> {
+    // ASCII uppercasing keeps byte offsets aligned with `value`
+    let upper = value.to_ascii_uppercase();
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = 0;
+
+    while let Some(start) = upper[rest..].find(REF_PREFIX).map(|i| rest + i) {
+        let Some(end) = value[start..].find('}').map(|i| start + i) else {
+            break;
+        };
+        resolved.push_str(&value[rest..start]);
+
+        let reference = &value[start + REF_PREFIX.len()..end];
+        match resolve_reference(root, reference, stack)? {
+            Some(text) => resolved.push_str(&text),
+            None => resolved.push_str(&value[start..=end]),
+        }
+        rest = end + 1;
+    }
+    resolved.push_str(&value[rest..]);
+
+    Ok(resolved)
+}
+
+/// Resolves the inside of a `{REF:...}` placeholder, or returns `None` if it
+/// is malformed or matches no entry.
+fn resolve_reference(
+    root: &KeepassGroup,
+    reference: &str,
+    stack: &mut Vec<(Uuid, String)>,
+) -> Result<Option<String>, AppError> {
+    let Some((wanted, search)) = reference.split_once('@') else {
+        return Ok(None);
+    };
+    let Some((search_in, text)) = search.split_once(':') else {
+        return Ok(None);
+    };
+    let (Some(wanted), Some(search_in)) = (single_char(wanted), single_char(search_in)) else {
+        return Ok(None);
+    };
+
+    let Some(target) = find_referenced_entry(root, search_in, text) else {
+        return Ok(None);
+    };
+    let key = match wanted {
+        'T' => "Title",
+        'U' => "UserName",
+        'P' => "Password",
+        'A' => "URL",
+        'N' => "Notes",
+        'I' => return Ok(Some(uuid_hex(&target.uuid))),
+        _ => return Ok(None),
+    };
+
+    resolve_value(root, target, key, stack).map(Some)
+}
+
+fn single_char(code: &str) -> Option<char> {
+    let mut chars = code.chars();
+    let c = chars.next()?.to_ascii_uppercase();
+    chars.next().is_none().then_some(c)
+}
+
+fn find_referenced_entry<'a>(
+    root: &'a KeepassGroup,
+    search_in: char,
+    text: &str,
+) -> Option<&'a KeepassEntry> {
+    if search_in == 'I' {
+        let uuid = Uuid::parse_str(text.trim()).ok()?;
+        return find_first(root, &|entry| entry.uuid == uuid);
+    }
+
+    let needle = text.to_lowercase();
+    let contains =
+        |value: Option<&str>| value.is_some_and(|value| value.to_lowercase().contains(&needle));
+    let key = match search_in {
+        'T' => "Title",
+        'U' => "UserName",
+        'P' => "Password",
+        'A' => "URL",
+        'N' => "Notes",
+        'O' => {
+            return find_first(root, &|entry| {
+                entry
+                    .fields
+                    .keys()
+                    .filter(|key| !is_standard_entry_field(key))
+                    .any(|key| contains(entry.get(key)))
+            })
+        }
+        _ => return None,
+    };
+    find_first(root, &|entry| contains(entry.get(key)))
+}
+
+/// Depth-first search visiting a group's entries before its subgroups.
+fn find_first<'a>(
+    group: &'a KeepassGroup,
+    predicate: &dyn Fn(&KeepassEntry) -> bool,
+) -> Option<&'a KeepassEntry> {
+    let entries = group.children.iter().filter_map(|node| match node {
+        Node::Entry(entry) => Some(entry),
+        Node::Group(_) => None,
+    });
+    for entry in entries {
+        if predicate(entry) {
+            return Some(entry);
+        }
+    }
+
+    group.children.iter().find_map(|node| match node {
+        Node::Group(child) => find_first(child, predicate),
+        Node::Entry(_) => None,
+    })
+}
+
+/// UUID in the 32-digit uppercase hex form used inside references.
+fn uuid_hex(uuid: &Uuid) -> String {
+    uuid.simple().to_string().to_uppercase()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, username: &str, password: &str) -> KeepassEntry {
+        let mut entry = KeepassEntry::new();
+        entry
+            .fields
+            .insert("Title".into(), Value::Unprotected(title.into()));
+        entry
+            .fields
+            .insert("UserName".into(), Value::Unprotected(username.into()));
+        entry.fields.insert(
+            "Password".into(),
+            Value::Protected(SecStr::new(password.as_bytes().to_vec())),
+        );
+        entry
+    }
+
+    fn root_with(entries: Vec<KeepassEntry>) -> KeepassGroup {
+        let mut root = KeepassGroup::new("Root");
+        for entry in entries {
+            root.add_child(entry);
+        }
+        root
+    }
+
+    fn get(root: &KeepassGroup, index: usize) -> &KeepassEntry {
+        match &root.children[index] {
+            Node::Entry(entry) => Some(entry),
+            Node::Group(_) => None,
+        }
+        .expect("entry")
+    }
+
+    #[test]
+    fn test_resolves_reference_by_uuid() {
+        let source = entry("Source", "alice", "s3cret");
+        let reference = format!("{{REF:P@I:{}}}", uuid_hex(&source.uuid));
+        let root = root_with(vec![source, entry("Linked", "bob", &reference)]);
+
+        let resolved = resolve_field(&root, get(&root, 1), "Password").expect("resolve");
+        assert_eq!(resolved, "s3cret");
+    }
+
+    #[test]
+    fn test_resolves_text_search_case_insensitively() {
+        let root = root_with(vec![
+            entry("Mail Server", "alice", "pw"),
+            entry("Linked", "{ref:u@t:mail}", "x"),
+        ]);
+        let resolved = resolve_field(&root, get(&root, 1), "UserName").expect("resolve");
+        assert_eq!(resolved, "alice");
+    }
+
+    #[test]
+    fn test_resolves_other_field_search_and_surrounding_text() {
+        let mut source = entry("Source", "alice", "pw");
+        source
+            .fields
+            .insert("Employee".into(), Value::Unprotected("E-1234".into()));
+        let root = root_with(vec![
+            source,
+            entry("Linked", "user: {REF:U@O:e-12} / {REF:T@O:e-12}", "x"),
+        ]);
+
+        let resolved = resolve_field(&root, get(&root, 1), "UserName").expect("resolve");
+        assert_eq!(resolved, "user: alice / Source");
+    }
+
+    #[test]
+    fn test_nested_references_and_uuid_field() {
+        let source = entry("Source", "alice", "pw");
+        let middle = entry(
+            "Middle",
+            &format!("{{REF:U@I:{}}}", uuid_hex(&source.uuid)),
+            "x",
+        );
+        let outer = entry(
+            "Outer",
+            &format!("{{REF:U@I:{}}}", uuid_hex(&middle.uuid)),
+            "{REF:I@T:Source}",
+        );
+        let source_hex = uuid_hex(&source.uuid);
+        let root = root_with(vec![source, middle, outer]);
+
+        let outer = get(&root, 2);
+        assert_eq!(
+            resolve_field(&root, outer, "UserName").expect("resolve"),
+            "alice"
+        );
+        assert_eq!(
+            resolve_field(&root, outer, "Password").expect("resolve"),
+            source_hex
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_references_are_kept() {
+        let root = root_with(vec![entry(
+            "Linked",
+            "{REF:U@T:nothing} {REF:bad} {REF:U@T:",
+            "x",
+        )]);
+        let resolved = resolve_field(&root, get(&root, 0), "UserName").expect("resolve");
+        assert_eq!(resolved, "{REF:U@T:nothing} {REF:bad} {REF:U@T:");
+    }
+
+    #[test]
+    fn test_reference_cycle_is_detected() {
+        let mut first = entry("First", "", "x");
+        let mut second = entry("Second", "", "x");
+        first.fields.insert(
+            "UserName".into(),
+            Value::Unprotected(format!("{{REF:U@I:{}}}", uuid_hex(&second.uuid))),
+        );
+        second.fields.insert(
+            "UserName".into(),
+            Value::Unprotected(format!("{{REF:U@I:{}}}", uuid_hex(&first.uuid))),
+        );
+        let root = root_with(vec![first, second]);
+
+        let result = resolve_field(&root, get(&root, 0), "UserName");
+        assert!(matches!(result, Err(AppError::FieldReference(_))));
+    }
+
+    #[test]
+    fn test_reference_depth_is_limited() {
+        let mut entries = vec![entry("E0", "end", "x")];
+        for i in 1..=MAX_REFERENCE_DEPTH + 1 {
+            let previous = uuid_hex(&entries[i - 1].uuid);
+            entries.push(entry(
+                &format!("E{i}"),
+                &format!("{{REF:U@I:{previous}}}"),
+                "x",
+            ));
+        }
+        let root = root_with(entries);
+
+        let shallow = resolve_field(&root, get(&root, MAX_REFERENCE_DEPTH - 1), "UserName");
+        assert_eq!(shallow.expect("resolve"), "end");
+        let deep = resolve_field(&root, get(&root, MAX_REFERENCE_DEPTH + 1), "UserName");
+        assert!(matches!(deep, Err(AppError::FieldReference(_))));
+    }
+}
@@ -1,24 +1,29 @@
 use crate::domain::secure::SecureString;
+use crate::dto::database::SaveResult;
 use crate::dto::error::AppError;
+use crate::dto::keeshare::ShareSyncStatus;
 use crate::services::file_lock::FileLockService;
-use crate::services::kdbx::key::build_database_key;
+use crate::services::kdbx::keeshare::{ensure_signing_key, export_shares};
+use crate::services::kdbx::key::{build_database_key, open_database_key};
+use crate::services::kdbx::recycle_bin::purge_expired_items;
 use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
 
-use super::KdbxService;
+use super::{writable, KdbxService};
 
 impl KdbxService {
-    /// Saves the open database.
-    pub fn save(&self) -> Result<(), AppError> {
+    /// Saves the open database, then writes its exporting shares.
+    ///
+    /// A share that cannot be written does not fail the save; it is
+    /// reported in [`SaveResult::share_failures`].
+    pub fn save(&self) -> Result<SaveResult, AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
-
-        if open_db.password.is_none() && open_db.keyfile_path.is_none() {
-            return Err(AppError::NoCredentials);
-        }
+        let open_db = writable(&mut db_lock)?;
 
+        let key = open_database_key(open_db)?;
         let path = open_db.path.clone();
-        let password = open_db.password.clone();
-        let keyfile_path = open_db.keyfile_path.clone();
+
+        purge_expired_items(&mut open_db.db);
+        ensure_signing_key(&mut open_db.db)?;
 
         atomic_write(
             &path,
@@ -26,10 +31,6 @@ impl KdbxService {
                 preserve_permissions: true,
             },
             |file| {
-                let key = build_database_key(
-                    password.as_ref().map(SecureString::as_str),
-                    keyfile_path.as_deref(),
-                )?;
                 open_db
                     .db
                     .save(file, key)
@@ -38,23 +39,27 @@ impl KdbxService {
         )?;
 
         open_db.is_modified = false;
-        Ok(())
+
+        let share_failures = export_shares(&open_db.db, &path)
+            .into_iter()
+            .filter(|result| result.status == ShareSyncStatus::Failed)
+            .collect();
+
+        Ok(SaveResult { share_failures })
     }
 
     /// Saves the database to a new path.
     pub fn save_as(&self, new_path: &str, new_password: Option<&str>) -> Result<(), AppError> {
         let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
-        let open_db = db_lock.as_mut().ok_or(AppError::DatabaseNotOpen)?;
+        let open_db = writable(&mut db_lock)?;
 
-        let effective_password: Option<SecureString> = new_password
-            .map(SecureString::from)
-            .or_else(|| open_db.password.clone());
-
-        if effective_password.is_none() && open_db.keyfile_path.is_none() {
-            return Err(AppError::NoCredentials);
-        }
+        // A new password replaces the composite key of a quick unlock
+        let key = match new_password {
+            Some(password) => build_database_key(Some(password), open_db.keyfile_path.as_deref())?,
+            None => open_database_key(open_db)?,
+        };
 
-        let keyfile_path = open_db.keyfile_path.clone();
+        purge_expired_items(&mut open_db.db);
 
         let new_lock = FileLockService::try_acquire_lock_allow_missing(new_path)?;
 
@@ -64,10 +69,6 @@ impl KdbxService {
                 preserve_permissions: false,
             },
             |file| {
-                let key = build_database_key(
-                    effective_password.as_ref().map(SecureString::as_str),
-                    keyfile_path.as_deref(),
-                )?;
                 open_db
                     .db
                     .save(file, key)
@@ -80,6 +81,7 @@ impl KdbxService {
         open_db.path = new_path.to_string();
         if new_password.is_some() {
             open_db.password = new_password.map(SecureString::from);
+            open_db.composite_key = None;
         }
         open_db.is_modified = false;
 
@@ -0,0 +1,99 @@
+use crate::dto::entry::Entry;
+use crate::dto::error::AppError;
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup};
+
+use super::entries::find_entry_by_id_ref;
+use super::health::collect_entries;
+use super::mapping::{find_group_by_id, is_standard_entry_field, recycle_bin_id};
+use super::references::{convert_entry_resolved, resolve_field};
+use super::KdbxService;
+
+impl KdbxService {
+    /// Finds entries whose title, username, URL, notes or tags contain
+    /// `query`, ignoring case. Entries in the recycle bin are skipped.
+    ///
+    /// With `within`, only entries in those groups or their subgroups are
+    /// searched.
+    pub fn search_entries(
+        &self,
+        query: &str,
+        within: Option<&[String]>,
+    ) -> Result<Vec<Entry>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root = &open_db.db.root;
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut candidates = Vec::new();
+        match within {
+            Some(group_ids) => {
+                for group in group_ids.iter().filter_map(|id| find_group_by_id(root, id)) {
+                    collect_entries(group, bin_id.as_deref(), &mut candidates);
+                }
+                // Nested scopes would otherwise list entries twice
+                let mut seen = std::collections::HashSet::new();
+                candidates.retain(|(_, entry)| seen.insert(entry.uuid));
+            }
+            None => collect_entries(root, bin_id.as_deref(), &mut candidates),
+        }
+
+        let query = query.to_lowercase();
+        let mut entries: Vec<Entry> = candidates
+            .into_iter()
+            .filter(|(_, entry)| matches_query(root, entry, &query))
+            .map(|(group_id, entry)| convert_entry_resolved(root, entry, &group_id))
+            .collect();
+        entries.sort_by(|a, b| {
+            a.title
+                .to_lowercase()
+                .cmp(&b.title.to_lowercase())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        Ok(entries)
+    }
+
+    /// Reads any field of an entry, standard or custom, protected or not,
+    /// with field references resolved.
+    pub fn get_entry_field(&self, entry_id: &str, field: &str) -> Result<String, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root = &open_db.db.root;
+        let entry = find_entry_by_id_ref(root, entry_id)
+            .ok_or_else(|| AppError::EntryNotFound(entry_id.to_string()))?;
+        if !is_standard_entry_field(field) && !entry.fields.contains_key(field) {
+            return Err(AppError::CustomFieldNotFound(field.to_string()));
+        }
+        resolve_field(root, entry, field)
+    }
+
+    /// Whether an entry lies in one of `group_ids` or their subgroups.
+    pub fn entry_in_groups(&self, entry_id: &str, group_ids: &[String]) -> Result<bool, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root = &open_db.db.root;
+        if find_entry_by_id_ref(root, entry_id).is_none() {
+            return Err(AppError::EntryNotFound(entry_id.to_string()));
+        }
+        Ok(group_ids
+            .iter()
+            .filter_map(|id| find_group_by_id(root, id))
+            .any(|group| find_entry_by_id_ref(group, entry_id).is_some()))
+    }
+}
+
+fn matches_query(root: &KeepassGroup, entry: &KeepassEntry, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let field_matches = ["Title", "UserName", "URL", "Notes"].iter().any(|key| {
+        resolve_field(root, entry, key).is_ok_and(|value| value.to_lowercase().contains(query))
+    });
+    field_matches
+        || entry
+            .tags
+            .iter()
+            .any(|tag| tag.to_lowercase().contains(query))
+}
@@ -0,0 +1,307 @@
+//! Storage for the Freedesktop Secret Service provider.
+//!
+//! Items are the entries directly inside one designated group: the title is
+//! the label, the password the secret and the unprotected custom fields the
+//! lookup attributes. The designation is kept in the database's custom data
+//! so it travels with the file.
+
+use chrono::NaiveDateTime;
+use keepass::db::{
+    CustomDataItem, Entry as KeepassEntry, Group as KeepassGroup, Node, Times, Value,
+};
+use secstr::SecStr;
+use std::collections::BTreeMap;
+
+use super::entries::find_entry_by_id_mut;
+use super::mapping::{find_group_by_id, find_group_by_id_mut, is_standard_entry_field};
+use super::references::resolve_field;
+use super::{writable, KdbxService};
+use crate::domain::kdbx::OpenDatabase;
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::secret_service::{SecretCollection, SecretItem, UpdateSecretItem};
+
+/// Database `CustomData` key holding the UUID of the designated group.
+const GROUP_KEY: &str = "MithrilVault.SecretService.Group";
+
+/// Group created when an item is stored before a group was designated.
+const DEFAULT_GROUP_NAME: &str = "Secret Service";
+
+/// Entry field holding the content type of secrets that are not plain text.
+/// Named as in `KeePassXC` so items keep their type across both apps.
+pub const CONTENT_TYPE_FIELD: &str = "FDO_SECRETS_CONTENT_TYPE";
+
+const DEFAULT_CONTENT_TYPE: &str = "text/plain";
+
+impl KdbxService {
+    /// The collection backing the Secret Service. Before a group is
+    /// designated it is empty and named after the default group.
+    pub fn secret_service_collection(&self) -> Result<SecretCollection, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        Ok(match designated_group(open_db) {
+            Some(group) => SecretCollection {
+                group_id: Some(group.uuid.to_string()),
+                label: group.name.clone(),
+                created: unix_seconds(group.times.get_creation()),
+                modified: unix_seconds(group.times.get_last_modification()),
+            },
+            None => SecretCollection {
+                group_id: None,
+                label: DEFAULT_GROUP_NAME.to_string(),
+                created: 0,
+                modified: 0,
+            },
+        })
+    }
+
+    /// Designates the group backing the Secret Service, or clears the
+    /// designation so the next stored item creates the default group.
+    pub fn set_secret_service_group(&self, group_id: Option<&str>) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let items = &mut open_db.db.meta.custom_data.items;
+        match group_id {
+            Some(id) => {
+                if find_group_by_id(&open_db.db.root, id).is_none() {
+                    return Err(AppError::GroupNotFound(id.to_string()));
+                }
+                items.insert(
+                    GROUP_KEY.to_string(),
+                    CustomDataItem {
+                        value: Some(Value::Unprotected(id.to_string())),
+                        last_modification_time: Some(Times::now()),
+                    },
+                );
+            }
+            None => {
+                items.remove(GROUP_KEY);
+            }
+        }
+        open_db.is_modified = true;
+
+        Ok(())
+    }
+
+    /// Items of the designated group, in database order.
+    pub fn list_secret_items(&self) -> Result<Vec<SecretItem>, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        Ok(designated_group(open_db)
+            .map(|group| {
+                group
+                    .children
+                    .iter()
+                    .filter_map(|node| match node {
+                        Node::Entry(entry) => Some(convert_item(entry)),
+                        Node::Group(_) => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Fetches an item; entries outside the designated group are not items.
+    pub fn get_secret_item(&self, id: &str) -> Result<SecretItem, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        find_item(open_db, id).map(convert_item)
+    }
+
+    /// The secret of an item, with field references resolved.
+    pub fn get_secret_item_value(&self, id: &str) -> Result<SecureString, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let entry = find_item(open_db, id)?;
+        resolve_field(&open_db.db.root, entry, "Password").map(SecureString::new)
+    }
+
+    /// Stores a new item in the designated group, designating the default
+    /// group first if needed.
+    pub fn create_secret_item(
+        &self,
+        label: &str,
+        attributes: &BTreeMap<String, String>,
+        secret: &SecureString,
+        content_type: &str,
+    ) -> Result<SecretItem, AppError> {
+        let group_id = self.secret_service_group_for_write()?;
+
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let group = find_group_by_id_mut(&mut open_db.db.root, &group_id)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.clone()))?;
+        let mut entry = KeepassEntry::new();
+        entry
+            .fields
+            .insert("Title".to_string(), Value::Unprotected(label.to_string()));
+        entry
+            .fields
+            .insert("UserName".to_string(), Value::Unprotected(String::new()));
+        set_secret(&mut entry, secret, content_type);
+        set_attributes(&mut entry, attributes);
+
+        let item = convert_item(&entry);
+        group.add_child(entry);
+        open_db.is_modified = true;
+
+        Ok(item)
+    }
+
+    /// Changes the label, attributes or secret of an item.
+    pub fn update_secret_item(
+        &self,
+        id: &str,
+        update: UpdateSecretItem,
+    ) -> Result<SecretItem, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let group_id = designated_group(open_db).map(|group| group.uuid.to_string());
+        let (entry, _) = find_entry_by_id_mut(&mut open_db.db.root, id)
+            .filter(|(_, parent_id)| Some(parent_id) == group_id.as_ref())
+            .ok_or_else(|| AppError::EntryNotFound(id.to_string()))?;
+
+        if let Some(label) = update.label {
+            entry
+                .fields
+                .insert("Title".to_string(), Value::Unprotected(label));
+        }
+        if let Some(attributes) = &update.attributes {
+            set_attributes(entry, attributes);
+        }
+        if let Some(secret) = &update.secret {
+            let content_type = update
+                .content_type
+                .as_deref()
+                .unwrap_or(DEFAULT_CONTENT_TYPE);
+            set_secret(entry, secret, content_type);
+        }
+        entry.times.set_last_modification(Times::now());
+        let item = convert_item(entry);
+        open_db.is_modified = true;
+
+        Ok(item)
+    }
+
+    /// Renames the designated group, designating the default group first if
+    /// needed.
+    pub fn set_secret_service_label(&self, label: &str) -> Result<(), AppError> {
+        let group_id = self.secret_service_group_for_write()?;
+
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = writable(&mut db_lock)?;
+
+        let group = find_group_by_id_mut(&mut open_db.db.root, &group_id)
+            .ok_or_else(|| AppError::GroupNotFound(group_id.clone()))?;
+        group.name = label.to_string();
+        group.times.set_last_modification(Times::now());
+        open_db.is_modified = true;
+
+        Ok(())
+    }
+
+    /// ID of the designated group, creating and designating the default
+    /// group below the root if there is none.
+    fn secret_service_group_for_write(&self) -> Result<String, AppError> {
+        {
+            let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+            let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+            if let Some(group) = designated_group(open_db) {
+                return Ok(group.uuid.to_string());
+            }
+        }
+        let group_id = self.create_group(None, DEFAULT_GROUP_NAME, None)?.id;
+        self.set_secret_service_group(Some(&group_id))?;
+        Ok(group_id)
+    }
+}
+
+/// The designated group, if it is set and still exists.
+fn designated_group(open_db: &OpenDatabase) -> Option<&KeepassGroup> {
+    let item = open_db.db.meta.custom_data.items.get(GROUP_KEY)?;
+    let Some(Value::Unprotected(group_id)) = &item.value else {
+        return None;
+    };
+    find_group_by_id(&open_db.db.root, group_id)
+}
+
+/// An entry directly inside the designated group.
+fn find_item<'a>(open_db: &'a OpenDatabase, id: &str) -> Result<&'a KeepassEntry, AppError> {
+    designated_group(open_db)
+        .and_then(|group| {
+            group.children.iter().find_map(|node| match node {
+                Node::Entry(entry) if entry.uuid.to_string() == id => Some(entry),
+                _ => None,
+            })
+        })
+        .ok_or_else(|| AppError::EntryNotFound(id.to_string()))
+}
+
+fn convert_item(entry: &KeepassEntry) -> SecretItem {
+    let attributes = entry
+        .fields
+        .iter()
+        .filter(|(key, _)| !is_standard_entry_field(key) && *key != CONTENT_TYPE_FIELD)
+        .filter_map(|(key, value)| match value {
+            Value::Unprotected(text) => Some((key.clone(), text.clone())),
+            _ => None,
+        })
+        .collect();
+
+    SecretItem {
+        id: entry.uuid.to_string(),
+        label: entry.get_title().unwrap_or_default().to_string(),
+        attributes,
+        content_type: entry
+            .get(CONTENT_TYPE_FIELD)
+            .unwrap_or(DEFAULT_CONTENT_TYPE)
+            .to_string(),
+        created: unix_seconds(entry.times.get_creation()),
+        modified: unix_seconds(entry.times.get_last_modification()),
+    }
+}
+
+/// Replaces the unprotected custom fields of `entry` with `attributes`.
+/// Protected fields and the content type are kept.
+fn set_attributes(entry: &mut KeepassEntry, attributes: &BTreeMap<String, String>) {
+    entry.fields.retain(|key, value| {
+        is_standard_entry_field(key)
+            || key == CONTENT_TYPE_FIELD
+            || !matches!(value, Value::Unprotected(_))
+    });
+    for (key, value) in attributes {
+        if is_standard_entry_field(key) || key == CONTENT_TYPE_FIELD {
+            continue;
+        }
+        entry
+            .fields
+            .insert(key.clone(), Value::Unprotected(value.clone()));
+    }
+}
+
+fn set_secret(entry: &mut KeepassEntry, secret: &SecureString, content_type: &str) {
+    entry.fields.insert(
+        "Password".to_string(),
+        Value::Protected(SecStr::new(secret.as_str().as_bytes().to_vec())),
+    );
+    if content_type.is_empty() || content_type == DEFAULT_CONTENT_TYPE {
+        entry.fields.remove(CONTENT_TYPE_FIELD);
+    } else {
+        entry.fields.insert(
+            CONTENT_TYPE_FIELD.to_string(),
+            Value::Unprotected(content_type.to_string()),
+        );
+    }
+}
+
+fn unix_seconds(time: Option<&NaiveDateTime>) -> u64 {
+    time.and_then(|time| u64::try_from(time.and_utc().timestamp()).ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,214 @@
+//! Session locking.
+//!
+//! Locking drops the decrypted database but keeps its file lock, so the
+//! file stays reserved for this process. Unlocking decrypts the file again
+//! with the password and the keyfile the database was opened with, or with
+//! the composite key kept for quick unlock (see `services::quick_unlock`).
+//!
+//! Locking never writes the file. Unsaved changes are kept encrypted under
+//! the database key and come back, still unsaved, on unlock.
+
+use std::fs::File;
+use std::io::Read;
+
+use keepass::{Database, DatabaseKey};
+use zeroize::Zeroizing;
+
+use crate::domain::kdbx::{LockedDatabase, OpenDatabase};
+use crate::domain::secure::SecureString;
+use crate::dto::database::DatabaseInfo;
+use crate::dto::error::AppError;
+use crate::services::kdbx::keeshare::import_shares;
+use crate::services::kdbx::key::{open_database_key, CompositeKey, DatabaseCredentials};
+use crate::services::kdbx::open::map_open_error;
+
+use super::KdbxService;
+
+impl KdbxService {
+    /// Locks the open database. The decrypted data is always dropped.
+    ///
+    /// If the unsaved changes cannot be encrypted, the session is still
+    /// locked, the changes are lost and the error is returned.
+    pub fn lock(&self) -> Result<(), AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let mut locked = self.locked.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.take().ok_or(AppError::DatabaseNotOpen)?;
+
+        let (unsaved, result) = if open_db.is_modified {
+            match encrypt_unsaved(&open_db) {
+                Ok(snapshot) => (Some(snapshot), Ok(())),
+                Err(e) => (
+                    None,
+                    Err(AppError::Kdbx(format!(
+                        "Database locked, but its unsaved changes were lost: {e}"
+                    ))),
+                ),
+            }
+        } else {
+            (None, Ok(()))
+        };
+
+        *locked = Some(LockedDatabase {
+            name: open_db.db.root.name.clone(),
+            root_group_id: open_db.db.root.uuid.to_string(),
+            path: open_db.path,
+            keyfile_path: open_db.keyfile_path,
+            version: open_db.version,
+            file_lock: open_db.file_lock,
+            read_only: open_db.read_only,
+            unsaved,
+        });
+        result
+    }
+
+    /// Returns true while the session is locked.
+    pub fn is_locked(&self) -> Result<bool, AppError> {
+        let locked = self.locked.lock().map_err(|_| AppError::Lock)?;
+        Ok(locked.is_some())
+    }
+
+    /// Returns metadata for the locked database, or `None` unless the
+    /// session is locked.
+    pub fn locked_info(&self) -> Result<Option<DatabaseInfo>, AppError> {
+        let locked = self.locked.lock().map_err(|_| AppError::Lock)?;
+
+        Ok(locked.as_ref().map(|locked_db| DatabaseInfo {
+            name: locked_db.name.clone(),
+            path: locked_db.path.clone(),
+            is_modified: locked_db.unsaved.is_some(),
+            is_locked: true,
+            is_read_only: locked_db.read_only,
+            root_group_id: locked_db.root_group_id.clone(),
+            version: locked_db.version.clone(),
+        }))
+    }
+
+    /// Unlocks the locked database with its password. The keyfile it was
+    /// opened with, if any, is read again.
+    pub fn unlock(&self, password: Option<&str>) -> Result<DatabaseInfo, AppError> {
+        let keyfile_path = {
+            let locked = self.locked.lock().map_err(|_| AppError::Lock)?;
+            let locked_db = locked.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+            locked_db.keyfile_path.clone()
+        };
+
+        self.unlock_with_credentials(&DatabaseCredentials {
+            password: password.map(SecureString::from),
+            keyfile: keyfile_path.as_deref().map(read_keyfile).transpose()?,
+        })
+    }
+
+    /// Unlocks the locked database with `credentials`. Changes left unsaved
+    /// when it was locked are restored, not the file.
+    pub fn unlock_with_credentials(
+        &self,
+        credentials: &DatabaseCredentials,
+    ) -> Result<DatabaseInfo, AppError> {
+        if credentials.password.is_none() && credentials.keyfile.is_none() {
+            return Err(AppError::NoCredentials);
+        }
+        self.unlock_with(credentials.to_key()?, credentials.password.clone(), None)
+    }
+
+    /// Unlocks the locked database with the composite key kept for quick
+    /// unlock. The password stays unknown until the database is closed.
+    pub fn unlock_with_composite_key(
+        &self,
+        composite_key: &CompositeKey,
+    ) -> Result<DatabaseInfo, AppError> {
+        self.unlock_with(composite_key.to_key()?, None, Some(composite_key.clone()))
+    }
+
+    fn unlock_with(
+        &self,
+        key: DatabaseKey,
+        password: Option<SecureString>,
+        composite_key: Option<CompositeKey>,
+    ) -> Result<DatabaseInfo, AppError> {
+        let mut db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let mut locked = self.locked.lock().map_err(|_| AppError::Lock)?;
+        let locked_db = locked.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let decrypt = || {
+            if let Some(snapshot) = &locked_db.unsaved {
+                Database::open(&mut snapshot.as_slice(), key)
+            } else {
+                let mut file = File::open(&locked_db.path)
+                    .map_err(|e| AppError::InvalidPath(e.to_string()))?;
+                Database::open(&mut file, key)
+            }
+            .map_err(map_open_error)
+        };
+        // A PIN has its own attempt counter
+        let mut db = if composite_key.is_some() {
+            decrypt()
+        } else {
+            self.throttled(&locked_db.path, decrypt)
+        }?;
+        let imported = !locked_db.read_only && import_shares(&mut db, &locked_db.path).1;
+        let is_modified = locked_db.unsaved.is_some() || imported;
+
+        let locked_db = locked.take().ok_or(AppError::DatabaseNotOpen)?;
+        let info = DatabaseInfo {
+            name: db.root.name.clone(),
+            path: locked_db.path.clone(),
+            is_modified,
+            is_locked: false,
+            is_read_only: locked_db.read_only,
+            root_group_id: db.root.uuid.to_string(),
+            version: locked_db.version.clone(),
+        };
+
+        *db_lock = Some(OpenDatabase {
+            db,
+            path: locked_db.path,
+            is_modified,
+            password,
+            keyfile_path: locked_db.keyfile_path,
+            composite_key,
+            version: locked_db.version,
+            file_lock: locked_db.file_lock,
+            read_only: locked_db.read_only,
+        });
+        Ok(info)
+    }
+
+    /// Returns the composite key of the open database. The keyfile is read
+    /// from disk.
+    pub fn composite_key(&self) -> Result<CompositeKey, AppError> {
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        if let Some(composite_key) = &open_db.composite_key {
+            return Ok(composite_key.clone());
+        }
+        let keyfile = open_db
+            .keyfile_path
+            .as_deref()
+            .map(read_keyfile)
+            .transpose()?;
+        CompositeKey::derive(
+            open_db.password.as_ref().map(SecureString::as_str),
+            keyfile.as_deref().map(Vec::as_slice),
+        )
+    }
+}
+
+/// Encrypts the open database as KDBX under its own key.
+fn encrypt_unsaved(open_db: &OpenDatabase) -> Result<Vec<u8>, AppError> {
+    let key = open_database_key(open_db)?;
+    let mut snapshot = Vec::new();
+    open_db
+        .db
+        .save(&mut snapshot, key)
+        .map_err(|e| AppError::Kdbx(e.to_string()))?;
+    Ok(snapshot)
+}
+
+fn read_keyfile(path: &str) -> Result<Zeroizing<Vec<u8>>, AppError> {
+    let mut contents = Zeroizing::new(Vec::new());
+    File::open(path)
+        .map_err(|_| AppError::KeyfileNotFound)?
+        .read_to_end(&mut contents)?;
+    Ok(contents)
+}
@@ -0,0 +1,96 @@
+//! Rendering config file templates with values from the database.
+//!
+//! `{{ vault "Group/Entry" "Field" }}` is replaced by a field of the entry
+//! at that path or with that UUID. Any standard or custom field can be
+//! used, protected or not, and field references are resolved. Arguments
+//! are double-quoted; `\"` and `\\` escape quotes and backslashes.
+//!
+//! Other `{{ ... }}` tags are left untouched, so templates meant for
+//! another engine pass through. Every `vault` tag must resolve: a missing
+//! entry or field, or a `{{` without its `}}`, fails the whole render rather
+//! than producing a partial file.
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+
+use super::KdbxService;
+
+const TAG_OPEN: &str = "{{";
+const TAG_CLOSE: &str = "}}";
+const FUNCTION: &str = "vault";
+
+impl KdbxService {
+    /// Renders `template`, failing on the first tag that does not resolve.
+    /// Errors name the line of the tag.
+    pub fn render_template(&self, template: &str) -> Result<SecureString, AppError> {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find(TAG_OPEN) {
+            output.push_str(&rest[..start]);
+            let tag = &rest[start..];
+            let line = template[..template.len() - tag.len()].matches('\n').count() + 1;
+            let Some(end) = tag.find(TAG_CLOSE) else {
+                return Err(AppError::Template(format!("line {line}: unterminated tag")));
+            };
+            let whole = &tag[..end + TAG_CLOSE.len()];
+            rest = &tag[end + TAG_CLOSE.len()..];
+
+            match parse_tag(&tag[TAG_OPEN.len()..end]) {
+                None => output.push_str(whole),
+                Some(Ok([reference, field])) => {
+                    let value = self
+                        .resolve_entry(&reference)
+                        .and_then(|entry| self.get_entry_field(&entry.id, &field))
+                        .map_err(|e| AppError::Template(format!("line {line}: {e}")))?;
+                    output.push_str(&value);
+                }
+                Some(Err(message)) => {
+                    return Err(AppError::Template(format!("line {line}: {message}")));
+                }
+            }
+        }
+        output.push_str(rest);
+
+        Ok(SecureString::from(output))
+    }
+}
+
+/// Parses the inside of a tag. Returns `None` for tags that are not `vault`
+/// calls, or the entry and field arguments.
+fn parse_tag(inner: &str) -> Option<Result<[String; 2], String>> {
+    let arguments = inner.trim().strip_prefix(FUNCTION)?;
+    if !arguments.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut values = Vec::new();
+    let mut chars = arguments.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            None => break,
+            Some('"') => {}
+            Some(other) => return Some(Err(format!("Expected a quoted string, found {other}"))),
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                None => return Some(Err("Unterminated string".to_string())),
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some(escaped @ ('"' | '\\')) => value.push(escaped),
+                    _ => return Some(Err("Invalid escape in string".to_string())),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+        values.push(value);
+    }
+
+    Some(
+        values
+            .try_into()
+            .map_err(|_| format!("{FUNCTION} expects an entry and a field")),
+    )
+}
@@ -0,0 +1,336 @@
+use crate::domain::secure::SecureString;
+use crate::dto::entry::{CreateEntryData, Entry, UpdateEntryData};
+use crate::dto::error::AppError;
+use crate::dto::url_match::{UrlMatch, UrlMatchKind, UrlMatchOptions};
+use keepass::db::{Entry as KeepassEntry, Group as KeepassGroup, Times};
+use publicsuffix::{List, Psl};
+use std::sync::LazyLock;
+use url::{Host, Url};
+
+use super::health::collect_entries;
+use super::mapping::{is_expired, recycle_bin_id};
+use super::references::resolve_field;
+use super::KdbxService;
+
+/// Prefix of custom fields holding additional URLs, as written by
+/// `Keepass2Android` and `KeePassXC` (`KP2A_URL`, `KP2A_URL_1`, …).
+const ADDITIONAL_URL_PREFIX: &str = "KP2A_URL";
+
+/// The bundled public suffix list. It is compiled in, so a parse failure is
+/// a build defect; an empty list would silently treat `co.uk` as a
+/// registrable domain and match unrelated sites.
+#[allow(clippy::expect_used)]
+static PUBLIC_SUFFIXES: LazyLock<List> = LazyLock::new(|| {
+    List::from_bytes(include_bytes!("../../../resources/public_suffix_list.dat"))
+        .expect("bundled public suffix list is valid")
+});
+
+impl KdbxService {
+    /// Finds entries with a URL matching `url`, for autofill.
+    ///
+    /// Hosts match when they are equal or share a registrable domain
+    /// (`login.example.co.uk` matches `www.example.co.uk` but not
+    /// `other.co.uk`). Besides the URL field, `KP2A_URL*` custom fields are
+    /// checked. Entries in the recycle bin and expired entries are skipped.
+    /// Exact host matches come first, then entries are ordered by title.
+    pub fn find_entries_for_url(
+        &self,
+        url: &str,
+        options: &UrlMatchOptions,
+    ) -> Result<Vec<UrlMatch>, AppError> {
+        let target = parse_url(url).ok_or_else(|| AppError::InvalidUrl(url.to_string()))?;
+
+        let db_lock = self.database.lock().map_err(|_| AppError::Lock)?;
+        let open_db = db_lock.as_ref().ok_or(AppError::DatabaseNotOpen)?;
+
+        let root = &open_db.db.root;
+        let bin_id = recycle_bin_id(&open_db.db);
+        let mut candidates = Vec::new();
+        collect_entries(root, bin_id.as_deref(), &mut candidates);
+
+        let now = Times::now();
+        let mut matches: Vec<UrlMatch> = candidates
+            .into_iter()
+            .filter(|(_, entry)| !is_expired(&entry.times, now))
+            .filter_map(|(group_id, entry)| {
+                let (match_kind, matched_url) = entry_urls(root, entry)
+                    .into_iter()
+                    .filter_map(|candidate| {
+                        let kind = match_url(&target, &parse_url(&candidate)?, *options)?;
+                        Some((kind, candidate))
+                    })
+                    .min_by_key(|(kind, _)| *kind)?;
+                Some(UrlMatch {
+                    id: entry.uuid.to_string(),
+                    group_id,
+                    title: resolve_field(root, entry, "Title").unwrap_or_default(),
+                    username: resolve_field(root, entry, "UserName").unwrap_or_default(),
+                    matched_url,
+                    match_kind,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            a.match_kind
+                .cmp(&b.match_kind)
+                .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        Ok(matches)
+    }
+
+    /// Saves a login for `url`, as after a successful sign-in. An entry for
+    /// the same host and username gets the new password; otherwise a new
+    /// entry titled after the host is created. Entries that only share the
+    /// registrable domain are never changed, since they belong to other
+    /// services.
+    ///
+    /// With `within`, only entries in those groups or their subgroups are
+    /// updated, and new entries go into the first of them instead of the
+    /// root group.
+    pub fn store_url_credential(
+        &self,
+        url: &str,
+        username: &str,
+        password: &SecureString,
+        within: Option<&[String]>,
+    ) -> Result<Entry, AppError> {
+        let target = parse_url(url).ok_or_else(|| AppError::InvalidUrl(url.to_string()))?;
+
+        for candidate in self.find_entries_for_url(url, &UrlMatchOptions::default())? {
+            if candidate.match_kind != UrlMatchKind::ExactHost || candidate.username != username {
+                continue;
+            }
+            if let Some(groups) = within {
+                if !self.entry_in_groups(&candidate.id, groups)? {
+                    continue;
+                }
+            }
+            // Unchanged passwords leave the entry and its history alone
+            if self.get_entry_password(&candidate.id)? == password.as_str() {
+                return self.get_entry(&candidate.id);
+            }
+            return self.update_entry(
+                &candidate.id,
+                UpdateEntryData {
+                    title: None,
+                    username: None,
+                    password: Some(password.clone()),
+                    url: None,
+                    notes: None,
+                    icon_id: None,
+                    tags: None,
+                    custom_fields: None,
+                    protected_custom_fields: None,
+                    expires: None,
+                    expiry_time: None,
+                    auto_type: None,
+                },
+            );
+        }
+
+        let group_id = match within.and_then(<[String]>::first) {
+            Some(group_id) => group_id.clone(),
+            None => self.get_info()?.root_group_id,
+        };
+        self.create_entry(
+            &group_id,
+            CreateEntryData {
+                title: target.host_str().unwrap_or(url).to_string(),
+                username: username.to_string(),
+                password: password.clone(),
+                url: Some(url.to_string()),
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+    }
+}
+
+/// The URL field and additional URL fields of an entry, references resolved.
+fn entry_urls(root: &KeepassGroup, entry: &KeepassEntry) -> Vec<String> {
+    entry
+        .fields
+        .keys()
+        .filter(|key| *key == "URL" || key.starts_with(ADDITIONAL_URL_PREFIX))
+        .filter_map(|key| resolve_field(root, entry, key).ok())
+        .filter(|value| !value.trim().is_empty())
+        .collect()
+}
+
+/// Parses a URL, assuming `https` when no scheme is given. URLs without a
+/// host, such as `mailto:` links, are ignored.
+fn parse_url(value: &str) -> Option<Url> {
+    let value = value.trim();
+    let parsed = match Url::parse(value) {
+        Ok(url) if value.contains("://") => url,
+        Err(_) if value.contains("://") => return None,
+        // `example.com:8080` parses as scheme `example.com` with path `8080`
+        Ok(url) if !url.path().starts_with(|c: char| c.is_ascii_digit()) => url,
+        _ => Url::parse(&format!("https://{value}")).ok()?,
+    };
+    parsed.host().is_some().then_some(parsed)
+}
+
+fn match_url(target: &Url, candidate: &Url, options: UrlMatchOptions) -> Option<UrlMatchKind> {
+    if options.match_scheme && candidate.scheme() != target.scheme() {
+        return None;
+    }
+    if options.match_port && candidate.port_or_known_default() != target.port_or_known_default() {
+        return None;
+    }
+    if options.match_path && !path_matches(candidate.path(), target.path()) {
+        return None;
+    }
+
+    if candidate.host() == target.host() {
+        return Some(UrlMatchKind::ExactHost);
+    }
+    let domain = registrable_domain(candidate)?;
+    (registrable_domain(target)? == domain).then_some(UrlMatchKind::Domain)
+}
+
+/// Whether `prefix` is `path` or one of its parent paths.
+fn path_matches(prefix: &str, path: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The registrable domain of a URL's host; IP addresses have none.
+fn registrable_domain(url: &Url) -> Option<String> {
+    let Some(Host::Domain(host)) = url.host() else {
+        return None;
+    };
+    let domain = PUBLIC_SUFFIXES.domain(host.as_bytes())?;
+    std::str::from_utf8(domain.as_bytes())
+        .ok()
+        .map(std::string::ToString::to_string)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn kind(target: &str, candidate: &str, options: UrlMatchOptions) -> Option<UrlMatchKind> {
+        let target = parse_url(target).expect("target URL");
+        let candidate = parse_url(candidate).expect("candidate URL");
+        match_url(&target, &candidate, options)
+    }
+
+    #[test]
+    fn test_bundled_public_suffix_list_parses() {
+        assert!(PUBLIC_SUFFIXES
+            .suffix(b"example.co.uk")
+            .is_some_and(|s| s.is_known()));
+    }
+
+    #[test]
+    fn test_registrable_domain_uses_public_suffixes() {
+        let domain = |url: &str| registrable_domain(&parse_url(url).expect("URL"));
+
+        assert_eq!(
+            domain("https://www.example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            domain("https://login.example.co.uk").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            domain("alice.github.io").as_deref(),
+            Some("alice.github.io")
+        );
+        assert_eq!(domain("https://192.168.1.1"), None);
+    }
+
+    #[test]
+    fn test_hosts_match_by_registrable_domain() {
+        let loose = UrlMatchOptions::default();
+
+        assert_eq!(
+            kind("https://example.com/login", "example.com", loose),
+            Some(UrlMatchKind::ExactHost)
+        );
+        assert_eq!(
+            kind(
+                "https://login.example.com",
+                "https://www.example.com",
+                loose
+            ),
+            Some(UrlMatchKind::Domain)
+        );
+        assert_eq!(
+            kind("https://example.co.uk", "https://other.co.uk", loose),
+            None
+        );
+        assert_eq!(
+            kind("https://alice.github.io", "https://bob.github.io", loose),
+            None
+        );
+        assert_eq!(
+            kind("https://example.com", "https://example.org", loose),
+            None
+        );
+        assert_eq!(kind("https://10.0.0.1", "https://10.0.0.2", loose), None);
+    }
+
+    #[test]
+    fn test_strictness_options() {
+        let scheme = UrlMatchOptions {
+            match_scheme: true,
+            ..UrlMatchOptions::default()
+        };
+        assert_eq!(
+            kind("https://example.com", "http://example.com", scheme),
+            None
+        );
+        assert!(kind("https://example.com", "example.com", scheme).is_some());
+
+        let port = UrlMatchOptions {
+            match_port: true,
+            ..UrlMatchOptions::default()
+        };
+        assert_eq!(
+            kind("https://example.com:8443", "https://example.com", port),
+            None
+        );
+        assert!(kind("https://example.com:443", "https://example.com", port).is_some());
+
+        let path = UrlMatchOptions {
+            match_path: true,
+            ..UrlMatchOptions::default()
+        };
+        assert!(kind(
+            "https://example.com/app/login",
+            "https://example.com/app",
+            path
+        )
+        .is_some());
+        assert!(kind("https://example.com/app", "https://example.com/", path).is_some());
+        assert_eq!(
+            kind("https://example.com/apple", "https://example.com/app", path),
+            None
+        );
+        assert_eq!(
+            kind("https://example.com/", "https://example.com/app", path),
+            None
+        );
+    }
+
+    #[test]
+    fn test_urls_without_host_are_ignored() {
+        assert!(parse_url("mailto:alice@example.com").is_none());
+        assert!(parse_url("").is_none());
+
+        let with_port = parse_url("example.com:8080").expect("URL");
+        assert_eq!(with_port.host_str(), Some("example.com"));
+        assert_eq!(with_port.port(), Some(8080));
+    }
+}
@@ -1,8 +1,17 @@
 // SPDX-License-Identifier: MIT
 
+pub mod breach;
+pub mod browser;
 pub mod clipboard;
 pub mod crypto;
 pub mod file_lock;
+pub mod git_credential;
+pub mod ipc;
 pub mod kdbx;
+pub mod quick_unlock;
+pub mod secret_service;
 pub mod secure_storage;
 pub mod settings;
+pub mod share_watcher;
+pub mod ssh_agent;
+pub mod unlock_throttle;
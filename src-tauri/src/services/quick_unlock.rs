@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT
+
+//! Quick unlock: unlocking a locked database with a short PIN.
+//!
+//! After a full unlock the user may set a PIN. The composite key of the
+//! database is sealed with a key derived from the PIN, and only the sealed
+//! form is kept, in the Stronghold session store (`SecureStorageService`).
+//! The composite key opens this one database; the password is never sealed.
+//! The Stronghold snapshot is encrypted with a key generated at launch and
+//! deleted on the next launch, so nothing survives the app exiting.
+//!
+//! The PIN is wiped after [`MAX_PIN_ATTEMPTS`] wrong guesses in a row, when
+//! its time to live runs out, and when the database is closed. The full
+//! password is needed again after that.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crypto_secretbox::aead::{Aead, AeadCore, KeyInit, OsRng};
+use crypto_secretbox::{Key, Nonce, XSalsa20Poly1305};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::dto::database::DatabaseInfo;
+use crate::dto::error::AppError;
+use crate::dto::quick_unlock::QuickUnlockStatus;
+use crate::services::kdbx::key::CompositeKey;
+use crate::services::kdbx::KdbxService;
+
+/// Wrong PINs in a row before the sealed key is wiped.
+pub const MAX_PIN_ATTEMPTS: u32 = 3;
+
+pub const MIN_PIN_LENGTH: usize = 4;
+
+const DEFAULT_TTL_SECS: u64 = 8 * 60 * 60;
+/// Longest time a PIN can stay valid.
+pub const MAX_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
+// Argon2id with the OWASP recommended minimum. PINs have little entropy,
+// so the attempt counter is the real protection; this only slows down
+// guessing against a memory dump.
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+
+/// Where the sealed composite key is kept.
+pub trait SealedKeyStore: Send + Sync {
+    fn store_sealed(&self, sealed: &[u8], ttl: Duration) -> Result<(), AppError>;
+
+    fn load_sealed(&self) -> Result<Option<Vec<u8>>, AppError>;
+
+    fn clear_sealed(&self) -> Result<(), AppError>;
+}
+
+/// A PIN set for one database.
+struct Enrollment {
+    path: String,
+    salt: [u8; SALT_LENGTH],
+    expires_at: Instant,
+    failed_attempts: u32,
+}
+
+impl Enrollment {
+    fn attempts_remaining(&self) -> u32 {
+        MAX_PIN_ATTEMPTS.saturating_sub(self.failed_attempts)
+    }
+}
+
+pub struct QuickUnlockService {
+    kdbx: Arc<KdbxService>,
+    store: Arc<dyn SealedKeyStore>,
+    enrollment: Mutex<Option<Enrollment>>,
+}
+
+impl QuickUnlockService {
+    pub fn new(kdbx: Arc<KdbxService>, store: Arc<dyn SealedKeyStore>) -> Self {
+        Self {
+            kdbx,
+            store,
+            enrollment: Mutex::new(None),
+        }
+    }
+
+    /// Returns how long a PIN stays valid unless the caller says otherwise.
+    pub fn default_ttl() -> Duration {
+        Duration::from_secs(DEFAULT_TTL_SECS)
+    }
+
+    /// Sets `pin` for the open database, replacing an earlier PIN.
+    pub fn enable(&self, pin: &str, ttl: Duration) -> Result<(), AppError> {
+        if pin.chars().count() < MIN_PIN_LENGTH {
+            return Err(AppError::PinTooShort(MIN_PIN_LENGTH));
+        }
+        let expires_at = Instant::now().checked_add(ttl).ok_or_else(|| {
+            AppError::InvalidArguments("Quick unlock time to live is too long".to_string())
+        })?;
+        let path = self.kdbx.get_info()?.path;
+        let composite_key = self.kdbx.composite_key()?;
+
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let sealed = seal(&composite_key, pin, &salt)?;
+
+        let mut enrollment = self.enrollment.lock().map_err(|_| AppError::Lock)?;
+        self.store.store_sealed(&sealed, ttl)?;
+        *enrollment = Some(Enrollment {
+            path,
+            salt,
+            expires_at,
+            failed_attempts: 0,
+        });
+        Ok(())
+    }
+
+    /// Wipes the PIN, if any.
+    pub fn disable(&self) -> Result<(), AppError> {
+        let mut enrollment = self.enrollment.lock().map_err(|_| AppError::Lock)?;
+        self.wipe(&mut enrollment)
+    }
+
+    /// Returns whether a PIN is set. An expired PIN is wiped.
+    pub fn status(&self) -> Result<QuickUnlockStatus, AppError> {
+        let mut enrollment = self.enrollment.lock().map_err(|_| AppError::Lock)?;
+        if enrollment
+            .as_ref()
+            .is_some_and(|current| current.expires_at <= Instant::now())
+        {
+            self.wipe(&mut enrollment)?;
+        }
+
+        Ok(match enrollment.as_ref() {
+            Some(current) => QuickUnlockStatus {
+                enabled: true,
+                path: Some(current.path.clone()),
+                attempts_remaining: current.attempts_remaining(),
+                expires_in_secs: Some(
+                    current
+                        .expires_at
+                        .saturating_duration_since(Instant::now())
+                        .as_secs(),
+                ),
+            },
+            None => QuickUnlockStatus {
+                enabled: false,
+                path: None,
+                attempts_remaining: 0,
+                expires_in_secs: None,
+            },
+        })
+    }
+
+    /// Unlocks the locked database with `pin`.
+    ///
+    /// A wrong PIN fails with [`AppError::IncorrectPin`]; the last allowed
+    /// attempt also wipes the PIN. A key that no longer opens the database,
+    /// because its credentials were changed elsewhere, is wiped too.
+    pub fn unlock(&self, pin: &str) -> Result<DatabaseInfo, AppError> {
+        let mut enrollment = self.enrollment.lock().map_err(|_| AppError::Lock)?;
+        let locked = self.kdbx.locked_info()?.ok_or(AppError::DatabaseNotOpen)?;
+
+        let Some(current) = enrollment
+            .as_mut()
+            .filter(|current| current.path == locked.path && current.expires_at > Instant::now())
+        else {
+            self.wipe(&mut enrollment)?;
+            return Err(AppError::QuickUnlockUnavailable);
+        };
+        // The store applies the same time to live, and may expire first
+        let Some(sealed) = self.store.load_sealed()? else {
+            self.wipe(&mut enrollment)?;
+            return Err(AppError::QuickUnlockUnavailable);
+        };
+
+        let composite_key = match unseal(&sealed, pin, &current.salt) {
+            Ok(composite_key) => composite_key,
+            Err(AppError::IncorrectPin(_)) => {
+                current.failed_attempts += 1;
+                let remaining = current.attempts_remaining();
+                if remaining == 0 {
+                    self.wipe(&mut enrollment)?;
+                }
+                return Err(AppError::IncorrectPin(remaining));
+            }
+            Err(err) => return Err(err),
+        };
+        current.failed_attempts = 0;
+
+        match self.kdbx.unlock_with_composite_key(&composite_key) {
+            Err(AppError::InvalidPassword) => {
+                self.wipe(&mut enrollment)?;
+                Err(AppError::QuickUnlockUnavailable)
+            }
+            result => result,
+        }
+    }
+
+    fn wipe(&self, enrollment: &mut Option<Enrollment>) -> Result<(), AppError> {
+        if enrollment.take().is_some() {
+            self.store.clear_sealed()?;
+        }
+        Ok(())
+    }
+}
+
+fn derive_key(pin: &str, salt: &[u8]) -> Result<Zeroizing<Vec<u8>>, AppError> {
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        version: argon2::Version::Version13,
+        mem_cost: KDF_MEMORY_KIB,
+        time_cost: KDF_ITERATIONS,
+        lanes: 1,
+        hash_length: 32,
+        ..argon2::Config::default()
+    };
+    argon2::hash_raw(pin.as_bytes(), salt, &config)
+        .map(Zeroizing::new)
+        .map_err(|e| AppError::Crypto(e.to_string()))
+}
+
+/// Encrypts the composite key under `pin`. The result is the nonce followed
+/// by the ciphertext.
+fn seal(composite_key: &CompositeKey, pin: &str, salt: &[u8]) -> Result<Vec<u8>, AppError> {
+    let key = derive_key(pin, salt)?;
+    let cipher = XSalsa20Poly1305::new(Key::from_slice(&key));
+    let nonce = XSalsa20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, composite_key.as_bytes())
+        .map_err(|e| AppError::Crypto(e.to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts a sealed composite key. A wrong PIN fails authentication and is
+/// reported as [`AppError::IncorrectPin`], without a meaningful count.
+fn unseal(sealed: &[u8], pin: &str, salt: &[u8]) -> Result<CompositeKey, AppError> {
+    if sealed.len() < NONCE_LENGTH {
+        return Err(AppError::SecureStorage(
+            "Sealed key is truncated".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+
+    let key = derive_key(pin, salt)?;
+    let cipher = XSalsa20Poly1305::new(Key::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map(Zeroizing::new)
+        .map_err(|_| AppError::IncorrectPin(0))?;
+
+    Ok(CompositeKey::from_bytes(&plaintext))
+}
@@ -0,0 +1,646 @@
+// SPDX-License-Identifier: MIT
+
+//! The Secret Service D-Bus API on top of [`SecretService`].
+//!
+//! The service, the collection and its alias are exported when the
+//! connection is built. Item and session objects are exported on demand:
+//! items whenever a method hands out their path, sessions when opened.
+//! Session paths carry a random ID and only work for the connection that
+//! opened them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use zbus::message::Header;
+use zbus::names::BusName;
+use zbus::object_server::ObjectServer;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::{fdo, interface, Connection};
+
+use super::session::ALGORITHM_PLAIN;
+use super::{SecretService, TransferSecret};
+use crate::dto::error::AppError;
+use crate::dto::secret_service::{SecretItem, UpdateSecretItem};
+
+/// Well-known name of the Secret Service.
+pub const BUS_NAME: &str = "org.freedesktop.secrets";
+
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const COLLECTION_PATH: &str = "/org/freedesktop/secrets/collection/mithril";
+const ALIAS_PATH: &str = "/org/freedesktop/secrets/aliases/default";
+const SESSION_PATH: &str = "/org/freedesktop/secrets/session";
+
+/// Aliases that resolve to the collection; GNOME apps ask for `login`.
+const ALIASES: [&str; 2] = ["default", "login"];
+
+const ITEM_LABEL: &str = "org.freedesktop.Secret.Item.Label";
+const ITEM_ATTRIBUTES: &str = "org.freedesktop.Secret.Item.Attributes";
+
+/// A secret on the wire: session, parameters, value and content type.
+type Secret = (OwnedObjectPath, Vec<u8>, Vec<u8>, String);
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "org.freedesktop.Secret.Error")]
+pub enum SecretError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    IsLocked(String),
+    NoSession(String),
+    NoSuchObject(String),
+}
+
+impl From<AppError> for SecretError {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::DatabaseNotOpen => Self::IsLocked(err.to_string()),
+            AppError::SecretSessionNotFound(_) => Self::NoSession(err.to_string()),
+            AppError::EntryNotFound(_) => Self::NoSuchObject(err.to_string()),
+            other => Self::ZBus(zbus::Error::FDO(Box::new(to_fdo(other)))),
+        }
+    }
+}
+
+impl From<fdo::Error> for SecretError {
+    fn from(err: fdo::Error) -> Self {
+        Self::ZBus(zbus::Error::FDO(Box::new(err)))
+    }
+}
+
+impl From<zbus::zvariant::Error> for SecretError {
+    fn from(err: zbus::zvariant::Error) -> Self {
+        fdo::Error::InvalidArgs(err.to_string()).into()
+    }
+}
+
+/// Property accessors can only fail with standard errors.
+fn to_fdo(err: AppError) -> fdo::Error {
+    match err {
+        AppError::SecretServiceAccessDenied(_) => fdo::Error::AccessDenied(err.to_string()),
+        other => fdo::Error::Failed(other.to_string()),
+    }
+}
+
+/// Claims the Secret Service name on the session bus, or on the bus at
+/// `address`, and exports the service. Fails if another keyring already
+/// owns the name. The returned connection has to be kept alive.
+pub fn start(
+    service: &Arc<SecretService>,
+    address: Option<&str>,
+) -> Result<zbus::blocking::Connection, AppError> {
+    let failed = |err: zbus::Error| AppError::SecretService(err.to_string());
+    let builder = match address {
+        Some(address) => zbus::blocking::connection::Builder::address(address),
+        None => zbus::blocking::connection::Builder::session(),
+    }
+    .map_err(failed)?;
+
+    let connection = builder
+        .serve_at(
+            SERVICE_PATH,
+            Service {
+                service: Arc::clone(service),
+            },
+        )
+        .and_then(|builder| {
+            builder.serve_at(
+                COLLECTION_PATH,
+                Collection {
+                    service: Arc::clone(service),
+                },
+            )
+        })
+        .and_then(|builder| {
+            builder.serve_at(
+                ALIAS_PATH,
+                Collection {
+                    service: Arc::clone(service),
+                },
+            )
+        })
+        .and_then(|builder| builder.name(BUS_NAME))
+        .and_then(zbus::blocking::connection::Builder::build)
+        .map_err(failed)?;
+
+    service.active.store(true, Ordering::SeqCst);
+    Ok(connection)
+}
+
+/// Names the client behind a request by its executable, falling back to
+/// its unique bus name.
+async fn client_name(connection: &Connection, header: &Header<'_>) -> String {
+    let Some(sender) = header.sender() else {
+        return "unknown client".to_string();
+    };
+    let pid = match fdo::DBusProxy::new(connection).await {
+        Ok(proxy) => proxy
+            .get_connection_unix_process_id(BusName::Unique(sender.clone()))
+            .await
+            .ok(),
+        Err(_) => None,
+    };
+    pid.and_then(|pid| std::fs::read_link(format!("/proc/{pid}/exe")).ok())
+        .map_or_else(|| sender.to_string(), |path| path.display().to_string())
+}
+
+/// Authorizes the sender of a request. The confirmation prompt blocks, so
+/// it runs on a blocking thread instead of the D-Bus executor.
+async fn authorize(
+    service: &Arc<SecretService>,
+    connection: &Connection,
+    header: &Header<'_>,
+) -> Result<(), AppError> {
+    let client = client_name(connection, header).await;
+    let service = Arc::clone(service);
+    blocking::unblock(move || service.authorize(&client)).await
+}
+
+/// Unique bus name of the connection that sent a request; sessions belong
+/// to it.
+fn sender(header: &Header<'_>) -> Result<String, SecretError> {
+    header
+        .sender()
+        .map(ToString::to_string)
+        .ok_or_else(|| fdo::Error::AccessDenied("Request has no sender".to_string()).into())
+}
+
+fn item_path(id: &str) -> Result<OwnedObjectPath, SecretError> {
+    Ok(OwnedObjectPath::try_from(format!(
+        "{COLLECTION_PATH}/{}",
+        id.replace('-', "")
+    ))?)
+}
+
+/// Entry ID of an item path.
+fn item_id(path: &ObjectPath<'_>) -> Option<String> {
+    let element = path
+        .as_str()
+        .strip_prefix(COLLECTION_PATH)?
+        .strip_prefix('/')?;
+    uuid::Uuid::try_parse(element)
+        .ok()
+        .map(|uuid| uuid.to_string())
+}
+
+fn session_id(path: &ObjectPath<'_>) -> Result<String, SecretError> {
+    path.as_str()
+        .strip_prefix(SESSION_PATH)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(str::to_string)
+        .ok_or_else(|| SecretError::NoSession(path.to_string()))
+}
+
+fn no_prompt() -> OwnedObjectPath {
+    OwnedObjectPath::from(ObjectPath::from_static_str_unchecked("/"))
+}
+
+/// Exports `items` and returns their paths.
+async fn export_items(
+    server: &ObjectServer,
+    service: &Arc<SecretService>,
+    items: &[SecretItem],
+) -> Result<Vec<OwnedObjectPath>, SecretError> {
+    let mut paths = Vec::with_capacity(items.len());
+    for item in items {
+        let path = item_path(&item.id)?;
+        server
+            .at(
+                &path,
+                Item {
+                    service: Arc::clone(service),
+                    id: item.id.clone(),
+                },
+            )
+            .await?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn to_wire(secret: TransferSecret) -> Result<Secret, SecretError> {
+    let session = OwnedObjectPath::try_from(format!("{SESSION_PATH}/{}", secret.session))?;
+    Ok((
+        session,
+        secret.parameters,
+        secret.value,
+        secret.content_type,
+    ))
+}
+
+fn from_wire(secret: Secret) -> Result<TransferSecret, SecretError> {
+    let (session, parameters, value, content_type) = secret;
+    Ok(TransferSecret {
+        session: session_id(&session)?,
+        parameters,
+        value,
+        content_type,
+    })
+}
+
+struct Service {
+    service: Arc<SecretService>,
+}
+
+// Method signatures are fixed by the specification
+#[allow(clippy::unused_self, clippy::needless_pass_by_value)]
+#[interface(name = "org.freedesktop.Secret.Service")]
+impl Service {
+    async fn open_session(
+        &self,
+        algorithm: &str,
+        input: Value<'_>,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(OwnedValue, OwnedObjectPath), SecretError> {
+        let input: Vec<u8> = if algorithm == ALGORITHM_PLAIN {
+            Vec::new()
+        } else {
+            input.try_into()?
+        };
+        let (id, output) = self
+            .service
+            .open_session(&sender(&header)?, algorithm, &input)?;
+
+        let path = OwnedObjectPath::try_from(format!("{SESSION_PATH}/{id}"))?;
+        server
+            .at(
+                &path,
+                Session {
+                    service: Arc::clone(&self.service),
+                    id,
+                },
+            )
+            .await?;
+        let output = if algorithm == ALGORITHM_PLAIN {
+            Value::from("")
+        } else {
+            Value::from(output)
+        };
+        Ok((output.try_to_owned()?, path))
+    }
+
+    /// Only one collection exists, so this returns it for the default
+    /// aliases and refuses anything else.
+    fn create_collection(
+        &self,
+        properties: HashMap<String, OwnedValue>,
+        alias: &str,
+    ) -> Result<(OwnedObjectPath, OwnedObjectPath), SecretError> {
+        // The label and other properties belong to the designated group
+        let _ = properties;
+        if !alias.is_empty() && !ALIASES.contains(&alias) {
+            return Err(
+                fdo::Error::NotSupported("Only one collection is available".to_string()).into(),
+            );
+        }
+        Ok((OwnedObjectPath::try_from(COLLECTION_PATH)?, no_prompt()))
+    }
+
+    async fn search_items(
+        &self,
+        attributes: HashMap<String, String>,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(Vec<OwnedObjectPath>, Vec<OwnedObjectPath>), SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        let attributes: BTreeMap<String, String> = attributes.into_iter().collect();
+        let items = self.service.search_items(&attributes)?;
+        Ok((
+            export_items(server, &self.service, &items).await?,
+            Vec::new(),
+        ))
+    }
+
+    /// The database can only be unlocked in the app, so this succeeds only
+    /// if it already is.
+    fn unlock(
+        &self,
+        objects: Vec<OwnedObjectPath>,
+    ) -> Result<(Vec<OwnedObjectPath>, OwnedObjectPath), SecretError> {
+        if self.service.is_locked() {
+            return Err(AppError::DatabaseNotOpen.into());
+        }
+        Ok((objects, no_prompt()))
+    }
+
+    /// Clients cannot lock the database, so none of `objects` is locked.
+    fn lock(&self, objects: Vec<OwnedObjectPath>) -> (Vec<OwnedObjectPath>, OwnedObjectPath) {
+        let _ = objects;
+        (Vec::new(), no_prompt())
+    }
+
+    async fn get_secrets(
+        &self,
+        items: Vec<OwnedObjectPath>,
+        session: ObjectPath<'_>,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<HashMap<OwnedObjectPath, Secret>, SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        let session = session_id(&session)?;
+        let sender = sender(&header)?;
+        let mut secrets = HashMap::new();
+        for path in items {
+            let Some(id) = item_id(&path) else {
+                continue;
+            };
+            match self.service.get_secret(&id, &session, &sender) {
+                Ok(secret) => {
+                    secrets.insert(path, to_wire(secret)?);
+                }
+                Err(AppError::EntryNotFound(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(secrets)
+    }
+
+    fn read_alias(&self, name: &str) -> Result<OwnedObjectPath, SecretError> {
+        if ALIASES.contains(&name) {
+            Ok(OwnedObjectPath::try_from(COLLECTION_PATH)?)
+        } else {
+            Ok(no_prompt())
+        }
+    }
+
+    fn set_alias(&self, name: &str, collection: ObjectPath<'_>) -> Result<(), SecretError> {
+        if ALIASES.contains(&name) && collection.as_str() == COLLECTION_PATH {
+            Ok(())
+        } else {
+            Err(fdo::Error::NotSupported("Aliases cannot be changed".to_string()).into())
+        }
+    }
+
+    #[zbus(property)]
+    fn collections(&self) -> Vec<OwnedObjectPath> {
+        vec![OwnedObjectPath::from(
+            ObjectPath::from_static_str_unchecked(COLLECTION_PATH),
+        )]
+    }
+}
+
+struct Collection {
+    service: Arc<SecretService>,
+}
+
+// Method signatures are fixed by the specification
+#[allow(clippy::unused_self, clippy::needless_pass_by_value)]
+#[interface(name = "org.freedesktop.Secret.Collection")]
+impl Collection {
+    /// The collection is the designated group, which is managed in the app.
+    fn delete(&self) -> Result<OwnedObjectPath, SecretError> {
+        Err(fdo::Error::NotSupported("The collection cannot be deleted".to_string()).into())
+    }
+
+    async fn search_items(
+        &self,
+        attributes: HashMap<String, String>,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<Vec<OwnedObjectPath>, SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        let attributes: BTreeMap<String, String> = attributes.into_iter().collect();
+        let items = self.service.search_items(&attributes)?;
+        export_items(server, &self.service, &items).await
+    }
+
+    async fn create_item(
+        &self,
+        properties: HashMap<String, OwnedValue>,
+        secret: Secret,
+        replace: bool,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(OwnedObjectPath, OwnedObjectPath), SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        let label = match properties.get(ITEM_LABEL) {
+            Some(value) => String::try_from(value.try_clone()?)?,
+            None => String::new(),
+        };
+        let attributes: BTreeMap<String, String> = match properties.get(ITEM_ATTRIBUTES) {
+            Some(value) => HashMap::<String, String>::try_from(value.try_clone()?)?
+                .into_iter()
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        let item = self.service.create_item(
+            &label,
+            &attributes,
+            &from_wire(secret)?,
+            replace,
+            &sender(&header)?,
+        )?;
+        let mut paths = export_items(server, &self.service, &[item]).await?;
+        Ok((paths.remove(0), no_prompt()))
+    }
+
+    #[zbus(property)]
+    async fn items(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> fdo::Result<Vec<OwnedObjectPath>> {
+        let items = self.service.items().map_err(to_fdo)?;
+        export_items(server, &self.service, &items)
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))
+    }
+
+    #[zbus(property)]
+    fn label(&self) -> fdo::Result<String> {
+        Ok(self.service.collection().map_err(to_fdo)?.label)
+    }
+
+    #[zbus(property)]
+    async fn set_label(
+        &mut self,
+        label: String,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<()> {
+        if let Some(header) = header {
+            authorize(&self.service, connection, &header)
+                .await
+                .map_err(to_fdo)?;
+        }
+        self.service.set_collection_label(&label).map_err(to_fdo)
+    }
+
+    #[zbus(property)]
+    fn locked(&self) -> bool {
+        self.service.is_locked()
+    }
+
+    #[zbus(property)]
+    fn created(&self) -> fdo::Result<u64> {
+        Ok(self.service.collection().map_err(to_fdo)?.created)
+    }
+
+    #[zbus(property)]
+    fn modified(&self) -> fdo::Result<u64> {
+        Ok(self.service.collection().map_err(to_fdo)?.modified)
+    }
+}
+
+struct Item {
+    service: Arc<SecretService>,
+    id: String,
+}
+
+impl Item {
+    fn item(&self) -> fdo::Result<SecretItem> {
+        self.service.item(&self.id).map_err(to_fdo)
+    }
+
+    async fn authorize_access(
+        &self,
+        connection: &Connection,
+        header: Option<Header<'_>>,
+    ) -> fdo::Result<()> {
+        match header {
+            Some(header) => authorize(&self.service, connection, &header)
+                .await
+                .map_err(to_fdo),
+            // Not a client request, e.g. a change signal being emitted
+            None => Ok(()),
+        }
+    }
+}
+
+#[interface(name = "org.freedesktop.Secret.Item")]
+impl Item {
+    /// Moves the entry to the recycle bin.
+    async fn delete(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<OwnedObjectPath, SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        self.service.delete_item(&self.id)?;
+        server.remove::<Self, _>(item_path(&self.id)?).await?;
+        Ok(no_prompt())
+    }
+
+    async fn get_secret(
+        &self,
+        session: ObjectPath<'_>,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<Secret, SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        to_wire(
+            self.service
+                .get_secret(&self.id, &session_id(&session)?, &sender(&header)?)?,
+        )
+    }
+
+    async fn set_secret(
+        &self,
+        secret: Secret,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<(), SecretError> {
+        authorize(&self.service, connection, &header).await?;
+        self.service
+            .set_secret(&self.id, &from_wire(secret)?, &sender(&header)?)?;
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn locked(&self) -> bool {
+        self.service.is_locked()
+    }
+
+    #[zbus(property)]
+    async fn attributes(
+        &self,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<HashMap<String, String>> {
+        self.authorize_access(connection, header).await?;
+        Ok(self.item()?.attributes.into_iter().collect())
+    }
+
+    #[zbus(property)]
+    async fn set_attributes(
+        &mut self,
+        attributes: HashMap<String, String>,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<()> {
+        self.authorize_access(connection, header).await?;
+        self.service
+            .update_item(
+                &self.id,
+                UpdateSecretItem {
+                    attributes: Some(attributes.into_iter().collect()),
+                    ..UpdateSecretItem::default()
+                },
+            )
+            .map(|_| ())
+            .map_err(to_fdo)
+    }
+
+    #[zbus(property)]
+    async fn label(
+        &self,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<String> {
+        self.authorize_access(connection, header).await?;
+        Ok(self.item()?.label)
+    }
+
+    #[zbus(property)]
+    async fn set_label(
+        &mut self,
+        label: String,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<()> {
+        self.authorize_access(connection, header).await?;
+        self.service
+            .update_item(
+                &self.id,
+                UpdateSecretItem {
+                    label: Some(label),
+                    ..UpdateSecretItem::default()
+                },
+            )
+            .map(|_| ())
+            .map_err(to_fdo)
+    }
+
+    #[zbus(property)]
+    fn created(&self) -> fdo::Result<u64> {
+        Ok(self.item()?.created)
+    }
+
+    #[zbus(property)]
+    fn modified(&self) -> fdo::Result<u64> {
+        Ok(self.item()?.modified)
+    }
+}
+
+struct Session {
+    service: Arc<SecretService>,
+    id: String,
+}
+
+#[interface(name = "org.freedesktop.Secret.Session")]
+impl Session {
+    async fn close(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(), SecretError> {
+        self.service.close_session(&self.id, &sender(&header)?)?;
+        let path = OwnedObjectPath::try_from(format!("{SESSION_PATH}/{}", self.id))?;
+        server.remove::<Self, _>(path).await?;
+        Ok(())
+    }
+}
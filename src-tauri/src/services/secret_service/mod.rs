@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: MIT
+
+//! Freedesktop Secret Service provider, so applications using libsecret
+//! keep their secrets in the open database.
+//!
+//! [`SecretService`] implements the operations of the specification on top
+//! of [`KdbxService`]; [`dbus`] exports them on the session bus as
+//! `org.freedesktop.secrets`. There is a single collection, backed by the
+//! designated group (see `services::kdbx::secret_service`) and reachable
+//! under the `default` alias.
+//!
+//! The database is the only lock: while it is closed every request is
+//! refused, and unlocking happens in the app. Each client executable has to
+//! be approved by the user once per run before it can search or read items.
+//! Sessions get random IDs and belong to the bus connection that opened
+//! them, so one client cannot decrypt secrets through another's session.
+
+#[cfg(target_os = "linux")]
+pub mod dbus;
+pub mod session;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+use crate::dto::secret_service::{
+    SecretCollection, SecretItem, SecretServiceStatus, UpdateSecretItem,
+};
+use crate::services::kdbx::KdbxService;
+use session::Session;
+
+/// Asks the user whether the named client may use the Secret Service.
+/// Called from the D-Bus thread, so it may block.
+pub type ClientConfirmer = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// An open session and the unique bus name of the client that opened it.
+struct OwnedSession {
+    sender: String,
+    session: Session,
+}
+
+/// A secret as exchanged with clients, encrypted for `session`.
+#[derive(Debug, Clone)]
+pub struct TransferSecret {
+    pub session: String,
+    pub parameters: Vec<u8>,
+    pub value: Vec<u8>,
+    pub content_type: String,
+}
+
+pub struct SecretService {
+    kdbx: Arc<KdbxService>,
+    confirm_client: ClientConfirmer,
+    approved_clients: Mutex<BTreeSet<String>>,
+    sessions: Mutex<HashMap<String, OwnedSession>>,
+    /// Set once the bus name is owned
+    pub(crate) active: AtomicBool,
+}
+
+impl SecretService {
+    /// Creates a provider backed by `kdbx`. Unknown clients are only served
+    /// if `confirm_client` returns true.
+    pub fn new(
+        kdbx: Arc<KdbxService>,
+        confirm_client: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            kdbx,
+            confirm_client: Box::new(confirm_client),
+            approved_clients: Mutex::new(BTreeSet::new()),
+            sessions: Mutex::new(HashMap::new()),
+            active: AtomicBool::new(false),
+        }
+    }
+
+    pub fn status(&self) -> Result<SecretServiceStatus, AppError> {
+        let collection = self.kdbx.secret_service_collection().ok();
+        let approved_clients = self
+            .approved_clients
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .iter()
+            .cloned()
+            .collect();
+
+        Ok(SecretServiceStatus {
+            active: self.active.load(Ordering::SeqCst),
+            group_name: collection
+                .as_ref()
+                .filter(|collection| collection.group_id.is_some())
+                .map(|collection| collection.label.clone()),
+            group_id: collection.and_then(|collection| collection.group_id),
+            approved_clients,
+        })
+    }
+
+    /// Forgets the approval of a client; it is asked for again on its next
+    /// request.
+    pub fn revoke_client(&self, client: &str) -> Result<(), AppError> {
+        let removed = self
+            .approved_clients
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .remove(client);
+        if removed {
+            Ok(())
+        } else {
+            Err(AppError::SecretService(format!("Unknown client: {client}")))
+        }
+    }
+
+    /// Whether requests are currently refused because no database is open.
+    pub fn is_locked(&self) -> bool {
+        self.kdbx.get_info().is_err()
+    }
+
+    /// Checks that the database is open and that `client` may use the
+    /// service, asking the user the first time.
+    pub fn authorize(&self, client: &str) -> Result<(), AppError> {
+        if self.is_locked() {
+            return Err(AppError::DatabaseNotOpen);
+        }
+        if self
+            .approved_clients
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .contains(client)
+        {
+            return Ok(());
+        }
+
+        // Asked without holding the lock: the prompt can take a while
+        if !(self.confirm_client)(client) {
+            return Err(AppError::SecretServiceAccessDenied(client.to_string()));
+        }
+        self.approved_clients
+            .lock()
+            .map_err(|_| AppError::Lock)?
+            .insert(client.to_string());
+        Ok(())
+    }
+
+    /// Opens a session for `sender` and returns its ID with the output for
+    /// the client.
+    pub fn open_session(
+        &self,
+        sender: &str,
+        algorithm: &str,
+        input: &[u8],
+    ) -> Result<(String, Vec<u8>), AppError> {
+        let (session, output) = Session::negotiate(algorithm, input)?;
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        self.sessions.lock().map_err(|_| AppError::Lock)?.insert(
+            id.clone(),
+            OwnedSession {
+                sender: sender.to_string(),
+                session,
+            },
+        );
+        Ok((id, output))
+    }
+
+    pub fn close_session(&self, id: &str, sender: &str) -> Result<(), AppError> {
+        let mut sessions = self.sessions.lock().map_err(|_| AppError::Lock)?;
+        owned_session(&sessions, id, sender)?;
+        sessions.remove(id);
+        Ok(())
+    }
+
+    pub fn collection(&self) -> Result<SecretCollection, AppError> {
+        self.kdbx.secret_service_collection()
+    }
+
+    pub fn set_collection_label(&self, label: &str) -> Result<(), AppError> {
+        self.kdbx.set_secret_service_label(label)
+    }
+
+    pub fn items(&self) -> Result<Vec<SecretItem>, AppError> {
+        self.kdbx.list_secret_items()
+    }
+
+    /// Items having all of `attributes` with exactly these values.
+    pub fn search_items(
+        &self,
+        attributes: &BTreeMap<String, String>,
+    ) -> Result<Vec<SecretItem>, AppError> {
+        Ok(self
+            .items()?
+            .into_iter()
+            .filter(|item| {
+                attributes
+                    .iter()
+                    .all(|(key, value)| item.attributes.get(key) == Some(value))
+            })
+            .collect())
+    }
+
+    pub fn item(&self, id: &str) -> Result<SecretItem, AppError> {
+        self.kdbx.get_secret_item(id)
+    }
+
+    /// The secret of an item, encrypted for `sender`'s `session`.
+    pub fn get_secret(
+        &self,
+        id: &str,
+        session: &str,
+        sender: &str,
+    ) -> Result<TransferSecret, AppError> {
+        let item = self.kdbx.get_secret_item(id)?;
+        let secret = self.kdbx.get_secret_item_value(id)?;
+
+        let sessions = self.sessions.lock().map_err(|_| AppError::Lock)?;
+        let (parameters, value) =
+            owned_session(&sessions, session, sender)?.encrypt(secret.as_bytes());
+        Ok(TransferSecret {
+            session: session.to_string(),
+            parameters,
+            value,
+            content_type: item.content_type,
+        })
+    }
+
+    /// Stores a new item. With `replace`, an item with the same attributes
+    /// is updated instead.
+    pub fn create_item(
+        &self,
+        label: &str,
+        attributes: &BTreeMap<String, String>,
+        secret: &TransferSecret,
+        replace: bool,
+        sender: &str,
+    ) -> Result<SecretItem, AppError> {
+        let value = self.decrypt(secret, sender)?;
+        if replace {
+            if let Some(existing) = self
+                .search_items(attributes)?
+                .into_iter()
+                .find(|item| item.attributes == *attributes)
+            {
+                return self.kdbx.update_secret_item(
+                    &existing.id,
+                    UpdateSecretItem {
+                        label: Some(label.to_string()),
+                        attributes: None,
+                        secret: Some(value),
+                        content_type: Some(secret.content_type.clone()),
+                    },
+                );
+            }
+        }
+        self.kdbx
+            .create_secret_item(label, attributes, &value, &secret.content_type)
+    }
+
+    pub fn set_secret(
+        &self,
+        id: &str,
+        secret: &TransferSecret,
+        sender: &str,
+    ) -> Result<SecretItem, AppError> {
+        let value = self.decrypt(secret, sender)?;
+        self.kdbx.update_secret_item(
+            id,
+            UpdateSecretItem {
+                secret: Some(value),
+                content_type: Some(secret.content_type.clone()),
+                ..UpdateSecretItem::default()
+            },
+        )
+    }
+
+    pub fn update_item(&self, id: &str, update: UpdateSecretItem) -> Result<SecretItem, AppError> {
+        self.kdbx.update_secret_item(id, update)
+    }
+
+    /// Moves an item to the recycle bin.
+    pub fn delete_item(&self, id: &str) -> Result<(), AppError> {
+        self.kdbx.get_secret_item(id)?;
+        self.kdbx.delete_entry(id, false)
+    }
+
+    /// Decrypts a secret sent by a client. Entries hold text, so binary
+    /// secrets are refused.
+    fn decrypt(&self, secret: &TransferSecret, sender: &str) -> Result<SecureString, AppError> {
+        let sessions = self.sessions.lock().map_err(|_| AppError::Lock)?;
+        let value = owned_session(&sessions, &secret.session, sender)?
+            .decrypt(&secret.parameters, &secret.value)?;
+        std::str::from_utf8(&value)
+            .map(SecureString::from)
+            .map_err(|_| AppError::SecretService("Only text secrets can be stored".to_string()))
+    }
+}
+
+/// Looks up a session opened by `sender`. Sessions of other clients are
+/// reported as missing.
+fn owned_session<'a>(
+    sessions: &'a HashMap<String, OwnedSession>,
+    id: &str,
+    sender: &str,
+) -> Result<&'a Session, AppError> {
+    sessions
+        .get(id)
+        .filter(|owned| owned.sender == sender)
+        .map(|owned| &owned.session)
+        .ok_or_else(|| AppError::SecretSessionNotFound(id.to_string()))
+}
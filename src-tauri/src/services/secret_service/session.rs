@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MIT
+
+//! Transfer encryption for Secret Service sessions.
+//!
+//! Clients choose the algorithm when opening a session. `plain` sends
+//! secrets as-is. `dh-ietf1024-sha256-aes128-cbc-pkcs7` agrees on a key with
+//! Diffie-Hellman in the 1024-bit MODP group of RFC 2409, derives an AES-128
+//! key from the shared secret with HKDF-SHA256 (no salt, empty info) and
+//! encrypts every secret with AES-CBC under a fresh IV, which travels as the
+//! secret's parameters.
+
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hkdf::Hkdf;
+use rand::RngCore;
+use rsa::BigUint;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::dto::error::AppError;
+
+pub const ALGORITHM_PLAIN: &str = "plain";
+pub const ALGORITHM_DH_AES: &str = "dh-ietf1024-sha256-aes128-cbc-pkcs7";
+
+/// Second Oakley group (RFC 2409 section 6.2), generator 2.
+const PRIME_HEX: &str = "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD1\
+                         29024E088A67CC74020BBEA63B139B22514A08798E3404DD\
+                         EF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245\
+                         E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+                         EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381\
+                         FFFFFFFFFFFFFFFF";
+const GENERATOR: u32 = 2;
+const PRIME_BYTES: usize = 128;
+
+const AES_KEY_BYTES: usize = 16;
+const IV_BYTES: usize = 16;
+
+/// Negotiated transfer encryption of one session.
+pub enum Session {
+    Plain,
+    DhAes(Zeroizing<[u8; AES_KEY_BYTES]>),
+}
+
+impl Session {
+    /// Starts a session for `algorithm`. `input` is the client's public key
+    /// for DH sessions; the returned bytes are ours (empty for `plain`).
+    pub fn negotiate(algorithm: &str, input: &[u8]) -> Result<(Self, Vec<u8>), AppError> {
+        match algorithm {
+            ALGORITHM_PLAIN => Ok((Self::Plain, Vec::new())),
+            ALGORITHM_DH_AES => {
+                let prime = prime();
+                let client_public = BigUint::from_bytes_be(input);
+                let one = BigUint::from(1u32);
+                if client_public <= one || client_public >= &prime - &one {
+                    return Err(AppError::SecretService(
+                        "Invalid Diffie-Hellman public key".to_string(),
+                    ));
+                }
+
+                let mut exponent = Zeroizing::new([0u8; PRIME_BYTES]);
+                rand::rngs::OsRng.fill_bytes(exponent.as_mut_slice());
+                let private = BigUint::from_bytes_be(exponent.as_slice());
+                let public = BigUint::from(GENERATOR).modpow(&private, &prime);
+                let shared = Zeroizing::new(padded(&client_public.modpow(&private, &prime)));
+
+                let mut key = Zeroizing::new([0u8; AES_KEY_BYTES]);
+                Hkdf::<Sha256>::new(None, &shared)
+                    .expand(&[], key.as_mut_slice())
+                    .map_err(|e| AppError::Crypto(e.to_string()))?;
+                Ok((Self::DhAes(key), padded(&public)))
+            }
+            other => Err(AppError::SecretService(format!(
+                "Unsupported algorithm: {other}"
+            ))),
+        }
+    }
+
+    /// Encrypts a secret for the client, returning `(parameters, value)`.
+    pub fn encrypt(&self, secret: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            Self::Plain => (Vec::new(), secret.to_vec()),
+            Self::DhAes(key) => {
+                let mut iv = [0u8; IV_BYTES];
+                rand::rngs::OsRng.fill_bytes(&mut iv);
+                let mut buffer = secret.to_vec();
+                buffer.resize(secret.len() + IV_BYTES, 0);
+                let length = cbc::Encryptor::<aes::Aes128>::new(key.as_ref().into(), &iv.into())
+                    .encrypt_padded_mut::<Pkcs7>(&mut buffer, secret.len())
+                    .map_or(0, <[u8]>::len);
+                buffer.truncate(length);
+                (iv.to_vec(), buffer)
+            }
+        }
+    }
+
+    /// Decrypts a secret sent by the client.
+    pub fn decrypt(&self, parameters: &[u8], value: &[u8]) -> Result<Zeroizing<Vec<u8>>, AppError> {
+        match self {
+            Self::Plain => Ok(Zeroizing::new(value.to_vec())),
+            Self::DhAes(key) => {
+                let invalid = || AppError::SecretService("Cannot decrypt secret".to_string());
+                let mut buffer = Zeroizing::new(value.to_vec());
+                let length =
+                    cbc::Decryptor::<aes::Aes128>::new_from_slices(key.as_slice(), parameters)
+                        .map_err(|_| invalid())?
+                        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+                        .map_err(|_| invalid())?
+                        .len();
+                buffer.truncate(length);
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+fn prime() -> BigUint {
+    let bytes: Vec<u8> = (0..PRIME_HEX.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(&PRIME_HEX[i..i + 2], 16).ok())
+        .collect();
+    BigUint::from_bytes_be(&bytes)
+}
+
+/// Big-endian bytes left-padded to the size of the prime.
+fn padded(value: &BigUint) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut out = vec![0u8; PRIME_BYTES.saturating_sub(bytes.len())];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prime_is_the_oakley_group() {
+        let prime = prime();
+        assert_eq!(prime.bits(), 1024);
+        assert_eq!(padded(&prime).len(), PRIME_BYTES);
+    }
+
+    #[test]
+    fn test_dh_sessions_agree_on_a_key() {
+        let client_private = BigUint::from_bytes_be(&[0x42; 64]);
+        let client_public = BigUint::from(GENERATOR).modpow(&client_private, &prime());
+
+        let (session, server_public) =
+            Session::negotiate(ALGORITHM_DH_AES, &client_public.to_bytes_be())
+                .expect("Failed to negotiate");
+        let shared =
+            padded(&BigUint::from_bytes_be(&server_public).modpow(&client_private, &prime()));
+        let mut key = [0u8; AES_KEY_BYTES];
+        Hkdf::<Sha256>::new(None, &shared)
+            .expand(&[], &mut key)
+            .expect("Failed to derive key");
+
+        let Session::DhAes(server_key) = &session else {
+            unreachable!("negotiated a plain session");
+        };
+        assert_eq!(server_key.as_slice(), &key);
+
+        let (iv, encrypted) = session.encrypt(b"hunter2");
+        assert_eq!(iv.len(), IV_BYTES);
+        assert_eq!(encrypted.len(), 16);
+        assert_eq!(
+            session
+                .decrypt(&iv, &encrypted)
+                .expect("Failed to decrypt")
+                .as_slice(),
+            b"hunter2"
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_negotiation() {
+        assert!(Session::negotiate(ALGORITHM_DH_AES, &[1]).is_err());
+        assert!(Session::negotiate(ALGORITHM_DH_AES, &padded(&prime())).is_err());
+        assert!(Session::negotiate("dh-ietf1024-sha256-aes128-cbc", &[]).is_err());
+
+        let (plain, output) = Session::negotiate(ALGORITHM_PLAIN, &[]).expect("plain");
+        assert!(output.is_empty());
+        assert!(plain.decrypt(&[], b"secret").is_ok());
+    }
+}
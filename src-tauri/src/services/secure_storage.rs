@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::dto::error::AppError;
+use crate::services::quick_unlock::SealedKeyStore;
 use rand::rngs::OsRng;
 use rand::RngCore;
 use std::path::PathBuf;
@@ -14,6 +15,7 @@ use zeroize::Zeroizing;
 
 const SESSION_CLIENT: &[u8] = b"mithrilvault-session";
 const SESSION_RECORD_KEY: &[u8] = b"session-key";
+const QUICK_UNLOCK_RECORD_KEY: &[u8] = b"quick-unlock-key";
 const SESSION_SNAPSHOT_FILE: &str = "session.hold";
 const DEFAULT_SESSION_TTL_SECS: u64 = 300;
 
@@ -56,71 +58,77 @@ impl SecureStorageService {
 
     /// Stores a session key with a TTL.
     pub fn store_session_key(&self, key: &[u8], ttl: Duration) -> Result<(), AppError> {
-        let stronghold = self.stronghold.lock().map_err(|_| AppError::Lock)?;
-        let client = Self::get_or_create_client(&stronghold)?;
+        self.insert(SESSION_RECORD_KEY, key, ttl)
+    }
 
-        client
-            .store()
-            .insert(SESSION_RECORD_KEY.to_vec(), key.to_vec(), Some(ttl))
-            .map_err(|err| AppError::SecureStorage(err.to_string()))?;
+    /// Checks if a session key is stored.
+    pub fn session_key_present(&self) -> Result<bool, AppError> {
+        Ok(self.get(SESSION_RECORD_KEY)?.is_some())
+    }
 
-        stronghold
-            .save()
-            .map_err(|err| AppError::SecureStorage(err.to_string()))?;
+    /// Loads the session key if present.
+    pub fn load_session_key(&self) -> Result<Option<Vec<u8>>, AppError> {
+        self.get(SESSION_RECORD_KEY)
+    }
+
+    /// Clears the stored session key.
+    pub fn clear_session_key(&self) -> Result<(), AppError> {
+        self.delete(SESSION_RECORD_KEY)?;
+
+        if self.snapshot_path.exists() {
+            std::fs::remove_file(&self.snapshot_path)?;
+        }
 
         Ok(())
     }
 
-    /// Checks if a session key is stored.
-    pub fn session_key_present(&self) -> Result<bool, AppError> {
+    /// Returns the default session TTL.
+    pub fn default_session_ttl() -> Duration {
+        Duration::from_secs(DEFAULT_SESSION_TTL_SECS)
+    }
+
+    fn insert(&self, record: &[u8], value: &[u8], ttl: Duration) -> Result<(), AppError> {
         let stronghold = self.stronghold.lock().map_err(|_| AppError::Lock)?;
         let client = Self::get_or_create_client(&stronghold)?;
 
-        let record = client
+        client
             .store()
-            .get(SESSION_RECORD_KEY)
+            .insert(record.to_vec(), value.to_vec(), Some(ttl))
             .map_err(|err| AppError::SecureStorage(err.to_string()))?;
 
-        Ok(record.is_some())
+        stronghold
+            .save()
+            .map_err(|err| AppError::SecureStorage(err.to_string()))?;
+
+        Ok(())
     }
 
-    /// Loads the session key if present.
-    pub fn load_session_key(&self) -> Result<Option<Vec<u8>>, AppError> {
+    fn get(&self, record: &[u8]) -> Result<Option<Vec<u8>>, AppError> {
         let stronghold = self.stronghold.lock().map_err(|_| AppError::Lock)?;
         let client = Self::get_or_create_client(&stronghold)?;
 
         client
             .store()
-            .get(SESSION_RECORD_KEY)
+            .get(record)
             .map_err(|err| AppError::SecureStorage(err.to_string()))
     }
 
-    /// Clears the stored session key.
-    pub fn clear_session_key(&self) -> Result<(), AppError> {
+    fn delete(&self, record: &[u8]) -> Result<(), AppError> {
         let stronghold = self.stronghold.lock().map_err(|_| AppError::Lock)?;
         let client = Self::get_or_create_client(&stronghold)?;
 
         let _ = client
             .store()
-            .delete(SESSION_RECORD_KEY)
+            .delete(record)
             .map_err(|err| AppError::SecureStorage(err.to_string()))?;
 
         stronghold
             .save()
             .map_err(|err| AppError::SecureStorage(err.to_string()))?;
 
-        if self.snapshot_path.exists() {
-            std::fs::remove_file(&self.snapshot_path)?;
-        }
-
         Ok(())
     }
 
-    /// Returns the default session TTL.
-    pub fn default_session_ttl() -> Duration {
-        Duration::from_secs(DEFAULT_SESSION_TTL_SECS)
-    }
-
     fn get_or_create_client(stronghold: &Stronghold) -> Result<iota_stronghold::Client, AppError> {
         // First try to get from in-memory state
         if let Ok(client) = stronghold.get_client(SESSION_CLIENT) {
@@ -145,6 +153,22 @@ impl SecureStorageService {
     }
 }
 
+/// Quick unlock keeps its sealed composite key in a record of its own, so
+/// it never mixes with the session key the frontend manages.
+impl SealedKeyStore for SecureStorageService {
+    fn store_sealed(&self, sealed: &[u8], ttl: Duration) -> Result<(), AppError> {
+        self.insert(QUICK_UNLOCK_RECORD_KEY, sealed, ttl)
+    }
+
+    fn load_sealed(&self) -> Result<Option<Vec<u8>>, AppError> {
+        self.get(QUICK_UNLOCK_RECORD_KEY)
+    }
+
+    fn clear_sealed(&self) -> Result<(), AppError> {
+        self.delete(QUICK_UNLOCK_RECORD_KEY)
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::expect_used)]
 mod tests {
@@ -170,7 +194,7 @@ mod tests {
 
         // Store a session key
         service
-            .store_session_key(test_key, Duration::from_secs(3600))
+            .store_session_key(test_key, Duration::from_hours(1))
             .expect("store");
 
         // Verify it's present and can be loaded
@@ -186,7 +210,7 @@ mod tests {
 
         // Store and then clear (use long TTL to avoid timing issues in CI)
         service
-            .store_session_key(test_key, Duration::from_secs(3600))
+            .store_session_key(test_key, Duration::from_hours(1))
             .expect("store");
         assert!(service.session_key_present().expect("presence check"));
 
@@ -204,10 +228,10 @@ mod tests {
         let key2 = b"second_key";
 
         service
-            .store_session_key(key1, Duration::from_secs(3600))
+            .store_session_key(key1, Duration::from_hours(1))
             .expect("store first");
         service
-            .store_session_key(key2, Duration::from_secs(3600))
+            .store_session_key(key2, Duration::from_hours(1))
             .expect("store second");
 
         let loaded = service.load_session_key().expect("load");
@@ -217,7 +241,27 @@ mod tests {
     #[test]
     fn test_default_session_ttl() {
         let ttl = SecureStorageService::default_session_ttl();
-        assert_eq!(ttl, Duration::from_secs(300));
+        assert_eq!(ttl, Duration::from_mins(5));
+    }
+
+    #[test]
+    fn test_sealed_key_is_separate_from_session_key() {
+        let (service, _temp_dir) = create_test_service();
+
+        service
+            .store_session_key(b"session", Duration::from_hours(1))
+            .expect("store session key");
+        service
+            .store_sealed(b"sealed", Duration::from_hours(1))
+            .expect("store sealed key");
+        assert_eq!(
+            service.load_sealed().expect("load"),
+            Some(b"sealed".to_vec())
+        );
+
+        service.clear_sealed().expect("clear");
+        assert!(service.load_sealed().expect("load").is_none());
+        assert!(service.session_key_present().expect("presence check"));
     }
 
     // Note: TTL expiration testing is omitted because Stronghold's TTL handling
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+
+//! Re-imports shared groups when their containers change on disk.
+//!
+//! The watcher polls the containers of the open database's importing shares
+//! and merges them when one was rewritten. Shares are already imported when
+//! a database is opened or unlocked, so the first look at a database only
+//! records the current state of its containers.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::dto::error::AppError;
+use crate::dto::keeshare::ShareSyncResult;
+use crate::services::kdbx::keeshare::resolve_path;
+use crate::services::kdbx::KdbxService;
+
+/// Time between two looks at the containers.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What is known about a container file; `None` while it does not exist.
+/// Containers are replaced by renaming, so a rewrite changes the time even
+/// when the size stays the same.
+type Fingerprint = Option<(SystemTime, u64)>;
+
+pub struct ShareWatcher {
+    kdbx: Arc<KdbxService>,
+    /// Database whose containers are in `seen`
+    database: Option<String>,
+    seen: BTreeMap<PathBuf, Fingerprint>,
+}
+
+impl ShareWatcher {
+    pub fn new(kdbx: Arc<KdbxService>) -> Self {
+        Self {
+            kdbx,
+            database: None,
+            seen: BTreeMap::new(),
+        }
+    }
+
+    /// Polls every [`POLL_INTERVAL`] on a background thread and passes the
+    /// results of each import to `on_import`.
+    pub fn start(
+        mut self,
+        on_import: impl Fn(Vec<ShareSyncResult>) + Send + 'static,
+    ) -> Result<(), AppError> {
+        thread::Builder::new()
+            .name("share-watcher".into())
+            .spawn(move || loop {
+                thread::sleep(POLL_INTERVAL);
+                if let Ok(Some(results)) = self.check() {
+                    on_import(results);
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Imports the shares if a container changed since the last check.
+    /// Returns `None` when nothing changed or no writable database is open.
+    pub fn check(&mut self) -> Result<Option<Vec<ShareSyncResult>>, AppError> {
+        let info = match self.kdbx.get_info() {
+            Ok(info) if !info.is_read_only => info,
+            Ok(_) | Err(AppError::DatabaseNotOpen) => {
+                self.database = None;
+                self.seen.clear();
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+
+        let current: BTreeMap<PathBuf, Fingerprint> = self
+            .kdbx
+            .list_shares()?
+            .into_iter()
+            .filter(|share| share.mode.imports())
+            .map(|share| {
+                let path = resolve_path(&info.path, &share.path);
+                let fingerprint = fingerprint(&path);
+                (path, fingerprint)
+            })
+            .collect();
+
+        let first_look = self.database.as_deref() != Some(info.path.as_str());
+        let changed = current
+            .iter()
+            .any(|(path, fingerprint)| self.seen.get(path) != Some(fingerprint));
+        self.database = Some(info.path);
+        self.seen = current;
+
+        if first_look || !changed {
+            return Ok(None);
+        }
+        self.kdbx.import_share_containers().map(Some)
+    }
+}
+
+fn fingerprint(path: &Path) -> Fingerprint {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
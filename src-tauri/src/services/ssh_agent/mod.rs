@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: MIT
+
+//! SSH agent serving private keys stored in the open database.
+//!
+//! Entries opt in through a `KeeAgent.settings` attachment (see
+//! [`settings`]), which points at the attachment holding the private key.
+//! Passphrase-protected keys are decrypted with the entry's password. Keys
+//! flagged to be added at database open are loaded after unlocking; others
+//! can be added from the UI. `SSH_AUTH_SOCK` clients reach the agent over
+//! the socket in [`server`].
+//!
+//! Keys never outlive the database they came from: the commands clear the
+//! agent on lock and close, and every agent request first checks that the
+//! same database is still open.
+
+pub mod keys;
+pub mod protocol;
+#[cfg(unix)]
+pub mod server;
+pub mod settings;
+
+use chrono::{DateTime, Duration, Utc};
+use ssh_key::{HashAlg, PrivateKey};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::dto::error::AppError;
+use crate::dto::ssh_agent::{SshAgentKey, SshAgentStatus, SshKeyLoadError};
+use crate::services::kdbx::KdbxService;
+use crate::utils::local_socket::socket_dir;
+use keys::{decode_private_key, sign};
+use protocol::{
+    failure, success, MessageReader, MessageWriter, SSH_AGENTC_REMOVE_ALL_IDENTITIES,
+    SSH_AGENTC_REMOVE_IDENTITY, SSH_AGENTC_REQUEST_IDENTITIES, SSH_AGENTC_SIGN_REQUEST,
+    SSH_AGENT_IDENTITIES_ANSWER, SSH_AGENT_SIGN_RESPONSE,
+};
+use settings::{KeeAgentSettings, KeyLocation, SETTINGS_ATTACHMENT};
+
+const SOCKET_NAME: &str = "ssh-agent.sock";
+
+/// Path of the agent socket, the value for `SSH_AUTH_SOCK`.
+pub fn socket_path() -> PathBuf {
+    socket_dir().join(SOCKET_NAME)
+}
+
+/// Asks the user whether the described key may sign a request. Called from
+/// the socket thread, so it may block.
+pub type UseConfirmer = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+struct LoadedKey {
+    entry_id: String,
+    title: String,
+    key: PrivateKey,
+    /// Public key in wire format, as clients refer to it
+    public_blob: Vec<u8>,
+    confirm: bool,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl LoadedKey {
+    fn comment(&self) -> &str {
+        if self.key.comment().is_empty() {
+            &self.title
+        } else {
+            self.key.comment()
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        self.key
+            .public_key()
+            .fingerprint(HashAlg::Sha256)
+            .to_string()
+    }
+
+    fn to_dto(&self) -> SshAgentKey {
+        SshAgentKey {
+            entry_id: self.entry_id.clone(),
+            title: self.title.clone(),
+            comment: self.comment().to_string(),
+            algorithm: self.key.algorithm().as_str().to_string(),
+            fingerprint: self.fingerprint(),
+            confirm: self.confirm,
+            expires_at: self.expires_at.map(|time| time.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct AgentState {
+    /// Database the keys belong to
+    database_path: Option<String>,
+    keys: Vec<LoadedKey>,
+    load_errors: Vec<SshKeyLoadError>,
+}
+
+pub struct SshAgentService {
+    kdbx: Arc<KdbxService>,
+    confirm_use: UseConfirmer,
+    socket_path: PathBuf,
+    listening: AtomicBool,
+    state: Mutex<AgentState>,
+}
+
+impl SshAgentService {
+    /// Creates an agent for `kdbx` that will listen on `socket_path`.
+    /// Signatures with keys that require confirmation only happen if
+    /// `confirm_use` returns true.
+    pub fn new(
+        kdbx: Arc<KdbxService>,
+        socket_path: PathBuf,
+        confirm_use: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            kdbx,
+            confirm_use: Box::new(confirm_use),
+            socket_path,
+            listening: AtomicBool::new(false),
+            state: Mutex::new(AgentState::default()),
+        }
+    }
+
+    pub fn status(&self) -> Result<SshAgentStatus, AppError> {
+        self.prune()?;
+        let state = self.state.lock().map_err(|_| AppError::Lock)?;
+        Ok(SshAgentStatus {
+            socket_path: self
+                .listening
+                .load(Ordering::SeqCst)
+                .then(|| self.socket_path.to_string_lossy().into_owned()),
+            keys: state.keys.iter().map(LoadedKey::to_dto).collect(),
+            load_errors: state.load_errors.clone(),
+        })
+    }
+
+    /// Adds the keys of every entry set to be added when the database opens.
+    /// Keys that fail to load are listed in the status rather than failing
+    /// the unlock.
+    pub fn load_database_keys(&self) -> Result<SshAgentStatus, AppError> {
+        let mut loaded = Vec::new();
+        let mut load_errors = Vec::new();
+        for entry_id in self
+            .kdbx
+            .find_entries_with_attachment(SETTINGS_ATTACHMENT)?
+        {
+            let settings = self
+                .kdbx
+                .get_entry_attachment(&entry_id, SETTINGS_ATTACHMENT)
+                .and_then(|content| KeeAgentSettings::parse(&content));
+            match settings {
+                Ok(settings) if settings.allow_use_of_ssh_key && settings.add_at_database_open => {
+                    match self.load_key(&entry_id, &settings) {
+                        Ok(key) => loaded.push(key),
+                        Err(err) => load_errors.push(self.load_error(&entry_id, &err)),
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => load_errors.push(self.load_error(&entry_id, &err)),
+            }
+        }
+
+        let path = self.kdbx.get_info()?.path;
+        {
+            let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+            if state.database_path.as_deref() != Some(path.as_str()) {
+                *state = AgentState::default();
+            }
+            state.database_path = Some(path);
+            for key in loaded {
+                insert_key(&mut state.keys, key);
+            }
+            state.load_errors = load_errors;
+        }
+        self.status()
+    }
+
+    /// Adds the key of one entry, whether or not it is set to be added at
+    /// database open.
+    pub fn add_key(&self, entry_id: &str) -> Result<SshAgentKey, AppError> {
+        let settings = match self
+            .kdbx
+            .get_entry_attachment(entry_id, SETTINGS_ATTACHMENT)
+        {
+            Ok(content) => KeeAgentSettings::parse(&content)?,
+            Err(AppError::AttachmentNotFound(_)) => {
+                return Err(AppError::SshAgent(
+                    "The entry has no SSH key settings".to_string(),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+        if !settings.allow_use_of_ssh_key {
+            return Err(AppError::SshAgent(
+                "SSH key use is not allowed for this entry".to_string(),
+            ));
+        }
+        let key = self.load_key(entry_id, &settings)?;
+        let path = self.kdbx.get_info()?.path;
+
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        if state.database_path.as_deref() != Some(path.as_str()) {
+            *state = AgentState::default();
+            state.database_path = Some(path);
+        }
+        state.load_errors.retain(|error| error.entry_id != entry_id);
+        let dto = key.to_dto();
+        insert_key(&mut state.keys, key);
+        Ok(dto)
+    }
+
+    /// Removes the key of an entry from the agent.
+    pub fn remove_key(&self, entry_id: &str) -> Result<(), AppError> {
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        let count = state.keys.len();
+        state.keys.retain(|key| key.entry_id != entry_id);
+        if state.keys.len() == count {
+            return Err(AppError::SshAgent(format!(
+                "No key loaded for entry {entry_id}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Removes every key, e.g. when the database is locked or closed.
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = AgentState::default();
+        }
+    }
+
+    /// Answers one agent protocol message.
+    pub fn handle_message(&self, message: &[u8]) -> Vec<u8> {
+        let Some((&message_type, payload)) = message.split_first() else {
+            return failure();
+        };
+        if self.prune().is_err() {
+            return failure();
+        }
+
+        let response = match message_type {
+            SSH_AGENTC_REQUEST_IDENTITIES => self.identities(),
+            SSH_AGENTC_SIGN_REQUEST => self.sign_request(payload),
+            SSH_AGENTC_REMOVE_IDENTITY => self.remove_identity(payload),
+            SSH_AGENTC_REMOVE_ALL_IDENTITIES => self.state.lock().ok().map(|mut state| {
+                state.keys.clear();
+                success()
+            }),
+            // Keys come from the database only; adding, locking and
+            // extensions are refused
+            _ => None,
+        };
+        response.unwrap_or_else(failure)
+    }
+
+    fn identities(&self) -> Option<Vec<u8>> {
+        let state = self.state.lock().ok()?;
+        let count = u32::try_from(state.keys.len()).ok()?;
+        let answer = state.keys.iter().fold(
+            MessageWriter::new(SSH_AGENT_IDENTITIES_ANSWER).u32(count),
+            |answer, key| {
+                answer
+                    .string(&key.public_blob)
+                    .string(key.comment().as_bytes())
+            },
+        );
+        Some(answer.finish())
+    }
+
+    fn sign_request(&self, payload: &[u8]) -> Option<Vec<u8>> {
+        let mut reader = MessageReader::new(payload);
+        let blob = reader.string()?;
+        let data = reader.string()?;
+        let flags = reader.u32().unwrap_or(0);
+
+        // Prompting may take a while; don't hold the lock meanwhile
+        let (key, confirm, description) = {
+            let state = self.state.lock().ok()?;
+            let loaded = state.keys.iter().find(|key| key.public_blob == blob)?;
+            (
+                loaded.key.clone(),
+                loaded.confirm,
+                format!("{} ({})", loaded.title, loaded.fingerprint()),
+            )
+        };
+        if confirm && !(self.confirm_use)(&description) {
+            return None;
+        }
+
+        let signature = sign(&key, data, flags).ok()?;
+        Some(
+            MessageWriter::new(SSH_AGENT_SIGN_RESPONSE)
+                .string(&signature)
+                .finish(),
+        )
+    }
+
+    fn remove_identity(&self, payload: &[u8]) -> Option<Vec<u8>> {
+        let blob = MessageReader::new(payload).string()?;
+        let mut state = self.state.lock().ok()?;
+        let count = state.keys.len();
+        state.keys.retain(|key| key.public_blob != blob);
+        (state.keys.len() < count).then(success)
+    }
+
+    /// Drops expired keys, and all keys once their database is no longer
+    /// open.
+    fn prune(&self) -> Result<(), AppError> {
+        let open_path = self.kdbx.get_info().ok().map(|info| info.path);
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        if state.database_path.is_some() && state.database_path != open_path {
+            *state = AgentState::default();
+        }
+        let now = Utc::now();
+        state
+            .keys
+            .retain(|key| key.expires_at.is_none_or(|expires_at| expires_at > now));
+        Ok(())
+    }
+
+    fn load_key(&self, entry_id: &str, settings: &KeeAgentSettings) -> Result<LoadedKey, AppError> {
+        let content = match &settings.location {
+            Some(KeyLocation::Attachment(name)) => {
+                self.kdbx.get_entry_attachment(entry_id, name)?
+            }
+            Some(KeyLocation::File(path)) => std::fs::read(path)?,
+            None => {
+                return Err(AppError::SshAgent(
+                    "The SSH key settings name no key".to_string(),
+                ))
+            }
+        };
+        let password = self.kdbx.get_entry_password(entry_id)?;
+        let key = decode_private_key(&content, &password)?;
+        let public_blob = key
+            .public_key()
+            .to_bytes()
+            .map_err(|e| AppError::SshAgent(format!("Invalid SSH key: {e}")))?;
+
+        Ok(LoadedKey {
+            entry_id: entry_id.to_string(),
+            title: self.kdbx.get_entry(entry_id)?.title,
+            key,
+            public_blob,
+            confirm: settings.use_confirm_constraint,
+            expires_at: settings
+                .use_lifetime_constraint
+                .then(|| Utc::now() + Duration::seconds(i64::from(settings.lifetime_seconds))),
+        })
+    }
+
+    fn load_error(&self, entry_id: &str, err: &AppError) -> SshKeyLoadError {
+        SshKeyLoadError {
+            entry_id: entry_id.to_string(),
+            title: self
+                .kdbx
+                .get_entry(entry_id)
+                .map(|entry| entry.title)
+                .unwrap_or_default(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Adds `key`, replacing an earlier copy of the same key or from the same
+/// entry.
+fn insert_key(keys: &mut Vec<LoadedKey>, key: LoadedKey) {
+    keys.retain(|existing| {
+        existing.entry_id != key.entry_id && existing.public_blob != key.public_blob
+    });
+    keys.push(key);
+}
@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MIT
+
+//! SSH agent wire format (draft-miller-ssh-agent).
+//!
+//! Every message is a 32-bit big-endian length followed by a type byte and
+//! its payload. Strings inside payloads are length-prefixed the same way.
+
+use std::io::{self, Read, Write};
+
+/// Largest message accepted; matches `OpenSSH`'s agent limit.
+pub const MAX_MESSAGE_SIZE: usize = 256 * 1024;
+
+pub const SSH_AGENT_FAILURE: u8 = 5;
+pub const SSH_AGENT_SUCCESS: u8 = 6;
+pub const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+pub const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+pub const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+pub const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+pub const SSH_AGENTC_REMOVE_IDENTITY: u8 = 18;
+pub const SSH_AGENTC_REMOVE_ALL_IDENTITIES: u8 = 19;
+
+/// Reads one length-prefixed message. Returns `None` at end of stream.
+pub fn read_message(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let length = u32::from_be_bytes(length) as usize;
+    if length == 0 || length > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid agent message length {length}"),
+        ));
+    }
+
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Writes one length-prefixed message and flushes the writer.
+pub fn write_message(writer: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(message.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Message is too large"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(message)?;
+    writer.flush()
+}
+
+/// Builds a message payload.
+#[derive(Debug, Default)]
+pub struct MessageWriter(Vec<u8>);
+
+impl MessageWriter {
+    pub fn new(message_type: u8) -> Self {
+        Self(vec![message_type])
+    }
+
+    #[must_use]
+    pub fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Appends a length-prefixed byte string.
+    #[must_use]
+    pub fn string(self, value: &[u8]) -> Self {
+        // Payloads are capped at MAX_MESSAGE_SIZE, so lengths fit in a u32
+        let length = u32::try_from(value.len()).unwrap_or(u32::MAX);
+        let mut this = self.u32(length);
+        this.0.extend_from_slice(value);
+        this
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// Reads fields from a message payload.
+pub struct MessageReader<'a>(&'a [u8]);
+
+impl<'a> MessageReader<'a> {
+    pub fn new(payload: &'a [u8]) -> Self {
+        Self(payload)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        let (bytes, rest) = self.0.split_first_chunk::<4>()?;
+        self.0 = rest;
+        Some(u32::from_be_bytes(*bytes))
+    }
+
+    /// Reads a length-prefixed byte string.
+    pub fn string(&mut self) -> Option<&'a [u8]> {
+        let length = self.u32()? as usize;
+        if length > self.0.len() {
+            return None;
+        }
+        let (value, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Some(value)
+    }
+}
+
+/// The one-byte reply for requests the agent refuses.
+pub fn failure() -> Vec<u8> {
+    vec![SSH_AGENT_FAILURE]
+}
+
+pub fn success() -> Vec<u8> {
+    vec![SSH_AGENT_SUCCESS]
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &[SSH_AGENTC_REQUEST_IDENTITIES]).expect("write");
+        assert_eq!(buffer, vec![0, 0, 0, 1, SSH_AGENTC_REQUEST_IDENTITIES]);
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(
+            read_message(&mut reader).expect("read"),
+            Some(vec![SSH_AGENTC_REQUEST_IDENTITIES])
+        );
+        assert_eq!(read_message(&mut reader).expect("read"), None);
+    }
+
+    #[test]
+    fn test_rejects_oversized_messages() {
+        let mut reader: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_strings_are_length_prefixed() {
+        let payload = MessageWriter::new(SSH_AGENT_SIGN_RESPONSE)
+            .string(b"sig")
+            .u32(7)
+            .finish();
+        assert_eq!(payload, vec![14, 0, 0, 0, 3, b's', b'i', b'g', 0, 0, 0, 7]);
+
+        let mut reader = MessageReader::new(&payload[1..]);
+        assert_eq!(reader.string(), Some(&b"sig"[..]));
+        assert_eq!(reader.u32(), Some(7));
+        assert_eq!(reader.string(), None);
+    }
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT
+
+//! Unix socket the SSH agent listens on, compatible with `SSH_AUTH_SOCK`.
+
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+use super::protocol::{read_message, write_message};
+use super::SshAgentService;
+use crate::dto::error::AppError;
+use crate::utils::local_socket::bind;
+
+/// Listens on the service's socket and answers each connection on its own
+/// thread.
+pub fn start(service: Arc<SshAgentService>) -> Result<(), AppError> {
+    let listener = bind(&service.socket_path)?;
+    service.listening.store(true, Ordering::SeqCst);
+    thread::Builder::new()
+        .name("ssh-agent-socket".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let service = Arc::clone(&service);
+                thread::spawn(move || serve(&service, stream));
+            }
+        })?;
+    Ok(())
+}
+
+fn serve(service: &SshAgentService, mut stream: UnixStream) {
+    while let Ok(Some(message)) = read_message(&mut stream) {
+        let response = service.handle_message(&message);
+        if write_message(&mut stream, &response).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT
+
+//! `KeeAgent.settings`, the per-entry SSH settings written by `KeeAgent` and
+//! `KeePassXC`.
+//!
+//! The settings are a small XML document stored as an entry attachment,
+//! usually in UTF-16 with a byte order mark. Only flat elements are read, so
+//! a simple element lookup is enough; unknown elements are ignored and
+//! missing ones take `KeeAgent`'s defaults.
+
+use crate::dto::error::AppError;
+
+/// Attachment name the settings are stored under.
+pub const SETTINGS_ATTACHMENT: &str = "KeeAgent.settings";
+
+/// Default key lifetime in seconds when the lifetime constraint is on.
+const DEFAULT_LIFETIME_SECONDS: u32 = 600;
+
+/// Where the private key of an entry is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyLocation {
+    /// An attachment of the same entry
+    Attachment(String),
+    /// A file outside the database
+    File(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct KeeAgentSettings {
+    /// Master switch: keys of entries without it are never served
+    pub allow_use_of_ssh_key: bool,
+    pub add_at_database_open: bool,
+    pub remove_at_database_close: bool,
+    /// Ask the user before every signature with the key
+    pub use_confirm_constraint: bool,
+    pub use_lifetime_constraint: bool,
+    /// Seconds the key stays loaded when the lifetime constraint is on
+    pub lifetime_seconds: u32,
+    pub location: Option<KeyLocation>,
+}
+
+impl KeeAgentSettings {
+    /// Parses the content of a `KeeAgent.settings` attachment.
+    pub fn parse(content: &[u8]) -> Result<Self, AppError> {
+        let xml = decode_text(content)
+            .filter(|xml| xml.contains("<EntrySettings"))
+            .ok_or_else(|| AppError::SshAgent("Invalid KeeAgent.settings".to_string()))?;
+
+        let flag = |name: &str| {
+            element_text(&xml, name).is_some_and(|value| value.eq_ignore_ascii_case("true"))
+        };
+        let location = element_text(&xml, "Location").and_then(|location| {
+            let selected = element_text(&location, "SelectedType").unwrap_or_default();
+            let name = |element: &str| element_text(&location, element).filter(|v| !v.is_empty());
+            if selected.eq_ignore_ascii_case("file") {
+                name("FileName").map(KeyLocation::File)
+            } else {
+                name("AttachmentName").map(KeyLocation::Attachment)
+            }
+        });
+
+        Ok(Self {
+            allow_use_of_ssh_key: flag("AllowUseOfSshKey"),
+            add_at_database_open: flag("AddAtDatabaseOpen"),
+            remove_at_database_close: flag("RemoveAtDatabaseClose"),
+            use_confirm_constraint: flag("UseConfirmConstraintWhenAdding"),
+            use_lifetime_constraint: flag("UseLifetimeConstraintWhenAdding"),
+            lifetime_seconds: element_text(&xml, "LifetimeConstraintDuration")
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_LIFETIME_SECONDS),
+            location,
+        })
+    }
+}
+
+/// Decodes UTF-8 or, when a byte order mark says so, UTF-16 text.
+fn decode_text(content: &[u8]) -> Option<String> {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).ok()
+    };
+
+    match content {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8(rest.to_vec()).ok(),
+        _ => String::from_utf8(content.to_vec()).ok(),
+    }
+}
+
+/// Text of the first `<name>` element, unescaped. Self-closing elements
+/// yield an empty string.
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{name}");
+    let mut search_from = 0;
+    while let Some(found) = xml[search_from..].find(&open) {
+        let start = search_from + found + open.len();
+        search_from = start;
+        let rest = &xml[start..];
+        // Skip elements that merely share the prefix, e.g. <FileNameX>
+        if !rest.starts_with(['>', '/', ' ', '\t', '\r', '\n']) {
+            continue;
+        }
+        let tag_end = rest.find('>')?;
+        if rest[..tag_end].ends_with('/') {
+            return Some(String::new());
+        }
+        let body = &rest[tag_end + 1..];
+        let close = body.find(&format!("</{name}>"))?;
+        return Some(unescape(body[..close].trim()));
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = r#"<?xml version="1.0" encoding="UTF-16"?>
+<EntrySettings xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AllowUseOfSshKey>true</AllowUseOfSshKey>
+  <AddAtDatabaseOpen>true</AddAtDatabaseOpen>
+  <RemoveAtDatabaseClose>true</RemoveAtDatabaseClose>
+  <UseConfirmConstraintWhenAdding>false</UseConfirmConstraintWhenAdding>
+  <UseLifetimeConstraintWhenAdding>true</UseLifetimeConstraintWhenAdding>
+  <LifetimeConstraintDuration>3600</LifetimeConstraintDuration>
+  <Location>
+    <SelectedType>attachment</SelectedType>
+    <AttachmentName>id_ed25519 &amp; co</AttachmentName>
+    <SaveAttachmentToTempFile>false</SaveAttachmentToTempFile>
+    <FileName />
+  </Location>
+</EntrySettings>"#;
+
+    fn utf16le_with_bom(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_parses_utf16_settings() {
+        let settings =
+            KeeAgentSettings::parse(&utf16le_with_bom(SETTINGS)).expect("Failed to parse");
+
+        assert!(settings.allow_use_of_ssh_key);
+        assert!(settings.add_at_database_open);
+        assert!(!settings.use_confirm_constraint);
+        assert!(settings.use_lifetime_constraint);
+        assert_eq!(settings.lifetime_seconds, 3600);
+        assert_eq!(
+            settings.location,
+            Some(KeyLocation::Attachment("id_ed25519 & co".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_missing_elements_use_defaults() {
+        let settings = KeeAgentSettings::parse(
+            b"<EntrySettings><AllowUseOfSshKey>true</AllowUseOfSshKey></EntrySettings>",
+        )
+        .expect("Failed to parse");
+
+        assert!(settings.allow_use_of_ssh_key);
+        assert!(!settings.add_at_database_open);
+        assert_eq!(settings.lifetime_seconds, DEFAULT_LIFETIME_SECONDS);
+        assert_eq!(settings.location, None);
+    }
+
+    #[test]
+    fn test_file_location_and_invalid_content() {
+        let xml = "<EntrySettings><Location><SelectedType>file</SelectedType>\
+                   <AttachmentName /><FileName>/home/me/.ssh/id_rsa</FileName>\
+                   </Location></EntrySettings>";
+        let settings = KeeAgentSettings::parse(xml.as_bytes()).expect("Failed to parse");
+        assert_eq!(
+            settings.location,
+            Some(KeyLocation::File("/home/me/.ssh/id_rsa".to_string()))
+        );
+
+        assert!(KeeAgentSettings::parse(b"not settings").is_err());
+    }
+}
@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: MIT
+
+//! Backoff after failed unlock attempts.
+//!
+//! Wrong passwords are counted per database file. The first
+//! [`FREE_ATTEMPTS`] failures cost nothing; after that each attempt has to
+//! wait twice as long as the one before, up to [`MAX_DELAY_SECS`]. The
+//! counters are kept in app data, so restarting the app does not reset
+//! them, and a successful unlock clears the counter of that database.
+//!
+//! `KdbxService` applies the backoff itself when it is given a throttle, so
+//! the app, the CLI and the git credential helper all count against the
+//! same file. An exclusive lock on a file next to it keeps their updates
+//! from overwriting each other.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use fs4::fs_std::FileExt;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::error::AppError;
+use crate::utils::atomic_write::{atomic_write, AtomicWriteOptions};
+
+const STATE_FILE: &str = "unlock-throttle.json";
+const LOCK_FILE: &str = "unlock-throttle.lock";
+
+/// Bundle identifier from `tauri.conf.json`; names the app data directory.
+const APP_IDENTIFIER: &str = "com.mithril-vault.app";
+
+/// Failed attempts allowed in a row before the backoff starts.
+pub const FREE_ATTEMPTS: u32 = 3;
+
+pub const MAX_DELAY_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FailureRecord {
+    failures: u32,
+    last_failure: DateTime<Utc>,
+}
+
+impl FailureRecord {
+    /// Time to wait after the last failure before the next attempt.
+    fn delay(&self) -> Duration {
+        let Some(doublings) = self.failures.checked_sub(FREE_ATTEMPTS) else {
+            return Duration::ZERO;
+        };
+        let secs = 1u64
+            .checked_shl(doublings)
+            .map_or(MAX_DELAY_SECS, |secs| secs.min(MAX_DELAY_SECS));
+        Duration::from_secs(secs)
+    }
+
+    fn remaining(&self, now: DateTime<Utc>) -> Duration {
+        let elapsed = (now - self.last_failure).to_std().unwrap_or_default();
+        self.delay().saturating_sub(elapsed)
+    }
+}
+
+/// Failure records by canonical database path.
+type Counters = BTreeMap<String, FailureRecord>;
+
+pub struct UnlockThrottleService {
+    /// Counters as last read from or written to the state file
+    state: Mutex<Counters>,
+    state_path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl UnlockThrottleService {
+    /// Creates the service with its counters in `data_dir`.
+    pub fn new(data_dir: &Path) -> Result<Self, AppError> {
+        std::fs::create_dir_all(data_dir)?;
+        let state_path = data_dir.join(STATE_FILE);
+        let state = match std::fs::read_to_string(&state_path) {
+            // A damaged file only loses the counters; it must not keep the
+            // app from starting
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            state: Mutex::new(state),
+            state_path,
+            lock_path: data_dir.join(LOCK_FILE),
+        })
+    }
+
+    /// Creates the service with the app's counters, for the binaries that
+    /// run without Tauri. The directory is the one Tauri resolves as
+    /// `app_local_data_dir`.
+    pub fn new_in_app_data() -> Result<Self, AppError> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| AppError::Io("No local data directory".to_string()))?;
+        Self::new(&data_dir.join(APP_IDENTIFIER))
+    }
+
+    /// Runs `unlock` for the database at `path`, unless earlier failures
+    /// still hold it back with [`AppError::UnlockThrottled`].
+    ///
+    /// The attempt is counted as a failure before `unlock` runs, so attempts
+    /// made meanwhile, here or in another process, already have to wait for
+    /// it. A success clears the counter and errors other than
+    /// [`AppError::InvalidPassword`] take the attempt back. The counters are
+    /// not locked while `unlock` runs.
+    pub fn attempt<T>(
+        &self,
+        path: &str,
+        unlock: impl FnOnce() -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let key = canonical_key(path);
+        {
+            let (mut state, _file_lock) = self.lock_state()?;
+            if let Some(record) = state.get(&key) {
+                let remaining = record.remaining(Utc::now());
+                if !remaining.is_zero() {
+                    return Err(AppError::UnlockThrottled(ceil_secs(remaining)));
+                }
+            }
+            let record = state.entry(key.clone()).or_insert(FailureRecord {
+                failures: 0,
+                last_failure: Utc::now(),
+            });
+            record.failures = record.failures.saturating_add(1);
+            record.last_failure = Utc::now();
+            self.save(&state);
+        }
+
+        let result = unlock();
+
+        let (mut state, _file_lock) = self.lock_state()?;
+        match &result {
+            Ok(_) => {
+                state.remove(&key);
+            }
+            Err(AppError::InvalidPassword) => {
+                // Already counted; the delay runs from when the attempt failed
+                let record = state.entry(key).or_insert(FailureRecord {
+                    failures: 1,
+                    last_failure: Utc::now(),
+                });
+                record.last_failure = Utc::now();
+            }
+            Err(_) => {
+                if let Some(record) = state.get_mut(&key) {
+                    record.failures = record.failures.saturating_sub(1);
+                    if record.failures == 0 {
+                        state.remove(&key);
+                    }
+                }
+            }
+        }
+        // The result stands even if the counters cannot be kept; they only
+        // come back after a restart
+        self.save(&state);
+        result
+    }
+
+    /// Returns how long the next attempt for `path` has to wait.
+    pub fn remaining_delay(&self, path: &str) -> Result<Duration, AppError> {
+        let (state, _file_lock) = self.lock_state()?;
+        Ok(state
+            .get(&canonical_key(path))
+            .map_or(Duration::ZERO, |record| record.remaining(Utc::now())))
+    }
+
+    /// Locks the counters in this process and, if possible, the state file
+    /// for other processes, and reads them again from the file.
+    fn lock_state(&self) -> Result<(MutexGuard<'_, Counters>, Option<File>), AppError> {
+        let mut state = self.state.lock().map_err(|_| AppError::Lock)?;
+        let file_lock = self.lock_file();
+        self.reload(&mut state);
+        Ok((state, file_lock))
+    }
+
+    /// Takes the exclusive lock on the lock file, held until the returned
+    /// file is dropped. Without it (for example when the directory is gone)
+    /// only this process is kept in step.
+    fn lock_file(&self) -> Option<File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.lock_path)
+            .and_then(|file| file.lock_exclusive().map(|()| file));
+        match file {
+            Ok(file) => Some(file),
+            Err(err) => {
+                log::warn!("Failed to lock unlock throttle state: {err}");
+                None
+            }
+        }
+    }
+
+    /// Replaces `state` with the state file. A missing or damaged file
+    /// keeps the counters in memory.
+    fn reload(&self, state: &mut Counters) {
+        if let Some(stored) = std::fs::read_to_string(&self.state_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            *state = stored;
+        }
+    }
+
+    /// Writes `state` to the state file. A failure is logged; the counters
+    /// stay in memory.
+    fn save(&self, state: &Counters) {
+        let result = serde_json::to_vec_pretty(state)
+            .map_err(|e| AppError::Io(e.to_string()))
+            .and_then(|content| {
+                atomic_write(
+                    &self.state_path.to_string_lossy(),
+                    &AtomicWriteOptions::default(),
+                    |file| Ok(file.write_all(&content)?),
+                )
+            });
+        if let Err(err) = result {
+            log::warn!("Failed to save unlock throttle state: {err}");
+        }
+    }
+}
+
+/// Counters are kept per file, however the path is spelled.
+fn canonical_key(path: &str) -> String {
+    std::fs::canonicalize(path).map_or_else(
+        |_| path.to_string(),
+        |canonical| canonical.to_string_lossy().into_owned(),
+    )
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
@@ -0,0 +1,459 @@
+// SPDX-License-Identifier: MIT
+
+//! Parser for auto-type keystroke sequences.
+//!
+//! Turns a sequence such as `{USERNAME}{TAB}{PASSWORD}{ENTER}{DELAY 200}` into
+//! [`KeyAction`]s that a keyboard injector can replay. Supported syntax:
+//! - plain characters are typed as text
+//! - special keys in braces (`{TAB}`, `{F5}`, `{NUMPAD3}`, `{VKEY 13}` …),
+//!   optionally repeated with a count (`{TAB 3}`, `{a 5}`)
+//! - escaped characters: `{+}`, `{^}`, `{%}`, `{#}`, `{~}`, `{(}`, `{)}`,
+//!   `{{}` and `{}}`
+//! - modifiers for the next key or parenthesized group: `+` shift, `^` ctrl,
+//!   `%` alt, `#` meta; `~` presses Enter
+//! - commands: `{DELAY n}`, `{DELAY=n}` and `{CLEARFIELD}`
+//! - field placeholders (`{USERNAME}`, `{S:Name}`, `{REF:...}` …), which are
+//!   kept as [`KeyAction::Placeholder`] for the caller to resolve
+
+use crate::dto::autotype::{Key, KeyAction, KeyModifiers};
+use crate::dto::error::AppError;
+
+/// Sequence used when neither the entry nor its groups define one.
+pub const DEFAULT_SEQUENCE: &str = "{USERNAME}{TAB}{PASSWORD}{ENTER}";
+
+/// Upper bound for `{KEY n}` repeat counts.
+const MAX_REPEAT: u32 = 1000;
+
+const PLACEHOLDERS: &[&str] = &[
+    "TITLE",
+    "USERNAME",
+    "PASSWORD",
+    "URL",
+    "NOTES",
+    "UUID",
+    "GROUP",
+    "GROUP_PATH",
+    "DB_NAME",
+    "DB_PATH",
+    "ENV_DIRSEP",
+    "NEWPASSWORD",
+];
+
+const PLACEHOLDER_PREFIXES: &[&str] = &["S:", "REF:", "URL:", "ENV:", "C:", "CMD:", "DT_"];
+
+/// Parses an auto-type sequence into key actions.
+pub fn parse_sequence(sequence: &str) -> Result<Vec<KeyAction>, AppError> {
+    let mut parser = Parser {
+        chars: sequence.chars().collect(),
+        pos: 0,
+    };
+    parser.parse(false)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    /// Parses actions until the end of input, or until the closing `)` of a
+    /// modifier group when `in_group` is set.
+    fn parse(&mut self, in_group: bool) -> Result<Vec<KeyAction>, AppError> {
+        let mut actions = Vec::new();
+        let mut modifiers = KeyModifiers::default();
+
+        while let Some(c) = self.next() {
+            let start = self.pos - 1;
+            let step = match c {
+                '+' | '^' | '%' | '#' => {
+                    match c {
+                        '+' => modifiers.shift = true,
+                        '^' => modifiers.ctrl = true,
+                        '%' => modifiers.alt = true,
+                        _ => modifiers.meta = true,
+                    }
+                    continue;
+                }
+                '~' => vec![key(Key::Enter)],
+                '{' => self.token(start)?,
+                '(' if !modifiers.is_empty() => split_text(self.parse(true)?),
+                ')' if in_group => {
+                    if !modifiers.is_empty() {
+                        return Err(invalid(format!("modifier without a key at {start}")));
+                    }
+                    return Ok(actions);
+                }
+                '}' | '(' | ')' => {
+                    return Err(invalid(format!("unexpected '{c}' at {start}")));
+                }
+                c => vec![KeyAction::Text {
+                    text: c.to_string(),
+                }],
+            };
+
+            for action in step {
+                push(&mut actions, apply_modifiers(action, modifiers, start)?);
+            }
+            modifiers = KeyModifiers::default();
+        }
+
+        if in_group {
+            return Err(invalid("unterminated '('".to_string()));
+        }
+        if !modifiers.is_empty() {
+            return Err(invalid("modifier without a key at end".to_string()));
+        }
+        Ok(actions)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    /// Parses a `{...}` token whose opening brace is at `start`.
+    fn token(&mut self, start: usize) -> Result<Vec<KeyAction>, AppError> {
+        // `{}}` types a closing brace
+        if self.chars.get(self.pos..self.pos + 2) == Some(&['}', '}']) {
+            self.pos += 2;
+            return Ok(vec![text('}')]);
+        }
+
+        let end = self.chars[self.pos..]
+            .iter()
+            .position(|c| *c == '}')
+            .map(|offset| self.pos + offset)
+            .ok_or_else(|| invalid(format!("unterminated '{{' at {start}")))?;
+        let content: String = self.chars[self.pos..end].iter().collect();
+        self.pos = end + 1;
+
+        parse_token(&content)
+    }
+}
+
+fn parse_token(content: &str) -> Result<Vec<KeyAction>, AppError> {
+    if content.is_empty() {
+        return Err(invalid("empty braces".to_string()));
+    }
+    if is_placeholder(content) {
+        return Ok(vec![KeyAction::Placeholder {
+            name: content.to_string(),
+        }]);
+    }
+
+    let mut chars = content.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(vec![text(c)]);
+    }
+
+    let upper = content.to_ascii_uppercase();
+    if let Some(value) = upper.strip_prefix("DELAY=") {
+        return Ok(vec![KeyAction::DefaultDelay {
+            milliseconds: parse_number(value.trim(), content)?,
+        }]);
+    }
+
+    let (name, argument) = match upper.split_once(' ') {
+        Some((name, argument)) => (name, Some(argument.trim())),
+        None => (upper.as_str(), None),
+    };
+
+    match (name, argument) {
+        ("DELAY", Some(value)) => Ok(vec![KeyAction::Delay {
+            milliseconds: parse_number(value, content)?,
+        }]),
+        ("CLEARFIELD", None) => Ok(vec![KeyAction::ClearField]),
+        ("VKEY", Some(value)) => Ok(vec![key(Key::VirtualKey(parse_number(value, content)?))]),
+        (name, argument) => {
+            let action = match named_key(name) {
+                Some(named) => key(named),
+                // `{x n}` repeats a single character
+                None => match content.split_once(' ') {
+                    Some((single, _)) if single.chars().count() == 1 && argument.is_some() => {
+                        KeyAction::Text {
+                            text: single.to_string(),
+                        }
+                    }
+                    _ => return Err(invalid(format!("unknown token {{{content}}}"))),
+                },
+            };
+            let count = match argument {
+                Some(value) => parse_number::<u32>(value, content)?,
+                None => 1,
+            };
+            if count > MAX_REPEAT {
+                return Err(invalid(format!(
+                    "repeat count above {MAX_REPEAT} in {{{content}}}"
+                )));
+            }
+            Ok(vec![action; count as usize])
+        }
+    }
+}
+
+fn named_key(name: &str) -> Option<Key> {
+    let key = match name {
+        "TAB" => Key::Tab,
+        "ENTER" => Key::Enter,
+        "SPACE" => Key::Space,
+        "BACKSPACE" | "BS" | "BKSP" => Key::Backspace,
+        "DELETE" | "DEL" => Key::Delete,
+        "INSERT" | "INS" => Key::Insert,
+        "HOME" => Key::Home,
+        "END" => Key::End,
+        "PGUP" => Key::PageUp,
+        "PGDN" => Key::PageDown,
+        "UP" => Key::Up,
+        "DOWN" => Key::Down,
+        "LEFT" => Key::Left,
+        "RIGHT" => Key::Right,
+        "ESC" => Key::Escape,
+        "CAPSLOCK" => Key::CapsLock,
+        "NUMLOCK" => Key::NumLock,
+        "SCROLLLOCK" => Key::ScrollLock,
+        "PRTSC" => Key::PrintScreen,
+        "BREAK" => Key::Break,
+        "HELP" => Key::Help,
+        "APPS" => Key::Apps,
+        "WIN" => Key::Win,
+        "LWIN" => Key::LeftWin,
+        "RWIN" => Key::RightWin,
+        "ADD" => Key::Add,
+        "SUBTRACT" => Key::Subtract,
+        "MULTIPLY" => Key::Multiply,
+        "DIVIDE" => Key::Divide,
+        _ => {
+            if let Some(digit) = name.strip_prefix("NUMPAD") {
+                return digit.parse().ok().filter(|d| *d <= 9).map(Key::Numpad);
+            }
+            return name
+                .strip_prefix('F')
+                .and_then(|n| n.parse().ok())
+                .filter(|n| (1..=16).contains(n))
+                .map(Key::Function);
+        }
+    };
+    Some(key)
+}
+
+fn is_placeholder(content: &str) -> bool {
+    let upper = content.to_ascii_uppercase();
+    PLACEHOLDERS.contains(&upper.as_str())
+        || PLACEHOLDER_PREFIXES
+            .iter()
+            .any(|prefix| upper.starts_with(prefix))
+}
+
+fn apply_modifiers(
+    action: KeyAction,
+    modifiers: KeyModifiers,
+    position: usize,
+) -> Result<KeyAction, AppError> {
+    if modifiers.is_empty() {
+        return Ok(action);
+    }
+    match action {
+        KeyAction::Key {
+            key,
+            modifiers: held,
+        } => Ok(KeyAction::Key {
+            key,
+            modifiers: held.union(modifiers),
+        }),
+        KeyAction::Text { text } if text.chars().count() == 1 => Ok(KeyAction::Key {
+            key: Key::Char(text.chars().next().unwrap_or_default()),
+            modifiers,
+        }),
+        _ => Err(invalid(format!(
+            "modifier applied to a non-key token at {position}"
+        ))),
+    }
+}
+
+/// Splits text into one action per character so that modifiers can be
+/// applied to each of them.
+fn split_text(actions: Vec<KeyAction>) -> Vec<KeyAction> {
+    actions
+        .into_iter()
+        .flat_map(|action| match action {
+            KeyAction::Text { text: chars } => chars.chars().map(text).collect(),
+            other => vec![other],
+        })
+        .collect()
+}
+
+/// Appends an action, merging consecutive text.
+fn push(actions: &mut Vec<KeyAction>, action: KeyAction) {
+    if let (Some(KeyAction::Text { text: last }), KeyAction::Text { text }) =
+        (actions.last_mut(), &action)
+    {
+        last.push_str(text);
+        return;
+    }
+    actions.push(action);
+}
+
+fn key(key: Key) -> KeyAction {
+    KeyAction::Key {
+        key,
+        modifiers: KeyModifiers::default(),
+    }
+}
+
+fn text(c: char) -> KeyAction {
+    KeyAction::Text {
+        text: c.to_string(),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, content: &str) -> Result<T, AppError> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid number in {{{content}}}")))
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::InvalidAutoTypeSequence(message)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn text_action(text: &str) -> KeyAction {
+        KeyAction::Text {
+            text: text.to_string(),
+        }
+    }
+
+    fn placeholder(name: &str) -> KeyAction {
+        KeyAction::Placeholder {
+            name: name.to_string(),
+        }
+    }
+
+    fn with(key: Key, modifiers: KeyModifiers) -> KeyAction {
+        KeyAction::Key { key, modifiers }
+    }
+
+    fn ctrl() -> KeyModifiers {
+        KeyModifiers {
+            ctrl: true,
+            ..KeyModifiers::default()
+        }
+    }
+
+    #[test]
+    fn test_default_sequence() {
+        let actions = parse_sequence("{USERNAME}{TAB}{PASSWORD}{ENTER}{DELAY 200}").expect("parse");
+        assert_eq!(
+            actions,
+            vec![
+                placeholder("USERNAME"),
+                key(Key::Tab),
+                placeholder("PASSWORD"),
+                key(Key::Enter),
+                KeyAction::Delay { milliseconds: 200 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_and_escapes_are_merged() {
+        let actions = parse_sequence("a{+}b{{}{}}{~} c").expect("parse");
+        assert_eq!(actions, vec![text_action("a+b{}~ c")]);
+    }
+
+    #[test]
+    fn test_modifiers_and_groups() {
+        let actions = parse_sequence("^a+{TAB}%(xy)~").expect("parse");
+        assert_eq!(
+            actions,
+            vec![
+                with(Key::Char('a'), ctrl()),
+                with(
+                    Key::Tab,
+                    KeyModifiers {
+                        shift: true,
+                        ..KeyModifiers::default()
+                    }
+                ),
+                with(
+                    Key::Char('x'),
+                    KeyModifiers {
+                        alt: true,
+                        ..KeyModifiers::default()
+                    }
+                ),
+                with(
+                    Key::Char('y'),
+                    KeyModifiers {
+                        alt: true,
+                        ..KeyModifiers::default()
+                    }
+                ),
+                key(Key::Enter),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeats_commands_and_named_keys() {
+        let actions = parse_sequence("{tab 2}{x 3}{DELAY=50}{CLEARFIELD}{F12}{NUMPAD7}{VKEY 13}")
+            .expect("parse");
+        assert_eq!(
+            actions,
+            vec![
+                key(Key::Tab),
+                key(Key::Tab),
+                text_action("xxx"),
+                KeyAction::DefaultDelay { milliseconds: 50 },
+                KeyAction::ClearField,
+                key(Key::Function(12)),
+                key(Key::Numpad(7)),
+                key(Key::VirtualKey(13)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_placeholders_keep_their_spelling() {
+        let actions = parse_sequence("{S:Employee ID}{url:host}{REF:P@I:0123}").expect("parse");
+        assert_eq!(
+            actions,
+            vec![
+                placeholder("S:Employee ID"),
+                placeholder("url:host"),
+                placeholder("REF:P@I:0123"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_tokens_are_rejected() {
+        for sequence in [
+            "{TABB}",
+            "{}",
+            "{TAB",
+            "abc}",
+            "{DELAY}",
+            "{DELAY abc}",
+            "{TAB 5000}",
+            "{F17}",
+            "^",
+            "+(ab",
+            "(ab)",
+            "^{USERNAME}",
+        ] {
+            let result = parse_sequence(sequence);
+            assert!(
+                matches!(result, Err(AppError::InvalidAutoTypeSequence(_))),
+                "{sequence} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+
+//! Location and setup of the app's Unix sockets (browser integration, IPC,
+//! SSH agent).
+//!
+//! Sockets live in a directory only the current user can enter, so the file
+//! permissions are the first line of access control.
+
+use std::path::PathBuf;
+
+#[cfg(unix)]
+use crate::dto::error::AppError;
+#[cfg(unix)]
+use std::fs::{self, DirBuilder};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+
+/// Directory holding the app's sockets: `$XDG_RUNTIME_DIR/mithril-vault`, or
+/// a per-user directory in the temp dir when no runtime dir is set.
+pub fn socket_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir).join("mithril-vault");
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+    std::env::temp_dir().join(format!("mithril-vault-{user}"))
+}
+
+/// Binds a socket readable only by the current user. A leftover socket from
+/// a crashed instance is replaced; a live one means the app is already
+/// running.
+#[cfg(unix)]
+pub fn bind(path: &Path) -> Result<UnixListener, AppError> {
+    if let Some(dir) = path.parent() {
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(AppError::Io(format!(
+                "Socket {} is in use by another instance",
+                path.display()
+            )));
+        }
+        fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
@@ -1,3 +1,8 @@
 // SPDX-License-Identifier: MIT
 
 pub mod atomic_write;
+pub mod autotype;
+pub mod local_socket;
+pub mod password_generator;
+pub mod password_strength;
+pub mod terminal;
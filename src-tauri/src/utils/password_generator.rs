@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT
+
+//! Random password generation from character class options.
+
+use crate::dto::error::AppError;
+use crate::dto::generator::PasswordGeneratorOptions;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const NUMBERS: &str = "0123456789";
+const SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>/?~";
+
+/// Characters that are easy to confuse with one another.
+const AMBIGUOUS: &str = "Il1O0o";
+
+/// Generates a password with at least one character from every selected
+/// class, using the OS random number generator.
+pub fn generate_password(options: &PasswordGeneratorOptions) -> Result<String, AppError> {
+    let excluded = |c: &char| {
+        (options.exclude_ambiguous && AMBIGUOUS.contains(*c))
+            || options
+                .exclude_chars
+                .as_deref()
+                .is_some_and(|chars| chars.contains(*c))
+    };
+
+    let classes: Vec<Vec<char>> = [
+        (options.uppercase, UPPERCASE),
+        (options.lowercase, LOWERCASE),
+        (options.numbers, NUMBERS),
+        (options.symbols, SYMBOLS),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, chars)| chars.chars().filter(|c| !excluded(c)).collect::<Vec<_>>())
+    .filter(|chars| !chars.is_empty())
+    .collect();
+
+    if classes.is_empty() {
+        return Err(AppError::InvalidGeneratorOptions(
+            "No characters available".into(),
+        ));
+    }
+    if options.length < classes.len() {
+        return Err(AppError::InvalidGeneratorOptions(format!(
+            "Length must be at least {}",
+            classes.len()
+        )));
+    }
+
+    let mut rng = OsRng;
+    let pool: Vec<char> = classes.iter().flatten().copied().collect();
+    let mut password: Vec<char> = classes
+        .iter()
+        .map(|chars| chars[rng.gen_range(0..chars.len())])
+        .collect();
+    password.extend((password.len()..options.length).map(|_| pool[rng.gen_range(0..pool.len())]));
+    password.shuffle(&mut rng);
+
+    Ok(password.into_iter().collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_cover_all_classes() {
+        let options = PasswordGeneratorOptions::default();
+        let password = generate_password(&options).expect("generate");
+
+        assert_eq!(password.chars().count(), options.length);
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        assert!(password.chars().any(|c| SYMBOLS.contains(c)));
+    }
+
+    #[test]
+    fn test_exclusions_are_honored() {
+        let options = PasswordGeneratorOptions {
+            length: 64,
+            symbols: false,
+            exclude_ambiguous: true,
+            exclude_chars: Some("abc".into()),
+            ..PasswordGeneratorOptions::default()
+        };
+        let password = generate_password(&options).expect("generate");
+
+        assert!(!password.chars().any(|c| AMBIGUOUS.contains(c)));
+        assert!(!password.chars().any(|c| "abc".contains(c)));
+        assert!(!password.chars().any(|c| SYMBOLS.contains(c)));
+    }
+
+    #[test]
+    fn test_invalid_options_are_rejected() {
+        let none = PasswordGeneratorOptions {
+            uppercase: false,
+            lowercase: false,
+            numbers: false,
+            symbols: false,
+            ..PasswordGeneratorOptions::default()
+        };
+        assert!(generate_password(&none).is_err());
+
+        let too_short = PasswordGeneratorOptions {
+            length: 2,
+            ..PasswordGeneratorOptions::default()
+        };
+        assert!(generate_password(&too_short).is_err());
+    }
+}
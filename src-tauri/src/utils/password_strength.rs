@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT
+
+//! Offline password strength estimation.
+//!
+//! The estimate starts from the character pool size (lowercase, uppercase,
+//! digits, symbols, other) and discounts repeated characters and runs such as
+//! `abc` or `321`. Well-known passwords, with or without trailing digits,
+//! score as almost no entropy.
+
+/// Entropy assigned to passwords from the common password list.
+const COMMON_PASSWORD_BITS: f64 = 8.0;
+
+/// Entropy of a character that repeats or continues a run.
+const PATTERN_CHAR_BITS: f64 = 1.0;
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "qwerty",
+    "letmein",
+    "welcome",
+    "admin",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "master",
+    "sunshine",
+    "princess",
+    "shadow",
+    "superman",
+    "trustno1",
+    "abc123",
+    "passw0rd",
+    "p@ssw0rd",
+    "p@ssword",
+    "login",
+    "starwars",
+    "whatever",
+    "freedom",
+    "hello",
+    "charlie",
+    "secret",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "1q2w3e4r",
+    "qazwsx",
+    "changeme",
+    "default",
+    "guest",
+    "root",
+    "test",
+    "access",
+    "mustang",
+    "michael",
+    "jordan",
+    "hunter",
+    "ranger",
+    "batman",
+    "computer",
+    "internet",
+    "summer",
+    "winter",
+];
+
+/// Estimates the entropy of `password` in bits.
+pub fn estimate_entropy(password: &str) -> f64 {
+    if password.is_empty() {
+        return 0.0;
+    }
+    if is_common_password(password) {
+        return COMMON_PASSWORD_BITS;
+    }
+
+    let bits_per_char = f64::from(pool_size(password)).log2();
+    let mut entropy = 0.0;
+    let mut previous: Option<char> = None;
+
+    for c in password.chars() {
+        let is_pattern = previous.is_some_and(|p| p == c || continues_run(p, c));
+        entropy += if is_pattern {
+            PATTERN_CHAR_BITS
+        } else {
+            bits_per_char
+        };
+        previous = Some(c);
+    }
+
+    entropy
+}
+
+fn is_common_password(password: &str) -> bool {
+    let lower = password.to_lowercase();
+    let stem = lower.trim_end_matches(|c: char| c.is_ascii_digit());
+    COMMON_PASSWORDS
+        .iter()
+        .any(|common| *common == lower || (!stem.is_empty() && *common == stem))
+}
+
+/// Whether `c` follows `previous` in an ascending or descending run of
+/// letters or digits.
+fn continues_run(previous: char, c: char) -> bool {
+    let same_class = (previous.is_ascii_lowercase() && c.is_ascii_lowercase())
+        || (previous.is_ascii_uppercase() && c.is_ascii_uppercase())
+        || (previous.is_ascii_digit() && c.is_ascii_digit());
+    same_class && (u32::from(previous)).abs_diff(u32::from(c)) == 1
+}
+
+fn pool_size(password: &str) -> u32 {
+    let mut lower = false;
+    let mut upper = false;
+    let mut digit = false;
+    let mut symbol = false;
+    let mut other = false;
+
+    for c in password.chars() {
+        match c {
+            'a'..='z' => lower = true,
+            'A'..='Z' => upper = true,
+            '0'..='9' => digit = true,
+            c if c.is_ascii_graphic() || c == ' ' => symbol = true,
+            _ => other = true,
+        }
+    }
+
+    [
+        (lower, 26),
+        (upper, 26),
+        (digit, 10),
+        (symbol, 33),
+        (other, 100),
+    ]
+    .iter()
+    .filter(|(present, _)| *present)
+    .map(|(_, size)| size)
+    .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_password_has_no_entropy() {
+        assert!(estimate_entropy("").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_common_passwords_score_low() {
+        assert!(estimate_entropy("Password") <= COMMON_PASSWORD_BITS);
+        assert!(estimate_entropy("password123") <= COMMON_PASSWORD_BITS);
+    }
+
+    #[test]
+    fn test_runs_and_repeats_are_discounted() {
+        assert!(estimate_entropy("abcdefgh") < estimate_entropy("axkqmvwe"));
+        assert!(estimate_entropy("zzzzzzzz") < 15.0);
+        assert!(estimate_entropy("98765432") < 15.0);
+    }
+
+    #[test]
+    fn test_mixed_classes_increase_entropy() {
+        let lower = estimate_entropy("kqmvxwrt");
+        let mixed = estimate_entropy("kQm7x#rT");
+        assert!(mixed > lower);
+    }
+
+    #[test]
+    fn test_long_random_password_is_strong() {
+        assert!(estimate_entropy("T4#vq9!Lm2@xZ8&pR5^wK1") > 100.0);
+    }
+}
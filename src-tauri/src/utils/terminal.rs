@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT
+
+//! Reading secrets from the controlling terminal, for the command-line
+//! binaries. The terminal is opened directly, so this works while stdin and
+//! stdout carry other data.
+
+use crate::domain::secure::SecureString;
+use crate::dto::error::AppError;
+
+/// Asks for a secret on the terminal with echo turned off.
+#[cfg(unix)]
+pub fn prompt_secret(message: &str) -> Result<SecureString, AppError> {
+    use std::io::{BufRead, BufReader, Write};
+    use zeroize::Zeroize;
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|e| AppError::Io(format!("No terminal to ask for the password: {e}")))?;
+    write!(tty, "{message}: ")?;
+    tty.flush()?;
+
+    set_echo(&tty, false);
+    let mut line = String::new();
+    let read = BufReader::new(&tty).read_line(&mut line);
+    set_echo(&tty, true);
+    writeln!(tty)?;
+    read?;
+
+    let secret = SecureString::from(line.trim_end_matches(['\r', '\n']));
+    line.zeroize();
+    Ok(secret)
+}
+
+#[cfg(not(unix))]
+pub fn prompt_secret(_message: &str) -> Result<SecureString, AppError> {
+    Err(AppError::NotImplemented(
+        "Terminal prompts require a Unix terminal".to_string(),
+    ))
+}
+
+#[cfg(unix)]
+fn set_echo(tty: &std::fs::File, enabled: bool) {
+    let _ = tty.try_clone().and_then(|tty| {
+        std::process::Command::new("stty")
+            .arg(if enabled { "echo" } else { "-echo" })
+            .stdin(tty)
+            .status()
+    });
+}
@@ -0,0 +1,444 @@
+#![allow(clippy::expect_used)]
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+use mithril_vault_lib::cli::{self, Prompt};
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::{tempdir, TempDir};
+
+#[path = "support/mod.rs"]
+mod support;
+
+use support::create_test_database;
+
+const PASSWORD: &str = "cli-password";
+
+/// `run` changes the signal handling of the whole process, so the tests
+/// using it take turns.
+static RUN_SIGNALS: Mutex<()> = Mutex::new(());
+
+/// Answers prompts from a fixed list, in order.
+struct ScriptedPrompt {
+    answers: VecDeque<String>,
+}
+
+impl Prompt for ScriptedPrompt {
+    fn secret(&mut self, _message: &str) -> Result<SecureString, AppError> {
+        self.answers
+            .pop_front()
+            .map(SecureString::from)
+            .ok_or_else(|| AppError::InvalidArguments("No more answers".to_string()))
+    }
+}
+
+/// Creates a database with an `Email/Work` group and returns its path.
+fn create_database(dir: &TempDir) -> String {
+    let path = dir.path().join("cli.kdbx");
+    let service = KdbxService::new();
+    create_test_database(&service, &path, "CLI", PASSWORD);
+    let email = service
+        .create_group(None, "Email", None)
+        .expect("Failed to create group");
+    service
+        .create_group(Some(&email.id), "Work", None)
+        .expect("Failed to create group");
+    service.save().expect("Failed to save database");
+    path.to_string_lossy().to_string()
+}
+
+/// Runs one invocation against `db` and returns its output.
+fn run(db: &str, args: &[&str], answers: &[&str]) -> Result<String, AppError> {
+    let mut prompt = ScriptedPrompt {
+        answers: answers.iter().map(|answer| (*answer).to_string()).collect(),
+    };
+    let mut out = Vec::new();
+    let global = ["--db", db];
+    let args = global.iter().chain(args).map(|arg| (*arg).to_string());
+    cli::run(args, KdbxService::new(), &mut prompt, &mut out)?;
+    Ok(String::from_utf8(out).expect("Output is not UTF-8"))
+}
+
+fn run_json(db: &str, args: &[&str], answers: &[&str]) -> serde_json::Value {
+    let mut full = vec!["--json"];
+    full.extend_from_slice(args);
+    let output = run(db, &full, answers).expect("Command failed");
+    serde_json::from_str(&output).expect("Output is not JSON")
+}
+
+#[test]
+fn test_add_show_list_and_search() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    let output = run(
+        &db,
+        &[
+            "add",
+            "Email/Work/Mail",
+            "--username",
+            "alice",
+            "--url",
+            "https://mail.example.com",
+            "--tag",
+            "work",
+            "--field",
+            "Account=42",
+            "--password-prompt",
+        ],
+        &[PASSWORD, "entry-secret"],
+    )
+    .expect("add failed");
+    assert_eq!(output, "Added /Email/Work/Mail\n");
+
+    let shown = run_json(&db, &["show", "Email/Work/Mail"], &[PASSWORD]);
+    assert_eq!(shown["path"], "/Email/Work/Mail");
+    assert_eq!(shown["username"], "alice");
+    assert_eq!(shown["customFields"]["Account"], "42");
+    assert!(shown.get("password").is_none());
+
+    let shown = run_json(
+        &db,
+        &["show", "/Email/Work/Mail", "--show-protected"],
+        &[PASSWORD],
+    );
+    assert_eq!(shown["password"], "entry-secret");
+
+    let id = shown["id"].as_str().expect("id");
+    let field =
+        run(&db, &["show", id, "--field", "Password"], &[PASSWORD]).expect("show by UUID failed");
+    assert_eq!(field, "entry-secret\n");
+
+    let listing = run(&db, &["ls", "--recursive"], &[PASSWORD]).expect("ls failed");
+    assert_eq!(listing, "Email/\nEmail/Work/\nEmail/Work/Mail\n");
+    let listing = run(&db, &["ls", "Email"], &[PASSWORD]).expect("ls failed");
+    assert_eq!(listing, "Work/\n");
+
+    let found = run_json(&db, &["search", "alice"], &[PASSWORD]);
+    assert_eq!(found[0]["path"], "/Email/Work/Mail");
+    assert_eq!(found.as_array().map(Vec::len), Some(1));
+}
+
+#[test]
+fn test_edit_move_and_remove() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    run(
+        &db,
+        &[
+            "add",
+            "Email/Mail",
+            "--generate",
+            "--length",
+            "32",
+            "--no-symbols",
+        ],
+        &[PASSWORD],
+    )
+    .expect("add failed");
+    let generated = run(
+        &db,
+        &["show", "Email/Mail", "--field", "Password"],
+        &[PASSWORD],
+    )
+    .expect("show failed");
+    assert_eq!(generated.trim_end().len(), 32);
+
+    let edited = run_json(
+        &db,
+        &[
+            "edit",
+            "Email/Mail",
+            "--title",
+            "Webmail",
+            "--notes",
+            "Shared",
+        ],
+        &[PASSWORD],
+    );
+    assert_eq!(edited["title"], "Webmail");
+    assert_eq!(edited["notes"], "Shared");
+
+    let output = run(&db, &["mv", "Email/Webmail", "Email/Work"], &[PASSWORD]).expect("mv failed");
+    assert_eq!(output, "Moved to /Email/Work/Webmail\n");
+    assert!(matches!(
+        run(&db, &["show", "Email/Webmail"], &[PASSWORD]),
+        Err(AppError::EntryNotFound(_))
+    ));
+
+    run(
+        &db,
+        &["rm", "Email/Work/Webmail", "--permanent"],
+        &[PASSWORD],
+    )
+    .expect("rm failed");
+    assert_eq!(
+        run(&db, &["ls", "Email/Work"], &[PASSWORD]).expect("ls failed"),
+        ""
+    );
+}
+
+#[test]
+fn test_duplicate_titles_are_ambiguous() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    run(&db, &["add", "Email/Mail"], &[PASSWORD]).expect("add failed");
+    run(&db, &["add", "Email/Mail"], &[PASSWORD]).expect("add failed");
+
+    assert!(matches!(
+        run(&db, &["show", "Email/Mail"], &[PASSWORD]),
+        Err(AppError::AmbiguousPath(_))
+    ));
+}
+
+#[test]
+fn test_database_info_and_export() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+    run(
+        &db,
+        &["add", "Email/Mail", "--username", "alice"],
+        &[PASSWORD],
+    )
+    .expect("add failed");
+
+    let info = run_json(&db, &["db-info"], &[PASSWORD]);
+    assert_eq!(info["name"], "CLI");
+    assert_eq!(info["groupCount"], 2);
+    assert_eq!(info["entryCount"], 1);
+
+    let export_path = dir.path().join("export.csv").to_string_lossy().to_string();
+    run(
+        &db,
+        &["export", &export_path, "--fields", "title,username"],
+        &[PASSWORD],
+    )
+    .expect("export failed");
+    let csv = std::fs::read_to_string(&export_path).expect("Failed to read export");
+    assert!(csv.contains("Mail,alice"));
+}
+
+#[test]
+fn test_lock_status_and_force_unlock() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    let status = run_json(&db, &["lock-status"], &[]);
+    assert_eq!(status["status"], "available");
+
+    // An open service holds the lock for this process
+    let service = KdbxService::new();
+    service
+        .open(&db, PASSWORD)
+        .expect("Failed to open database");
+    let status = run_json(&db, &["lock-status"], &[]);
+    assert_eq!(status["status"], "lockedByCurrentProcess");
+    drop(service);
+
+    let output = run(&db, &["force-unlock"], &[]).expect("force-unlock failed");
+    assert!(output.starts_with("Unlocked "));
+}
+
+#[test]
+fn test_generate_and_usage_errors() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    let password =
+        run(&db, &["generate", "--length", "12", "--no-symbols"], &[]).expect("generate failed");
+    assert_eq!(password.trim_end().len(), 12);
+    assert!(password.trim_end().chars().all(char::is_alphanumeric));
+
+    assert!(matches!(
+        run(&db, &["frobnicate"], &[]),
+        Err(AppError::InvalidArguments(_))
+    ));
+    assert!(matches!(
+        run(&db, &["show", "Email/Mail", "--bogus"], &[]),
+        Err(AppError::InvalidArguments(_))
+    ));
+    assert!(matches!(
+        run(&db, &["--no-password", "ls"], &[]),
+        Err(AppError::InvalidArguments(_))
+    ));
+    assert!(run(&db, &["ls"], &["wrong-password"]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_passes_secrets_and_masks_output() {
+    let _signals = RUN_SIGNALS.lock().unwrap_or_else(PoisonError::into_inner);
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+    run(
+        &db,
+        &["add", "Email/Work/Mail", "--password-prompt"],
+        &[PASSWORD, "hunter2-secret"],
+    )
+    .expect("add failed");
+
+    let output = run(
+        &db,
+        &[
+            "run",
+            "--env",
+            "DB_PASS=ref:Email/Work/Mail/Password",
+            "--env",
+            "PLAIN=visible",
+            "--file",
+            "KEY_FILE=ref:Email/Work/Mail/Password",
+            "--",
+            "sh",
+            "-c",
+            "echo \"pass=$DB_PASS plain=$PLAIN\"; cat \"$KEY_FILE\"; echo; echo \"$KEY_FILE\"",
+        ],
+        &[PASSWORD],
+    )
+    .expect("run failed");
+
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("pass=***** plain=visible"));
+    assert_eq!(lines.next(), Some("*****"));
+    let key_file = lines.next().expect("key file path");
+    assert!(!std::path::Path::new(key_file).exists());
+    assert!(!output.contains("hunter2-secret"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_returns_the_exit_code() {
+    let _signals = RUN_SIGNALS.lock().unwrap_or_else(PoisonError::into_inner);
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+
+    let mut out = Vec::new();
+    let mut prompt = ScriptedPrompt {
+        answers: VecDeque::new(),
+    };
+    let args = ["--db", &db, "run", "--", "sh", "-c", "exit 3"];
+    let code = cli::run(
+        args.iter().map(|arg| (*arg).to_string()),
+        KdbxService::new(),
+        &mut prompt,
+        &mut out,
+    )
+    .expect("run failed");
+    assert_eq!(code, 3);
+
+    assert!(matches!(
+        run(
+            &db,
+            &["run", "--env", "X=ref:Missing/Password", "--", "true"],
+            &[PASSWORD]
+        ),
+        Err(AppError::EntryNotFound(_))
+    ));
+    assert!(matches!(
+        run(&db, &["run", "--env", "X=1"], &[]),
+        Err(AppError::InvalidArguments(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_cleans_up_after_sigterm() {
+    let _signals = RUN_SIGNALS.lock().unwrap_or_else(PoisonError::into_inner);
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+    run(
+        &db,
+        &["add", "Email/Work/Mail", "--password-prompt"],
+        &[PASSWORD, "hunter2-secret"],
+    )
+    .expect("add failed");
+
+    // The program terminates this process; the signal is passed on to it
+    let mut out = Vec::new();
+    let mut prompt = ScriptedPrompt {
+        answers: VecDeque::from([PASSWORD.to_string()]),
+    };
+    let args = [
+        "--db",
+        &db,
+        "run",
+        "--file",
+        "KEY_FILE=ref:Email/Work/Mail/Password",
+        "--",
+        "sh",
+        "-c",
+        "echo \"$KEY_FILE\"; kill -TERM $PPID; exec sleep 5",
+    ];
+    let code = cli::run(
+        args.iter().map(|arg| (*arg).to_string()),
+        KdbxService::new(),
+        &mut prompt,
+        &mut out,
+    )
+    .expect("run failed");
+    assert_eq!(code, 128 + 15);
+
+    let output = String::from_utf8(out).expect("Output is not UTF-8");
+    let key_file = output.lines().next().expect("key file path");
+    assert!(!std::path::Path::new(key_file).exists());
+}
+
+#[test]
+fn test_render_writes_a_private_file() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db = create_database(&dir);
+    run(
+        &db,
+        &[
+            "add",
+            "Email/Work/Mail",
+            "--username",
+            "alice",
+            "--password-prompt",
+        ],
+        &[PASSWORD, "mail-secret"],
+    )
+    .expect("add failed");
+
+    let template = dir.path().join("app.conf.tmpl");
+    let output = dir.path().join("app.conf");
+    let template_path = template.to_string_lossy().to_string();
+    let output_path = output.to_string_lossy().to_string();
+    std::fs::write(
+        &template,
+        "user = {{ vault \"Email/Work/Mail\" \"UserName\" }}\npass = {{ vault \"Email/Work/Mail\" \"Password\" }}\n",
+    )
+    .expect("Failed to write template");
+
+    let printed =
+        run(&db, &["render", &template_path, &output_path], &[PASSWORD]).expect("render failed");
+    assert_eq!(printed, format!("Rendered {output_path}\n"));
+    assert_eq!(
+        std::fs::read_to_string(&output).expect("Failed to read output"),
+        "user = alice\npass = mail-secret\n"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&output)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // A missing reference leaves the previous output untouched
+    std::fs::write(&template, "{{ vault \"Email/Gone\" \"Password\" }}\n")
+        .expect("Failed to write template");
+    assert!(matches!(
+        run(&db, &["render", &template_path, &output_path], &[PASSWORD]),
+        Err(AppError::Template(_))
+    ));
+    assert_eq!(
+        std::fs::read_to_string(&output).expect("Failed to read output"),
+        "user = alice\npass = mail-secret\n"
+    );
+}
@@ -9,13 +9,14 @@ use mithril_vault_lib::commands::database::{
 use mithril_vault_lib::commands::entries::list_entries;
 use mithril_vault_lib::commands::generator::{
     calculate_password_strength, generate_passphrase, generate_password,
-    PassphraseGeneratorOptions, PasswordGeneratorOptions,
 };
 use mithril_vault_lib::commands::groups::list_groups;
+use mithril_vault_lib::commands::quick_unlock::enable_quick_unlock;
 use mithril_vault_lib::commands::secure_storage::{
     clear_session_key, has_session_key, store_session_key,
 };
 use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::generator::{PassphraseGeneratorOptions, PasswordGeneratorOptions};
 use mithril_vault_lib::register_services;
 use tauri::test::mock_app;
 use tauri::Manager;
@@ -30,6 +31,8 @@ fn cleanup_app_files(app: &tauri::App<tauri::test::MockRuntime>) {
     if let Ok(data_dir) = app.path().app_local_data_dir() {
         let _ = std::fs::remove_file(data_dir.join("settings.json"));
         let _ = std::fs::remove_file(data_dir.join("session.hold"));
+        let _ = std::fs::remove_file(data_dir.join("unlock-throttle.json"));
+        let _ = std::fs::remove_file(data_dir.join("unlock-throttle.lock"));
     }
 }
 
@@ -82,6 +85,7 @@ fn database_commands_handle_missing_database() {
     let err = tauri::async_runtime::block_on(open_database(
         "missing.kdbx".into(),
         "password".into(),
+        app.handle().clone(),
         app.state(),
     ))
     .expect_err("expected invalid path");
@@ -91,13 +95,17 @@ fn database_commands_handle_missing_database() {
         tauri::async_runtime::block_on(get_database_info(app.state())).expect("get database info");
     assert!(info.is_none());
 
-    let err =
-        tauri::async_runtime::block_on(lock_database()).expect_err("expected not implemented");
-    assert!(matches!(err, AppError::NotImplemented(_)));
+    let err = tauri::async_runtime::block_on(lock_database(app.handle().clone(), app.state()))
+        .expect_err("expected database not open");
+    assert!(matches!(err, AppError::DatabaseNotOpen));
 
-    let err = tauri::async_runtime::block_on(unlock_database("password".into()))
-        .expect_err("expected not implemented");
-    assert!(matches!(err, AppError::NotImplemented(_)));
+    let err = tauri::async_runtime::block_on(unlock_database(
+        Some("password".into()),
+        app.handle().clone(),
+        app.state(),
+    ))
+    .expect_err("expected database not open");
+    assert!(matches!(err, AppError::DatabaseNotOpen));
 
     cleanup_app_files(&app);
 }
@@ -116,3 +124,18 @@ fn entries_and_groups_commands_fail_when_not_open() {
 
     cleanup_app_files(&app);
 }
+
+#[test]
+fn quick_unlock_command_rejects_overlong_ttl() {
+    let app = setup_app();
+
+    let err = tauri::async_runtime::block_on(enable_quick_unlock(
+        "2468".into(),
+        Some(u64::MAX),
+        app.state(),
+    ))
+    .expect_err("expected invalid arguments");
+    assert!(matches!(err, AppError::InvalidArguments(_)));
+
+    cleanup_app_files(&app);
+}
@@ -44,14 +44,41 @@ fn open_test_database() -> Option<(KdbxService, TempDir)> {
     Some((service, temp_dir))
 }
 
+#[path = "commands/autotype_test.rs"]
+mod autotype_test;
+
+#[path = "commands/breach_test.rs"]
+mod breach_test;
+
+#[path = "commands/browser_test.rs"]
+mod browser_test;
+
 #[path = "commands/database_test.rs"]
 mod database_test;
 
 #[path = "commands/entries_test.rs"]
 mod entries_test;
 
+#[path = "commands/export_test.rs"]
+mod export_test;
+
 #[path = "commands/groups_test.rs"]
 mod groups_test;
 
+#[path = "commands/health_test.rs"]
+mod health_test;
+
+#[path = "commands/keeshare_test.rs"]
+mod keeshare_test;
+
+#[path = "commands/placeholders_test.rs"]
+mod placeholders_test;
+
+#[path = "commands/recycle_bin_test.rs"]
+mod recycle_bin_test;
+
 #[path = "commands/settings_test.rs"]
 mod settings_test;
+
+#[path = "commands/url_match_test.rs"]
+mod url_match_test;
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MIT
+//! Tests for auto-type command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the auto-type commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::autotype::{
+    AutoTypeAssociation, EntryAutoType, GroupAutoType, Key, KeyAction, KeyModifiers,
+};
+use mithril_vault_lib::dto::entry::{CreateEntryData, UpdateEntryData};
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::group::UpdateGroupData;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+struct Fixture {
+    service: KdbxService,
+    dir: TempDir,
+    root_id: String,
+    outer_id: String,
+    inner_id: String,
+    entry_id: String,
+}
+
+/// Creates a database with an entry in "Outer/Inner".
+fn create_database() -> Fixture {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("autotype.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Auto-Type", "testpass");
+    let outer = service
+        .create_group(Some(&info.root_group_id), "Outer", None)
+        .expect("Failed to create group");
+    let inner = service
+        .create_group(Some(&outer.id), "Inner", None)
+        .expect("Failed to create group");
+    let entry = service
+        .create_entry(
+            &inner.id,
+            CreateEntryData {
+                title: "Login".to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("s3cret"),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    Fixture {
+        service,
+        dir,
+        root_id: info.root_group_id,
+        outer_id: outer.id,
+        inner_id: inner.id,
+        entry_id: entry.id,
+    }
+}
+
+fn auto_type_update(auto_type: EntryAutoType) -> UpdateEntryData {
+    UpdateEntryData {
+        title: None,
+        username: None,
+        password: None,
+        url: None,
+        notes: None,
+        icon_id: None,
+        tags: None,
+        custom_fields: None,
+        protected_custom_fields: None,
+        expires: None,
+        expiry_time: None,
+        auto_type: Some(auto_type),
+    }
+}
+
+fn group_update(enabled: Option<bool>, default_sequence: Option<&str>) -> UpdateGroupData {
+    UpdateGroupData {
+        name: None,
+        icon: None,
+        auto_type: Some(GroupAutoType {
+            enabled,
+            default_sequence: default_sequence.map(ToString::to_string),
+        }),
+    }
+}
+
+// ============================================================================
+// Auto-type configuration tests
+// ============================================================================
+
+#[test]
+fn test_new_entry_uses_default_sequence() {
+    let fixture = create_database();
+
+    let entry = fixture
+        .service
+        .get_entry(&fixture.entry_id)
+        .expect("get entry");
+    assert_eq!(entry.auto_type, EntryAutoType::default());
+
+    let sequence = fixture
+        .service
+        .get_auto_type_sequence(&fixture.entry_id, None)
+        .expect("sequence");
+    assert_eq!(sequence.sequence, "{USERNAME}{TAB}{PASSWORD}{ENTER}");
+    assert_eq!(sequence.actions.len(), 4);
+}
+
+#[test]
+fn test_entry_auto_type_round_trips() {
+    let fixture = create_database();
+    let auto_type = EntryAutoType {
+        enabled: true,
+        default_sequence: Some("{USERNAME}{ENTER}{DELAY 200}{PASSWORD}{ENTER}".to_string()),
+        associations: vec![
+            AutoTypeAssociation {
+                window: "*Firefox".to_string(),
+                sequence: Some("^a{PASSWORD}~".to_string()),
+            },
+            AutoTypeAssociation {
+                window: "Terminal*".to_string(),
+                sequence: None,
+            },
+        ],
+    };
+
+    let updated = fixture
+        .service
+        .update_entry(&fixture.entry_id, auto_type_update(auto_type.clone()))
+        .expect("update entry");
+    assert_eq!(updated.auto_type, auto_type);
+
+    fixture.service.save().expect("save");
+    fixture.service.close().expect("close");
+    let db_path = fixture.dir.path().join("autotype.kdbx");
+    fixture
+        .service
+        .open(&db_path.to_string_lossy(), "testpass")
+        .expect("reopen");
+
+    let entry = fixture
+        .service
+        .get_entry(&fixture.entry_id)
+        .expect("get entry");
+    assert_eq!(entry.auto_type, auto_type);
+}
+
+#[test]
+fn test_window_association_selects_sequence() {
+    let fixture = create_database();
+    let service = &fixture.service;
+    service
+        .update_entry(
+            &fixture.entry_id,
+            auto_type_update(EntryAutoType {
+                enabled: true,
+                default_sequence: Some("{PASSWORD}{ENTER}".to_string()),
+                associations: vec![
+                    AutoTypeAssociation {
+                        window: "*Firefox".to_string(),
+                        sequence: Some("^a{PASSWORD}".to_string()),
+                    },
+                    AutoTypeAssociation {
+                        window: "Terminal*".to_string(),
+                        sequence: None,
+                    },
+                ],
+            }),
+        )
+        .expect("update entry");
+
+    let firefox = service
+        .get_auto_type_sequence(&fixture.entry_id, Some("Login - Mozilla Firefox"))
+        .expect("sequence");
+    assert_eq!(
+        firefox.actions,
+        vec![
+            KeyAction::Key {
+                key: Key::Char('a'),
+                modifiers: KeyModifiers {
+                    ctrl: true,
+                    ..KeyModifiers::default()
+                },
+            },
+            KeyAction::Placeholder {
+                name: "PASSWORD".to_string(),
+            },
+        ]
+    );
+
+    // Associations without a sequence and unmatched windows use the default
+    for window in [Some("Terminal - bash"), Some("Editor"), None] {
+        let sequence = service
+            .get_auto_type_sequence(&fixture.entry_id, window)
+            .expect("sequence");
+        assert_eq!(sequence.sequence, "{PASSWORD}{ENTER}");
+    }
+}
+
+#[test]
+fn test_group_settings_are_inherited() {
+    let fixture = create_database();
+    let service = &fixture.service;
+
+    let outer = service
+        .update_group(&fixture.outer_id, group_update(None, Some("{TITLE}{TAB}")))
+        .expect("update group");
+    assert_eq!(
+        outer.auto_type.default_sequence.as_deref(),
+        Some("{TITLE}{TAB}")
+    );
+
+    let sequence = service
+        .get_auto_type_sequence(&fixture.entry_id, None)
+        .expect("sequence");
+    assert_eq!(sequence.sequence, "{TITLE}{TAB}");
+
+    service
+        .update_group(&fixture.root_id, group_update(Some(false), None))
+        .expect("update group");
+    let result = service.get_auto_type_sequence(&fixture.entry_id, None);
+    assert!(matches!(result, Err(AppError::AutoTypeDisabled(_))));
+
+    // The nearest explicit setting wins
+    service
+        .update_group(&fixture.inner_id, group_update(Some(true), None))
+        .expect("update group");
+    assert!(service
+        .get_auto_type_sequence(&fixture.entry_id, None)
+        .is_ok());
+}
+
+#[test]
+fn test_disabled_entry_is_rejected() {
+    let fixture = create_database();
+    fixture
+        .service
+        .update_entry(
+            &fixture.entry_id,
+            auto_type_update(EntryAutoType {
+                enabled: false,
+                ..EntryAutoType::default()
+            }),
+        )
+        .expect("update entry");
+
+    let result = fixture
+        .service
+        .get_auto_type_sequence(&fixture.entry_id, None);
+    assert!(matches!(result, Err(AppError::AutoTypeDisabled(_))));
+}
+
+#[test]
+fn test_invalid_sequences_are_rejected() {
+    let fixture = create_database();
+    let service = &fixture.service;
+
+    let result = service.update_entry(
+        &fixture.entry_id,
+        auto_type_update(EntryAutoType {
+            enabled: true,
+            default_sequence: Some("{USERNAME}{TABB}".to_string()),
+            associations: Vec::new(),
+        }),
+    );
+    assert!(matches!(result, Err(AppError::InvalidAutoTypeSequence(_))));
+    let entry = service.get_entry(&fixture.entry_id).expect("get entry");
+    assert_eq!(entry.auto_type, EntryAutoType::default());
+
+    let result = service.update_group(&fixture.outer_id, group_update(None, Some("{DELAY x}")));
+    assert!(matches!(result, Err(AppError::InvalidAutoTypeSequence(_))));
+    let group = service.get_group(&fixture.outer_id).expect("get group");
+    assert_eq!(group.auto_type, GroupAutoType::default());
+}
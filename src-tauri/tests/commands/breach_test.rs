@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT
+//! Tests for breached-password command handlers
+//!
+//! These tests exercise the `KdbxService` methods and breach index functions
+//! that the breach commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::breach::{build_index, password_hash};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::fmt::Write as _;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates a database with entries "Mail" (password "password"), "Bank"
+/// (unlisted password) and "Blank" (empty password).
+/// Returns the service, temp dir and the "Mail" entry ID.
+fn create_database() -> (KdbxService, TempDir, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("breach.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Breach", "testpass");
+
+    let ids: Vec<String> = [
+        ("Mail", "password"),
+        ("Bank", "Zr8&nB3^cT6*hJ1%"),
+        ("Blank", ""),
+    ]
+    .iter()
+    .map(|(title, password)| {
+        service
+            .create_entry(
+                &info.root_group_id,
+                CreateEntryData {
+                    title: (*title).to_string(),
+                    username: "user".to_string(),
+                    password: SecureString::from(*password),
+                    url: None,
+                    notes: None,
+                    icon_id: None,
+                    tags: None,
+                    custom_fields: None,
+                    protected_custom_fields: None,
+                    expiry_time: None,
+                },
+            )
+            .expect("Failed to create entry")
+            .id
+    })
+    .collect();
+
+    (service, dir, ids[0].clone())
+}
+
+/// Writes a sorted HIBP-style list containing "password" seen 42 times.
+fn write_hash_list(dir: &TempDir) -> String {
+    let mut hashes: Vec<([u8; 20], u32)> = (0..50)
+        .map(|i| (password_hash(&format!("other-{i}")), 1))
+        .collect();
+    hashes.push((password_hash("password"), 42));
+    hashes.sort_unstable();
+
+    let content = hashes.iter().fold(String::new(), |mut out, (hash, count)| {
+        for byte in hash {
+            let _ = write!(out, "{byte:02X}");
+        }
+        let _ = write!(out, ":{count}\r\n");
+        out
+    });
+    let path = dir.path().join("pwned-passwords-sha1-ordered-by-hash.txt");
+    std::fs::write(&path, content).expect("write hash list");
+    path.to_string_lossy().into_owned()
+}
+
+// ============================================================================
+// check_breached_passwords command tests
+// ============================================================================
+
+#[test]
+fn test_check_breached_passwords_with_text_list() {
+    let (service, dir, mail_id) = create_database();
+    let list = write_hash_list(&dir);
+
+    let report = service.check_breached_passwords(&list).expect("check");
+    assert_eq!(report.checked_count, 2, "Empty passwords are not looked up");
+    assert_eq!(report.breached.len(), 1);
+    assert_eq!(report.breached[0].entry_id, mail_id);
+    assert_eq!(report.breached[0].title, "Mail");
+    assert_eq!(report.breached[0].count, 42);
+}
+
+#[test]
+fn test_check_breached_passwords_with_binary_index() {
+    let (service, dir, mail_id) = create_database();
+    let list = write_hash_list(&dir);
+    let index = dir.path().join("pwned.bin").to_string_lossy().into_owned();
+
+    assert_eq!(build_index(&list, &index).expect("build index"), 51);
+    let report = service.check_breached_passwords(&index).expect("check");
+    assert_eq!(report.breached.len(), 1);
+    assert_eq!(report.breached[0].entry_id, mail_id);
+    assert_eq!(report.breached[0].count, 42);
+}
+
+#[test]
+fn test_check_breached_passwords_skips_recycle_bin() {
+    let (service, dir, mail_id) = create_database();
+    let list = write_hash_list(&dir);
+    service.delete_entry(&mail_id, false).expect("delete entry");
+
+    let report = service.check_breached_passwords(&list).expect("check");
+    assert_eq!(report.checked_count, 1);
+    assert!(report.breached.is_empty());
+}
+
+#[test]
+fn test_check_breached_passwords_missing_list() {
+    let (service, dir, _) = create_database();
+    let missing = dir.path().join("missing.txt");
+
+    let result = service.check_breached_passwords(&missing.to_string_lossy());
+    assert!(matches!(result, Err(AppError::BreachIndex(_))));
+}
+
+#[test]
+fn test_check_breached_passwords_requires_open_database() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let list = write_hash_list(&dir);
+
+    let result = KdbxService::new().check_breached_passwords(&list);
+    assert!(matches!(result, Err(AppError::DatabaseNotOpen)));
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+//! Tests for browser integration command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the browser association commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+fn create_database() -> (KdbxService, TempDir, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("browser.kdbx");
+
+    let service = KdbxService::new();
+    create_test_database(&service, &db_path, "Browser", "testpass");
+
+    (service, dir, db_path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn test_associations_persist_in_database() {
+    let (service, _dir, path) = create_database();
+    service
+        .add_browser_association("Firefox", "aWRlbnRpdHkta2V5")
+        .expect("Failed to add association");
+    service.save().expect("Failed to save");
+    service.close().expect("Failed to close");
+
+    service.open(&path, "testpass").expect("Failed to reopen");
+    let associations = service
+        .list_browser_associations()
+        .expect("Failed to list associations");
+    assert_eq!(associations.len(), 1);
+    assert_eq!(associations[0].id, "Firefox");
+    assert_eq!(associations[0].key, "aWRlbnRpdHkta2V5");
+    assert!(associations[0].created_at.is_some());
+    assert_eq!(
+        service
+            .browser_association_key("Firefox")
+            .expect("Failed to read key")
+            .as_deref(),
+        Some("aWRlbnRpdHkta2V5")
+    );
+}
+
+#[test]
+fn test_remove_association() {
+    let (service, _dir, _path) = create_database();
+    service
+        .add_browser_association("Firefox", "a2V5")
+        .expect("Failed to add association");
+    service
+        .add_browser_association("Chromium", "b3RoZXI=")
+        .expect("Failed to add association");
+
+    service
+        .remove_browser_association("Firefox")
+        .expect("Failed to remove association");
+    let ids: Vec<String> = service
+        .list_browser_associations()
+        .expect("Failed to list associations")
+        .into_iter()
+        .map(|association| association.id)
+        .collect();
+    assert_eq!(ids, vec!["Chromium".to_string()]);
+
+    assert!(matches!(
+        service.remove_browser_association("Firefox"),
+        Err(AppError::BrowserIntegration(_))
+    ));
+}
+
+#[test]
+fn test_database_hash_is_stable() {
+    let (service, _dir, path) = create_database();
+    let hash = service.database_hash().expect("Failed to hash");
+    assert_eq!(hash.len(), 64);
+
+    service.close().expect("Failed to close");
+    assert!(matches!(
+        service.database_hash(),
+        Err(AppError::DatabaseNotOpen)
+    ));
+    service.open(&path, "testpass").expect("Failed to reopen");
+    assert_eq!(service.database_hash().expect("Failed to hash"), hash);
+}
@@ -9,7 +9,9 @@
 
 use mithril_vault_lib::domain::secure::SecureString;
 use mithril_vault_lib::dto::database::DatabaseCreationOptions;
-use mithril_vault_lib::dto::entry::{CreateEntryData, UpdateEntryData};
+use mithril_vault_lib::dto::entry::{
+    CreateEntryData, CreateReferenceEntryData, ReferenceField, UpdateEntryData,
+};
 use mithril_vault_lib::dto::error::AppError;
 use mithril_vault_lib::services::kdbx::KdbxService;
 use std::collections::BTreeMap;
@@ -240,6 +242,7 @@ fn test_create_entry_success() {
         tags: Some(vec!["tag1".to_string()]),
         custom_fields: Some(custom_fields),
         protected_custom_fields: Some(protected_custom_fields),
+        expiry_time: None,
     };
 
     let entry = service
@@ -291,6 +294,7 @@ fn test_create_entry_group_not_found() {
         tags: None,
         custom_fields: None,
         protected_custom_fields: None,
+        expiry_time: None,
     };
 
     let result = service.create_entry("missing-group", data);
@@ -322,6 +326,7 @@ fn test_get_entry_protected_custom_field_requires_protection() {
                 tags: None,
                 custom_fields: Some(custom_fields),
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -353,6 +358,7 @@ fn test_update_entry_success() {
         tags: None,
         custom_fields: None,
         protected_custom_fields: None,
+        expiry_time: None,
     };
 
     let entry = service
@@ -380,6 +386,9 @@ fn test_update_entry_success() {
                 tags: Some(vec!["tag2".to_string()]),
                 custom_fields: Some(custom_fields),
                 protected_custom_fields: Some(protected_custom_fields),
+                expires: None,
+                expiry_time: None,
+                auto_type: None,
             },
         )
         .expect("update entry");
@@ -427,6 +436,9 @@ fn test_update_entry_not_found() {
             tags: None,
             custom_fields: None,
             protected_custom_fields: None,
+            expires: None,
+            expiry_time: None,
+            auto_type: None,
         },
     );
 
@@ -458,6 +470,7 @@ fn test_delete_entry_moves_to_recycle_bin() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -470,7 +483,9 @@ fn test_delete_entry_moves_to_recycle_bin() {
         "Entry should exist in root before delete"
     );
 
-    service.delete_entry(&entry.id).expect("delete entry");
+    service
+        .delete_entry(&entry.id, false)
+        .expect("delete entry");
 
     let root_entries_after = service
         .list_entries(Some(&info.root_group_id))
@@ -497,7 +512,7 @@ fn test_delete_entry_moves_to_recycle_bin() {
 fn test_delete_entry_not_found() {
     let (service, _dir) = create_test_database();
 
-    let result = service.delete_entry("missing-entry");
+    let result = service.delete_entry("missing-entry", false);
 
     assert!(
         matches!(result, Err(AppError::EntryNotFound(_))),
@@ -532,6 +547,7 @@ fn test_move_entry_success() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -608,6 +624,7 @@ fn test_protected_custom_field_roundtrip_save_reopen() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -657,6 +674,7 @@ fn test_protected_custom_field_empty_value() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -696,6 +714,7 @@ fn test_protected_custom_field_unicode_value() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -735,6 +754,7 @@ fn test_protected_custom_field_special_characters() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -768,6 +788,7 @@ fn test_update_entry_add_protected_custom_field() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -789,6 +810,9 @@ fn test_update_entry_add_protected_custom_field() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expires: None,
+                expiry_time: None,
+                auto_type: None,
             },
         )
         .expect("update entry");
@@ -832,6 +856,7 @@ fn test_update_entry_modify_protected_custom_field() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -859,6 +884,9 @@ fn test_update_entry_modify_protected_custom_field() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(updated_protected),
+                expires: None,
+                expiry_time: None,
+                auto_type: None,
             },
         )
         .expect("update entry");
@@ -899,6 +927,7 @@ fn test_multiple_protected_custom_fields() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -958,6 +987,7 @@ fn test_mixed_protected_and_unprotected_fields() {
                 tags: None,
                 custom_fields: Some(custom_fields),
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -1044,6 +1074,7 @@ fn test_get_protected_field_field_not_found() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -1111,6 +1142,7 @@ fn test_password_roundtrip_save_reopen() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -1156,6 +1188,7 @@ fn test_password_unicode() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -1190,6 +1223,7 @@ fn test_password_special_characters() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -1203,3 +1237,288 @@ fn test_password_special_characters() {
         "Password with special characters should be stored and retrieved correctly"
     );
 }
+
+// ============================================================================
+// Expiry tests
+// ============================================================================
+
+/// Creates an entry in the root group that expires at `expiry_time`.
+fn create_expiring_entry(service: &KdbxService, title: &str, expiry_time: Option<&str>) -> String {
+    let info = service.get_info().expect("database info");
+    service
+        .create_entry(
+            &info.root_group_id,
+            CreateEntryData {
+                title: title.to_string(),
+                username: "user".to_string(),
+                password: SecureString::from("secret"),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: expiry_time.map(ToString::to_string),
+            },
+        )
+        .expect("create entry")
+        .id
+}
+
+fn expiry_update(expires: Option<bool>, expiry_time: Option<&str>) -> UpdateEntryData {
+    UpdateEntryData {
+        title: None,
+        username: None,
+        password: None,
+        url: None,
+        notes: None,
+        icon_id: None,
+        tags: None,
+        custom_fields: None,
+        protected_custom_fields: None,
+        expires,
+        expiry_time: expiry_time.map(ToString::to_string),
+        auto_type: None,
+    }
+}
+
+fn days_from_now(days: i64) -> String {
+    (chrono::Utc::now() + chrono::Duration::days(days)).to_rfc3339()
+}
+
+#[test]
+fn test_create_entry_with_expiry_survives_save() {
+    let (service, dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Expiring", Some("2030-06-01T12:00:00Z"));
+
+    let entry = service.get_entry(&id).expect("get entry");
+    assert!(entry.expires);
+    assert_eq!(entry.expiry_time.as_deref(), Some("2030-06-01 12:00:00"));
+
+    service.save().expect("save");
+    service.close().expect("close");
+    service
+        .open(
+            &dir.path().join("entry-crud.kdbx").to_string_lossy(),
+            "testpass",
+        )
+        .expect("reopen");
+    let entry = service.get_entry(&id).expect("get entry");
+    assert!(entry.expires);
+    assert_eq!(entry.expiry_time.as_deref(), Some("2030-06-01 12:00:00"));
+}
+
+#[test]
+fn test_create_entry_without_expiry() {
+    let (service, _dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Forever", None);
+
+    let entry = service.get_entry(&id).expect("get entry");
+    assert!(!entry.expires);
+    assert!(entry.expiry_time.is_none());
+}
+
+#[test]
+fn test_update_entry_sets_and_clears_expiry() {
+    let (service, _dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Toggle", None);
+
+    let updated = service
+        .update_entry(&id, expiry_update(None, Some("2031-01-02 03:04:05")))
+        .expect("set expiry");
+    assert!(updated.expires);
+    assert_eq!(updated.expiry_time.as_deref(), Some("2031-01-02 03:04:05"));
+
+    let updated = service
+        .update_entry(&id, expiry_update(Some(false), None))
+        .expect("clear expiry");
+    assert!(!updated.expires);
+    assert!(updated.expiry_time.is_none());
+}
+
+#[test]
+fn test_invalid_expiry_time_is_rejected() {
+    let (service, _dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Invalid", None);
+
+    let mut data = expiry_update(None, Some("next tuesday"));
+    data.title = Some("Renamed".to_string());
+    let result = service.update_entry(&id, data);
+    assert!(matches!(result, Err(AppError::InvalidTimestamp(_))));
+    assert_eq!(
+        service.get_entry(&id).expect("get entry").title,
+        "Invalid",
+        "A rejected update should not change the entry"
+    );
+}
+
+#[test]
+fn test_list_expiring_entries() {
+    let (service, _dir) = create_test_database();
+    let expired = create_expiring_entry(&service, "Expired", Some(&days_from_now(-3)));
+    let soon = create_expiring_entry(&service, "Soon", Some(&days_from_now(5)));
+    create_expiring_entry(&service, "Later", Some(&days_from_now(60)));
+    create_expiring_entry(&service, "Never", None);
+
+    let entries = service.list_expiring_entries(7).expect("list expiring");
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec![expired.as_str(), soon.as_str()], "Soonest first");
+    assert!(entries[0].expired);
+    assert!(!entries[1].expired);
+
+    let summary = service
+        .expiry_summary(7)
+        .expect("summary")
+        .expect("entries are expiring");
+    assert_eq!(summary.expired_count, 1);
+    assert_eq!(summary.expiring_count, 1);
+
+    assert_eq!(service.list_expiring_entries(90).expect("list").len(), 3);
+    assert!(service.expiry_summary(0).expect("summary").is_some());
+}
+
+#[test]
+fn test_list_expiring_entries_with_unbounded_window() {
+    let (service, _dir) = create_test_database();
+    create_expiring_entry(&service, "Later", Some(&days_from_now(60)));
+    create_expiring_entry(&service, "Never", None);
+
+    let entries = service
+        .list_expiring_entries(u32::MAX)
+        .expect("list expiring");
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn test_list_expiring_entries_skips_recycle_bin() {
+    let (service, _dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Expired", Some(&days_from_now(-1)));
+    service.delete_entry(&id, false).expect("delete entry");
+
+    assert!(service.list_expiring_entries(7).expect("list").is_empty());
+    assert!(service.expiry_summary(7).expect("summary").is_none());
+}
+
+// ============================================================================
+// Field reference tests
+// ============================================================================
+
+fn reference_data(source_entry_id: &str, fields: Vec<ReferenceField>) -> CreateReferenceEntryData {
+    CreateReferenceEntryData {
+        source_entry_id: source_entry_id.to_string(),
+        fields,
+        title: None,
+    }
+}
+
+#[test]
+fn test_create_reference_entry_shares_fields() {
+    let (service, _dir) = create_test_database();
+    let info = service.get_info().expect("database info");
+    let source = create_expiring_entry(&service, "Shared Login", None);
+
+    let linked = service
+        .create_reference_entry(
+            &info.root_group_id,
+            &reference_data(
+                &source,
+                vec![ReferenceField::Username, ReferenceField::Password],
+            ),
+        )
+        .expect("create reference entry");
+    assert_eq!(linked.title, "Shared Login", "Title is copied by default");
+    assert_eq!(linked.username, "user");
+    assert_eq!(linked.reference_fields, vec!["Password", "UserName"]);
+    assert_eq!(
+        service.get_entry_password(&linked.id).expect("password"),
+        "secret"
+    );
+
+    // Changing the source is reflected in the referencing entry
+    let mut update = expiry_update(None, None);
+    update.username = Some("carol".to_string());
+    update.password = Some(SecureString::from("rotated"));
+    service
+        .update_entry(&source, update)
+        .expect("update source");
+
+    assert_eq!(
+        service.get_entry(&linked.id).expect("get entry").username,
+        "carol"
+    );
+    assert_eq!(
+        service.get_entry_password(&linked.id).expect("password"),
+        "rotated"
+    );
+    let listed = service.list_entries(None).expect("list entries");
+    let listed = listed
+        .iter()
+        .find(|entry| entry.id == linked.id)
+        .expect("linked entry listed");
+    assert_eq!(listed.username, "carol");
+}
+
+#[test]
+fn test_create_reference_entry_title_options() {
+    let (service, _dir) = create_test_database();
+    let info = service.get_info().expect("database info");
+    let source = create_expiring_entry(&service, "Source", None);
+
+    let referenced = service
+        .create_reference_entry(
+            &info.root_group_id,
+            &reference_data(&source, vec![ReferenceField::Title]),
+        )
+        .expect("create reference entry");
+    assert_eq!(referenced.title, "Source");
+    assert_eq!(referenced.reference_fields, vec!["Title"]);
+
+    let mut data = reference_data(&source, vec![ReferenceField::Title]);
+    data.title = Some("Own title".to_string());
+    let explicit = service
+        .create_reference_entry(&info.root_group_id, &data)
+        .expect("create reference entry");
+    assert_eq!(explicit.title, "Own title");
+    assert!(explicit.reference_fields.is_empty());
+}
+
+#[test]
+fn test_create_reference_entry_errors() {
+    let (service, _dir) = create_test_database();
+    let info = service.get_info().expect("database info");
+    let source = create_expiring_entry(&service, "Source", None);
+
+    let result =
+        service.create_reference_entry(&info.root_group_id, &reference_data(&source, vec![]));
+    assert!(matches!(result, Err(AppError::FieldReference(_))));
+
+    let result = service.create_reference_entry(
+        &info.root_group_id,
+        &reference_data(
+            "00000000-0000-0000-0000-000000000000",
+            vec![ReferenceField::Password],
+        ),
+    );
+    assert!(matches!(result, Err(AppError::EntryNotFound(_))));
+}
+
+#[test]
+fn test_reference_cycle_in_password_is_an_error() {
+    let (service, _dir) = create_test_database();
+    let id = create_expiring_entry(&service, "Loop", None);
+
+    let mut update = expiry_update(None, None);
+    update.password = Some(SecureString::from(format!(
+        "{{REF:P@I:{}}}",
+        id.replace('-', "")
+    )));
+    let updated = service.update_entry(&id, update).expect("update entry");
+    assert_eq!(updated.reference_fields, vec!["Password"]);
+
+    assert!(matches!(
+        service.get_entry_password(&id),
+        Err(AppError::FieldReference(_))
+    ));
+    // Listing still works and shows the raw value
+    assert!(service.get_entry(&id).is_ok());
+}
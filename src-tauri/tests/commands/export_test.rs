@@ -0,0 +1,485 @@
+// SPDX-License-Identifier: MIT
+//! Tests for export command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the export commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::database::KdbImportSource;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::export::{ExportField, ExportFormat, ExportOptions};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::collections::BTreeMap;
+use tempfile::TempDir;
+
+use super::support::{create_test_database, fixture_path, low_kdf_options};
+
+/// Helper to create a database with one entry in the root group and one in a
+/// nested "Work/Servers" group.
+fn create_export_database() -> (KdbxService, TempDir, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("export.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Export", "testpass");
+
+    let work = service
+        .create_group(Some(&info.root_group_id), "Work", None)
+        .expect("Failed to create group");
+    let servers = service
+        .create_group(Some(&work.id), "Servers", None)
+        .expect("Failed to create group");
+
+    service
+        .create_entry(
+            &info.root_group_id,
+            CreateEntryData {
+                title: "Mail".to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("mail-secret"),
+                url: Some("https://mail.example.com".to_string()),
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    let mut custom_fields = BTreeMap::new();
+    custom_fields.insert("Host".to_string(), "db.internal".to_string());
+    let mut protected_custom_fields = BTreeMap::new();
+    protected_custom_fields.insert("Token".to_string(), SecureString::from("tok-123"));
+
+    service
+        .create_entry(
+            &servers.id,
+            CreateEntryData {
+                title: "Database".to_string(),
+                username: "root".to_string(),
+                password: SecureString::from("db-secret"),
+                url: None,
+                notes: Some("line one\nline \"two\"".to_string()),
+                icon_id: None,
+                tags: None,
+                custom_fields: Some(custom_fields),
+                protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    (service, dir, work.id)
+}
+
+fn export_options(format: ExportFormat) -> ExportOptions {
+    ExportOptions {
+        format,
+        fields: vec![
+            ExportField::Title,
+            ExportField::Username,
+            ExportField::Password,
+            ExportField::Notes,
+            ExportField::Custom("Host".to_string()),
+            ExportField::Custom("Token".to_string()),
+        ],
+        group_id: None,
+        include_group_path: true,
+        include_protected: false,
+        acknowledge_plaintext: false,
+    }
+}
+
+fn read_json(path: &std::path::Path) -> Vec<serde_json::Value> {
+    let content = std::fs::read_to_string(path).expect("Failed to read export");
+    serde_json::from_str(&content).expect("Export should be valid JSON")
+}
+
+// ============================================================================
+// export_entries command tests
+// ============================================================================
+
+#[test]
+fn test_export_csv_without_protected_values() {
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("export.csv");
+
+    let result = service
+        .export_entries(
+            &export_path.to_string_lossy(),
+            &export_options(ExportFormat::Csv),
+        )
+        .expect("Export should succeed");
+
+    assert_eq!(result.entry_count, 2);
+    assert!(!result.includes_protected);
+
+    let content = std::fs::read_to_string(&export_path).expect("Failed to read export");
+    let mut lines = content.lines();
+    assert_eq!(
+        lines.next(),
+        Some("Group,Title,Username,Password,Notes,Fields/Host,Fields/Token")
+    );
+    assert!(content.contains("Export,Mail,alice,,,,"));
+    assert!(content.contains(
+        "Export/Work/Servers,Database,root,,\"line one\nline \"\"two\"\"\",db.internal,"
+    ));
+    assert!(!content.contains("secret"));
+    assert!(!content.contains("tok-123"));
+}
+
+#[test]
+fn test_export_json_with_acknowledged_protected_values() {
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("export.json");
+
+    let mut options = export_options(ExportFormat::Json);
+    options.include_protected = true;
+    options.acknowledge_plaintext = true;
+
+    let result = service
+        .export_entries(&export_path.to_string_lossy(), &options)
+        .expect("Export should succeed");
+    assert!(result.includes_protected);
+
+    let rows = read_json(&export_path);
+    let database = rows
+        .iter()
+        .find(|row| row["Title"] == "Database")
+        .expect("Database entry should be exported");
+    assert_eq!(database["Group"], "Export/Work/Servers");
+    assert_eq!(database["Password"], "db-secret");
+    assert_eq!(database["Fields"]["Host"], "db.internal");
+    assert_eq!(database["Fields"]["Token"], "tok-123");
+}
+
+#[test]
+fn test_export_json_custom_fields_do_not_clash_with_columns() {
+    let (service, dir, work_id) = create_export_database();
+    let mut custom_fields = BTreeMap::new();
+    custom_fields.insert("Tags".to_string(), "custom tags".to_string());
+    custom_fields.insert("Group".to_string(), "custom group".to_string());
+    service
+        .create_entry(
+            &work_id,
+            CreateEntryData {
+                title: "Clash".to_string(),
+                username: String::new(),
+                password: SecureString::from(""),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: Some(custom_fields),
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    let export_path = dir.path().join("export.json");
+    let mut options = export_options(ExportFormat::Json);
+    options.group_id = Some(work_id);
+    options.fields = vec![
+        ExportField::Title,
+        ExportField::Tags,
+        ExportField::Custom("Tags".to_string()),
+        ExportField::Custom("Group".to_string()),
+        ExportField::Tags,
+    ];
+    service
+        .export_entries(&export_path.to_string_lossy(), &options)
+        .expect("Export should succeed");
+
+    let content = std::fs::read_to_string(&export_path).expect("Failed to read export");
+    let clash = content
+        .split("},\n  {")
+        .find(|row| row.contains("\"Clash\""))
+        .expect("Clash entry should be exported");
+    assert_eq!(clash.matches("\"Tags\"").count(), 2, "no duplicate keys");
+    assert_eq!(clash.matches("\"Group\"").count(), 2, "no duplicate keys");
+
+    let rows = read_json(&export_path);
+    let row = rows
+        .iter()
+        .find(|row| row["Title"] == "Clash")
+        .expect("Clash entry should be exported");
+    assert_eq!(row["Group"], "Export/Work");
+    assert_eq!(row["Tags"], "");
+    assert_eq!(row["Fields"]["Tags"], "custom tags");
+    assert_eq!(row["Fields"]["Group"], "custom group");
+}
+
+#[test]
+fn test_export_csv_custom_fields_do_not_clash_with_columns() {
+    let (service, dir, work_id) = create_export_database();
+    let mut custom_fields = BTreeMap::new();
+    custom_fields.insert("Tags".to_string(), "custom tags".to_string());
+    custom_fields.insert("Group".to_string(), "custom group".to_string());
+    service
+        .create_entry(
+            &work_id,
+            CreateEntryData {
+                title: "Clash".to_string(),
+                username: String::new(),
+                password: SecureString::from(""),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: Some(custom_fields),
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    let export_path = dir.path().join("export.csv");
+    let mut options = export_options(ExportFormat::Csv);
+    options.group_id = Some(work_id);
+    options.fields = vec![
+        ExportField::Title,
+        ExportField::Tags,
+        ExportField::Custom("Tags".to_string()),
+        ExportField::Custom("Group".to_string()),
+    ];
+    service
+        .export_entries(&export_path.to_string_lossy(), &options)
+        .expect("Export should succeed");
+
+    let content = std::fs::read_to_string(&export_path).expect("Failed to read export");
+    let mut lines = content.lines();
+    assert_eq!(
+        lines.next(),
+        Some("Group,Title,Tags,Fields/Tags,Fields/Group")
+    );
+    assert!(content.contains("Export/Work,Clash,,custom tags,custom group"));
+}
+
+#[test]
+fn test_export_protected_requires_acknowledgement() {
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("export.csv");
+
+    let mut options = export_options(ExportFormat::Csv);
+    options.include_protected = true;
+
+    let result = service.export_entries(&export_path.to_string_lossy(), &options);
+
+    assert!(matches!(
+        result,
+        Err(AppError::PlaintextExportNotAcknowledged)
+    ));
+    assert!(!export_path.exists(), "Nothing should be written");
+}
+
+#[test]
+fn test_export_group_subtree_only() {
+    let (service, dir, work_id) = create_export_database();
+    let export_path = dir.path().join("export.json");
+
+    let mut options = export_options(ExportFormat::Json);
+    options.group_id = Some(work_id);
+    options.include_group_path = false;
+    options.fields = vec![ExportField::Title];
+
+    let result = service
+        .export_entries(&export_path.to_string_lossy(), &options)
+        .expect("Export should succeed");
+
+    assert_eq!(result.entry_count, 1);
+    let rows = read_json(&export_path);
+    assert_eq!(rows, vec![serde_json::json!({ "Title": "Database" })]);
+}
+
+#[test]
+fn test_export_unknown_group() {
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("export.csv");
+
+    let mut options = export_options(ExportFormat::Csv);
+    options.group_id = Some("00000000-0000-0000-0000-000000000000".to_string());
+
+    let result = service.export_entries(&export_path.to_string_lossy(), &options);
+    assert!(matches!(result, Err(AppError::GroupNotFound(_))));
+}
+
+#[test]
+fn test_export_refuses_open_database_path() {
+    let (service, _dir, _) = create_export_database();
+    let db_path = service.get_info().expect("Failed to get info").path;
+
+    let result = service.export_entries(&db_path, &export_options(ExportFormat::Csv));
+    assert!(matches!(result, Err(AppError::InvalidPath(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_export_file_is_owner_only() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("export.csv");
+
+    service
+        .export_entries(
+            &export_path.to_string_lossy(),
+            &export_options(ExportFormat::Csv),
+        )
+        .expect("Export should succeed");
+
+    let mode = std::fs::metadata(&export_path)
+        .expect("Failed to read metadata")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+// ============================================================================
+// export_group command tests
+// ============================================================================
+
+#[test]
+fn test_export_group_writes_standalone_database() {
+    let (service, dir, work_id) = create_export_database();
+    service.save().expect("Failed to save");
+    let export_path = dir.path().join("work.kdbx");
+
+    let result = service
+        .export_group(
+            &work_id,
+            &export_path.to_string_lossy(),
+            Some("contractor-pass"),
+            None,
+            &low_kdf_options(),
+        )
+        .expect("Group export should succeed");
+
+    assert_eq!(result.group_count, 2);
+    assert_eq!(result.entry_count, 1);
+    assert!(!service.get_info().expect("Failed to get info").is_modified);
+    assert_eq!(service.list_entries(None).expect("list").len(), 2);
+
+    let exported = KdbxService::new();
+    let info = exported
+        .open(&export_path.to_string_lossy(), "contractor-pass")
+        .expect("Exported database should open with its own password");
+    assert_eq!(info.name, "Work");
+    assert_eq!(info.root_group_id, work_id);
+
+    let entries = exported.list_entries(None).expect("list");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].title, "Database");
+    assert_eq!(
+        exported
+            .get_entry_password(&entries[0].id)
+            .expect("password"),
+        "db-secret"
+    );
+    assert_eq!(
+        exported
+            .get_entry_protected_custom_field(&entries[0].id, "Token")
+            .expect("protected field")
+            .value,
+        "tok-123"
+    );
+}
+
+#[test]
+fn test_export_group_copies_attachments() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("imported.kdbx");
+    let export_path = dir.path().join("internet.kdbx");
+
+    let service = KdbxService::new();
+    service
+        .import_kdb(
+            &KdbImportSource {
+                path: fixture_path("test-kdb-aes.kdb")
+                    .to_string_lossy()
+                    .to_string(),
+                password: Some(SecureString::from("test123")),
+                keyfile_path: None,
+            },
+            &db_path.to_string_lossy(),
+            Some("testpass"),
+            None,
+            "Imported",
+            &low_kdf_options(),
+        )
+        .expect("Failed to import fixture");
+
+    let groups = service.list_groups().expect("Failed to list groups");
+    let internet = groups[0]
+        .children
+        .iter()
+        .find(|g| g.name == "Internet")
+        .expect("Internet group should exist");
+
+    let result = service
+        .export_group(
+            &internet.id,
+            &export_path.to_string_lossy(),
+            Some("contractor-pass"),
+            None,
+            &low_kdf_options(),
+        )
+        .expect("Group export should succeed");
+
+    assert_eq!(result.group_count, 2);
+    assert_eq!(result.entry_count, 2);
+    assert_eq!(result.attachment_count, 1);
+
+    let exported = KdbxService::new();
+    exported
+        .open(&export_path.to_string_lossy(), "contractor-pass")
+        .expect("Exported database should open");
+    assert_eq!(exported.list_entries(None).expect("list").len(), 2);
+}
+
+#[test]
+fn test_export_group_requires_credentials() {
+    let (service, dir, work_id) = create_export_database();
+    let export_path = dir.path().join("work.kdbx");
+
+    let result = service.export_group(
+        &work_id,
+        &export_path.to_string_lossy(),
+        None,
+        None,
+        &low_kdf_options(),
+    );
+
+    assert!(matches!(result, Err(AppError::NoCredentials)));
+    assert!(!export_path.exists());
+}
+
+#[test]
+fn test_export_group_unknown_group() {
+    let (service, dir, _) = create_export_database();
+    let export_path = dir.path().join("work.kdbx");
+
+    let result = service.export_group(
+        "00000000-0000-0000-0000-000000000000",
+        &export_path.to_string_lossy(),
+        Some("pass"),
+        None,
+        &low_kdf_options(),
+    );
+
+    assert!(matches!(result, Err(AppError::GroupNotFound(_))));
+}
+
+#[test]
+fn test_export_group_refuses_open_database_path() {
+    let (service, _dir, work_id) = create_export_database();
+    let db_path = service.get_info().expect("Failed to get info").path;
+
+    let result = service.export_group(&work_id, &db_path, Some("pass"), None, &low_kdf_options());
+
+    assert!(matches!(result, Err(AppError::InvalidPath(_))));
+}
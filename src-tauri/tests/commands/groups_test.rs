@@ -227,6 +227,7 @@ fn test_update_group_name() {
         UpdateGroupData {
             name: Some("Updated Name".to_string()),
             icon: None,
+            auto_type: None,
         },
     );
 
@@ -248,6 +249,7 @@ fn test_update_group_icon() {
         UpdateGroupData {
             name: None,
             icon: Some("10".to_string()),
+            auto_type: None,
         },
     );
 
@@ -269,6 +271,7 @@ fn test_update_group_both_fields() {
         UpdateGroupData {
             name: Some("New Name".to_string()),
             icon: Some("15".to_string()),
+            auto_type: None,
         },
     );
 
@@ -287,6 +290,7 @@ fn test_update_group_not_found() {
         UpdateGroupData {
             name: Some("Test".to_string()),
             icon: None,
+            auto_type: None,
         },
     );
 
@@ -315,6 +319,7 @@ fn test_update_group_modified_timestamp_changes() {
             UpdateGroupData {
                 name: Some("Updated".to_string()),
                 icon: None,
+                auto_type: None,
             },
         )
         .expect("update group");
@@ -343,6 +348,7 @@ fn test_rename_group_success() {
         UpdateGroupData {
             name: Some("New Name".to_string()),
             icon: None,
+            auto_type: None,
         },
     );
 
@@ -360,6 +366,7 @@ fn test_rename_group_not_found() {
         UpdateGroupData {
             name: Some("New Name".to_string()),
             icon: None,
+            auto_type: None,
         },
     );
 
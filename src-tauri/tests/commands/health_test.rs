@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT
+//! Tests for password health report command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the health commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::health::{
+    EntryHealth, HealthIssue, HealthReport, HealthReportOptions, PasswordQuality,
+};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::path::Path;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+fn entry_data(title: &str, password: &str) -> CreateEntryData {
+    CreateEntryData {
+        title: title.to_string(),
+        username: "user".to_string(),
+        password: SecureString::from(password),
+        url: None,
+        notes: None,
+        icon_id: None,
+        tags: None,
+        custom_fields: None,
+        protected_custom_fields: None,
+        expiry_time: None,
+    }
+}
+
+/// Creates a database whose root group holds entries with the given
+/// titles and passwords. Returns the service, temp dir and entry IDs.
+fn create_database(entries: &[(&str, &str)]) -> (KdbxService, TempDir, Vec<String>) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("health.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Health", "testpass");
+
+    let ids = entries
+        .iter()
+        .map(|(title, password)| {
+            service
+                .create_entry(&info.root_group_id, entry_data(title, password))
+                .expect("Failed to create entry")
+                .id
+        })
+        .collect();
+
+    (service, dir, ids)
+}
+
+/// Saves the database, edits the entry titled `title` with the keepass crate
+/// directly and reopens the file in the service.
+fn edit_entry_on_disk(
+    service: &KdbxService,
+    path: &Path,
+    title: &str,
+    edit: impl FnOnce(&mut keepass::db::Entry),
+) {
+    service.save().expect("save");
+    service.close().expect("close");
+
+    let key = || keepass::DatabaseKey::new().with_password("testpass");
+    let mut db = keepass::Database::open(&mut std::fs::File::open(path).expect("open file"), key())
+        .expect("open database");
+    let entry = db
+        .root
+        .children
+        .iter_mut()
+        .find_map(|node| match node {
+            keepass::db::Node::Entry(entry) if entry.get_title() == Some(title) => Some(entry),
+            _ => None,
+        })
+        .expect("entry on disk");
+    edit(entry);
+    db.save(
+        &mut std::fs::File::create(path).expect("create file"),
+        key(),
+    )
+    .expect("save database");
+
+    service
+        .open(&path.to_string_lossy(), "testpass")
+        .expect("reopen");
+}
+
+fn report(service: &KdbxService) -> HealthReport {
+    service
+        .health_report(&HealthReportOptions::default())
+        .expect("health report")
+}
+
+fn find<'a>(report: &'a HealthReport, id: &str) -> &'a EntryHealth {
+    report
+        .entries
+        .iter()
+        .find(|entry| entry.entry_id == id)
+        .expect("entry in report")
+}
+
+// ============================================================================
+// get_health_report command tests
+// ============================================================================
+
+#[test]
+fn test_health_report_rates_password_quality() {
+    let (service, _dir, ids) = create_database(&[
+        ("Weak", "letmein"),
+        ("Strong", "T4#vq9!Lm2@xZ8&pR5^wK1"),
+        ("Empty", ""),
+    ]);
+    let report = report(&service);
+    assert_eq!(report.entries.len(), 3);
+
+    let weak = find(&report, &ids[0]);
+    assert_eq!(weak.quality, PasswordQuality::Poor);
+    assert_eq!(weak.issues, vec![HealthIssue::Weak]);
+
+    let strong = find(&report, &ids[1]);
+    assert_eq!(strong.quality, PasswordQuality::Excellent);
+    assert!(strong.issues.is_empty());
+    assert!(strong.password_changed_at.is_some());
+
+    let empty = find(&report, &ids[2]);
+    assert_eq!(empty.issues, vec![HealthIssue::Empty]);
+}
+
+#[test]
+fn test_health_report_groups_reused_passwords() {
+    let password = "Xq7#mK2$wP9!vL4@";
+    let (service, _dir, ids) = create_database(&[
+        ("Mail", password),
+        ("Bank", password),
+        ("Shop", password),
+        ("Unique", "Zr8&nB3^cT6*hJ1%"),
+    ]);
+    let report = report(&service);
+
+    assert_eq!(report.reused.len(), 1);
+    let mut reused = report.reused[0].entry_ids.clone();
+    reused.sort();
+    let mut expected = ids[..3].to_vec();
+    expected.sort();
+    assert_eq!(reused, expected);
+
+    for id in &ids[..3] {
+        let entry = find(&report, id);
+        assert_eq!(entry.reuse_count, 2);
+        assert!(entry.issues.contains(&HealthIssue::Reused));
+    }
+    assert_eq!(find(&report, &ids[3]).reuse_count, 0);
+}
+
+#[test]
+fn test_health_report_does_not_expose_passwords() {
+    let (service, _dir, _) = create_database(&[("Mail", "hunter2"), ("Bank", "hunter2")]);
+    let json = serde_json::to_string(&report(&service)).expect("serialize");
+    assert!(!json.contains("hunter2"));
+}
+
+#[test]
+fn test_health_report_flags_old_passwords() {
+    let (service, _dir, ids) = create_database(&[("Mail", "Xq7#mK2$wP9!vL4@")]);
+    assert!(find(&report(&service), &ids[0]).issues.is_empty());
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let report = service
+        .health_report(&HealthReportOptions {
+            max_password_age_days: 0,
+        })
+        .expect("health report");
+    assert_eq!(find(&report, &ids[0]).issues, vec![HealthIssue::Old]);
+}
+
+#[test]
+fn test_health_report_flags_expired_entries() {
+    let (service, dir, ids) = create_database(&[("Mail", "Xq7#mK2$wP9!vL4@")]);
+    edit_entry_on_disk(&service, &dir.path().join("health.kdbx"), "Mail", |entry| {
+        entry.times.expires = true;
+        entry
+            .times
+            .set_expiry(keepass::db::Times::now() - chrono::Duration::days(1));
+    });
+
+    let report = report(&service);
+    assert_eq!(find(&report, &ids[0]).issues, vec![HealthIssue::Expired]);
+}
+
+#[test]
+fn test_health_report_honors_exclusion_flag() {
+    let (service, dir, ids) = create_database(&[("Mail", "letmein"), ("Bank", "letmein")]);
+    edit_entry_on_disk(&service, &dir.path().join("health.kdbx"), "Mail", |entry| {
+        entry.custom_data.items.insert(
+            "KnownBad".to_string(),
+            keepass::db::CustomDataItem {
+                value: Some(keepass::db::Value::Unprotected("true".to_string())),
+                last_modification_time: None,
+            },
+        );
+    });
+
+    let report = report(&service);
+    assert_eq!(report.excluded_entry_ids, vec![ids[0].clone()]);
+    assert_eq!(report.entries.len(), 1);
+    assert!(
+        report.reused.is_empty(),
+        "Excluded entries are not compared"
+    );
+}
+
+#[test]
+fn test_health_report_honors_quality_check_flag() {
+    let (service, dir, ids) = create_database(&[("Mail", "letmein"), ("Bank", "letmein")]);
+    edit_entry_on_disk(&service, &dir.path().join("health.kdbx"), "Bank", |entry| {
+        entry.quality_check = Some(false);
+    });
+
+    let report = report(&service);
+    assert_eq!(report.excluded_entry_ids, vec![ids[1].clone()]);
+    assert_eq!(report.entries.len(), 1);
+    assert!(report.reused.is_empty());
+}
+
+#[test]
+fn test_health_report_skips_recycle_bin() {
+    let (service, _dir, ids) = create_database(&[("Mail", "letmein"), ("Old", "letmein")]);
+    service.delete_entry(&ids[1], false).expect("delete entry");
+
+    let report = report(&service);
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].entry_id, ids[0]);
+    assert!(report.reused.is_empty());
+}
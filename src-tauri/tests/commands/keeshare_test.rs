@@ -0,0 +1,442 @@
+// SPDX-License-Identifier: MIT
+//! Tests for group sharing command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the share commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::{CreateEntryData, UpdateEntryData};
+use mithril_vault_lib::dto::keeshare::{ShareMode, ShareSettings, ShareSignature, ShareSyncStatus};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::io::{Read, Write};
+use std::path::Path;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates a database at `dir/name` with a "Team" group and returns the
+/// service and the group ID.
+fn create_database(dir: &Path, name: &str) -> (KdbxService, String) {
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &dir.join(name), name, "testpass");
+    let team = service
+        .create_group(Some(&info.root_group_id), "Team", None)
+        .expect("Failed to create group");
+    (service, team.id)
+}
+
+fn entry(title: &str, password: &str) -> CreateEntryData {
+    CreateEntryData {
+        title: title.to_string(),
+        username: "team".to_string(),
+        password: SecureString::from(password),
+        url: None,
+        notes: None,
+        icon_id: None,
+        tags: None,
+        custom_fields: None,
+        protected_custom_fields: None,
+        expiry_time: None,
+    }
+}
+
+fn share(mode: ShareMode, path: &str) -> ShareSettings {
+    ShareSettings {
+        mode,
+        path: path.to_string(),
+        password: Some(SecureString::from("share-pass")),
+        keep_groups: true,
+        trusted_signers: Vec::new(),
+        allow_unsigned: true,
+    }
+}
+
+/// Creates an exporting database with one shared entry and saves it.
+fn create_exporter(dir: &TempDir, container: &str) -> (KdbxService, String, String) {
+    let (service, team_id) = create_database(dir.path(), "exporter.kdbx");
+    let created = service
+        .create_entry(&team_id, entry("Wiki", "wiki-secret"))
+        .expect("Failed to create entry");
+    service
+        .set_group_share(&team_id, Some(&share(ShareMode::Export, container)))
+        .expect("Failed to configure share");
+    let result = service.save().expect("Save should export the share");
+    assert!(result.share_failures.is_empty());
+    (service, team_id, created.id)
+}
+
+// ============================================================================
+// set_group_share / list_shares command tests
+// ============================================================================
+
+#[test]
+fn test_set_and_list_shares() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (service, team_id) = create_database(dir.path(), "main.kdbx");
+
+    service
+        .set_group_share(
+            &team_id,
+            Some(&share(ShareMode::Synchronize, "team.kdbx.share")),
+        )
+        .expect("Failed to configure share");
+    assert!(service.get_info().expect("info").is_modified);
+
+    let shares = service.list_shares().expect("Failed to list shares");
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].group_id, team_id);
+    assert_eq!(shares[0].mode, ShareMode::Synchronize);
+    assert_eq!(shares[0].path, "team.kdbx.share");
+    assert!(shares[0].is_signed);
+
+    service
+        .set_group_share(&team_id, None)
+        .expect("Failed to remove share");
+    assert!(service.list_shares().expect("list").is_empty());
+}
+
+#[test]
+fn test_share_survives_save_and_reopen() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (service, team_id) = create_database(dir.path(), "main.kdbx");
+    service
+        .set_group_share(&team_id, Some(&share(ShareMode::Import, "team.kdbx")))
+        .expect("Failed to configure share");
+    service.save().expect("Failed to save");
+    service.close().expect("Failed to close");
+
+    service
+        .open(&dir.path().join("main.kdbx").to_string_lossy(), "testpass")
+        .expect("Failed to reopen");
+    let shares = service.list_shares().expect("list");
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].mode, ShareMode::Import);
+}
+
+// ============================================================================
+// Export on save
+// ============================================================================
+
+#[test]
+fn test_save_exports_unsigned_container() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (_service, team_id, _) = create_exporter(&dir, "team.kdbx");
+
+    let container = KdbxService::new();
+    let info = container
+        .open(
+            &dir.path().join("team.kdbx").to_string_lossy(),
+            "share-pass",
+        )
+        .expect("Container should open with the share password");
+    assert_eq!(info.root_group_id, team_id);
+    assert!(
+        container.list_shares().expect("list").is_empty(),
+        "Share references are not exported"
+    );
+
+    let entries = container.list_entries(None).expect("list");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        container.get_entry_password(&entries[0].id).expect("pw"),
+        "wiki-secret"
+    );
+}
+
+#[test]
+fn test_save_reports_failed_share_export() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (service, team_id) = create_database(dir.path(), "main.kdbx");
+    service
+        .set_group_share(
+            &team_id,
+            Some(&share(ShareMode::Export, "missing-dir/team.kdbx")),
+        )
+        .expect("Failed to configure share");
+
+    let result = service
+        .save()
+        .expect("A failed share export does not fail the save");
+    assert_eq!(result.share_failures.len(), 1);
+    assert_eq!(result.share_failures[0].group_id, team_id);
+    assert_eq!(result.share_failures[0].status, ShareSyncStatus::Failed);
+    assert!(!service.get_info().expect("info").is_modified);
+}
+
+// ============================================================================
+// sync_shares command tests
+// ============================================================================
+
+#[test]
+fn test_sync_imports_container() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (_exporter, _, entry_id) = create_exporter(&dir, "team.kdbx");
+
+    let (importer, team_id) = create_database(dir.path(), "importer.kdbx");
+    importer
+        .set_group_share(&team_id, Some(&share(ShareMode::Import, "team.kdbx")))
+        .expect("Failed to configure share");
+
+    let results = importer.sync_shares().expect("Sync should succeed");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, ShareSyncStatus::Imported);
+    assert_eq!(results[0].signature, Some(ShareSignature::Unsigned));
+
+    let entries = importer.list_entries(Some(&team_id)).expect("list");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, entry_id);
+    assert_eq!(
+        importer.get_entry_password(&entry_id).expect("pw"),
+        "wiki-secret"
+    );
+}
+
+#[test]
+fn test_sync_keeps_newer_version() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (exporter, _, entry_id) = create_exporter(&dir, "team.kdbx");
+
+    let (importer, team_id) = create_database(dir.path(), "importer.kdbx");
+    importer
+        .set_group_share(&team_id, Some(&share(ShareMode::Import, "team.kdbx")))
+        .expect("Failed to configure share");
+    importer.sync_shares().expect("Initial sync");
+
+    // Modification times have one-second resolution
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    exporter
+        .update_entry(
+            &entry_id,
+            UpdateEntryData {
+                title: None,
+                username: None,
+                password: Some(SecureString::from("rotated")),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expires: None,
+                expiry_time: None,
+                auto_type: None,
+            },
+        )
+        .expect("Failed to update entry");
+    exporter.save().expect("Failed to save");
+
+    importer.sync_shares().expect("Second sync");
+    assert_eq!(
+        importer.list_entries(Some(&team_id)).expect("list").len(),
+        1
+    );
+    assert_eq!(
+        importer.get_entry_password(&entry_id).expect("pw"),
+        "rotated"
+    );
+
+    // An unchanged container does not modify the importer again
+    importer.save().expect("Failed to save");
+    importer.sync_shares().expect("Third sync");
+    assert!(!importer.get_info().expect("info").is_modified);
+}
+
+#[test]
+fn test_sync_skips_missing_container() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (service, team_id) = create_database(dir.path(), "main.kdbx");
+    service
+        .set_group_share(&team_id, Some(&share(ShareMode::Import, "missing.kdbx")))
+        .expect("Failed to configure share");
+
+    let results = service.sync_shares().expect("Sync should succeed");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, ShareSyncStatus::Skipped);
+}
+
+#[test]
+fn test_sync_reports_wrong_password() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    create_exporter(&dir, "team.kdbx");
+
+    let (importer, team_id) = create_database(dir.path(), "importer.kdbx");
+    let mut settings = share(ShareMode::Import, "team.kdbx");
+    settings.password = Some(SecureString::from("wrong"));
+    importer
+        .set_group_share(&team_id, Some(&settings))
+        .expect("Failed to configure share");
+
+    let results = importer.sync_shares().expect("Sync should succeed");
+    assert_eq!(results[0].status, ShareSyncStatus::Failed);
+    assert!(results[0].message.is_some());
+    assert!(importer
+        .list_entries(Some(&team_id))
+        .expect("list")
+        .is_empty());
+}
+
+#[test]
+fn test_sync_refuses_unsigned_container_by_default() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    create_exporter(&dir, "team.kdbx");
+
+    let (importer, team_id) = create_database(dir.path(), "importer.kdbx");
+    let mut settings = share(ShareMode::Import, "team.kdbx");
+    settings.allow_unsigned = false;
+    importer
+        .set_group_share(&team_id, Some(&settings))
+        .expect("Failed to configure share");
+
+    let results = importer.sync_shares().expect("Sync should succeed");
+    assert_eq!(results[0].status, ShareSyncStatus::Failed);
+    assert_eq!(results[0].signature, Some(ShareSignature::Unsigned));
+    assert!(importer
+        .list_entries(Some(&team_id))
+        .expect("list")
+        .is_empty());
+}
+
+// ============================================================================
+// Signed containers
+// ============================================================================
+
+#[test]
+fn test_signed_container_verification() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (exporter, team_id, _) = create_exporter(&dir, "team.kdbx.share");
+
+    // The exporting database knows its own key
+    exporter
+        .set_group_share(
+            &team_id,
+            Some(&share(ShareMode::Synchronize, "team.kdbx.share")),
+        )
+        .expect("Failed to configure share");
+    let results = exporter.sync_shares().expect("Sync should succeed");
+    let import = results
+        .iter()
+        .find(|r| r.status == ShareSyncStatus::Imported)
+        .expect("Container should be imported");
+    assert_eq!(import.signature, Some(ShareSignature::Verified));
+
+    // Another database refuses the container until it trusts the signer
+    let (importer, other_team) = create_database(dir.path(), "importer.kdbx");
+    let mut settings = share(ShareMode::Import, "team.kdbx.share");
+    settings.allow_unsigned = false;
+    importer
+        .set_group_share(&other_team, Some(&settings))
+        .expect("Failed to configure share");
+    let results = importer.sync_shares().expect("Sync should succeed");
+    assert_eq!(results[0].status, ShareSyncStatus::Failed);
+    assert_eq!(results[0].signature, Some(ShareSignature::Unverified));
+    assert!(importer
+        .list_entries(Some(&other_team))
+        .expect("list")
+        .is_empty());
+
+    settings.trusted_signers = vec![exporter.share_public_key().expect("public key")];
+    importer
+        .set_group_share(&other_team, Some(&settings))
+        .expect("Failed to configure share");
+    let results = importer.sync_shares().expect("Sync should succeed");
+    assert_eq!(results[0].status, ShareSyncStatus::Imported);
+    assert_eq!(results[0].signature, Some(ShareSignature::Verified));
+    assert_eq!(
+        importer
+            .list_entries(Some(&other_team))
+            .expect("list")
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_signing_key_survives_reopen() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (exporter, _, _) = create_exporter(&dir, "team.kdbx.share");
+    let public_key = exporter.share_public_key().expect("public key");
+    exporter.close().expect("Failed to close");
+
+    exporter
+        .open(
+            &dir.path().join("exporter.kdbx").to_string_lossy(),
+            "testpass",
+        )
+        .expect("Failed to reopen");
+    assert_eq!(exporter.share_public_key().expect("public key"), public_key);
+    assert!(!exporter.get_info().expect("info").is_modified);
+}
+
+#[test]
+fn test_tampered_container_is_rejected() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (exporter, team_id, _) = create_exporter(&dir, "team.kdbx.share");
+    exporter
+        .set_group_share(
+            &team_id,
+            Some(&share(ShareMode::Synchronize, "team.kdbx.share")),
+        )
+        .expect("Failed to configure share");
+
+    // Replace the database in the container but keep the old signature
+    let container = dir.path().join("team.kdbx.share");
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&container).expect("open"))
+        .expect("Container should be a zip archive");
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).expect("zip entry");
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).expect("read zip entry");
+        files.push((file.name().to_string(), content));
+    }
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&container).expect("create"));
+    for (name, mut content) in files {
+        if name == "container.share.kdbx" {
+            let last = content.len() - 1;
+            content[last] ^= 0xff;
+        }
+        writer
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .expect("zip entry");
+        writer.write_all(&content).expect("write zip entry");
+    }
+    writer.finish().expect("finish zip");
+
+    let results = exporter.sync_shares().expect("Sync should succeed");
+    let import = results
+        .iter()
+        .find(|r| r.status != ShareSyncStatus::Exported)
+        .expect("Import result");
+    assert_eq!(import.status, ShareSyncStatus::Failed);
+    assert_eq!(import.signature, Some(ShareSignature::Unverified));
+}
+
+// ============================================================================
+// Import on open
+// ============================================================================
+
+#[test]
+fn test_open_imports_shares() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (importer, team_id) = create_database(dir.path(), "importer.kdbx");
+    importer
+        .set_group_share(&team_id, Some(&share(ShareMode::Import, "team.kdbx")))
+        .expect("Failed to configure share");
+    importer.save().expect("Failed to save");
+    importer.close().expect("Failed to close");
+
+    create_exporter(&dir, "team.kdbx");
+
+    let info = importer
+        .open(
+            &dir.path().join("importer.kdbx").to_string_lossy(),
+            "testpass",
+        )
+        .expect("Failed to reopen");
+    assert!(info.is_modified, "Imported changes are unsaved");
+    assert_eq!(
+        importer.list_entries(Some(&team_id)).expect("list").len(),
+        1
+    );
+}
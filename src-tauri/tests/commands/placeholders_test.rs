@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MIT
+//! Tests for placeholder expansion command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the placeholder commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::{CreateEntryData, CreateReferenceEntryData, ReferenceField};
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::placeholder::PlaceholderOptions;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates a database with an "Internet/Email" group holding a "Mail" entry.
+/// Returns the service, temp dir, group ID and entry ID.
+fn create_database() -> (KdbxService, TempDir, String, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("placeholders.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Vault", "testpass");
+    let internet = service
+        .create_group(Some(&info.root_group_id), "Internet", None)
+        .expect("Failed to create group");
+    let email = service
+        .create_group(Some(&internet.id), "Email", None)
+        .expect("Failed to create group");
+    let entry = service
+        .create_entry(
+            &email.id,
+            CreateEntryData {
+                title: "Mail".to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("s3cret"),
+                url: Some("https://mail.example.com/login".to_string()),
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    (service, dir, email.id, entry.id)
+}
+
+fn safe() -> PlaceholderOptions {
+    PlaceholderOptions::default()
+}
+
+fn unsafe_mode() -> PlaceholderOptions {
+    PlaceholderOptions { safe_mode: false }
+}
+
+// ============================================================================
+// expand_placeholders command tests
+// ============================================================================
+
+#[test]
+fn test_expand_placeholders_for_entry() {
+    let (service, _dir, _, entry_id) = create_database();
+
+    let expanded = service
+        .expand_placeholders(
+            &entry_id,
+            "{USERNAME}@{URL:HOST} in {GROUP_PATH} of {DB_NAME}{TAB}{PASSWORD}{ENTER}",
+            &safe(),
+        )
+        .expect("expand");
+    assert_eq!(
+        expanded,
+        "alice@mail.example.com in Internet.Email of Vault{TAB}s3cret{ENTER}"
+    );
+}
+
+#[test]
+fn test_expand_placeholders_resolves_references() {
+    let (service, _dir, group_id, entry_id) = create_database();
+    let linked = service
+        .create_reference_entry(
+            &group_id,
+            &CreateReferenceEntryData {
+                source_entry_id: entry_id.clone(),
+                fields: vec![ReferenceField::Password],
+                title: Some("Linked".to_string()),
+            },
+        )
+        .expect("create reference entry");
+
+    let expanded = service
+        .expand_placeholders(&linked.id, "{TITLE}:{PASSWORD}", &safe())
+        .expect("expand");
+    assert_eq!(expanded, "Linked:s3cret");
+
+    let by_title = service
+        .expand_placeholders(&linked.id, "{REF:U@T:Mail}", &safe())
+        .expect("expand");
+    assert_eq!(by_title, "alice");
+}
+
+#[test]
+fn test_new_password_is_stored_outside_safe_mode() {
+    let (service, _dir, _, entry_id) = create_database();
+
+    let kept = service
+        .expand_placeholders(&entry_id, "{NEWPASSWORD}", &safe())
+        .expect("expand");
+    assert_eq!(kept, "{NEWPASSWORD}");
+    assert_eq!(
+        service.get_entry_password(&entry_id).expect("password"),
+        "s3cret"
+    );
+
+    let generated = service
+        .expand_placeholders(&entry_id, "{NEWPASSWORD}", &unsafe_mode())
+        .expect("expand");
+    assert_ne!(generated, "s3cret");
+    assert_eq!(
+        service.get_entry_password(&entry_id).expect("password"),
+        generated
+    );
+}
+
+#[test]
+fn test_new_password_keeps_old_password_in_history() {
+    let (service, dir, _, entry_id) = create_database();
+
+    service
+        .expand_placeholders(&entry_id, "{NEWPASSWORD}", &unsafe_mode())
+        .expect("expand");
+    service.save().expect("save");
+
+    let mut file = std::fs::File::open(dir.path().join("placeholders.kdbx")).expect("open file");
+    let db = keepass::Database::open(
+        &mut file,
+        keepass::DatabaseKey::new().with_password("testpass"),
+    )
+    .expect("open database");
+    let entry = db
+        .root
+        .iter()
+        .find_map(|node| match node {
+            keepass::db::NodeRef::Entry(entry) if entry.uuid.to_string() == entry_id => Some(entry),
+            _ => None,
+        })
+        .expect("entry on disk");
+    let history = entry.history.as_ref().expect("history");
+
+    assert_eq!(history.get_entries().len(), 1);
+    assert_eq!(history.get_entries()[0].get_password(), Some("s3cret"));
+}
+
+#[test]
+fn test_expand_placeholders_unknown_entry() {
+    let (service, _dir, _, _) = create_database();
+    let result =
+        service.expand_placeholders("00000000-0000-0000-0000-000000000000", "{TITLE}", &safe());
+    assert!(matches!(result, Err(AppError::EntryNotFound(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unsafe_expansion_on_read_only_database_runs_nothing() {
+    let (service, dir, _, entry_id) = create_database();
+    service.save().expect("save");
+    service.close().expect("close");
+    service
+        .open_read_only(
+            &dir.path().join("placeholders.kdbx").to_string_lossy(),
+            Some("testpass"),
+            None,
+        )
+        .expect("open read-only");
+
+    let marker = dir.path().join("ran");
+    let text = format!("{{CMD:|touch '{}'||}}", marker.display());
+    let result = service.expand_placeholders(&entry_id, &text, &unsafe_mode());
+    assert!(matches!(result, Err(AppError::DatabaseReadOnly)));
+    assert!(!marker.exists(), "The command must not run");
+
+    assert_eq!(
+        service
+            .expand_placeholders(&entry_id, "{USERNAME}", &safe())
+            .expect("expand"),
+        "alice"
+    );
+}
@@ -0,0 +1,475 @@
+// SPDX-License-Identifier: MIT
+//! Tests for recycle bin command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the recycle bin commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::recycle_bin::{RecycleBinItemKind, RecycleBinSettings};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates a database with a "Work" group holding one entry.
+/// Returns the service, temp dir, root group ID, work group ID and entry ID.
+fn create_database() -> (KdbxService, TempDir, String, String, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("bin.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "Bin", "testpass");
+    let work = service
+        .create_group(Some(&info.root_group_id), "Work", None)
+        .expect("Failed to create group");
+    let entry = service
+        .create_entry(
+            &work.id,
+            CreateEntryData {
+                title: "VPN".to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("vpn-secret"),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    (service, dir, info.root_group_id, work.id, entry.id)
+}
+
+/// Saves the database and checks that `ids` are in its deleted objects list.
+fn assert_deleted_objects(service: &KdbxService, dir: &TempDir, ids: &[&str]) {
+    service.save().expect("save");
+    let mut file = std::fs::File::open(dir.path().join("bin.kdbx")).expect("open file");
+    let db = keepass::Database::open(
+        &mut file,
+        keepass::DatabaseKey::new().with_password("testpass"),
+    )
+    .expect("open database");
+    for id in ids {
+        let uuid = id.parse().expect("uuid");
+        assert!(db.deleted_objects.contains(uuid), "{id} should be recorded");
+    }
+}
+
+// ============================================================================
+// list_recycle_bin command tests
+// ============================================================================
+
+#[test]
+fn test_list_recycle_bin_without_bin() {
+    let (service, _dir, _, _, _) = create_database();
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+}
+
+#[test]
+fn test_list_recycle_bin_records_previous_parent() {
+    let (service, _dir, _, work_id, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+
+    let items = service.list_recycle_bin().expect("list");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, entry_id);
+    assert_eq!(items[0].kind, RecycleBinItemKind::Entry);
+    assert_eq!(items[0].name, "VPN");
+    assert_eq!(
+        items[0].previous_parent_id.as_deref(),
+        Some(work_id.as_str())
+    );
+    assert!(items[0].deleted_at.is_some());
+}
+
+// ============================================================================
+// restore_entry / restore_group command tests
+// ============================================================================
+
+#[test]
+fn test_restore_entry_to_target_group() {
+    let (service, _dir, _, work_id, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+
+    let restored = service
+        .restore_entry(&entry_id, Some(&work_id))
+        .expect("restore entry");
+    assert_eq!(restored.group_id, work_id);
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+    assert_eq!(
+        service.get_entry_password(&entry_id).expect("password"),
+        "vpn-secret"
+    );
+}
+
+#[test]
+fn test_restore_entry_to_previous_parent() {
+    let (service, _dir, root_id, work_id, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+
+    let restored = service
+        .restore_entry(&entry_id, None)
+        .expect("restore entry");
+    assert_eq!(restored.group_id, work_id);
+
+    // The recorded parent is cleared, so a later explicit move is not undone
+    service.move_entry(&entry_id, &root_id).expect("move entry");
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    let restored = service
+        .restore_entry(&entry_id, None)
+        .expect("restore entry");
+    assert_eq!(restored.group_id, root_id);
+}
+
+#[test]
+fn test_restore_entry_moved_into_bin() {
+    let (service, _dir, root_id, work_id, entry_id) = create_database();
+    let spare = service
+        .create_entry(
+            &root_id,
+            CreateEntryData {
+                title: "Spare".to_string(),
+                username: String::new(),
+                password: SecureString::from("spare"),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+    service
+        .delete_entry(&spare.id, false)
+        .expect("delete entry");
+    let bin_id = service.get_entry(&spare.id).expect("entry").group_id;
+
+    service
+        .move_entry(&entry_id, &bin_id)
+        .expect("move entry into bin");
+    let restored = service
+        .restore_entry(&entry_id, None)
+        .expect("restore entry");
+    assert_eq!(restored.group_id, work_id);
+}
+
+#[test]
+fn test_restore_entry_falls_back_to_root() {
+    let (service, _dir, root_id, work_id, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    service
+        .delete_group(&work_id, true, false)
+        .expect("delete group");
+
+    // The previous parent is now in the bin itself
+    let restored = service
+        .restore_entry(&entry_id, None)
+        .expect("restore entry");
+    assert_eq!(restored.group_id, root_id);
+
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    service
+        .delete_group(&work_id, true, true)
+        .expect("delete group permanently");
+    let restored = service
+        .restore_entry(&entry_id, None)
+        .expect("restore entry");
+    assert_eq!(restored.group_id, root_id);
+}
+
+#[test]
+fn test_restore_entry_to_missing_group() {
+    let (service, _dir, _, work_id, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    service
+        .delete_group(&work_id, true, true)
+        .expect("delete group permanently");
+
+    let result = service.restore_entry(&entry_id, Some(&work_id));
+    assert!(matches!(result, Err(AppError::GroupNotFound(_))));
+}
+
+#[test]
+fn test_restore_entry_into_recycle_bin_is_rejected() {
+    let (service, _dir, _, _, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    let bin_id = service.get_entry(&entry_id).expect("entry").group_id;
+
+    let result = service.restore_entry(&entry_id, Some(&bin_id));
+    assert!(matches!(result, Err(AppError::InvalidArguments(_))));
+}
+
+#[test]
+fn test_restore_entry_not_in_bin() {
+    let (service, _dir, _, _, entry_id) = create_database();
+    let result = service.restore_entry(&entry_id, None);
+    assert!(matches!(result, Err(AppError::NotInRecycleBin(_))));
+}
+
+#[test]
+fn test_restore_group_with_contents() {
+    let (service, _dir, root_id, work_id, entry_id) = create_database();
+    service
+        .delete_group(&work_id, true, false)
+        .expect("delete group");
+
+    let items = service.list_recycle_bin().expect("list");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, RecycleBinItemKind::Group);
+    assert_eq!(items[0].entry_count, 1);
+
+    let restored = service
+        .restore_group(&work_id, None)
+        .expect("restore group");
+    assert_eq!(restored.parent_id.as_deref(), Some(root_id.as_str()));
+    assert_eq!(
+        service.get_entry(&entry_id).expect("entry").group_id,
+        work_id
+    );
+}
+
+#[test]
+fn test_restore_recycle_bin_itself_is_rejected() {
+    let (service, _dir, _, _, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    let bin_id = service.get_entry(&entry_id).expect("entry").group_id;
+
+    let result = service.restore_group(&bin_id, None);
+    assert!(matches!(result, Err(AppError::NotInRecycleBin(_))));
+}
+
+// ============================================================================
+// empty_recycle_bin command tests
+// ============================================================================
+
+#[test]
+fn test_empty_recycle_bin_records_deleted_objects() {
+    let (service, dir, _, work_id, entry_id) = create_database();
+    service
+        .delete_group(&work_id, true, false)
+        .expect("delete group");
+
+    let removed = service.empty_recycle_bin().expect("empty bin");
+    assert_eq!(removed, 2, "Group and its entry are removed");
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+    assert!(matches!(
+        service.get_entry(&entry_id),
+        Err(AppError::EntryNotFound(_))
+    ));
+
+    assert_deleted_objects(&service, &dir, &[&work_id, &entry_id]);
+}
+
+#[test]
+fn test_empty_recycle_bin_without_items() {
+    let (service, _dir, _, _, _) = create_database();
+    assert_eq!(service.empty_recycle_bin().expect("empty bin"), 0);
+}
+
+// ============================================================================
+// Retention
+// ============================================================================
+
+#[test]
+fn test_recycle_bin_settings_round_trip() {
+    let (service, _dir, _, _, _) = create_database();
+    assert_eq!(
+        service
+            .get_recycle_bin_settings()
+            .expect("settings")
+            .retention_days,
+        None
+    );
+
+    service
+        .update_recycle_bin_settings(&RecycleBinSettings {
+            enabled: true,
+            retention_days: Some(30),
+        })
+        .expect("update settings");
+    assert_eq!(
+        service
+            .get_recycle_bin_settings()
+            .expect("settings")
+            .retention_days,
+        Some(30)
+    );
+}
+
+#[test]
+fn test_save_purges_items_past_retention() {
+    let (service, _dir, _, _, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+
+    service
+        .update_recycle_bin_settings(&RecycleBinSettings {
+            enabled: true,
+            retention_days: Some(30),
+        })
+        .expect("update settings");
+    service.save().expect("save");
+    assert_eq!(service.list_recycle_bin().expect("list").len(), 1);
+
+    service
+        .update_recycle_bin_settings(&RecycleBinSettings {
+            enabled: true,
+            retention_days: Some(0),
+        })
+        .expect("update settings");
+    service.save().expect("save");
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+}
+
+#[test]
+fn test_retention_period_out_of_range_is_rejected() {
+    let (service, _dir, _, _, _) = create_database();
+    let result = service.update_recycle_bin_settings(&RecycleBinSettings {
+        enabled: true,
+        retention_days: Some(u32::MAX),
+    });
+    assert!(matches!(result, Err(AppError::InvalidArguments(_))));
+    assert_eq!(
+        service
+            .get_recycle_bin_settings()
+            .expect("settings")
+            .retention_days,
+        None
+    );
+}
+
+#[test]
+fn test_save_skips_purge_for_unrepresentable_retention() {
+    let (service, dir, _, _, entry_id) = create_database();
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    service.save().expect("save");
+    service.close().expect("close");
+
+    // Another application may store any value
+    let path = dir.path().join("bin.kdbx");
+    let key = || keepass::DatabaseKey::new().with_password("testpass");
+    let mut db =
+        keepass::Database::open(&mut std::fs::File::open(&path).expect("open file"), key())
+            .expect("open database");
+    db.meta.custom_data.items.insert(
+        "MithrilVault.RecycleBin/RetentionDays".to_string(),
+        keepass::db::CustomDataItem {
+            value: Some(keepass::db::Value::Unprotected(u32::MAX.to_string())),
+            last_modification_time: None,
+        },
+    );
+    db.save(
+        &mut std::fs::File::create(&path).expect("create file"),
+        key(),
+    )
+    .expect("save database");
+
+    service
+        .open(&path.to_string_lossy(), "testpass")
+        .expect("reopen");
+    service.save().expect("save");
+    assert_eq!(service.list_recycle_bin().expect("list").len(), 1);
+}
+
+// ============================================================================
+// Permanent deletion
+// ============================================================================
+
+#[test]
+fn test_delete_entry_permanently() {
+    let (service, dir, _, _, entry_id) = create_database();
+    service.delete_entry(&entry_id, true).expect("delete entry");
+
+    assert!(matches!(
+        service.get_entry(&entry_id),
+        Err(AppError::EntryNotFound(_))
+    ));
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+    assert_deleted_objects(&service, &dir, &[&entry_id]);
+}
+
+#[test]
+fn test_delete_entry_from_bin_is_permanent() {
+    let (service, dir, _, _, entry_id) = create_database();
+    service.delete_entry(&entry_id, false).expect("move to bin");
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete from bin");
+
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+    assert_deleted_objects(&service, &dir, &[&entry_id]);
+}
+
+#[test]
+fn test_disabled_bin_deletes_permanently() {
+    let (service, dir, root_id, work_id, entry_id) = create_database();
+    service
+        .update_recycle_bin_settings(&RecycleBinSettings {
+            enabled: false,
+            retention_days: None,
+        })
+        .expect("update settings");
+    assert!(
+        !service
+            .get_recycle_bin_settings()
+            .expect("settings")
+            .enabled
+    );
+
+    service
+        .delete_entry(&entry_id, false)
+        .expect("delete entry");
+    service
+        .delete_group(&work_id, false, false)
+        .expect("delete group");
+
+    let groups = service.list_groups().expect("groups");
+    assert!(
+        groups[0].children.is_empty(),
+        "No recycle bin is created under {root_id}"
+    );
+    assert_deleted_objects(&service, &dir, &[&entry_id, &work_id]);
+}
+
+#[test]
+fn test_delete_group_from_bin_is_permanent() {
+    let (service, dir, _, work_id, entry_id) = create_database();
+    service
+        .delete_group(&work_id, true, false)
+        .expect("move to bin");
+    service
+        .delete_group(&work_id, true, false)
+        .expect("delete from bin");
+
+    assert!(service.list_recycle_bin().expect("list").is_empty());
+    assert_deleted_objects(&service, &dir, &[&work_id, &entry_id]);
+}
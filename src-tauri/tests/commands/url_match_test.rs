@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MIT
+//! Tests for URL matching command handlers
+//!
+//! These tests exercise the `KdbxService` methods that the URL matching commands delegate to.
+
+#![allow(clippy::expect_used)] // expect() is acceptable in tests
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::url_match::{UrlMatchKind, UrlMatchOptions};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::collections::BTreeMap;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates an empty database and returns the service, temp dir and root group ID.
+fn create_database() -> (KdbxService, TempDir, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("url_match.kdbx");
+
+    let service = KdbxService::new();
+    let info = create_test_database(&service, &db_path, "URL Matching", "testpass");
+
+    (service, dir, info.root_group_id)
+}
+
+fn create_entry(
+    service: &KdbxService,
+    group_id: &str,
+    title: &str,
+    url: Option<&str>,
+    custom_fields: &[(&str, &str)],
+    expiry_time: Option<&str>,
+) -> String {
+    let custom_fields: BTreeMap<String, String> = custom_fields
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+        .collect();
+    service
+        .create_entry(
+            group_id,
+            CreateEntryData {
+                title: title.to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("secret"),
+                url: url.map(ToString::to_string),
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: Some(custom_fields),
+                protected_custom_fields: None,
+                expiry_time: expiry_time.map(ToString::to_string),
+            },
+        )
+        .expect("Failed to create entry")
+        .id
+}
+
+fn titles(service: &KdbxService, url: &str, options: UrlMatchOptions) -> Vec<String> {
+    service
+        .find_entries_for_url(url, &options)
+        .expect("find entries")
+        .into_iter()
+        .map(|m| m.title)
+        .collect()
+}
+
+// ============================================================================
+// find_entries_for_url command tests
+// ============================================================================
+
+#[test]
+fn test_find_entries_ranks_exact_hosts_first() {
+    let (service, _dir, root_id) = create_database();
+    create_entry(
+        &service,
+        &root_id,
+        "Sibling",
+        Some("https://www.example.com"),
+        &[],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Login",
+        Some("login.example.com"),
+        &[],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Additional",
+        Some("https://example.org"),
+        &[("KP2A_URL_1", "https://login.example.com/sso")],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Unrelated",
+        Some("https://example.org"),
+        &[],
+        None,
+    );
+    create_entry(&service, &root_id, "No URL", None, &[], None);
+
+    let matches = service
+        .find_entries_for_url(
+            "https://login.example.com/account",
+            &UrlMatchOptions::default(),
+        )
+        .expect("find entries");
+
+    let summary: Vec<(&str, UrlMatchKind)> = matches
+        .iter()
+        .map(|m| (m.title.as_str(), m.match_kind))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Additional", UrlMatchKind::ExactHost),
+            ("Login", UrlMatchKind::ExactHost),
+            ("Sibling", UrlMatchKind::Domain),
+        ]
+    );
+    assert_eq!(matches[0].matched_url, "https://login.example.com/sso");
+    assert_eq!(matches[0].username, "alice");
+}
+
+#[test]
+fn test_find_entries_skips_recycle_bin_and_expired() {
+    let (service, _dir, root_id) = create_database();
+    create_entry(
+        &service,
+        &root_id,
+        "Active",
+        Some("https://example.com"),
+        &[],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Expired",
+        Some("https://example.com"),
+        &[],
+        Some("2000-01-01 00:00:00"),
+    );
+    let deleted = create_entry(
+        &service,
+        &root_id,
+        "Deleted",
+        Some("https://example.com"),
+        &[],
+        None,
+    );
+    service.delete_entry(&deleted, false).expect("delete entry");
+
+    assert_eq!(
+        titles(&service, "https://example.com", UrlMatchOptions::default()),
+        vec!["Active"]
+    );
+}
+
+#[test]
+fn test_find_entries_strictness_options() {
+    let (service, _dir, root_id) = create_database();
+    create_entry(
+        &service,
+        &root_id,
+        "Plain HTTP",
+        Some("http://example.com"),
+        &[],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Admin",
+        Some("https://example.com:8443/admin"),
+        &[],
+        None,
+    );
+
+    let loose = UrlMatchOptions::default();
+    assert_eq!(
+        titles(&service, "https://example.com/", loose),
+        vec!["Admin", "Plain HTTP"]
+    );
+
+    let scheme = UrlMatchOptions {
+        match_scheme: true,
+        ..UrlMatchOptions::default()
+    };
+    assert_eq!(
+        titles(&service, "https://example.com/", scheme),
+        vec!["Admin"]
+    );
+
+    let port_and_path = UrlMatchOptions {
+        match_port: true,
+        match_path: true,
+        ..UrlMatchOptions::default()
+    };
+    assert_eq!(
+        titles(
+            &service,
+            "https://example.com:8443/admin/users",
+            port_and_path
+        ),
+        vec!["Admin"]
+    );
+    assert!(titles(&service, "https://example.com/admin", port_and_path).is_empty());
+}
+
+#[test]
+fn test_find_entries_does_not_match_across_public_suffix() {
+    let (service, _dir, root_id) = create_database();
+    create_entry(
+        &service,
+        &root_id,
+        "Sibling",
+        Some("https://www.example.co.uk"),
+        &[],
+        None,
+    );
+    create_entry(
+        &service,
+        &root_id,
+        "Unrelated",
+        Some("https://other.co.uk"),
+        &[],
+        None,
+    );
+
+    assert_eq!(
+        titles(
+            &service,
+            "https://login.example.co.uk",
+            UrlMatchOptions::default()
+        ),
+        vec!["Sibling"]
+    );
+}
+
+#[test]
+fn test_find_entries_invalid_url() {
+    let (service, _dir, _) = create_database();
+    let result =
+        service.find_entries_for_url("mailto:alice@example.com", &UrlMatchOptions::default());
+    assert!(matches!(result, Err(AppError::InvalidUrl(_))));
+}
+
+#[test]
+fn test_find_entries_database_not_open() {
+    let service = KdbxService::new();
+    let result = service.find_entries_for_url("https://example.com", &UrlMatchOptions::default());
+    assert!(matches!(result, Err(AppError::DatabaseNotOpen)));
+}
@@ -3,13 +3,121 @@
 
 #![allow(clippy::expect_used)]
 
+use std::collections::HashSet;
+
 use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::lock::LockFileInfoDto;
+
+const GROUP_ID: &str = "8f0c3b0e-5d1a-4b7e-9a43-2f6f0f5b2c11";
+const ENTRY_ID: &str = "c2a1d7e4-9b3f-4e6a-8d21-7a5e3c9b1f04";
+
+fn lock_holder() -> LockFileInfoDto {
+    LockFileInfoDto {
+        pid: 4242,
+        application: "KeePassXC".to_string(),
+        version: "2.7.9".to_string(),
+        opened_at: "2026-01-02T03:04:05+00:00".to_string(),
+        hostname: "workstation".to_string(),
+    }
+}
+
+/// One value of every variant. A new variant belongs here so its code and
+/// shape get a snapshot.
+fn every_variant() -> Vec<AppError> {
+    let text = |value: &str| value.to_string();
+    vec![
+        AppError::DatabaseNotOpen,
+        AppError::DatabaseAlreadyOpen,
+        AppError::InvalidPassword,
+        AppError::EntryNotFound(text(ENTRY_ID)),
+        AppError::CustomFieldNotFound(text("api-key")),
+        AppError::CustomFieldNotProtected(text("api-key")),
+        AppError::GroupNotFound(text(GROUP_ID)),
+        AppError::CannotDeleteRootGroup,
+        AppError::CannotMoveRootGroup,
+        AppError::CircularReference,
+        AppError::GroupNotEmpty(text(GROUP_ID)),
+        AppError::InvalidPath(text("Database file not found: vault.kdbx")),
+        AppError::Io(text("disk full")),
+        AppError::Crypto(text("bad key length")),
+        AppError::Kdbx(text("corrupt payload")),
+        AppError::InvalidKdbxFile,
+        AppError::UnsupportedKdbxVersion(text("5.0")),
+        AppError::HeaderIntegrityError,
+        AppError::UnsupportedCipher(text("Serpent")),
+        AppError::UnsupportedKdf(text("scrypt")),
+        AppError::HeaderParseError(text("truncated header")),
+        AppError::KeyfileNotFound,
+        AppError::KeyfileInvalid,
+        AppError::NoCredentials,
+        AppError::Keychain(text("no keyring")),
+        AppError::SecureStorage(text("snapshot unreadable")),
+        AppError::QuickUnlockUnavailable,
+        AppError::PinTooShort(4),
+        AppError::IncorrectPin(2),
+        AppError::UnlockThrottled(8),
+        AppError::Lock,
+        AppError::DatabaseLocked(
+            text("Database is locked by KeePassXC (PID: 4242)"),
+            Some(Box::new(lock_holder())),
+        ),
+        AppError::DatabaseReadOnly,
+        AppError::FileLockFailed(text("permission denied")),
+        AppError::NotImplemented(text("generate_password")),
+        AppError::AtomicWrite(text("rename failed")),
+        AppError::SyncFailed(text("fsync failed")),
+        AppError::PlaintextExportNotAcknowledged,
+        AppError::KeeShare(text("signature mismatch")),
+        AppError::NotInRecycleBin(text(ENTRY_ID)),
+        AppError::BreachIndex(text("index missing")),
+        AppError::InvalidTimestamp(text("yesterday")),
+        AppError::FieldReference(text("{REF:X@I:0}")),
+        AppError::InvalidGeneratorOptions(text("length is zero")),
+        AppError::Placeholder(text("{UNKNOWN}")),
+        AppError::InvalidAutoTypeSequence(text("{TAB")),
+        AppError::AutoTypeDisabled(text(ENTRY_ID)),
+        AppError::InvalidUrl(text("not a url")),
+        AppError::BrowserIntegration(text("unknown action")),
+        AppError::IpcClientNotFound(text("client-1")),
+        AppError::Ipc(text("connection closed")),
+        AppError::AttachmentNotFound(text("id_ed25519")),
+        AppError::SshAgent(text("unsupported key")),
+        AppError::SecretService(text("bus unavailable")),
+        AppError::SecretSessionNotFound(text("/org/freedesktop/secrets/session/1")),
+        AppError::SecretServiceAccessDenied(text("seahorse")),
+        AppError::AmbiguousPath(text("Email/Work")),
+        AppError::InvalidArguments(text("missing path")),
+        AppError::Template(text("line 3: unterminated tag")),
+    ]
+}
+
+#[test]
+fn app_error_snapshots() {
+    for err in every_variant() {
+        insta::assert_json_snapshot!(err.code(), err);
+    }
+}
+
+#[test]
+fn app_error_codes_are_unique() {
+    let variants = every_variant();
+    let codes: HashSet<_> = variants.iter().map(AppError::code).collect();
+    assert_eq!(codes.len(), variants.len());
+}
+
+#[test]
+fn app_error_keeps_message() {
+    let json = serde_json::to_value(AppError::InvalidPassword).expect("serialize error");
+    assert_eq!(json["message"], "Invalid password");
+    assert!(json["details"].is_null());
+}
 
 #[test]
-fn app_error_serializes_to_string() {
-    let err = AppError::InvalidPassword;
-    let json = serde_json::to_string(&err).expect("serialize error");
-    assert_eq!(json, "\"Invalid password\"");
+fn database_locked_without_holder_has_no_details() {
+    let err = AppError::DatabaseLocked("Database is locked by another process".into(), None);
+    let json = serde_json::to_value(err).expect("serialize error");
+    assert_eq!(json["code"], "databaseLocked");
+    assert!(json["details"].is_null());
 }
 
 #[test]
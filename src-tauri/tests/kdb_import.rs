@@ -0,0 +1,169 @@
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::database::KdbImportSource;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::tempdir;
+
+#[path = "support/mod.rs"]
+mod support;
+
+use support::{fixture_path, low_kdf_options};
+
+const TEST_ENTRY_ID: &str = "01010101-0101-0101-0101-010101010101";
+const MAIL_ENTRY_ID: &str = "02020202-0202-0202-0202-020202020202";
+
+fn kdb_source(fixture: &str, password: &str) -> KdbImportSource {
+    KdbImportSource {
+        path: fixture_path(fixture).to_string_lossy().to_string(),
+        password: Some(SecureString::from(password)),
+        keyfile_path: None,
+    }
+}
+
+#[test]
+fn test_import_kdb_aes() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("imported.kdbx");
+
+    let service = KdbxService::new();
+    let result = service
+        .import_kdb(
+            &kdb_source("test-kdb-aes.kdb", "test123"),
+            &db_path.to_string_lossy(),
+            Some("newpass123"),
+            None,
+            "Imported",
+            &low_kdf_options(),
+        )
+        .expect("Failed to import KDB database");
+
+    assert_eq!(result.database.name, "Imported");
+    assert_eq!(result.database.version, "KDBX 4.0");
+    assert_eq!(result.group_count, 3);
+    assert_eq!(result.entry_count, 2);
+    assert_eq!(result.attachment_count, 1);
+    assert_eq!(result.skipped_meta_streams, vec!["KPX_UNKNOWN_STREAM"]);
+
+    let groups = service.list_groups().expect("Failed to list groups");
+    let root = &groups[0];
+    let names: Vec<&str> = root.children.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["Internet", "Backup"]);
+    assert_eq!(root.children[0].children[0].name, "Email");
+
+    let entry = service
+        .get_entry(TEST_ENTRY_ID)
+        .expect("Imported entry should keep its UUID");
+    assert_eq!(entry.title, "Test Entry");
+    assert_eq!(entry.username, "testuser");
+    assert_eq!(entry.url.as_deref(), Some("https://example.com"));
+    assert_eq!(entry.notes.as_deref(), Some("Imported from KeePass 1.x"));
+    assert_eq!(entry.group_id, root.children[0].id);
+    assert_eq!(entry.created_at, "2010-05-01 10:00:00");
+    assert_eq!(
+        service
+            .get_entry_password(TEST_ENTRY_ID)
+            .expect("Failed to get password"),
+        "testpass123"
+    );
+
+    let mail = service
+        .get_entry(MAIL_ENTRY_ID)
+        .expect("Nested entry should be imported");
+    assert_eq!(mail.group_id, root.children[0].children[0].id);
+}
+
+#[test]
+fn test_import_kdb_twofish_reopens_with_new_credentials() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("imported-twofish.kdbx");
+
+    let service = KdbxService::new();
+    service
+        .import_kdb(
+            &kdb_source("test-kdb-twofish.kdb", "test123"),
+            &db_path.to_string_lossy(),
+            Some("newpass123"),
+            None,
+            "Imported Twofish",
+            &low_kdf_options(),
+        )
+        .expect("Failed to import Twofish KDB database");
+
+    service.close().expect("Failed to close");
+
+    service
+        .open(&db_path.to_string_lossy(), "newpass123")
+        .expect("Imported database should open with the new password");
+    let entries = service.list_entries(None).expect("Failed to list entries");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(
+        service
+            .get_entry_password(MAIL_ENTRY_ID)
+            .expect("Failed to get password"),
+        "mailpass456"
+    );
+}
+
+#[test]
+fn test_import_kdb_wrong_password() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("imported.kdbx");
+
+    let service = KdbxService::new();
+    let result = service.import_kdb(
+        &kdb_source("test-kdb-aes.kdb", "wrongpassword"),
+        &db_path.to_string_lossy(),
+        Some("newpass123"),
+        None,
+        "Imported",
+        &low_kdf_options(),
+    );
+
+    assert!(matches!(result, Err(AppError::InvalidPassword)));
+    assert!(!db_path.exists(), "No database should be written");
+    assert!(matches!(service.get_info(), Err(AppError::DatabaseNotOpen)));
+}
+
+#[test]
+fn test_import_kdb_rejects_kdbx_file() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("imported.kdbx");
+
+    let service = KdbxService::new();
+    let result = service.import_kdb(
+        &kdb_source("test-kdbx4-low-KDF.kdbx", "test123"),
+        &db_path.to_string_lossy(),
+        Some("newpass123"),
+        None,
+        "Imported",
+        &low_kdf_options(),
+    );
+
+    assert!(matches!(result, Err(AppError::InvalidKdbxFile)));
+}
+
+#[test]
+fn test_import_kdb_rejects_source_as_target() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let source_path = dir.path().join("legacy.kdb");
+    std::fs::copy(fixture_path("test-kdb-aes.kdb"), &source_path).expect("Failed to copy fixture");
+
+    let service = KdbxService::new();
+    let source = KdbImportSource {
+        path: source_path.to_string_lossy().to_string(),
+        password: Some(SecureString::from("test123")),
+        keyfile_path: None,
+    };
+    let result = service.import_kdb(
+        &source,
+        &source_path.to_string_lossy(),
+        Some("newpass123"),
+        None,
+        "Imported",
+        &low_kdf_options(),
+    );
+
+    assert!(matches!(result, Err(AppError::InvalidPath(_))));
+}
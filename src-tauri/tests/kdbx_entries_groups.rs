@@ -358,6 +358,7 @@ fn test_protected_fields_kdbx4_roundtrip() {
                 tags: None,
                 custom_fields: Some(custom_fields),
                 protected_custom_fields: Some(protected_custom_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -449,6 +450,7 @@ fn test_protected_fields_persist_after_save() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected1),
+                expiry_time: None,
             },
         )
         .expect("create entry 1");
@@ -473,6 +475,7 @@ fn test_protected_fields_persist_after_save() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected2),
+                expiry_time: None,
             },
         )
         .expect("create entry 2");
@@ -343,3 +343,69 @@ fn test_keyfile_not_found_for_password_plus_keyfile() {
         "Should fail when keyfile path doesn't exist: got {result:?}"
     );
 }
+
+#[test]
+fn test_open_read_only_while_locked_elsewhere() {
+    let Some((_temp_dir, path)) = copy_fixture_to_temp("test-kdbx4-low-KDF.kdbx") else {
+        eprintln!("Skipping test: fixture not found");
+        return;
+    };
+    let path = path.to_string_lossy();
+
+    let writer = KdbxService::new();
+    writer
+        .open(&path, "test123")
+        .expect("Failed to open database");
+
+    let reader = KdbxService::new();
+    let info = reader
+        .open_read_only(&path, Some("test123"), None)
+        .expect("Failed to open database read-only");
+    assert!(info.is_read_only);
+    assert!(reader.get_info().expect("get info").is_read_only);
+    assert!(reader.list_entries(None).is_ok());
+
+    // Closing the read-only copy leaves the writer's lock alone
+    reader.close().expect("Failed to close database");
+    assert!(writer.save().is_ok());
+}
+
+#[test]
+fn test_read_only_refuses_changes() {
+    let Some((_temp_dir, path)) = copy_fixture_to_temp("test-kdbx4-low-KDF.kdbx") else {
+        eprintln!("Skipping test: fixture not found");
+        return;
+    };
+    let service = KdbxService::new();
+    let info = service
+        .open_read_only(&path.to_string_lossy(), Some("test123"), None)
+        .expect("Failed to open database read-only");
+
+    assert!(matches!(
+        service.create_group(Some(&info.root_group_id), "New", None),
+        Err(AppError::DatabaseReadOnly)
+    ));
+    assert!(matches!(service.save(), Err(AppError::DatabaseReadOnly)));
+
+    // Read-only survives locking the session
+    service.lock().expect("Failed to lock database");
+    let locked = service
+        .locked_info()
+        .expect("locked info")
+        .expect("database is locked");
+    assert!(locked.is_read_only);
+    let info = service.unlock(Some("test123")).expect("Failed to unlock");
+    assert!(info.is_read_only);
+    assert!(matches!(service.save(), Err(AppError::DatabaseReadOnly)));
+}
+
+#[test]
+fn test_open_read_only_wrong_password() {
+    let Some((_temp_dir, path)) = copy_fixture_to_temp("test-kdbx4-low-KDF.kdbx") else {
+        eprintln!("Skipping test: fixture not found");
+        return;
+    };
+    let service = KdbxService::new();
+    let result = service.open_read_only(&path.to_string_lossy(), Some("wrong"), None);
+    assert!(matches!(result, Err(AppError::InvalidPassword)));
+}
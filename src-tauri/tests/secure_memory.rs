@@ -95,6 +95,7 @@ fn test_save_reopen_with_secure_password() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: None,
+                expiry_time: None,
             },
         )
         .expect("create entry");
@@ -172,6 +173,7 @@ fn test_protected_custom_fields_with_secure_string() {
                 tags: None,
                 custom_fields: None,
                 protected_custom_fields: Some(protected_fields),
+                expiry_time: None,
             },
         )
         .expect("create entry");
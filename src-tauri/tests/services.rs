@@ -3,6 +3,9 @@
 
 use std::sync::{Mutex, MutexGuard};
 
+#[path = "support/mod.rs"]
+mod support;
+
 // Tauri's test app uses a shared app-local data directory within a test process.
 // These settings tests perform filesystem operations on the same settings.json path,
 // so we serialize them to avoid cross-test races.
@@ -17,8 +20,35 @@ fn settings_test_lock() -> MutexGuard<'static, ()> {
     }
 }
 
+#[path = "services/browser_service_test.rs"]
+mod browser_service_test;
+
+#[path = "services/ipc_service_test.rs"]
+mod ipc_service_test;
+
 #[path = "services/settings_service_test.rs"]
 mod settings_service_test;
 
 #[path = "services/settings_service_unitlike_test.rs"]
 mod settings_service_unitlike_test;
+
+#[path = "services/ssh_agent_service_test.rs"]
+mod ssh_agent_service_test;
+
+#[path = "services/secret_service_service_test.rs"]
+mod secret_service_service_test;
+
+#[path = "services/git_credential_service_test.rs"]
+mod git_credential_service_test;
+
+#[path = "services/template_service_test.rs"]
+mod template_service_test;
+
+#[path = "services/quick_unlock_service_test.rs"]
+mod quick_unlock_service_test;
+
+#[path = "services/unlock_throttle_service_test.rs"]
+mod unlock_throttle_service_test;
+
+#[path = "services/share_watcher_service_test.rs"]
+mod share_watcher_service_test;
@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the `KeePassXC-Browser` protocol handler
+
+#![allow(clippy::expect_used)]
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use crypto_box::aead::{Aead, OsRng};
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::services::browser::BrowserService;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// An extension talking to the service, holding its session keys.
+struct Client {
+    id: &'static str,
+    secret_key: SecretKey,
+    crypto: Option<SalsaBox>,
+    /// Long-lived identification key used for associating
+    id_key: String,
+}
+
+impl Client {
+    fn new(id: &'static str) -> Self {
+        Self {
+            id,
+            secret_key: SecretKey::generate(&mut OsRng),
+            crypto: None,
+            id_key: BASE64.encode(SecretKey::generate(&mut OsRng).public_key().as_bytes()),
+        }
+    }
+
+    fn public_key(&self) -> String {
+        BASE64.encode(self.secret_key.public_key().as_bytes())
+    }
+
+    fn change_public_keys(&mut self, service: &BrowserService) -> Value {
+        let nonce = [7u8; 24];
+        let reply = service.handle_request(&json!({
+            "action": "change-public-keys",
+            "publicKey": self.public_key(),
+            "nonce": BASE64.encode(nonce),
+            "clientID": self.id,
+        }));
+        if let Some(server_key) = reply["publicKey"].as_str() {
+            let server_key = BASE64.decode(server_key).expect("server key");
+            let server_key = PublicKey::from_slice(&server_key).expect("server key length");
+            self.crypto = Some(SalsaBox::new(&server_key, &self.secret_key));
+        }
+        reply
+    }
+
+    /// Sends an encrypted request and returns the decrypted reply, or the
+    /// plain reply if the service answered with an error.
+    fn send(&self, service: &BrowserService, message: &Value) -> Value {
+        let crypto = self.crypto.as_ref().expect("keys exchanged");
+        let nonce = [42u8; 24];
+        let ciphertext = crypto
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                serde_json::to_vec(message).expect("encode").as_slice(),
+            )
+            .expect("encrypt");
+        let reply = service.handle_request(&json!({
+            "action": message["action"],
+            "message": BASE64.encode(ciphertext),
+            "nonce": BASE64.encode(nonce),
+            "clientID": self.id,
+        }));
+
+        let Some(ciphertext) = reply["message"].as_str() else {
+            return reply;
+        };
+        let reply_nonce = BASE64
+            .decode(reply["nonce"].as_str().expect("nonce"))
+            .expect("nonce");
+        let mut expected_nonce = nonce;
+        expected_nonce[0] += 1;
+        assert_eq!(reply_nonce, expected_nonce);
+
+        let plaintext = crypto
+            .decrypt(
+                Nonce::from_slice(&reply_nonce),
+                BASE64.decode(ciphertext).expect("message").as_slice(),
+            )
+            .expect("decrypt");
+        serde_json::from_slice(&plaintext).expect("reply JSON")
+    }
+
+    fn associate(&self, service: &BrowserService) -> Value {
+        self.send(
+            service,
+            &json!({
+                "action": "associate",
+                "key": self.public_key(),
+                "idKey": self.id_key,
+            }),
+        )
+    }
+}
+
+fn create_service(approve: bool) -> (BrowserService, Arc<KdbxService>, TempDir) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("browser.kdbx");
+
+    let kdbx = Arc::new(KdbxService::new());
+    let info = create_test_database(&kdbx, &db_path, "Browser", "testpass");
+    kdbx.create_entry(
+        &info.root_group_id,
+        CreateEntryData {
+            title: "Example".to_string(),
+            username: "alice".to_string(),
+            password: SecureString::from("hunter2"),
+            url: Some("https://www.example.com/login".to_string()),
+            notes: None,
+            icon_id: None,
+            tags: None,
+            custom_fields: None,
+            protected_custom_fields: None,
+            expiry_time: None,
+        },
+    )
+    .expect("Failed to create entry");
+
+    let service = BrowserService::new(Arc::clone(&kdbx), move |_| approve);
+    (service, kdbx, dir)
+}
+
+#[test]
+fn test_change_public_keys_increments_nonce() {
+    let (service, _kdbx, _dir) = create_service(true);
+    let mut client = Client::new("client-a");
+
+    let reply = client.change_public_keys(&service);
+    assert_eq!(reply["success"], "true");
+    assert_eq!(reply["action"], "change-public-keys");
+
+    let mut expected = [7u8; 24];
+    expected[0] = 8;
+    assert_eq!(reply["nonce"], BASE64.encode(expected));
+}
+
+#[test]
+fn test_associate_and_get_logins() {
+    let (service, kdbx, _dir) = create_service(true);
+    let mut client = Client::new("client-a");
+    client.change_public_keys(&service);
+
+    let hash = client.send(&service, &json!({ "action": "get-databasehash" }));
+    assert_eq!(hash["success"], "true");
+    assert_eq!(
+        hash["hash"],
+        kdbx.database_hash().expect("database hash").as_str()
+    );
+
+    let associated = client.associate(&service);
+    assert_eq!(associated["success"], "true");
+    let id = associated["id"]
+        .as_str()
+        .expect("association id")
+        .to_string();
+    assert_eq!(
+        kdbx.browser_association_key(&id).expect("association key"),
+        Some(client.id_key.clone())
+    );
+
+    let logins = client.send(
+        &service,
+        &json!({
+            "action": "get-logins",
+            "url": "https://login.example.com/",
+            "keys": [{ "id": id, "key": client.id_key }],
+        }),
+    );
+    assert_eq!(logins["count"], 1);
+    assert_eq!(logins["entries"][0]["login"], "alice");
+    assert_eq!(logins["entries"][0]["password"], "hunter2");
+    assert_eq!(logins["entries"][0]["name"], "Example");
+
+    let none = client.send(
+        &service,
+        &json!({
+            "action": "get-logins",
+            "url": "https://example.org/",
+            "keys": [{ "id": id, "key": client.id_key }],
+        }),
+    );
+    assert_eq!(none["errorCode"], "15");
+}
+
+#[test]
+fn test_unknown_keys_are_rejected() {
+    let (service, _kdbx, _dir) = create_service(true);
+    let mut client = Client::new("client-a");
+    client.change_public_keys(&service);
+    let id = client.associate(&service)["id"]
+        .as_str()
+        .expect("association id")
+        .to_string();
+
+    let mut other = Client::new("client-b");
+    other.change_public_keys(&service);
+    let test = other.send(
+        &service,
+        &json!({ "action": "test-associate", "id": id, "key": other.id_key }),
+    );
+    assert_eq!(test["errorCode"], "8");
+
+    let logins = other.send(
+        &service,
+        &json!({
+            "action": "get-logins",
+            "url": "https://www.example.com/",
+            "keys": [{ "id": id, "key": other.id_key }],
+        }),
+    );
+    assert_eq!(logins["errorCode"], "8");
+
+    // Saving requires an association proven in the same session
+    let saved = other.send(
+        &service,
+        &json!({
+            "action": "set-login",
+            "id": id,
+            "url": "https://www.example.com/",
+            "login": "mallory",
+            "password": "stolen",
+        }),
+    );
+    assert_eq!(saved["errorCode"], "8");
+}
+
+#[test]
+fn test_associate_requires_approval_and_session_key() {
+    let (service, kdbx, _dir) = create_service(false);
+    let mut client = Client::new("client-a");
+    client.change_public_keys(&service);
+
+    assert_eq!(client.associate(&service)["errorCode"], "6");
+    assert!(kdbx
+        .list_browser_associations()
+        .expect("associations")
+        .is_empty());
+
+    let wrong_key = client.send(
+        &service,
+        &json!({
+            "action": "associate",
+            "key": client.id_key,
+            "idKey": client.id_key,
+        }),
+    );
+    assert_eq!(wrong_key["errorCode"], "8");
+}
+
+#[test]
+fn test_set_login_creates_and_updates_entries() {
+    let (service, kdbx, _dir) = create_service(true);
+    let mut client = Client::new("client-a");
+    client.change_public_keys(&service);
+    let id = client.associate(&service)["id"]
+        .as_str()
+        .expect("association id")
+        .to_string();
+
+    let saved = client.send(
+        &service,
+        &json!({
+            "action": "set-login",
+            "id": id,
+            "url": "https://accounts.example.net/signup",
+            "login": "bob",
+            "password": "correct horse",
+        }),
+    );
+    assert_eq!(saved["success"], "true");
+
+    let logins = kdbx
+        .browser_logins("https://accounts.example.net/")
+        .expect("logins");
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].title, "accounts.example.net");
+    assert_eq!(logins[0].group, "KeePassXC-Browser Passwords");
+    assert_eq!(logins[0].password.as_str(), "correct horse");
+
+    let uuid = uuid::Uuid::parse_str(&logins[0].entry_id)
+        .expect("entry id")
+        .simple()
+        .to_string();
+    client.send(
+        &service,
+        &json!({
+            "action": "set-login",
+            "id": id,
+            "url": "https://accounts.example.net/signup",
+            "uuid": uuid,
+            "login": "bob",
+            "password": "battery staple",
+        }),
+    );
+    let logins = kdbx
+        .browser_logins("https://accounts.example.net/")
+        .expect("logins");
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].password.as_str(), "battery staple");
+}
+
+#[test]
+fn test_errors_without_session_or_database() {
+    let (service, kdbx, _dir) = create_service(true);
+    let mut client = Client::new("client-a");
+
+    let reply = service.handle_request(&json!({
+        "action": "get-databasehash",
+        "message": "",
+        "nonce": BASE64.encode([0u8; 24]),
+        "clientID": client.id,
+    }));
+    assert_eq!(reply["errorCode"], "10");
+
+    client.change_public_keys(&service);
+    kdbx.close().expect("close database");
+    let hash = client.send(&service, &json!({ "action": "get-databasehash" }));
+    assert_eq!(hash["errorCode"], "1");
+
+    let generated = client.send(&service, &json!({ "action": "generate-password" }));
+    assert_eq!(generated["success"], "true");
+    assert_eq!(generated["password"].as_str().map(str::len), Some(20));
+}
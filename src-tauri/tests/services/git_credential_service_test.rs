@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the git credential helper protocol
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::ipc::IpcScopes;
+use mithril_vault_lib::services::git_credential::{self, CredentialStore};
+use mithril_vault_lib::services::ipc::IpcService;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+fn setup() -> (KdbxService, TempDir) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let kdbx = KdbxService::new();
+    create_test_database(&kdbx, &dir.path().join("git.kdbx"), "Git", "testpass");
+    (kdbx, dir)
+}
+
+fn add_login(kdbx: &KdbxService, title: &str, url: &str, username: &str, password: &str) -> String {
+    let root = kdbx.get_info().expect("info").root_group_id;
+    kdbx.create_entry(
+        &root,
+        CreateEntryData {
+            title: title.to_string(),
+            username: username.to_string(),
+            password: SecureString::from(password),
+            url: Some(url.to_string()),
+            notes: None,
+            icon_id: None,
+            tags: None,
+            custom_fields: None,
+            protected_custom_fields: None,
+            expiry_time: None,
+        },
+    )
+    .expect("Failed to create entry")
+    .id
+}
+
+/// Runs a helper operation and returns what it printed.
+fn helper(store: &mut dyn CredentialStore, operation: &str, input: &str) -> String {
+    let mut output = Vec::new();
+    git_credential::run(operation, &mut input.as_bytes(), &mut output, store)
+        .expect("Helper operation failed");
+    String::from_utf8(output).expect("Output is not UTF-8")
+}
+
+#[test]
+fn test_get_matches_host_path_and_username() {
+    let (mut kdbx, _dir) = setup();
+    add_login(&kdbx, "GitHub", "https://github.com", "alice", "gh-secret");
+    add_login(
+        &kdbx,
+        "Work repos",
+        "https://github.com/acme",
+        "alice-work",
+        "acme-secret",
+    );
+
+    let answer = helper(&mut kdbx, "get", "protocol=https\nhost=github.com\n\n");
+    assert_eq!(answer, "username=alice\npassword=gh-secret\n");
+
+    let answer = helper(
+        &mut kdbx,
+        "get",
+        "protocol=https\nhost=github.com\nusername=alice-work\n\n",
+    );
+    assert_eq!(answer, "username=alice-work\npassword=acme-secret\n");
+
+    // With credential.useHttpPath, entry paths must be a prefix
+    let answer = helper(&mut kdbx, "get", "url=https://github.com/acme/app.git\n\n");
+    assert_eq!(answer, "username=alice-work\npassword=acme-secret\n");
+
+    assert_eq!(
+        helper(&mut kdbx, "get", "protocol=https\nhost=gitlab.com\n\n"),
+        ""
+    );
+    assert_eq!(
+        helper(
+            &mut kdbx,
+            "get",
+            "protocol=https\nhost=github.com\nusername=bob\n\n"
+        ),
+        ""
+    );
+}
+
+#[test]
+fn test_store_updates_same_host_and_creates_otherwise() {
+    let (mut kdbx, _dir) = setup();
+    let existing = add_login(&kdbx, "Git", "https://git.example.com", "alice", "old");
+    let other = add_login(&kdbx, "Wiki", "https://wiki.example.com", "alice", "wiki");
+
+    helper(
+        &mut kdbx,
+        "store",
+        "protocol=https\nhost=git.example.com\nusername=alice\npassword=new\n\n",
+    );
+    assert_eq!(kdbx.get_entry_password(&existing).expect("password"), "new");
+    // Same registrable domain, but a different service
+    assert_eq!(kdbx.get_entry_password(&other).expect("password"), "wiki");
+
+    helper(
+        &mut kdbx,
+        "store",
+        "protocol=https\nhost=code.example.org\nusername=bob\npassword=fresh\n\n",
+    );
+    let created = kdbx
+        .search_entries("code.example.org", None)
+        .expect("search");
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].username, "bob");
+
+    // Erase never removes entries
+    helper(
+        &mut kdbx,
+        "erase",
+        "protocol=https\nhost=git.example.com\nusername=alice\npassword=new\n\n",
+    );
+    assert!(kdbx.get_entry(&existing).is_ok());
+}
+
+#[test]
+fn test_rejects_malformed_input() {
+    let (mut kdbx, _dir) = setup();
+    let mut output = Vec::new();
+    let result = git_credential::run(
+        "get",
+        &mut "protocol=https\ngarbage\n\n".as_bytes(),
+        &mut output,
+        &mut kdbx,
+    );
+    assert!(result.is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_app_connection_requires_approval() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (kdbx, dir) = setup();
+    let kdbx = Arc::new(kdbx);
+    add_login(&kdbx, "GitHub", "https://github.com", "alice", "gh-secret");
+    let socket = dir.path().join("ipc.sock");
+    let ipc = Arc::new(
+        IpcService::new(Arc::clone(&kdbx), &dir.path().join("data"), socket.clone())
+            .expect("Failed to create IPC service"),
+    );
+    ipc.set_enabled(true).expect("Failed to enable IPC");
+    let token_file = dir.path().join("config/git-credential-token");
+
+    // No app listening
+    assert!(
+        git_credential::connect_app(&dir.path().join("missing.sock"), &token_file)
+            .expect("connect")
+            .is_none()
+    );
+
+    // First contact registers and stores the token, but gets no access yet
+    assert!(git_credential::connect_app(&socket, &token_file).is_err());
+    assert!(token_file.exists());
+    let mode = std::fs::metadata(&token_file)
+        .expect("metadata")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(git_credential::connect_app(&socket, &token_file).is_err());
+
+    let client = ipc.status().expect("status").clients.remove(0);
+    assert_eq!(client.name, git_credential::CLIENT_NAME);
+    ipc.approve_client(
+        &client.id,
+        IpcScopes {
+            allowed_groups: vec![kdbx.get_info().expect("info").root_group_id],
+            read_only: true,
+        },
+    )
+    .expect("Failed to approve");
+
+    let mut connection = git_credential::connect_app(&socket, &token_file)
+        .expect("connect")
+        .expect("app is listening");
+    let answer = helper(
+        &mut connection,
+        "get",
+        "protocol=https\nhost=github.com\n\n",
+    );
+    assert_eq!(answer, "username=alice\npassword=gh-secret\n");
+
+    // Read-only clients cannot store
+    let mut output = Vec::new();
+    let stored = git_credential::run(
+        "store",
+        &mut "protocol=https\nhost=github.com\nusername=alice\npassword=new\n\n".as_bytes(),
+        &mut output,
+        &mut connection,
+    );
+    assert!(stored.is_err());
+
+    ipc.set_enabled(false).expect("Failed to disable IPC");
+}
@@ -0,0 +1,479 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the IPC JSON-RPC service
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::dto::ipc::{IpcAuditOutcome, IpcScopes};
+use mithril_vault_lib::services::ipc::{
+    IpcService, IpcSession, FORBIDDEN, METHOD_NOT_FOUND, UNAUTHORIZED,
+};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+struct Fixture {
+    ipc: Arc<IpcService>,
+    kdbx: Arc<KdbxService>,
+    dir: TempDir,
+    root_group: String,
+    work_group: String,
+    work_entry: String,
+    home_entry: String,
+}
+
+fn create_entry(kdbx: &KdbxService, group_id: &str, title: &str, password: &str) -> String {
+    kdbx.create_entry(
+        group_id,
+        CreateEntryData {
+            title: title.to_string(),
+            username: "alice".to_string(),
+            password: SecureString::from(password),
+            url: Some(format!("https://{}.example.com", title.to_lowercase())),
+            notes: None,
+            icon_id: None,
+            tags: None,
+            custom_fields: None,
+            protected_custom_fields: None,
+            expiry_time: None,
+        },
+    )
+    .expect("Failed to create entry")
+    .id
+}
+
+fn setup() -> Fixture {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let kdbx = Arc::new(KdbxService::new());
+    let info = create_test_database(&kdbx, &dir.path().join("ipc.kdbx"), "IPC", "testpass");
+    let work_group = kdbx
+        .create_group(Some(&info.root_group_id), "Work", None)
+        .expect("Failed to create group")
+        .id;
+    let home_group = kdbx
+        .create_group(Some(&info.root_group_id), "Home", None)
+        .expect("Failed to create group")
+        .id;
+    let work_entry = create_entry(&kdbx, &work_group, "Jira", "work-secret");
+    let home_entry = create_entry(&kdbx, &home_group, "Router", "home-secret");
+
+    let ipc = Arc::new(
+        IpcService::new(
+            Arc::clone(&kdbx),
+            &dir.path().join("data"),
+            dir.path().join("ipc.sock"),
+        )
+        .expect("Failed to create IPC service"),
+    );
+    ipc.set_enabled(true).expect("Failed to enable IPC");
+
+    Fixture {
+        ipc,
+        kdbx,
+        dir,
+        root_group: info.root_group_id,
+        work_group,
+        work_entry,
+        home_entry,
+    }
+}
+
+/// Scopes allowing every group, with or without write access.
+fn all_groups(fixture: &Fixture, read_only: bool) -> IpcScopes {
+    IpcScopes {
+        allowed_groups: vec![fixture.root_group.clone()],
+        read_only,
+    }
+}
+
+fn call(ipc: &IpcService, session: &mut IpcSession, method: &str, params: &Value) -> Value {
+    ipc.handle_request(
+        session,
+        &json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+    )
+}
+
+fn error_code(response: &Value) -> Option<i64> {
+    response["error"]["code"].as_i64()
+}
+
+/// Registers a client, approves it with `scopes` and authenticates a session.
+fn authenticated_session(fixture: &Fixture, scopes: IpcScopes) -> IpcSession {
+    let mut session = IpcSession::default();
+    let registered = call(
+        &fixture.ipc,
+        &mut session,
+        "register",
+        &json!({ "name": "backup script" }),
+    );
+    let client_id = registered["result"]["clientId"]
+        .as_str()
+        .expect("client id");
+    let token = registered["result"]["token"].as_str().expect("token");
+    fixture
+        .ipc
+        .approve_client(client_id, scopes)
+        .expect("Failed to approve client");
+
+    let auth = call(
+        &fixture.ipc,
+        &mut session,
+        "authenticate",
+        &json!({ "token": token }),
+    );
+    assert!(auth.get("result").is_some(), "authenticate failed: {auth}");
+    session
+}
+
+#[test]
+fn test_tokens_require_approval() {
+    let fixture = setup();
+    let mut session = IpcSession::default();
+
+    let search = call(
+        &fixture.ipc,
+        &mut session,
+        "search",
+        &json!({ "query": "" }),
+    );
+    assert_eq!(error_code(&search), Some(UNAUTHORIZED));
+
+    let registered = call(
+        &fixture.ipc,
+        &mut session,
+        "register",
+        &json!({ "name": "deploy" }),
+    );
+    let token = registered["result"]["token"].as_str().expect("token");
+    let pending = call(
+        &fixture.ipc,
+        &mut session,
+        "authenticate",
+        &json!({ "token": token }),
+    );
+    assert_eq!(error_code(&pending), Some(UNAUTHORIZED));
+
+    let status = fixture.ipc.status().expect("status");
+    assert_eq!(status.clients.len(), 1);
+    assert!(!status.clients[0].approved);
+
+    // Only the hash of the token is stored
+    let stored = std::fs::read_to_string(fixture.dir.path().join("data/ipc.json"))
+        .expect("Failed to read state");
+    assert!(!stored.contains(token));
+}
+
+#[test]
+fn test_search_and_get_field_respect_allowed_groups() {
+    let fixture = setup();
+    let mut session = authenticated_session(
+        &fixture,
+        IpcScopes {
+            allowed_groups: vec![fixture.work_group.clone()],
+            read_only: true,
+        },
+    );
+
+    let search = call(
+        &fixture.ipc,
+        &mut session,
+        "search",
+        &json!({ "query": "" }),
+    );
+    let results = search["result"].as_array().expect("search results");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["title"], "Jira");
+
+    let field = call(
+        &fixture.ipc,
+        &mut session,
+        "getField",
+        &json!({ "entryId": fixture.work_entry, "field": "Password" }),
+    );
+    assert_eq!(field["result"]["value"], "work-secret");
+
+    let outside = call(
+        &fixture.ipc,
+        &mut session,
+        "getField",
+        &json!({ "entryId": fixture.home_entry, "field": "Password" }),
+    );
+    assert_eq!(error_code(&outside), Some(FORBIDDEN));
+}
+
+#[test]
+fn test_clients_without_groups_are_denied() {
+    let fixture = setup();
+    let mut session = IpcSession::default();
+    let registered = call(
+        &fixture.ipc,
+        &mut session,
+        "register",
+        &json!({ "name": "backup script" }),
+    );
+    let client_id = registered["result"]["clientId"]
+        .as_str()
+        .expect("client id");
+    let token = registered["result"]["token"].as_str().expect("token");
+
+    let result = fixture.ipc.approve_client(
+        client_id,
+        IpcScopes {
+            allowed_groups: Vec::new(),
+            read_only: true,
+        },
+    );
+    assert!(matches!(result, Err(AppError::Ipc(_))));
+    assert!(!fixture.ipc.status().expect("status").clients[0].approved);
+
+    // A client stored without groups, e.g. by an older version, can
+    // authenticate but do nothing
+    fixture
+        .ipc
+        .approve_client(client_id, all_groups(&fixture, true))
+        .expect("Failed to approve client");
+    let state_path = fixture.dir.path().join("data/ipc.json");
+    let state = std::fs::read_to_string(&state_path).expect("Failed to read state");
+    let group = serde_json::to_string(&fixture.root_group).expect("group JSON");
+    std::fs::write(&state_path, state.replace(&group, "")).expect("Failed to write state");
+    let reloaded = IpcService::new(
+        Arc::clone(&fixture.kdbx),
+        &fixture.dir.path().join("data"),
+        fixture.dir.path().join("reloaded.sock"),
+    )
+    .expect("Failed to create IPC service");
+
+    let auth = call(
+        &reloaded,
+        &mut session,
+        "authenticate",
+        &json!({ "token": token }),
+    );
+    assert!(auth.get("result").is_some(), "authenticate failed: {auth}");
+    let search = call(&reloaded, &mut session, "search", &json!({ "query": "" }));
+    assert_eq!(error_code(&search), Some(FORBIDDEN));
+}
+
+#[test]
+fn test_pending_registrations_are_capped_and_expire() {
+    let fixture = setup();
+    let mut session = IpcSession::default();
+    for i in 0..8 {
+        let registered = call(
+            &fixture.ipc,
+            &mut session,
+            "register",
+            &json!({ "name": format!("client {i}") }),
+        );
+        assert!(registered.get("result").is_some(), "register failed");
+    }
+    let refused = call(
+        &fixture.ipc,
+        &mut session,
+        "register",
+        &json!({ "name": "one too many" }),
+    );
+    assert_eq!(error_code(&refused), Some(FORBIDDEN));
+
+    // Backdate the pending registrations past the approval window
+    let state_path = fixture.dir.path().join("data/ipc.json");
+    let mut state: Value =
+        serde_json::from_str(&std::fs::read_to_string(&state_path).expect("Failed to read state"))
+            .expect("state JSON");
+    for client in state["clients"].as_array_mut().expect("clients") {
+        client["createdAt"] = json!("2020-01-01T00:00:00Z");
+    }
+    std::fs::write(&state_path, state.to_string()).expect("Failed to write state");
+    let reloaded = IpcService::new(
+        Arc::clone(&fixture.kdbx),
+        &fixture.dir.path().join("data"),
+        fixture.dir.path().join("reloaded.sock"),
+    )
+    .expect("Failed to create IPC service");
+
+    let registered = call(
+        &reloaded,
+        &mut session,
+        "register",
+        &json!({ "name": "late" }),
+    );
+    assert!(registered.get("result").is_some(), "register failed");
+    let status = reloaded.status().expect("status");
+    assert_eq!(status.clients.len(), 1);
+    assert_eq!(status.clients[0].name, "late");
+}
+
+#[test]
+fn test_invalid_state_file_is_set_aside() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let data_dir = dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).expect("Failed to create data dir");
+    std::fs::write(data_dir.join("ipc.json"), "{ not json").expect("Failed to write state");
+
+    let ipc = IpcService::new(
+        Arc::new(KdbxService::new()),
+        &data_dir,
+        dir.path().join("ipc.sock"),
+    )
+    .expect("Failed to create IPC service");
+
+    let status = ipc.status().expect("status");
+    assert!(!status.enabled);
+    assert!(status.clients.is_empty());
+    let backups: Vec<String> = std::fs::read_dir(&data_dir)
+        .expect("Failed to list data dir")
+        .map(|entry| {
+            entry
+                .expect("dir entry")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .filter(|name| name.starts_with("ipc.json.bad-"))
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(data_dir.join(&backups[0])).expect("Failed to read backup"),
+        "{ not json"
+    );
+}
+
+#[test]
+fn test_generate_password_into_entry_requires_write_scope() {
+    let fixture = setup();
+    let mut read_only = authenticated_session(&fixture, all_groups(&fixture, true));
+    let generated = call(
+        &fixture.ipc,
+        &mut read_only,
+        "generatePassword",
+        &json!({ "options": { "length": 32, "uppercase": true, "lowercase": true,
+                              "numbers": true, "symbols": false, "excludeAmbiguous": false } }),
+    );
+    assert_eq!(
+        generated["result"]["password"].as_str().map(str::len),
+        Some(32)
+    );
+
+    let denied = call(
+        &fixture.ipc,
+        &mut read_only,
+        "generatePassword",
+        &json!({ "entryId": fixture.work_entry }),
+    );
+    assert_eq!(error_code(&denied), Some(FORBIDDEN));
+
+    let mut writer = authenticated_session(&fixture, all_groups(&fixture, false));
+    let rotated = call(
+        &fixture.ipc,
+        &mut writer,
+        "generatePassword",
+        &json!({ "entryId": fixture.work_entry }),
+    );
+    let password = rotated["result"]["password"].as_str().expect("password");
+    assert_eq!(
+        fixture
+            .kdbx
+            .get_entry_password(&fixture.work_entry)
+            .expect("password"),
+        password
+    );
+}
+
+#[test]
+fn test_revocation_and_disabling_apply_to_open_sessions() {
+    let fixture = setup();
+    let mut session = authenticated_session(&fixture, all_groups(&fixture, false));
+    let client_id = fixture.ipc.status().expect("status").clients[0].id.clone();
+
+    fixture.ipc.set_enabled(false).expect("Failed to disable");
+    let disabled = call(
+        &fixture.ipc,
+        &mut session,
+        "search",
+        &json!({ "query": "" }),
+    );
+    assert_eq!(error_code(&disabled), Some(UNAUTHORIZED));
+
+    fixture.ipc.set_enabled(true).expect("Failed to enable");
+    fixture
+        .ipc
+        .revoke_client(&client_id)
+        .expect("Failed to revoke");
+    let revoked = call(
+        &fixture.ipc,
+        &mut session,
+        "search",
+        &json!({ "query": "" }),
+    );
+    assert_eq!(error_code(&revoked), Some(UNAUTHORIZED));
+}
+
+#[test]
+fn test_requests_are_audited_without_values() {
+    let fixture = setup();
+    let mut session = authenticated_session(&fixture, all_groups(&fixture, false));
+    call(
+        &fixture.ipc,
+        &mut session,
+        "getField",
+        &json!({ "entryId": fixture.work_entry, "field": "Password" }),
+    );
+    let unknown = call(&fixture.ipc, &mut session, "deleteEverything", &json!({}));
+    assert_eq!(error_code(&unknown), Some(METHOD_NOT_FOUND));
+
+    let log = fixture.ipc.audit_log(10).expect("audit log");
+    let methods: Vec<&str> = log.iter().map(|r| r.method.as_str()).collect();
+    assert_eq!(
+        methods,
+        vec!["register", "authenticate", "getField", "deleteEverything"]
+    );
+    assert_eq!(log[2].outcome, IpcAuditOutcome::Allowed);
+    assert_eq!(log[2].target.as_deref(), Some(fixture.work_entry.as_str()));
+    assert_eq!(log[2].client_name.as_deref(), Some("backup script"));
+    assert_eq!(log[3].outcome, IpcAuditOutcome::Failed);
+
+    let raw = std::fs::read_to_string(fixture.dir.path().join("data/ipc-audit.jsonl"))
+        .expect("Failed to read audit log");
+    assert!(!raw.contains("work-secret"));
+
+    assert_eq!(fixture.ipc.audit_log(1).expect("audit log").len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_socket_round_trip() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let fixture = setup();
+    let socket = fixture
+        .ipc
+        .status()
+        .expect("status")
+        .socket_path
+        .expect("socket is listening");
+
+    let mut stream = UnixStream::connect(&socket).expect("Failed to connect");
+    stream
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":7,\"method\":\"register\",\"params\":{\"name\":\"cli\"}}\nnot json\n")
+        .expect("Failed to write");
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).expect("Failed to read");
+    let response: Value = serde_json::from_str(&line).expect("response JSON");
+    assert_eq!(response["id"], 7);
+    assert!(response["result"]["token"].is_string());
+
+    line.clear();
+    reader.read_line(&mut line).expect("Failed to read");
+    let response: Value = serde_json::from_str(&line).expect("response JSON");
+    assert_eq!(response["error"]["code"], -32700);
+
+    fixture.ipc.set_enabled(false).expect("Failed to disable");
+    assert!(!std::path::Path::new(&socket).exists());
+}
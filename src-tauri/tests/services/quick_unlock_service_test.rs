@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MIT
+//! Tests for session locking and PIN quick unlock
+
+#![allow(clippy::expect_used)]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::quick_unlock::{
+    QuickUnlockService, SealedKeyStore, MAX_PIN_ATTEMPTS,
+};
+use tempfile::TempDir;
+
+use super::support::{fixture_path, low_kdf_options};
+
+/// Keeps the sealed key in memory, like the session store does.
+#[derive(Default)]
+struct MemoryStore {
+    sealed: Mutex<Option<Vec<u8>>>,
+}
+
+impl SealedKeyStore for MemoryStore {
+    fn store_sealed(&self, sealed: &[u8], _ttl: Duration) -> Result<(), AppError> {
+        *self.sealed.lock().map_err(|_| AppError::Lock)? = Some(sealed.to_vec());
+        Ok(())
+    }
+
+    fn load_sealed(&self) -> Result<Option<Vec<u8>>, AppError> {
+        Ok(self.sealed.lock().map_err(|_| AppError::Lock)?.clone())
+    }
+
+    fn clear_sealed(&self) -> Result<(), AppError> {
+        *self.sealed.lock().map_err(|_| AppError::Lock)? = None;
+        Ok(())
+    }
+}
+
+fn setup() -> (
+    Arc<KdbxService>,
+    Arc<MemoryStore>,
+    QuickUnlockService,
+    TempDir,
+) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let keyfile = dir.path().join("db.key");
+    std::fs::write(&keyfile, "quick-unlock-keyfile").expect("Failed to write keyfile");
+
+    let kdbx = Arc::new(KdbxService::new());
+    kdbx.create_database(
+        &dir.path().join("quick.kdbx").to_string_lossy(),
+        Some("full-password"),
+        Some(&keyfile.to_string_lossy()),
+        "Quick",
+        &low_kdf_options(),
+    )
+    .expect("Failed to create test database");
+
+    let store = Arc::new(MemoryStore::default());
+    let quick_unlock = QuickUnlockService::new(Arc::clone(&kdbx), store.clone());
+    (kdbx, store, quick_unlock, dir)
+}
+
+#[test]
+fn test_lock_keeps_file_lock_and_unlocks_with_password() {
+    let (kdbx, _store, _quick_unlock, dir) = setup();
+    let path = kdbx.get_info().expect("info").path;
+    let root = kdbx.get_info().expect("info").root_group_id;
+    kdbx.create_group(Some(&root), "Unsaved", None)
+        .expect("Failed to create group");
+
+    kdbx.lock().expect("Failed to lock");
+    assert!(kdbx.is_locked().expect("is_locked"));
+    assert!(matches!(kdbx.get_info(), Err(AppError::DatabaseNotOpen)));
+    let info = kdbx.locked_info().expect("locked info").expect("locked");
+    assert!(info.is_locked);
+    assert!(info.is_modified);
+    assert_eq!(info.path, path);
+    // The file stays reserved for this process
+    assert!(KdbxService::new().open(&path, "full-password").is_err());
+    assert!(matches!(
+        kdbx.open(&path, "full-password"),
+        Err(AppError::DatabaseAlreadyOpen)
+    ));
+
+    assert!(matches!(
+        kdbx.unlock(Some("wrong")),
+        Err(AppError::InvalidPassword)
+    ));
+    assert!(kdbx.is_locked().expect("is_locked"));
+
+    let info = kdbx
+        .unlock(Some("full-password"))
+        .expect("Failed to unlock");
+    assert!(!info.is_locked);
+    assert!(!kdbx.is_locked().expect("is_locked"));
+    // Unsaved changes survive the lock without being written
+    assert!(info.is_modified);
+    assert!(kdbx.resolve_group("Unsaved").is_ok());
+
+    kdbx.lock().expect("Failed to lock");
+    kdbx.close().expect("Failed to close a locked database");
+    assert!(kdbx.locked_info().expect("locked info").is_none());
+    let reopened = KdbxService::new();
+    reopened
+        .open_with_keyfile(
+            &path,
+            "full-password",
+            &dir.path().join("db.key").to_string_lossy(),
+        )
+        .expect("Closing releases the file lock");
+    assert!(reopened.resolve_group("Unsaved").is_err());
+}
+
+#[test]
+fn test_lock_drops_the_database_when_changes_cannot_be_kept() {
+    let (kdbx, _store, _quick_unlock, dir) = setup();
+    let root = kdbx.get_info().expect("info").root_group_id;
+    kdbx.create_group(Some(&root), "Unsaved", None)
+        .expect("Failed to create group");
+    std::fs::remove_file(dir.path().join("db.key")).expect("Failed to remove keyfile");
+
+    assert!(kdbx.lock().is_err());
+    assert!(kdbx.is_locked().expect("is_locked"));
+    assert!(matches!(kdbx.get_info(), Err(AppError::DatabaseNotOpen)));
+    let info = kdbx.locked_info().expect("locked info").expect("locked");
+    assert!(!info.is_modified);
+}
+
+#[test]
+fn test_pin_unlocks_until_disabled() {
+    let (kdbx, store, quick_unlock, _dir) = setup();
+
+    assert!(matches!(
+        quick_unlock.enable("12", QuickUnlockService::default_ttl()),
+        Err(AppError::PinTooShort(_))
+    ));
+    quick_unlock
+        .enable("2468", QuickUnlockService::default_ttl())
+        .expect("Failed to enable quick unlock");
+    let status = quick_unlock.status().expect("status");
+    assert!(status.enabled);
+    assert_eq!(status.attempts_remaining, MAX_PIN_ATTEMPTS);
+
+    // Only the sealed composite key is stored
+    let sealed = store.load_sealed().expect("load").expect("sealed");
+    assert!(!sealed.windows(13).any(|w| w == b"full-password"));
+    assert!(!sealed.windows(20).any(|w| w == b"quick-unlock-keyfile"));
+
+    // A PIN only unlocks a locked database
+    assert!(matches!(
+        quick_unlock.unlock("2468"),
+        Err(AppError::DatabaseNotOpen)
+    ));
+
+    for _ in 0..2 {
+        kdbx.lock().expect("Failed to lock");
+        let info = quick_unlock
+            .unlock("2468")
+            .expect("Failed to unlock with PIN");
+        assert!(!info.is_locked);
+        // The unlocked database can still be saved with the full key
+        kdbx.save().expect("Failed to save");
+    }
+
+    quick_unlock.disable().expect("Failed to disable");
+    assert!(store.load_sealed().expect("load").is_none());
+    kdbx.lock().expect("Failed to lock");
+    assert!(matches!(
+        quick_unlock.unlock("2468"),
+        Err(AppError::QuickUnlockUnavailable)
+    ));
+}
+
+#[test]
+fn test_wrong_pins_wipe_the_sealed_key() {
+    let (kdbx, store, quick_unlock, _dir) = setup();
+    quick_unlock
+        .enable("2468", QuickUnlockService::default_ttl())
+        .expect("Failed to enable quick unlock");
+    kdbx.lock().expect("Failed to lock");
+
+    // A correct PIN resets the counter
+    assert!(matches!(
+        quick_unlock.unlock("0000"),
+        Err(AppError::IncorrectPin(2))
+    ));
+    quick_unlock
+        .unlock("2468")
+        .expect("Failed to unlock with PIN");
+    kdbx.lock().expect("Failed to lock");
+
+    for remaining in (0..MAX_PIN_ATTEMPTS).rev() {
+        let result = quick_unlock.unlock("1111");
+        assert!(
+            matches!(result, Err(AppError::IncorrectPin(left)) if left == remaining),
+            "{result:?}"
+        );
+    }
+    assert!(store.load_sealed().expect("load").is_none());
+    assert!(!quick_unlock.status().expect("status").enabled);
+    assert!(matches!(
+        quick_unlock.unlock("2468"),
+        Err(AppError::QuickUnlockUnavailable)
+    ));
+    assert!(kdbx.is_locked().expect("is_locked"));
+}
+
+#[test]
+fn test_expired_pin_is_wiped() {
+    let (kdbx, store, quick_unlock, _dir) = setup();
+    quick_unlock
+        .enable("2468", Duration::ZERO)
+        .expect("Failed to enable quick unlock");
+    kdbx.lock().expect("Failed to lock");
+
+    assert!(matches!(
+        quick_unlock.unlock("2468"),
+        Err(AppError::QuickUnlockUnavailable)
+    ));
+    assert!(store.load_sealed().expect("load").is_none());
+}
+
+#[test]
+fn test_overflowing_ttl_is_rejected_before_storing() {
+    let (_kdbx, store, quick_unlock, _dir) = setup();
+    assert!(matches!(
+        quick_unlock.enable("2468", Duration::MAX),
+        Err(AppError::InvalidArguments(_))
+    ));
+    assert!(store.load_sealed().expect("load").is_none());
+    assert!(!quick_unlock.status().expect("status").enabled);
+
+    quick_unlock
+        .enable("2468", QuickUnlockService::default_ttl())
+        .expect("Failed to enable quick unlock");
+    assert!(quick_unlock.status().expect("status").enabled);
+}
+
+#[test]
+fn test_pin_unlocks_with_every_keyfile_format() {
+    let xml_v2 = std::fs::read(fixture_path("test-keyfile.keyx")).expect("Failed to read keyfile");
+    let xml_v1 = b"<?xml version=\"1.0\"?><KeyFile><Meta><Version>1.00</Version></Meta>\
+        <Key><Data>q83vASNFZ4mrze8BI0VniavN7wEjRWeJq83vASNFZ4k=</Data></Key></KeyFile>"
+        .to_vec();
+    let keyfiles = [
+        ("v2.keyx", Some("full-password"), xml_v2),
+        ("v1.key", None, xml_v1),
+        ("binary.key", Some("full-password"), vec![7u8; 32]),
+        (
+            "other.key",
+            Some("full-password"),
+            b"any file at all".to_vec(),
+        ),
+    ];
+
+    for (name, password, contents) in keyfiles {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let keyfile = dir.path().join(name);
+        std::fs::write(&keyfile, &contents).expect("Failed to write keyfile");
+        let path = dir.path().join("quick.kdbx").to_string_lossy().to_string();
+
+        let kdbx = Arc::new(KdbxService::new());
+        kdbx.create_database(
+            &path,
+            password,
+            Some(&keyfile.to_string_lossy()),
+            "Quick",
+            &low_kdf_options(),
+        )
+        .expect("Failed to create test database");
+        let quick_unlock =
+            QuickUnlockService::new(Arc::clone(&kdbx), Arc::new(MemoryStore::default()));
+        quick_unlock
+            .enable("2468", QuickUnlockService::default_ttl())
+            .expect("Failed to enable quick unlock");
+
+        kdbx.lock().expect("Failed to lock");
+        let unlocked = quick_unlock.unlock("2468");
+        assert!(unlocked.is_ok(), "{name}: {unlocked:?}");
+        let root = kdbx.get_info().expect("info").root_group_id;
+        kdbx.create_group(Some(&root), "Saved", None)
+            .expect("Failed to create group");
+        kdbx.save().expect("Failed to save");
+        kdbx.close().expect("Failed to close");
+
+        // The file is still opened by its password and keyfile
+        let reopened = KdbxService::new();
+        let keyfile = keyfile.to_string_lossy();
+        let opened = match password {
+            Some(password) => reopened.open_with_keyfile(&path, password, &keyfile),
+            None => reopened.open_with_keyfile_only(&path, &keyfile),
+        };
+        assert!(opened.is_ok(), "{name}: {opened:?}");
+        assert!(reopened.resolve_group("Saved").is_ok(), "{name}");
+    }
+}
@@ -0,0 +1,523 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the Freedesktop Secret Service provider
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::secret_service::session::ALGORITHM_PLAIN;
+use mithril_vault_lib::services::secret_service::{SecretService, TransferSecret};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Unique bus name of the client in these tests.
+const SENDER: &str = ":1.42";
+
+struct Fixture {
+    kdbx: Arc<KdbxService>,
+    root_id: String,
+    _dir: TempDir,
+}
+
+fn setup() -> Fixture {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let kdbx = Arc::new(KdbxService::new());
+    let info = create_test_database(
+        &kdbx,
+        &dir.path().join("keyring.kdbx"),
+        "Keyring",
+        "testpass",
+    );
+
+    Fixture {
+        kdbx,
+        root_id: info.root_group_id,
+        _dir: dir,
+    }
+}
+
+fn service(fixture: &Fixture) -> SecretService {
+    SecretService::new(Arc::clone(&fixture.kdbx), |_| true)
+}
+
+fn attributes(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+        .collect()
+}
+
+fn plain_secret(session: &str, value: &str) -> TransferSecret {
+    TransferSecret {
+        session: session.to_string(),
+        parameters: Vec::new(),
+        value: value.as_bytes().to_vec(),
+        content_type: "text/plain".to_string(),
+    }
+}
+
+#[test]
+fn test_refuses_requests_while_locked() {
+    let fixture = setup();
+    let asked = Arc::new(AtomicBool::new(false));
+    let seen = Arc::clone(&asked);
+    let service = SecretService::new(Arc::clone(&fixture.kdbx), move |_| {
+        seen.store(true, Ordering::SeqCst);
+        true
+    });
+
+    fixture.kdbx.close().expect("Failed to close database");
+
+    assert!(service.is_locked());
+    assert!(matches!(
+        service.authorize("/usr/bin/seahorse"),
+        Err(AppError::DatabaseNotOpen)
+    ));
+    assert!(!asked.load(Ordering::SeqCst));
+    assert!(matches!(service.items(), Err(AppError::DatabaseNotOpen)));
+}
+
+#[test]
+fn test_new_clients_need_confirmation_once() {
+    let fixture = setup();
+    let prompts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&prompts);
+    let service = SecretService::new(Arc::clone(&fixture.kdbx), move |client| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        client != "/usr/bin/untrusted"
+    });
+
+    service.authorize("/usr/bin/seahorse").expect("approved");
+    service
+        .authorize("/usr/bin/seahorse")
+        .expect("still approved");
+    assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+    assert!(matches!(
+        service.authorize("/usr/bin/untrusted"),
+        Err(AppError::SecretServiceAccessDenied(_))
+    ));
+    assert_eq!(
+        service.status().expect("status").approved_clients,
+        vec!["/usr/bin/seahorse".to_string()]
+    );
+
+    service
+        .revoke_client("/usr/bin/seahorse")
+        .expect("Failed to revoke");
+    service.authorize("/usr/bin/seahorse").expect("approved");
+    assert_eq!(prompts.load(Ordering::SeqCst), 3);
+    assert!(service.revoke_client("/usr/bin/untrusted").is_err());
+}
+
+#[test]
+fn test_store_search_and_read_items() {
+    let fixture = setup();
+    let service = service(&fixture);
+    assert!(service.status().expect("status").group_id.is_none());
+
+    let (session, output) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    assert!(output.is_empty());
+
+    let mail = service
+        .create_item(
+            "Mail",
+            &attributes(&[("service", "imap"), ("user", "alice")]),
+            &plain_secret(&session, "hunter2"),
+            false,
+            SENDER,
+        )
+        .expect("Failed to create item");
+    service
+        .create_item(
+            "Chat",
+            &attributes(&[("service", "xmpp"), ("user", "alice")]),
+            &plain_secret(&session, "correct horse"),
+            false,
+            SENDER,
+        )
+        .expect("Failed to create item");
+
+    // The first item creates and designates the default group
+    let status = service.status().expect("status");
+    assert_eq!(status.group_name.as_deref(), Some("Secret Service"));
+    let group_id = status.group_id.expect("designated group");
+    assert_eq!(
+        fixture
+            .kdbx
+            .get_entry(&mail.id)
+            .expect("entry of item")
+            .group_id,
+        group_id
+    );
+
+    let found = service
+        .search_items(&attributes(&[("service", "imap")]))
+        .expect("Failed to search");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].label, "Mail");
+    assert_eq!(
+        service
+            .search_items(&attributes(&[("user", "alice")]))
+            .expect("Failed to search")
+            .len(),
+        2
+    );
+    assert!(service
+        .search_items(&attributes(&[("user", "bob")]))
+        .expect("Failed to search")
+        .is_empty());
+
+    let secret = service
+        .get_secret(&mail.id, &session, SENDER)
+        .expect("Failed to get secret");
+    assert_eq!(secret.value, b"hunter2");
+    assert_eq!(secret.content_type, "text/plain");
+
+    service
+        .close_session(&session, SENDER)
+        .expect("Failed to close");
+    assert!(matches!(
+        service.get_secret(&mail.id, &session, SENDER),
+        Err(AppError::SecretSessionNotFound(_))
+    ));
+}
+
+#[test]
+fn test_replace_updates_item_with_same_attributes() {
+    let fixture = setup();
+    let service = service(&fixture);
+    let (session, _) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    let lookup = attributes(&[("service", "imap")]);
+
+    let first = service
+        .create_item(
+            "Mail",
+            &lookup,
+            &plain_secret(&session, "old"),
+            true,
+            SENDER,
+        )
+        .expect("Failed to create item");
+    let second = service
+        .create_item(
+            "Mail",
+            &lookup,
+            &plain_secret(&session, "new"),
+            true,
+            SENDER,
+        )
+        .expect("Failed to replace item");
+
+    assert_eq!(first.id, second.id);
+    assert_eq!(service.items().expect("items").len(), 1);
+    assert_eq!(
+        service
+            .get_secret(&first.id, &session, SENDER)
+            .expect("Failed to get secret")
+            .value,
+        b"new"
+    );
+}
+
+#[test]
+fn test_sessions_belong_to_their_sender() {
+    let fixture = setup();
+    let service = service(&fixture);
+    let (session, _) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    let (other, _) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    assert_ne!(session, other);
+    assert_eq!(session.len(), 32);
+
+    let item = service
+        .create_item(
+            "Mail",
+            &attributes(&[("service", "imap")]),
+            &plain_secret(&session, "hunter2"),
+            false,
+            SENDER,
+        )
+        .expect("Failed to create item");
+
+    let intruder = ":1.99";
+    assert!(matches!(
+        service.get_secret(&item.id, &session, intruder),
+        Err(AppError::SecretSessionNotFound(_))
+    ));
+    assert!(matches!(
+        service.set_secret(&item.id, &plain_secret(&session, "pwned"), intruder),
+        Err(AppError::SecretSessionNotFound(_))
+    ));
+    assert!(matches!(
+        service.close_session(&session, intruder),
+        Err(AppError::SecretSessionNotFound(_))
+    ));
+    assert_eq!(
+        service
+            .get_secret(&item.id, &session, SENDER)
+            .expect("Failed to get secret")
+            .value,
+        b"hunter2"
+    );
+}
+
+#[test]
+fn test_only_entries_of_the_designated_group_are_items() {
+    let fixture = setup();
+    let service = service(&fixture);
+    let group = fixture
+        .kdbx
+        .create_group(Some(&fixture.root_id), "Keyring", None)
+        .expect("Failed to create group");
+    let outside = fixture
+        .kdbx
+        .create_entry(
+            &fixture.root_id,
+            CreateEntryData {
+                title: "Bank".to_string(),
+                username: "alice".to_string(),
+                password: SecureString::from("secret"),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: Some(attributes(&[("service", "imap")])),
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+
+    fixture
+        .kdbx
+        .set_secret_service_group(Some(&group.id))
+        .expect("Failed to designate group");
+    assert!(service.items().expect("items").is_empty());
+    assert!(matches!(
+        service.item(&outside.id),
+        Err(AppError::EntryNotFound(_))
+    ));
+
+    let (session, _) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    let item = service
+        .create_item(
+            "Mail",
+            &attributes(&[("service", "imap")]),
+            &plain_secret(&session, "hunter2"),
+            false,
+            SENDER,
+        )
+        .expect("Failed to create item");
+    assert_eq!(
+        fixture.kdbx.get_entry(&item.id).expect("entry").group_id,
+        group.id
+    );
+    assert_eq!(
+        service.status().expect("status").group_name.as_deref(),
+        Some("Keyring")
+    );
+
+    assert!(matches!(
+        fixture.kdbx.set_secret_service_group(Some("missing")),
+        Err(AppError::GroupNotFound(_))
+    ));
+}
+
+#[test]
+fn test_delete_moves_item_to_recycle_bin() {
+    let fixture = setup();
+    let service = service(&fixture);
+    let (session, _) = service
+        .open_session(SENDER, ALGORITHM_PLAIN, &[])
+        .expect("Failed to open session");
+    let item = service
+        .create_item(
+            "Mail",
+            &attributes(&[("service", "imap")]),
+            &plain_secret(&session, "hunter2"),
+            false,
+            SENDER,
+        )
+        .expect("Failed to create item");
+
+    service.delete_item(&item.id).expect("Failed to delete");
+
+    assert!(service.items().expect("items").is_empty());
+    assert!(fixture
+        .kdbx
+        .list_recycle_bin()
+        .expect("recycle bin")
+        .iter()
+        .any(|bin_item| bin_item.id == item.id));
+}
+
+/// Starts a private `dbus-daemon` and returns it with its address, or
+/// `None` where no daemon is installed.
+#[cfg(target_os = "linux")]
+fn private_bus() -> Option<(std::process::Child, String)> {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    let mut daemon = Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut address = String::new();
+    BufReader::new(daemon.stdout.take()?)
+        .read_line(&mut address)
+        .ok()?;
+    Some((daemon, address.trim().to_string()))
+}
+
+#[cfg(target_os = "linux")]
+fn call<B>(
+    client: &zbus::blocking::Connection,
+    path: &str,
+    interface: &str,
+    method: &str,
+    body: &B,
+) -> zbus::Message
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    client
+        .call_method(
+            Some(mithril_vault_lib::services::secret_service::dbus::BUS_NAME),
+            path,
+            Some(interface),
+            method,
+            body,
+        )
+        .expect("D-Bus call failed")
+}
+
+/// Runs the provider on a private bus and talks to it like libsecret
+/// would. Skipped where no daemon is installed.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_dbus_round_trip() {
+    use mithril_vault_lib::services::secret_service::dbus;
+    use std::collections::HashMap;
+    use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+    let Some((mut daemon, address)) = private_bus() else {
+        eprintln!("dbus-daemon not available, skipping");
+        return;
+    };
+
+    let fixture = setup();
+    let service = Arc::new(service(&fixture));
+    let _server = dbus::start(&service, Some(&address)).expect("Failed to start");
+    assert!(service.status().expect("status").active);
+
+    let client = zbus::blocking::connection::Builder::address(address.as_str())
+        .expect("Invalid address")
+        .build()
+        .expect("Failed to connect");
+
+    let (_, session): (OwnedValue, OwnedObjectPath) = call(
+        &client,
+        "/org/freedesktop/secrets",
+        "org.freedesktop.Secret.Service",
+        "OpenSession",
+        &(ALGORITHM_PLAIN, Value::from("")),
+    )
+    .body()
+    .deserialize()
+    .expect("OpenSession reply");
+
+    let collection: OwnedObjectPath = call(
+        &client,
+        "/org/freedesktop/secrets",
+        "org.freedesktop.Secret.Service",
+        "ReadAlias",
+        &("default",),
+    )
+    .body()
+    .deserialize()
+    .expect("ReadAlias reply");
+
+    let properties: HashMap<&str, Value<'_>> = HashMap::from([
+        ("org.freedesktop.Secret.Item.Label", Value::from("Mail")),
+        (
+            "org.freedesktop.Secret.Item.Attributes",
+            Value::from(HashMap::from([("service", "imap")])),
+        ),
+    ]);
+    let secret = (
+        &session,
+        Vec::<u8>::new(),
+        b"hunter2".to_vec(),
+        "text/plain",
+    );
+    let (item, _): (OwnedObjectPath, OwnedObjectPath) = call(
+        &client,
+        collection.as_str(),
+        "org.freedesktop.Secret.Collection",
+        "CreateItem",
+        &(properties, secret, false),
+    )
+    .body()
+    .deserialize()
+    .expect("CreateItem reply");
+
+    let (unlocked, locked): (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) = call(
+        &client,
+        "/org/freedesktop/secrets",
+        "org.freedesktop.Secret.Service",
+        "SearchItems",
+        &(HashMap::from([("service", "imap")]),),
+    )
+    .body()
+    .deserialize()
+    .expect("SearchItems reply");
+    assert_eq!(unlocked, vec![item.clone()]);
+    assert!(locked.is_empty());
+
+    let (_, _, value, content_type): (OwnedObjectPath, Vec<u8>, Vec<u8>, String) = call(
+        &client,
+        item.as_str(),
+        "org.freedesktop.Secret.Item",
+        "GetSecret",
+        &(&session,),
+    )
+    .body()
+    .deserialize()
+    .expect("GetSecret reply");
+    assert_eq!(value, b"hunter2");
+    assert_eq!(content_type, "text/plain");
+
+    // Closing the database locks the keyring for clients
+    fixture.kdbx.close().expect("Failed to close database");
+    let err = client
+        .call_method(
+            Some(dbus::BUS_NAME),
+            item.as_str(),
+            Some("org.freedesktop.Secret.Item"),
+            "GetSecret",
+            &(&session,),
+        )
+        .expect_err("locked keyring answered");
+    assert!(err.to_string().contains("IsLocked"), "{err}");
+
+    let _ = daemon.kill();
+    let _ = daemon.wait();
+}
@@ -117,6 +117,8 @@ fn load_settings_from_existing_file() {
         start_minimized: true,
         theme: "dark".into(),
         recent_databases: Vec::new(),
+        ssh_agent_enabled: true,
+        secret_service_enabled: true,
     };
     let content = serde_json::to_string_pretty(&settings).expect("serialize settings");
     std::fs::write(&settings_path, content).expect("write settings");
@@ -129,6 +131,35 @@ fn load_settings_from_existing_file() {
     assert!(!loaded.minimize_to_tray);
     assert!(loaded.start_minimized);
     assert_eq!(loaded.theme, "dark");
+    assert!(loaded.ssh_agent_enabled);
+    assert!(loaded.secret_service_enabled);
+
+    cleanup_settings_file(&app);
+}
+
+#[test]
+fn settings_without_service_toggles_keep_services_off() {
+    let _lock = crate::settings_test_lock();
+    let app = setup_app();
+    cleanup_settings_file(&app);
+
+    let settings_path = settings_file_path(&app);
+    let content = r#"{
+        "autoLockTimeout": 120,
+        "clipboardClearTimeout": 45,
+        "showPasswordByDefault": false,
+        "minimizeToTray": true,
+        "startMinimized": false,
+        "theme": "dark",
+        "recentDatabases": []
+    }"#;
+    std::fs::write(&settings_path, content).expect("write settings");
+
+    let service = new_service(&app);
+    let loaded = service.get_settings().expect("get settings");
+    assert_eq!(loaded.auto_lock_timeout, 120);
+    assert!(!loaded.ssh_agent_enabled);
+    assert!(!loaded.secret_service_enabled);
 
     cleanup_settings_file(&app);
 }
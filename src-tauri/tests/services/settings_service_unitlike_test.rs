@@ -61,6 +61,8 @@ fn save_and_load_roundtrip() {
         start_minimized: true,
         theme: "dark".into(),
         recent_databases: Vec::new(),
+        ssh_agent_enabled: true,
+        secret_service_enabled: true,
     };
 
     service.update_settings(settings).expect("save settings");
@@ -74,6 +76,8 @@ fn save_and_load_roundtrip() {
     assert!(loaded.start_minimized);
     assert_eq!(loaded.theme, "dark");
     assert!(loaded.recent_databases.is_empty());
+    assert!(loaded.ssh_agent_enabled);
+    assert!(loaded.secret_service_enabled);
 
     cleanup_settings_file(&app);
 }
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MIT
+//! Tests for re-importing shares when their containers change
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::keeshare::{ShareMode, ShareSettings, ShareSyncStatus};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::share_watcher::ShareWatcher;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+fn share(mode: ShareMode) -> ShareSettings {
+    ShareSettings {
+        mode,
+        path: "team.kdbx".to_string(),
+        password: Some(SecureString::from("share-pass")),
+        keep_groups: true,
+        trusted_signers: Vec::new(),
+        allow_unsigned: true,
+    }
+}
+
+/// Creates a database with a "Team" group shared in `mode`.
+fn shared_database(dir: &TempDir, name: &str, mode: ShareMode) -> (Arc<KdbxService>, String) {
+    let service = Arc::new(KdbxService::new());
+    let info = create_test_database(&service, &dir.path().join(name), name, "testpass");
+    let team = service
+        .create_group(Some(&info.root_group_id), "Team", None)
+        .expect("Failed to create group");
+    service
+        .set_group_share(&team.id, Some(&share(mode)))
+        .expect("Failed to configure share");
+    (service, team.id)
+}
+
+fn entry(title: &str) -> CreateEntryData {
+    CreateEntryData {
+        title: title.to_string(),
+        username: "team".to_string(),
+        password: SecureString::from("secret"),
+        url: None,
+        notes: None,
+        icon_id: None,
+        tags: None,
+        custom_fields: None,
+        protected_custom_fields: None,
+        expiry_time: None,
+    }
+}
+
+#[test]
+fn test_watcher_imports_changed_container() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (exporter, exporter_team) = shared_database(&dir, "exporter.kdbx", ShareMode::Export);
+    exporter
+        .create_entry(&exporter_team, entry("Wiki"))
+        .expect("Failed to create entry");
+    exporter.save().expect("Failed to save");
+
+    let (importer, importer_team) = shared_database(&dir, "importer.kdbx", ShareMode::Import);
+    let mut watcher = ShareWatcher::new(Arc::clone(&importer));
+
+    // The first look only records the containers
+    assert!(watcher.check().expect("check").is_none());
+    assert!(watcher.check().expect("check").is_none());
+
+    exporter
+        .create_entry(&exporter_team, entry("Chat"))
+        .expect("Failed to create entry");
+    exporter.save().expect("Failed to save");
+
+    let results = watcher
+        .check()
+        .expect("check")
+        .expect("Changed container should be imported");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, ShareSyncStatus::Imported);
+    assert_eq!(
+        importer
+            .list_entries(Some(&importer_team))
+            .expect("list")
+            .len(),
+        2
+    );
+    assert!(watcher.check().expect("check").is_none());
+}
+
+#[test]
+fn test_watcher_ignores_closed_database() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let (importer, _) = shared_database(&dir, "importer.kdbx", ShareMode::Import);
+    let mut watcher = ShareWatcher::new(Arc::clone(&importer));
+    assert!(watcher.check().expect("check").is_none());
+
+    importer.close().expect("Failed to close");
+    assert!(watcher.check().expect("check").is_none());
+}
@@ -0,0 +1,471 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the SSH agent service
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::ssh_agent::keys::{
+    SSH_AGENT_RSA_SHA2_256, SSH_AGENT_RSA_SHA2_512,
+};
+use mithril_vault_lib::services::ssh_agent::protocol::{
+    MessageReader, MessageWriter, SSH_AGENTC_REMOVE_ALL_IDENTITIES, SSH_AGENTC_REQUEST_IDENTITIES,
+    SSH_AGENTC_SIGN_REQUEST, SSH_AGENT_FAILURE, SSH_AGENT_IDENTITIES_ANSWER,
+    SSH_AGENT_SIGN_RESPONSE, SSH_AGENT_SUCCESS,
+};
+use mithril_vault_lib::services::ssh_agent::settings::SETTINGS_ATTACHMENT;
+use mithril_vault_lib::services::ssh_agent::SshAgentService;
+use rand::rngs::OsRng;
+use rsa::signature::Verifier;
+use ssh_key::{Algorithm, LineEnding, PrivateKey, PublicKey, Signature};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use super::support::{create_test_database, fixture_path};
+
+const ENTRY_PASSWORD: &str = "test123";
+
+struct Fixture {
+    kdbx: Arc<KdbxService>,
+    root_id: String,
+    dir: TempDir,
+}
+
+fn setup() -> Fixture {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let kdbx = Arc::new(KdbxService::new());
+    let info = create_test_database(&kdbx, &dir.path().join("ssh.kdbx"), "SSH", "testpass");
+
+    Fixture {
+        kdbx,
+        root_id: info.root_group_id,
+        dir,
+    }
+}
+
+fn agent(fixture: &Fixture) -> SshAgentService {
+    SshAgentService::new(
+        Arc::clone(&fixture.kdbx),
+        fixture.dir.path().join("agent.sock"),
+        |_| true,
+    )
+}
+
+fn settings_xml(add_at_open: bool, confirm: bool, lifetime: Option<u32>) -> Vec<u8> {
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-16"?>
+<EntrySettings>
+  <AllowUseOfSshKey>true</AllowUseOfSshKey>
+  <AddAtDatabaseOpen>{add_at_open}</AddAtDatabaseOpen>
+  <RemoveAtDatabaseClose>true</RemoveAtDatabaseClose>
+  <UseConfirmConstraintWhenAdding>{confirm}</UseConfirmConstraintWhenAdding>
+  <UseLifetimeConstraintWhenAdding>{}</UseLifetimeConstraintWhenAdding>
+  <LifetimeConstraintDuration>{}</LifetimeConstraintDuration>
+  <Location>
+    <SelectedType>attachment</SelectedType>
+    <AttachmentName>id_key</AttachmentName>
+  </Location>
+</EntrySettings>"#,
+        lifetime.is_some(),
+        lifetime.unwrap_or(600),
+    );
+    // KeeAgent writes UTF-16 with a byte order mark
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+    bytes
+}
+
+/// Creates an entry with the given key attachment and `KeeAgent.settings`.
+fn add_key_entry(fixture: &Fixture, title: &str, key: &[u8], settings: Option<Vec<u8>>) -> String {
+    let entry_id = fixture
+        .kdbx
+        .create_entry(
+            &fixture.root_id,
+            CreateEntryData {
+                title: title.to_string(),
+                username: "git".to_string(),
+                password: SecureString::from(ENTRY_PASSWORD),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: None,
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry")
+        .id;
+    fixture
+        .kdbx
+        .add_entry_attachment(&entry_id, "id_key", key.to_vec())
+        .expect("Failed to attach key");
+    if let Some(settings) = settings {
+        fixture
+            .kdbx
+            .add_entry_attachment(&entry_id, SETTINGS_ATTACHMENT, settings)
+            .expect("Failed to attach settings");
+    }
+    entry_id
+}
+
+fn ed25519_key(comment: &str) -> PrivateKey {
+    let mut key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).expect("Failed to generate");
+    key.set_comment(comment);
+    key
+}
+
+fn openssh(key: &PrivateKey) -> Vec<u8> {
+    key.to_openssh(LineEnding::LF)
+        .expect("Failed to encode key")
+        .as_bytes()
+        .to_vec()
+}
+
+fn fixture_key(name: &str) -> Vec<u8> {
+    std::fs::read(fixture_path(name)).expect("Failed to read key fixture")
+}
+
+/// Public key blobs and comments from an identities answer.
+fn identities(agent: &SshAgentService) -> Vec<(Vec<u8>, String)> {
+    let answer = agent.handle_message(&[SSH_AGENTC_REQUEST_IDENTITIES]);
+    assert_eq!(answer[0], SSH_AGENT_IDENTITIES_ANSWER);
+    let mut reader = MessageReader::new(&answer[1..]);
+    let count = reader.u32().expect("key count");
+    (0..count)
+        .map(|_| {
+            let blob = reader.string().expect("key blob").to_vec();
+            let comment = reader.string().expect("comment");
+            (blob, String::from_utf8_lossy(comment).into_owned())
+        })
+        .collect()
+}
+
+fn sign_request(agent: &SshAgentService, blob: &[u8], data: &[u8], flags: u32) -> Vec<u8> {
+    agent.handle_message(
+        &MessageWriter::new(SSH_AGENTC_SIGN_REQUEST)
+            .string(blob)
+            .string(data)
+            .u32(flags)
+            .finish(),
+    )
+}
+
+/// Checks that a sign response holds a valid signature of `data` by the key
+/// in `blob` and returns the signature algorithm.
+fn verify_response(response: &[u8], blob: &[u8], data: &[u8]) -> String {
+    assert_eq!(response[0], SSH_AGENT_SIGN_RESPONSE);
+    let signature_bytes = MessageReader::new(&response[1..])
+        .string()
+        .expect("signature");
+    let signature = Signature::try_from(signature_bytes).expect("Invalid signature encoding");
+    let public = PublicKey::from_bytes(blob).expect("Invalid key blob");
+    public
+        .key_data()
+        .verify(data, &signature)
+        .expect("Signature does not verify");
+    signature.algorithm().as_str().to_string()
+}
+
+#[test]
+fn test_loads_keys_flagged_for_database_open() {
+    let fixture = setup();
+    let plain = ed25519_key("deploy@example");
+    let encrypted = ed25519_key("")
+        .encrypt(&mut OsRng, ENTRY_PASSWORD)
+        .expect("Failed to encrypt key");
+    add_key_entry(
+        &fixture,
+        "Deploy",
+        &openssh(&plain),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "Encrypted",
+        &openssh(&encrypted),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "Legacy RSA",
+        &fixture_key("test-ssh-rsa-pem.key"),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "PKCS8 ECDSA",
+        &fixture_key("test-ssh-ecdsa-pkcs8.key"),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "Manual",
+        &openssh(&ed25519_key("manual")),
+        Some(settings_xml(false, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "No Settings",
+        &openssh(&ed25519_key("none")),
+        None,
+    );
+
+    let agent = agent(&fixture);
+    let status = agent.load_database_keys().expect("Failed to load keys");
+
+    assert!(status.load_errors.is_empty(), "{:?}", status.load_errors);
+    let mut titles: Vec<&str> = status.keys.iter().map(|k| k.title.as_str()).collect();
+    titles.sort_unstable();
+    assert_eq!(
+        titles,
+        vec!["Deploy", "Encrypted", "Legacy RSA", "PKCS8 ECDSA"]
+    );
+
+    let deploy = status
+        .keys
+        .iter()
+        .find(|k| k.title == "Deploy")
+        .expect("deploy key");
+    assert_eq!(deploy.algorithm, "ssh-ed25519");
+    assert_eq!(deploy.comment, "deploy@example");
+    assert!(deploy.fingerprint.starts_with("SHA256:"));
+    // Keys without a comment are listed under the entry title
+    let comments: Vec<String> = identities(&agent).into_iter().map(|(_, c)| c).collect();
+    assert!(comments.contains(&"Encrypted".to_string()));
+}
+
+#[test]
+fn test_signs_with_loaded_keys() {
+    let fixture = setup();
+    let key = ed25519_key("signer");
+    add_key_entry(
+        &fixture,
+        "Signer",
+        &openssh(&key),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "RSA",
+        &fixture_key("test-ssh-rsa-pem.key"),
+        Some(settings_xml(true, false, None)),
+    );
+    let agent = agent(&fixture);
+    agent.load_database_keys().expect("Failed to load keys");
+
+    let ed25519_blob = key.public_key().to_bytes().expect("blob");
+    let response = sign_request(&agent, &ed25519_blob, b"session data", 0);
+    assert_eq!(
+        verify_response(&response, &ed25519_blob, b"session data"),
+        "ssh-ed25519"
+    );
+
+    let (rsa_blob, comment) = identities(&agent)
+        .into_iter()
+        .find(|(blob, _)| *blob != ed25519_blob)
+        .expect("rsa key");
+    assert_eq!(comment, "RSA");
+    let sha512 = sign_request(&agent, &rsa_blob, b"data", SSH_AGENT_RSA_SHA2_512);
+    assert_eq!(verify_response(&sha512, &rsa_blob, b"data"), "rsa-sha2-512");
+    let sha256 = sign_request(&agent, &rsa_blob, b"data", SSH_AGENT_RSA_SHA2_256);
+    assert_eq!(verify_response(&sha256, &rsa_blob, b"data"), "rsa-sha2-256");
+    // Legacy SHA-1 signatures are refused
+    assert_eq!(
+        sign_request(&agent, &rsa_blob, b"data", 0),
+        vec![SSH_AGENT_FAILURE]
+    );
+
+    // Unknown keys are refused
+    let other = ed25519_key("other").public_key().to_bytes().expect("blob");
+    assert_eq!(
+        sign_request(&agent, &other, b"data", 0),
+        vec![SSH_AGENT_FAILURE]
+    );
+}
+
+#[test]
+fn test_confirmation_constraint_asks_before_signing() {
+    let fixture = setup();
+    let key = ed25519_key("confirm");
+    add_key_entry(
+        &fixture,
+        "Confirm",
+        &openssh(&key),
+        Some(settings_xml(true, true, None)),
+    );
+    let prompts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&prompts);
+    let agent = SshAgentService::new(
+        Arc::clone(&fixture.kdbx),
+        fixture.dir.path().join("agent.sock"),
+        move |description| {
+            assert!(description.starts_with("Confirm (SHA256:"));
+            counter.fetch_add(1, Ordering::SeqCst);
+            false
+        },
+    );
+    let status = agent.load_database_keys().expect("Failed to load keys");
+    assert!(status.keys[0].confirm);
+
+    let blob = key.public_key().to_bytes().expect("blob");
+    assert_eq!(
+        sign_request(&agent, &blob, b"data", 0),
+        vec![SSH_AGENT_FAILURE]
+    );
+    assert_eq!(prompts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_keys_are_removed_when_the_database_closes() {
+    let fixture = setup();
+    add_key_entry(
+        &fixture,
+        "Deploy",
+        &openssh(&ed25519_key("deploy")),
+        Some(settings_xml(true, false, None)),
+    );
+    let agent = agent(&fixture);
+    agent.load_database_keys().expect("Failed to load keys");
+    assert_eq!(identities(&agent).len(), 1);
+
+    agent.clear();
+    assert!(identities(&agent).is_empty());
+
+    agent.load_database_keys().expect("Failed to load keys");
+    fixture.kdbx.close().expect("Failed to close");
+    assert!(identities(&agent).is_empty());
+    assert!(agent.status().expect("status").keys.is_empty());
+}
+
+#[test]
+fn test_lifetime_constraint_expires_keys() {
+    let fixture = setup();
+    add_key_entry(
+        &fixture,
+        "Short Lived",
+        &openssh(&ed25519_key("short")),
+        Some(settings_xml(true, false, Some(0))),
+    );
+    add_key_entry(
+        &fixture,
+        "Long Lived",
+        &openssh(&ed25519_key("long")),
+        Some(settings_xml(true, false, Some(3600))),
+    );
+    let agent = agent(&fixture);
+    agent.load_database_keys().expect("Failed to load keys");
+
+    let status = agent.status().expect("status");
+    assert_eq!(status.keys.len(), 1);
+    assert_eq!(status.keys[0].title, "Long Lived");
+    assert!(status.keys[0].expires_at.is_some());
+}
+
+#[test]
+fn test_add_and_remove_keys_manually() {
+    let fixture = setup();
+    let manual = add_key_entry(
+        &fixture,
+        "Manual",
+        &openssh(&ed25519_key("manual")),
+        Some(settings_xml(false, false, None)),
+    );
+    let no_settings = add_key_entry(&fixture, "Bare", &openssh(&ed25519_key("bare")), None);
+    let agent = agent(&fixture);
+
+    let added = agent.add_key(&manual).expect("Failed to add key");
+    assert_eq!(added.title, "Manual");
+    assert_eq!(identities(&agent).len(), 1);
+    assert!(agent.add_key(&no_settings).is_err());
+
+    agent.remove_key(&manual).expect("Failed to remove key");
+    assert!(identities(&agent).is_empty());
+    assert!(agent.remove_key(&manual).is_err());
+
+    agent.add_key(&manual).expect("Failed to add key");
+    assert_eq!(
+        agent.handle_message(&[SSH_AGENTC_REMOVE_ALL_IDENTITIES]),
+        vec![SSH_AGENT_SUCCESS]
+    );
+    assert!(identities(&agent).is_empty());
+}
+
+#[test]
+fn test_load_errors_are_reported() {
+    let fixture = setup();
+    let wrong_password = ed25519_key("")
+        .encrypt(&mut OsRng, "another passphrase")
+        .expect("Failed to encrypt key");
+    add_key_entry(
+        &fixture,
+        "Wrong Password",
+        &openssh(&wrong_password),
+        Some(settings_xml(true, false, None)),
+    );
+    add_key_entry(
+        &fixture,
+        "Garbage",
+        b"not a key",
+        Some(settings_xml(true, false, None)),
+    );
+    let agent = agent(&fixture);
+    let status = agent.load_database_keys().expect("Failed to load keys");
+
+    assert!(status.keys.is_empty());
+    let mut failed: Vec<&str> = status
+        .load_errors
+        .iter()
+        .map(|e| e.title.as_str())
+        .collect();
+    failed.sort_unstable();
+    assert_eq!(failed, vec!["Garbage", "Wrong Password"]);
+}
+
+#[test]
+fn test_unsupported_requests_fail() {
+    let fixture = setup();
+    let agent = agent(&fixture);
+
+    assert_eq!(agent.handle_message(&[]), vec![SSH_AGENT_FAILURE]);
+    // SSH_AGENTC_ADD_IDENTITY: keys only come from the database
+    assert_eq!(agent.handle_message(&[17, 0, 0]), vec![SSH_AGENT_FAILURE]);
+    assert_eq!(
+        agent.handle_message(&[SSH_AGENTC_SIGN_REQUEST, 0, 0]),
+        vec![SSH_AGENT_FAILURE]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_socket_serves_identities() {
+    use mithril_vault_lib::services::ssh_agent::protocol::{read_message, write_message};
+    use mithril_vault_lib::services::ssh_agent::server;
+    use std::os::unix::net::UnixStream;
+
+    let fixture = setup();
+    add_key_entry(
+        &fixture,
+        "Deploy",
+        &openssh(&ed25519_key("deploy")),
+        Some(settings_xml(true, false, None)),
+    );
+    let agent = Arc::new(agent(&fixture));
+    agent.load_database_keys().expect("Failed to load keys");
+    server::start(Arc::clone(&agent)).expect("Failed to start agent");
+    let socket = agent
+        .status()
+        .expect("status")
+        .socket_path
+        .expect("agent is listening");
+
+    let mut stream = UnixStream::connect(&socket).expect("Failed to connect");
+    write_message(&mut stream, &[SSH_AGENTC_REQUEST_IDENTITIES]).expect("Failed to write");
+    let answer = read_message(&mut stream)
+        .expect("Failed to read")
+        .expect("answer");
+    assert_eq!(answer[0], SSH_AGENT_IDENTITIES_ANSWER);
+    assert_eq!(MessageReader::new(&answer[1..]).u32(), Some(1));
+}
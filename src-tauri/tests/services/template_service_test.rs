@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+//! Tests for rendering config templates with vault references
+
+#![allow(clippy::expect_used)]
+
+use std::collections::BTreeMap;
+
+use mithril_vault_lib::domain::secure::SecureString;
+use mithril_vault_lib::dto::entry::CreateEntryData;
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+/// Creates a database with an `Infra/Postgres` entry that has a protected
+/// `Api Key` field. Returns the service, temp dir and entry ID.
+fn setup() -> (KdbxService, TempDir, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let kdbx = KdbxService::new();
+    let info = create_test_database(
+        &kdbx,
+        &dir.path().join("template.kdbx"),
+        "Templates",
+        "testpass",
+    );
+    let group = kdbx
+        .create_group(Some(&info.root_group_id), "Infra", None)
+        .expect("Failed to create group");
+    let entry = kdbx
+        .create_entry(
+            &group.id,
+            CreateEntryData {
+                title: "Postgres".to_string(),
+                username: "app".to_string(),
+                password: SecureString::from("pg-\"secret\""),
+                url: None,
+                notes: None,
+                icon_id: None,
+                tags: None,
+                custom_fields: None,
+                protected_custom_fields: Some(BTreeMap::from([(
+                    "Api Key".to_string(),
+                    SecureString::from("key-123"),
+                )])),
+                expiry_time: None,
+            },
+        )
+        .expect("Failed to create entry");
+    (kdbx, dir, entry.id)
+}
+
+#[test]
+fn test_renders_paths_uuids_and_protected_fields() {
+    let (kdbx, _dir, id) = setup();
+    let template = format!(
+        "user={{{{ vault \"Infra/Postgres\" \"UserName\" }}}}\n\
+         pass={{{{vault \"/Infra/Postgres\" \"Password\"}}}}\n\
+         key={{{{ vault \"{id}\" \"Api Key\" }}}}\n\
+         keep={{{{ .Values.other }}}} {{{{ vaultish }}}}\n"
+    );
+
+    let rendered = kdbx.render_template(&template).expect("render failed");
+    assert_eq!(
+        rendered.as_str(),
+        "user=app\npass=pg-\"secret\"\nkey=key-123\nkeep={{ .Values.other }} {{ vaultish }}\n"
+    );
+
+    let escaped = kdbx
+        .render_template(r#"{{ vault "Infra\\Missing\"" "Password" }}"#)
+        .expect_err("entry should be missing");
+    assert!(escaped.to_string().contains(r#"Infra\Missing""#));
+}
+
+#[test]
+fn test_missing_references_fail_the_render() {
+    let (kdbx, _dir, _id) = setup();
+
+    for template in [
+        "ok\n{{ vault \"Infra/Nothing\" \"Password\" }}",
+        "ok\n{{ vault \"Infra/Postgres\" \"No Such Field\" }}",
+        "ok\n{{ vault \"Infra/Postgres\" }}",
+        "ok\n{{ vault \"Infra/Postgres\" \"Password }}",
+        "ok\n{{ vault Infra/Postgres Password }}",
+    ] {
+        let error = kdbx
+            .render_template(template)
+            .expect_err("render should fail");
+        assert!(matches!(error, AppError::Template(_)), "{template}");
+        assert!(error.to_string().contains("line 2:"), "{error}");
+    }
+}
+
+#[test]
+fn test_unclosed_tag_fails_the_render() {
+    let (kdbx, _dir, _id) = setup();
+
+    for template in [
+        "a\n{{ vault",
+        "a\n{{ vault \"Infra/Postgres\" \"Password\" }",
+    ] {
+        let error = kdbx
+            .render_template(template)
+            .expect_err("render should fail");
+        assert!(matches!(error, AppError::Template(_)), "{template}");
+        assert_eq!(
+            error.to_string(),
+            "Template error: line 2: unterminated tag"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MIT
+//! Tests for the backoff after failed unlock attempts
+
+#![allow(clippy::expect_used)]
+
+use mithril_vault_lib::dto::error::AppError;
+use mithril_vault_lib::services::kdbx::KdbxService;
+use mithril_vault_lib::services::unlock_throttle::{UnlockThrottleService, FREE_ATTEMPTS};
+use tempfile::TempDir;
+
+use super::support::create_test_database;
+
+const DATABASE: &str = "/nonexistent/vault.kdbx";
+
+fn fail(throttle: &UnlockThrottleService, path: &str) -> AppError {
+    throttle
+        .attempt(path, || Err::<(), _>(AppError::InvalidPassword))
+        .expect_err("attempt should fail")
+}
+
+#[test]
+fn test_backoff_starts_after_free_attempts() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+
+    for _ in 0..FREE_ATTEMPTS {
+        assert!(matches!(
+            fail(&throttle, DATABASE),
+            AppError::InvalidPassword
+        ));
+    }
+
+    let err = fail(&throttle, DATABASE);
+    assert!(matches!(err, AppError::UnlockThrottled(1)), "{err:?}");
+    assert!(!throttle
+        .remaining_delay(DATABASE)
+        .expect("remaining delay")
+        .is_zero());
+
+    // Other databases are counted separately
+    throttle
+        .attempt("/nonexistent/other.kdbx", || Ok(()))
+        .expect("other database is not throttled");
+}
+
+#[test]
+fn test_throttled_attempt_does_not_run() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+    for _ in 0..FREE_ATTEMPTS {
+        fail(&throttle, DATABASE);
+    }
+
+    let mut ran = false;
+    let result = throttle.attempt(DATABASE, || {
+        ran = true;
+        Ok(())
+    });
+    assert!(matches!(result, Err(AppError::UnlockThrottled(_))));
+    assert!(!ran);
+}
+
+#[test]
+fn test_counters_survive_restart() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    {
+        let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+        for _ in 0..FREE_ATTEMPTS {
+            fail(&throttle, DATABASE);
+        }
+    }
+
+    let throttle = UnlockThrottleService::new(dir.path()).expect("reload throttle");
+    assert!(matches!(
+        fail(&throttle, DATABASE),
+        AppError::UnlockThrottled(_)
+    ));
+}
+
+#[test]
+fn test_success_resets_counter() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+
+    for _ in 1..FREE_ATTEMPTS {
+        fail(&throttle, DATABASE);
+    }
+    throttle
+        .attempt(DATABASE, || Ok(()))
+        .expect("unlock succeeds");
+
+    for _ in 0..FREE_ATTEMPTS {
+        assert!(matches!(
+            fail(&throttle, DATABASE),
+            AppError::InvalidPassword
+        ));
+    }
+}
+
+#[test]
+fn test_other_errors_are_not_counted() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+
+    for _ in 0..=FREE_ATTEMPTS {
+        let err = throttle
+            .attempt(DATABASE, || {
+                Err::<(), _>(AppError::InvalidPath(DATABASE.to_string()))
+            })
+            .expect_err("attempt should fail");
+        assert!(matches!(err, AppError::InvalidPath(_)));
+    }
+}
+
+#[test]
+fn test_damaged_state_file_is_ignored() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    std::fs::write(dir.path().join("unlock-throttle.json"), "not json").expect("write state");
+
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+    assert!(matches!(
+        fail(&throttle, DATABASE),
+        AppError::InvalidPassword
+    ));
+}
+
+#[test]
+fn test_success_is_returned_when_the_state_cannot_be_saved() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let data_dir = dir.path().join("data");
+    let throttle = UnlockThrottleService::new(&data_dir).expect("create throttle");
+    fail(&throttle, DATABASE);
+    std::fs::remove_dir_all(&data_dir).expect("Failed to remove data dir");
+
+    throttle
+        .attempt(DATABASE, || Ok(()))
+        .expect("unlock succeeds");
+    assert!(throttle
+        .remaining_delay(DATABASE)
+        .expect("remaining delay")
+        .is_zero());
+}
+
+#[test]
+fn test_failure_is_returned_when_the_state_cannot_be_saved() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let data_dir = dir.path().join("data");
+    let throttle = UnlockThrottleService::new(&data_dir).expect("create throttle");
+    std::fs::remove_dir_all(&data_dir).expect("Failed to remove data dir");
+
+    for _ in 0..FREE_ATTEMPTS {
+        assert!(matches!(
+            fail(&throttle, DATABASE),
+            AppError::InvalidPassword
+        ));
+    }
+    // The counters are still kept in memory
+    assert!(matches!(
+        fail(&throttle, DATABASE),
+        AppError::UnlockThrottled(_)
+    ));
+}
+
+#[test]
+fn test_running_attempt_counts_for_concurrent_attempts() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+    let other_process = UnlockThrottleService::new(dir.path()).expect("create throttle");
+    for _ in 1..FREE_ATTEMPTS {
+        fail(&throttle, DATABASE);
+    }
+
+    // The last free attempt is counted while it runs
+    throttle
+        .attempt(DATABASE, || {
+            assert!(matches!(
+                fail(&other_process, DATABASE),
+                AppError::UnlockThrottled(_)
+            ));
+            Ok(())
+        })
+        .expect("unlock succeeds");
+    assert!(other_process
+        .remaining_delay(DATABASE)
+        .expect("remaining delay")
+        .is_zero());
+}
+
+#[test]
+fn test_other_databases_unlock_during_an_attempt() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let throttle = UnlockThrottleService::new(dir.path()).expect("create throttle");
+
+    // The counters are not locked while an unlock runs
+    throttle
+        .attempt(DATABASE, || {
+            throttle.attempt("/nonexistent/other.kdbx", || Ok(()))
+        })
+        .expect("nested attempt succeeds");
+}
+
+#[test]
+fn test_counters_are_shared_between_processes() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let app = UnlockThrottleService::new(dir.path()).expect("create throttle");
+    let cli = UnlockThrottleService::new(dir.path()).expect("create throttle");
+
+    for _ in 0..FREE_ATTEMPTS {
+        fail(&cli, DATABASE);
+    }
+    assert!(matches!(fail(&app, DATABASE), AppError::UnlockThrottled(_)));
+}
+
+#[test]
+fn test_kdbx_service_throttles_open_and_unlock() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = dir.path().join("throttled.kdbx");
+    let creator = KdbxService::new();
+    create_test_database(&creator, &db_path, "Throttled", "right-password");
+    creator.close().expect("Failed to close");
+    let path = db_path.to_string_lossy();
+
+    let state_dir = dir.path().join("data");
+    let kdbx = KdbxService::new()
+        .with_unlock_throttle(UnlockThrottleService::new(&state_dir).expect("create throttle"));
+    for _ in 0..FREE_ATTEMPTS {
+        assert!(matches!(
+            kdbx.open(&path, "wrong-password"),
+            Err(AppError::InvalidPassword)
+        ));
+    }
+    assert!(matches!(
+        kdbx.open(&path, "right-password"),
+        Err(AppError::UnlockThrottled(_))
+    ));
+
+    // Session unlocks count against the same file
+    let kdbx = KdbxService::new();
+    kdbx.open(&path, "right-password").expect("Failed to open");
+    kdbx.lock().expect("Failed to lock");
+    let kdbx =
+        kdbx.with_unlock_throttle(UnlockThrottleService::new(&state_dir).expect("create throttle"));
+    assert!(matches!(
+        kdbx.unlock(Some("right-password")),
+        Err(AppError::UnlockThrottled(_))
+    ));
+}
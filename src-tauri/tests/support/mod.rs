@@ -1,4 +1,9 @@
-use std::path::PathBuf;
+// Each test crate uses a different subset of these helpers
+#![allow(dead_code, clippy::expect_used)]
+
+use mithril_vault_lib::dto::database::{DatabaseCreationOptions, DatabaseInfo};
+use mithril_vault_lib::services::kdbx::KdbxService;
+use std::path::{Path, PathBuf};
 
 pub fn fixture_path(filename: &str) -> PathBuf {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -7,3 +12,34 @@ pub fn fixture_path(filename: &str) -> PathBuf {
     path.push(filename);
     path
 }
+
+/// Creation options with the cheapest KDF settings and no default groups,
+/// so tests don't spend their time deriving keys.
+pub fn low_kdf_options() -> DatabaseCreationOptions {
+    DatabaseCreationOptions {
+        create_default_groups: false,
+        kdf_memory: Some(1024 * 1024),
+        kdf_iterations: Some(1),
+        kdf_parallelism: Some(1),
+        description: None,
+    }
+}
+
+/// Creates an empty database at `path` with [`low_kdf_options`] and leaves
+/// it open in `service`.
+pub fn create_test_database(
+    service: &KdbxService,
+    path: &Path,
+    name: &str,
+    password: &str,
+) -> DatabaseInfo {
+    service
+        .create_database(
+            &path.to_string_lossy(),
+            Some(password),
+            None,
+            name,
+            &low_kdf_options(),
+        )
+        .expect("Failed to create test database")
+}
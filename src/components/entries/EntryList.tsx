@@ -17,6 +17,14 @@ export default function EntryList() {
             createdAt=""
             modifiedAt=""
             accessedAt=""
+            expires={false}
+            expiryTime={null}
+            referenceFields={[]}
+            autoType={{
+              enabled: true,
+              defaultSequence: null,
+              associations: [],
+            }}
           />
           <ItemSeparator />
         </div>
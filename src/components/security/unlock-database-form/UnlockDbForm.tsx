@@ -17,6 +17,7 @@ import {
 } from "@/components/ui/input-group.tsx";
 import { useEffect, useState } from "react";
 import { Alert, AlertDescription, AlertTitle } from "@/components/ui/alert.tsx";
+import { Button } from "@/components/ui/button.tsx";
 import { Controller, useForm, useWatch } from "react-hook-form";
 import { zodResolver } from "@hookform/resolvers/zod";
 import {
@@ -25,7 +26,7 @@ import {
 } from "@/lib/formTypes.ts";
 import { useNavigate } from "@tanstack/react-router";
 import { open } from "@tauri-apps/plugin-dialog";
-import { database, settings } from "@/lib/tauri.ts";
+import { AppError, database, settings } from "@/lib/tauri.ts";
 import { Checkbox } from "@/components/ui/checkbox.tsx";
 import { Label } from "@/components/ui/label.tsx";
 import { toast } from "sonner";
@@ -41,37 +42,40 @@ function getFilenameFromPath(path: string | undefined): string {
 }
 
 function mapErrorToMessage(error: unknown): string {
-  const errorStr = String(error);
-
-  if (errorStr.includes("Invalid password")) {
-    return "The password you entered is incorrect. Please try again.";
-  }
-  if (errorStr.includes("Keyfile not found")) {
-    return "The keyfile could not be found at the specified location.";
-  }
-  if (errorStr.includes("Invalid keyfile format")) {
-    return "The selected keyfile has an invalid format.";
-  }
-  if (errorStr.includes("No credentials provided")) {
-    return "Please enter a password or select a keyfile.";
-  }
-  if (errorStr.includes("Database is locked")) {
-    return "This database is currently open in another application. Close it first or force unlock.";
-  }
-  if (errorStr.includes("Not a valid KDBX file")) {
-    return "The selected file is not a valid KeePass database.";
-  }
-  if (errorStr.includes("Unsupported KDBX version")) {
-    return "This database uses an unsupported KeePass format version.";
-  }
-  if (
-    errorStr.includes("IO error") ||
-    errorStr.includes("No such file or directory")
-  ) {
-    return "The database file could not be found or read.";
+  if (!(error instanceof AppError)) {
+    return "Failed to unlock database. Please check your credentials and try again.";
   }
 
-  return "Failed to unlock database. Please check your credentials and try again.";
+  switch (error.code) {
+    case "invalidPassword":
+      return "The password you entered is incorrect. Please try again.";
+    case "unlockThrottled": {
+      const seconds =
+        error.details && "retryAfterSecs" in error.details
+          ? error.details.retryAfterSecs
+          : null;
+      return seconds === null
+        ? "Too many failed attempts. Please wait before trying again."
+        : `Too many failed attempts. Please wait ${seconds} seconds before trying again.`;
+    }
+    case "keyfileNotFound":
+      return "The keyfile could not be found at the specified location.";
+    case "keyfileInvalid":
+      return "The selected keyfile has an invalid format.";
+    case "noCredentials":
+      return "Please enter a password or select a keyfile.";
+    case "databaseLocked":
+      return "This database is currently open in another application. Close it first, force unlock, or open it read-only.";
+    case "invalidKdbxFile":
+      return "The selected file is not a valid KeePass database.";
+    case "unsupportedKdbxVersion":
+      return "This database uses an unsupported KeePass format version.";
+    case "io":
+    case "invalidPath":
+      return "The database file could not be found or read.";
+    default:
+      return "Failed to unlock database. Please check your credentials and try again.";
+  }
 }
 
 export function UnlockDbForm({ initialPath }: UnlockDbFormProps) {
@@ -79,6 +83,7 @@ export function UnlockDbForm({ initialPath }: UnlockDbFormProps) {
   const [showPassword, setShowPassword] = useState(false);
   const [isUnlocking, setIsUnlocking] = useState(false);
   const [unlockError, setUnlockError] = useState<string | null>(null);
+  const [lockedElsewhere, setLockedElsewhere] = useState(false);
   const [rememberKeyfile, setRememberKeyfile] = useState(false);
 
   const openDbForm = useForm<OpenDatabaseFormValues>({
@@ -170,13 +175,20 @@ export function UnlockDbForm({ initialPath }: UnlockDbFormProps) {
     setRememberKeyfile(false);
   }
 
-  async function onSubmit(data: OpenDatabaseFormValues) {
+  async function unlock(data: OpenDatabaseFormValues, readOnly: boolean) {
     setIsUnlocking(true);
     setUnlockError(null);
+    setLockedElsewhere(false);
 
     try {
       // Determine which unlock method to use
-      if (data.keyfilePath && data.password) {
+      if (readOnly) {
+        await database.openReadOnly(
+          data.filePath,
+          data.password || undefined,
+          data.keyfilePath || undefined
+        );
+      } else if (data.keyfilePath && data.password) {
         await database.openWithKeyfile(
           data.filePath,
           data.password,
@@ -206,11 +218,22 @@ export function UnlockDbForm({ initialPath }: UnlockDbFormProps) {
       await navigate({ to: "/dashboard" });
     } catch (error) {
       setUnlockError(mapErrorToMessage(error));
+      setLockedElsewhere(
+        error instanceof AppError && error.code === "databaseLocked"
+      );
     } finally {
       setIsUnlocking(false);
     }
   }
 
+  async function onSubmit(data: OpenDatabaseFormValues) {
+    await unlock(data, false);
+  }
+
+  async function onOpenReadOnly(data: OpenDatabaseFormValues) {
+    await unlock(data, true);
+  }
+
   const filename = getFilenameFromPath(filePath);
   const keyfilename = getFilenameFromPath(keyfilePath);
 
@@ -335,7 +358,21 @@ export function UnlockDbForm({ initialPath }: UnlockDbFormProps) {
           <Alert variant="destructive">
             <ShieldAlert />
             <AlertTitle>Error unlocking database</AlertTitle>
-            <AlertDescription>{unlockError}</AlertDescription>
+            <AlertDescription>
+              {unlockError}
+              {lockedElsewhere && (
+                <Button
+                  type="button"
+                  size="sm"
+                  variant="outline"
+                  className="mt-2"
+                  onClick={openDbForm.handleSubmit(onOpenReadOnly)}
+                  disabled={isUnlocking}
+                >
+                  Open read-only
+                </Button>
+              )}
+            </AlertDescription>
           </Alert>
         )}
       </div>
@@ -1,36 +1,139 @@
 // SPDX-License-Identifier: MIT
 
-import { invoke } from "@tauri-apps/api/core";
+import { type InvokeArgs, invoke as tauriInvoke } from "@tauri-apps/api/core";
 import { z } from "zod/v4";
 import type {
+  AppErrorPayload,
   AppSettings,
+  AutoTypeSequence,
+  BreachReport,
+  BrowserAssociation,
   CreateEntryData,
+  CreateReferenceEntryData,
   CustomFieldValue,
   DatabaseConfig,
   DatabaseCreationOptions,
   DatabaseHeaderInfo,
   DatabaseInfo,
   Entry,
+  ExpiringEntry,
+  ExportOptions,
+  ExportResult,
   Group,
+  GroupExportResult,
+  HealthReport,
+  HealthReportOptions,
+  IpcAuditRecord,
+  IpcClient,
+  IpcScopes,
+  IpcStatus,
+  KdbImportResult,
+  KdbImportSource,
+  KeyAction,
   LockStatus,
   PasswordGeneratorOptions,
+  QuickUnlockStatus,
+  RecycleBinItem,
+  RecycleBinSettings,
+  SaveResult,
+  ShareInfo,
+  ShareSettings,
+  ShareSyncResult,
+  SecretServiceStatus,
+  SshAgentKey,
+  SshAgentStatus,
   UpdateEntryData,
+  UpdateGroupData,
+  UrlMatch,
+  UrlMatchOptions,
 } from "./types";
 import {
+  AppErrorPayloadSchema,
   AppSettingsSchema,
+  AutoTypeSequenceSchema,
+  BreachReportSchema,
+  BrowserAssociationSchema,
   CreateEntryDataSchema,
+  CreateReferenceEntryDataSchema,
   CustomFieldValueSchema,
   DatabaseConfigSchema,
   DatabaseCreationOptionsSchema,
   DatabaseHeaderInfoSchema,
   DatabaseInfoSchema,
   EntrySchema,
+  ExpiringEntrySchema,
+  ExportOptionsSchema,
+  ExportResultSchema,
+  GroupExportResultSchema,
   GroupSchema,
+  HealthReportOptionsSchema,
+  HealthReportSchema,
+  IpcAuditRecordSchema,
+  IpcClientSchema,
+  IpcScopesSchema,
+  IpcStatusSchema,
+  KdbImportResultSchema,
+  KdbImportSourceSchema,
+  KeyActionSchema,
   LockStatusSchema,
   PasswordGeneratorOptionsSchema,
+  QuickUnlockStatusSchema,
+  RecycleBinItemSchema,
+  RecycleBinSettingsSchema,
+  SaveResultSchema,
+  ShareInfoSchema,
+  ShareSettingsSchema,
+  ShareSyncResultSchema,
+  SecretServiceStatusSchema,
+  SshAgentKeySchema,
+  SshAgentStatusSchema,
   UpdateEntryDataSchema,
+  UpdateGroupDataSchema,
+  UrlMatchOptionsSchema,
+  UrlMatchSchema,
 } from "./types";
 
+/**
+ * Error thrown by every command below. Match on `code`. For callers written
+ * before codes existed, `String(error)` stays the bare backend message
+ * until the next release.
+ */
+export class AppError extends Error {
+  readonly code: string;
+  readonly details: AppErrorPayload["details"];
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message);
+    this.name = "AppError";
+    this.code = payload.code;
+    this.details = payload.details;
+  }
+
+  override toString(): string {
+    return this.message;
+  }
+}
+
+function toAppError(error: unknown): unknown {
+  const payload = AppErrorPayloadSchema.safeParse(error);
+  if (payload.success) {
+    return new AppError(payload.data);
+  }
+  // Plain messages come from backends older than structured errors
+  if (typeof error === "string") {
+    return new AppError({ code: "unknown", message: error, details: null });
+  }
+  return error;
+}
+
+async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (error) {
+    throw toAppError(error);
+  }
+}
+
 const PathPasswordSchema = z.object({
   path: z.string().min(1),
   password: z.string(),
@@ -63,6 +166,14 @@ const NameSchema = z.object({
   name: z.string().min(1),
 });
 
+const AssociationIdSchema = z.object({
+  id: z.string().min(1),
+});
+
+const ClientIdSchema = z.object({
+  clientId: z.string().min(1),
+});
+
 const CopyPasswordSchema = z.object({
   entryId: z.uuid(),
   timeoutMs: z.number().int().positive().optional(),
@@ -76,6 +187,10 @@ const CreateDatabaseSchema = z.object({
   options: DatabaseCreationOptionsSchema.optional(),
 });
 
+const ImportKdbSchema = CreateDatabaseSchema.extend({
+  source: KdbImportSourceSchema,
+});
+
 const PathOnlySchema = z.object({
   path: z.string().min(1),
 });
@@ -94,8 +209,32 @@ export const database = {
     return invoke("close_database");
   },
 
-  async save(): Promise<void> {
-    return invoke("save_database");
+  /**
+   * Saves the database. Shares whose container could not be written are
+   * listed in `shareFailures`; the database itself was saved.
+   */
+  async save(): Promise<SaveResult> {
+    const result = await invoke("save_database");
+    return SaveResultSchema.parse(result);
+  },
+
+  /**
+   * Lock the session: decrypted data is dropped, the file stays reserved.
+   * Unsaved changes are kept encrypted in memory, not written to the file,
+   * and restored on unlock. If they cannot be kept the session is still
+   * locked and the call rejects.
+   */
+  async lock(): Promise<void> {
+    return invoke("lock_database");
+  },
+
+  /**
+   * Unlock the locked session. The keyfile it was opened with is read again;
+   * keyfile-only databases pass no password.
+   */
+  async unlock(password?: string): Promise<DatabaseInfo> {
+    const result = await invoke("unlock_database", { password });
+    return DatabaseInfoSchema.parse(result);
   },
 
   /**
@@ -125,6 +264,43 @@ export const database = {
     return DatabaseInfoSchema.parse(result);
   },
 
+  /**
+   * Import a legacy KDB (KeePass 1.x) database into a new KDBX4 database
+   *
+   * @param source - Path and credentials of the .kdb file
+   * @param path - File path where the new database will be saved
+   * @param name - Database name (also used as root group name)
+   * @param password - Optional password for the new database (required if no keyfile)
+   * @param keyfilePath - Optional keyfile for the new database
+   * @param options - Optional creation options (KDF settings, description)
+   */
+  async importKdb(
+    source: KdbImportSource,
+    path: string,
+    name: string,
+    password?: string,
+    keyfilePath?: string,
+    options?: DatabaseCreationOptions
+  ): Promise<KdbImportResult> {
+    ImportKdbSchema.parse({
+      source,
+      path,
+      name,
+      password,
+      keyfilePath,
+      options,
+    });
+    const result = await invoke("import_kdb_database", {
+      source,
+      path,
+      name,
+      password,
+      keyfilePath,
+      options,
+    });
+    return KdbImportResultSchema.parse(result);
+  },
+
   async openWithKeyfile(
     path: string,
     password: string,
@@ -139,6 +315,24 @@ export const database = {
     return DatabaseInfoSchema.parse(result);
   },
 
+  /**
+   * Open a database read-only, without taking its file lock. Meant for
+   * databases another application has open; every change is refused.
+   */
+  async openReadOnly(
+    path: string,
+    password?: string,
+    keyfilePath?: string
+  ): Promise<DatabaseInfo> {
+    PathOnlySchema.parse({ path });
+    const result = await invoke("open_database_read_only", {
+      path,
+      password,
+      keyfilePath,
+    });
+    return DatabaseInfoSchema.parse(result);
+  },
+
   async openWithKeyfileOnly(
     path: string,
     keyfilePath: string
@@ -234,6 +428,29 @@ export const entries = {
     return z.string().parse(result);
   },
 
+  /**
+   * Expands placeholders against an entry in safe mode: `{CMD:...}` and
+   * `{NEWPASSWORD}` are left as they are.
+   */
+  async expandPlaceholders(entryId: string, text: string): Promise<string> {
+    const result = await invoke("expand_placeholders", { entryId, text });
+    return z.string().parse(result);
+  },
+
+  /**
+   * Entries whose URL or `KP2A_URL*` fields match a site, exact host
+   * matches first. Recycle bin and expired entries are skipped.
+   */
+  async findForUrl(
+    url: string,
+    options?: UrlMatchOptions
+  ): Promise<UrlMatch[]> {
+    z.string().min(1).parse(url);
+    if (options) UrlMatchOptionsSchema.parse(options);
+    const result = await invoke("find_entries_for_url", { url, options });
+    return z.array(UrlMatchSchema).parse(result);
+  },
+
   async getProtectedCustomField(
     id: string,
     key: string
@@ -254,6 +471,20 @@ export const entries = {
     return EntrySchema.parse(result);
   },
 
+  /**
+   * Create an entry whose selected fields reference another entry, so both
+   * stay in sync (e.g. a shared password).
+   */
+  async createReference(
+    groupId: string,
+    data: CreateReferenceEntryData
+  ): Promise<Entry> {
+    GroupIdSchema.parse({ groupId });
+    CreateReferenceEntryDataSchema.parse(data);
+    const result = await invoke("create_reference_entry", { groupId, data });
+    return EntrySchema.parse(result);
+  },
+
   async update(id: string, data: UpdateEntryData): Promise<Entry> {
     IdSchema.parse({ id });
     UpdateEntryDataSchema.parse(data);
@@ -261,9 +492,35 @@ export const entries = {
     return EntrySchema.parse(result);
   },
 
-  async delete(id: string): Promise<void> {
+  /**
+   * Delete an entry. It goes to the recycle bin unless `permanent` is set,
+   * the bin is disabled or the entry is already in the bin.
+   */
+  async delete(id: string, permanent?: boolean): Promise<void> {
     IdSchema.parse({ id });
-    return invoke("delete_entry", { id });
+    return invoke("delete_entry", { id, permanent });
+  },
+
+  /**
+   * Entries that have expired or expire within `withinDays` days
+   * (default 7), soonest first.
+   */
+  async listExpiring(withinDays?: number): Promise<ExpiringEntry[]> {
+    const result = await invoke("list_expiring_entries", { withinDays });
+    return z.array(ExpiringEntrySchema).parse(result);
+  },
+
+  /**
+   * Export entries to a plaintext CSV or JSON file
+   *
+   * @param path - Destination file (created with owner-only permissions)
+   * @param options - Format, columns and whether to include protected values
+   */
+  async export(path: string, options: ExportOptions): Promise<ExportResult> {
+    PathOnlySchema.parse({ path });
+    ExportOptionsSchema.parse(options);
+    const result = await invoke("export_entries", { path, options });
+    return ExportResultSchema.parse(result);
   },
 };
 
@@ -296,9 +553,336 @@ export const groups = {
     return GroupSchema.parse(result);
   },
 
-  async delete(id: string): Promise<void> {
+  async update(id: string, data: UpdateGroupData): Promise<Group> {
+    IdSchema.parse({ id });
+    UpdateGroupDataSchema.parse(data);
+    const result = await invoke("update_group", { id, data });
+    return GroupSchema.parse(result);
+  },
+
+  async delete(
+    id: string,
+    options?: { recursive?: boolean; permanent?: boolean }
+  ): Promise<void> {
     IdSchema.parse({ id });
-    return invoke("delete_group", { id });
+    return invoke("delete_group", { id, ...options });
+  },
+
+  /**
+   * Write a group subtree to a new KDBX4 database with its own credentials
+   *
+   * @param groupId - Group to export, including all subgroups
+   * @param path - File path of the new database
+   * @param password - Optional password (required if no keyfile)
+   * @param keyfilePath - Optional path to keyfile for authentication
+   * @param options - Optional creation options (KDF settings, description)
+   */
+  async exportToDatabase(
+    groupId: string,
+    path: string,
+    password?: string,
+    keyfilePath?: string,
+    options?: DatabaseCreationOptions
+  ): Promise<GroupExportResult> {
+    GroupIdSchema.parse({ groupId });
+    PathOnlySchema.parse({ path });
+    const result = await invoke("export_group", {
+      groupId,
+      path,
+      password,
+      keyfilePath,
+      options,
+    });
+    return GroupExportResultSchema.parse(result);
+  },
+
+  /**
+   * Configures a group as shared group, or removes the share when
+   * `settings` is null. Containers are written on the next save.
+   */
+  async setShare(
+    groupId: string,
+    settings: ShareSettings | null
+  ): Promise<void> {
+    GroupIdSchema.parse({ groupId });
+    if (settings) {
+      ShareSettingsSchema.parse(settings);
+    }
+    await invoke("set_group_share", { groupId, settings });
+  },
+
+  async listShares(): Promise<ShareInfo[]> {
+    const result = await invoke("list_shares");
+    return z.array(ShareInfoSchema).parse(result);
+  },
+
+  /**
+   * Returns the public key that signs this database's containers, for
+   * other databases to add to a share's trusted signers.
+   */
+  async getSharePublicKey(): Promise<string> {
+    const result = await invoke("get_share_public_key");
+    return z.string().parse(result);
+  },
+
+  /**
+   * Imports changed share containers and re-exports shared groups.
+   * Containers that change on disk are imported by the backend, which
+   * emits a "shares-imported" event with the results.
+   */
+  async syncShares(): Promise<ShareSyncResult[]> {
+    const result = await invoke("sync_shares");
+    return z.array(ShareSyncResultSchema).parse(result);
+  },
+};
+
+/**
+ * Auto-type sequences resolved and parsed into key actions.
+ */
+export const autoType = {
+  /**
+   * Effective sequence for an entry, using the window association matching
+   * `windowTitle` if given. Fails if auto-type is disabled for the entry.
+   */
+  async getSequence(
+    entryId: string,
+    windowTitle?: string
+  ): Promise<AutoTypeSequence> {
+    IdSchema.parse({ id: entryId });
+    const result = await invoke("get_auto_type_sequence", {
+      entryId,
+      windowTitle,
+    });
+    return AutoTypeSequenceSchema.parse(result);
+  },
+
+  async parseSequence(sequence: string): Promise<KeyAction[]> {
+    const result = await invoke("parse_auto_type_sequence", { sequence });
+    return z.array(KeyActionSchema).parse(result);
+  },
+};
+
+/**
+ * Recycle bin contents, restore and retention.
+ */
+export const recycleBin = {
+  async list(): Promise<RecycleBinItem[]> {
+    const result = await invoke("list_recycle_bin");
+    return z.array(RecycleBinItemSchema).parse(result);
+  },
+
+  /** Restores an entry into `targetGroupId`, or where it was deleted from. */
+  async restoreEntry(id: string, targetGroupId?: string): Promise<Entry> {
+    IdSchema.parse({ id });
+    const result = await invoke(
+      "restore_entry",
+      targetGroupId ? { id, targetGroupId } : { id }
+    );
+    return EntrySchema.parse(result);
+  },
+
+  /** Restores a group into `targetGroupId`, or where it was deleted from. */
+  async restoreGroup(id: string, targetGroupId?: string): Promise<Group> {
+    IdSchema.parse({ id });
+    const result = await invoke(
+      "restore_group",
+      targetGroupId ? { id, targetGroupId } : { id }
+    );
+    return GroupSchema.parse(result);
+  },
+
+  /** Permanently deletes the bin contents; returns the number removed. */
+  async empty(): Promise<number> {
+    const result = await invoke("empty_recycle_bin");
+    return z.number().int().parse(result);
+  },
+
+  async getSettings(): Promise<RecycleBinSettings> {
+    const result = await invoke("get_recycle_bin_settings");
+    return RecycleBinSettingsSchema.parse(result);
+  },
+
+  async updateSettings(
+    settings: RecycleBinSettings
+  ): Promise<RecycleBinSettings> {
+    RecycleBinSettingsSchema.parse(settings);
+    const result = await invoke("update_recycle_bin_settings", { settings });
+    return RecycleBinSettingsSchema.parse(result);
+  },
+};
+
+/**
+ * Password health report and offline breached-password check.
+ */
+export const health = {
+  async getReport(options?: HealthReportOptions): Promise<HealthReport> {
+    if (options) HealthReportOptionsSchema.parse(options);
+    const result = await invoke("get_health_report", { options });
+    return HealthReportSchema.parse(result);
+  },
+
+  /**
+   * Checks passwords against a local HIBP SHA-1 list or binary index.
+   * Nothing is sent over the network.
+   */
+  async checkBreaches(indexPath: string): Promise<BreachReport> {
+    PathOnlySchema.parse({ path: indexPath });
+    const result = await invoke("check_breached_passwords", { indexPath });
+    return BreachReportSchema.parse(result);
+  },
+
+  /**
+   * Converts the HIBP text file into a binary index.
+   * Returns the number of hashes written.
+   */
+  async buildBreachIndex(
+    sourcePath: string,
+    indexPath: string
+  ): Promise<number> {
+    PathOnlySchema.parse({ path: sourcePath });
+    PathOnlySchema.parse({ path: indexPath });
+    const result = await invoke("build_breach_index", {
+      sourcePath,
+      indexPath,
+    });
+    return z.number().int().parse(result);
+  },
+};
+
+/**
+ * Browser extensions connected through the native messaging host.
+ */
+export const browser = {
+  async listAssociations(): Promise<BrowserAssociation[]> {
+    const result = await invoke("list_browser_associations");
+    return z.array(BrowserAssociationSchema).parse(result);
+  },
+
+  /** Revokes an extension; it has to be approved again to connect. */
+  async removeAssociation(id: string): Promise<void> {
+    AssociationIdSchema.parse({ id });
+    return invoke("remove_browser_association", { id });
+  },
+};
+
+/**
+ * Local JSON-RPC socket for scripts and other apps.
+ */
+export const ipc = {
+  async getStatus(): Promise<IpcStatus> {
+    const result = await invoke("get_ipc_status");
+    return IpcStatusSchema.parse(result);
+  },
+
+  async setEnabled(enabled: boolean): Promise<IpcStatus> {
+    const result = await invoke("set_ipc_enabled", { enabled });
+    return IpcStatusSchema.parse(result);
+  },
+
+  /**
+   * Approves a pending client, or changes the scopes of an approved one.
+   * At least one group must be allowed.
+   */
+  async approveClient(
+    clientId: string,
+    scopes: IpcScopes
+  ): Promise<IpcClient> {
+    ClientIdSchema.parse({ clientId });
+    IpcScopesSchema.parse(scopes);
+    const result = await invoke("approve_ipc_client", { clientId, scopes });
+    return IpcClientSchema.parse(result);
+  },
+
+  /** Removes a client; its token stops working immediately. */
+  async revokeClient(clientId: string): Promise<void> {
+    ClientIdSchema.parse({ clientId });
+    return invoke("revoke_ipc_client", { clientId });
+  },
+
+  /** The most recent requests, oldest first. */
+  async getAuditLog(limit?: number): Promise<IpcAuditRecord[]> {
+    const result = await invoke("get_ipc_audit_log", { limit });
+    return z.array(IpcAuditRecordSchema).parse(result);
+  },
+};
+
+/**
+ * Built-in SSH agent serving keys stored in entries.
+ */
+export const sshAgent = {
+  async getStatus(): Promise<SshAgentStatus> {
+    const result = await invoke("get_ssh_agent_status");
+    return SshAgentStatusSchema.parse(result);
+  },
+
+  /** Adds the key of an entry that allows SSH agent use. */
+  async addKey(entryId: string): Promise<SshAgentKey> {
+    IdSchema.parse({ id: entryId });
+    const result = await invoke("add_ssh_agent_key", { entryId });
+    return SshAgentKeySchema.parse(result);
+  },
+
+  async removeKey(entryId: string): Promise<void> {
+    IdSchema.parse({ id: entryId });
+    return invoke("remove_ssh_agent_key", { entryId });
+  },
+};
+
+const PinSchema = z.object({
+  pin: z.string().min(4),
+});
+
+/**
+ * Quick unlock: a short PIN unlocks the locked database until the app exits,
+ * the PIN expires or too many wrong PINs are entered.
+ */
+export const quickUnlock = {
+  async getStatus(): Promise<QuickUnlockStatus> {
+    const result = await invoke("get_quick_unlock_status");
+    return QuickUnlockStatusSchema.parse(result);
+  },
+
+  /** Sets a PIN for the open database, replacing an earlier one. */
+  async enable(pin: string, ttlSecs?: number): Promise<QuickUnlockStatus> {
+    PinSchema.parse({ pin });
+    const result = await invoke("enable_quick_unlock", { pin, ttlSecs });
+    return QuickUnlockStatusSchema.parse(result);
+  },
+
+  async disable(): Promise<void> {
+    return invoke("disable_quick_unlock");
+  },
+
+  /** Unlocks the locked database with the PIN. */
+  async unlock(pin: string): Promise<DatabaseInfo> {
+    PinSchema.parse({ pin });
+    const result = await invoke("unlock_database_with_pin", { pin });
+    return DatabaseInfoSchema.parse(result);
+  },
+};
+
+/**
+ * Freedesktop Secret Service provider (Linux system keyring).
+ */
+export const secretService = {
+  async getStatus(): Promise<SecretServiceStatus> {
+    const result = await invoke("get_secret_service_status");
+    return SecretServiceStatusSchema.parse(result);
+  },
+
+  /** Designates the group holding keyring items; `null` clears it. */
+  async setGroup(groupId: string | null): Promise<SecretServiceStatus> {
+    if (groupId) {
+      GroupIdSchema.parse({ groupId });
+    }
+    const result = await invoke("set_secret_service_group", { groupId });
+    return SecretServiceStatusSchema.parse(result);
+  },
+
+  /** Forgets an approved client; it is asked again on its next request. */
+  async revokeClient(client: string): Promise<void> {
+    z.string().min(1).parse(client);
+    return invoke("revoke_secret_service_client", { client });
   },
 };
 
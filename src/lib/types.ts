@@ -7,6 +7,7 @@ export const DatabaseInfoSchema = z.object({
   path: z.string(),
   isModified: z.boolean(),
   isLocked: z.boolean(),
+  isReadOnly: z.boolean(),
   rootGroupId: z.string(),
   version: z.string(),
 });
@@ -32,12 +33,124 @@ export const LockStatusSchema = z.discriminatedUnion("status", [
 ]);
 export type LockStatus = z.infer<typeof LockStatusSchema>;
 
+/**
+ * Error returned by every command. `code` is stable across releases; the
+ * message is for display only.
+ */
+export const AppErrorPayloadSchema = z.object({
+  code: z.string(),
+  message: z.string(),
+  details: z
+    .union([
+      z.object({ entryId: z.string() }),
+      z.object({ groupId: z.string() }),
+      z.object({ holder: LockFileInfoSchema }),
+      z.object({ retryAfterSecs: z.number().int() }),
+      z.object({ attemptsRemaining: z.number().int() }),
+      z.object({ minLength: z.number().int() }),
+    ])
+    .nullable(),
+});
+export type AppErrorPayload = z.infer<typeof AppErrorPayloadSchema>;
+
 export const CustomFieldMetaSchema = z.object({
   key: z.string(),
   isProtected: z.boolean(),
 });
 export type CustomFieldMeta = z.infer<typeof CustomFieldMetaSchema>;
 
+export const AutoTypeAssociationSchema = z.object({
+  /** Window title pattern; `*` matches any run of characters */
+  window: z.string(),
+  sequence: z.string().nullable(),
+});
+export type AutoTypeAssociation = z.infer<typeof AutoTypeAssociationSchema>;
+
+export const EntryAutoTypeSchema = z.object({
+  enabled: z.boolean(),
+  /** Inherited from the group when null */
+  defaultSequence: z.string().nullable(),
+  associations: z.array(AutoTypeAssociationSchema),
+});
+export type EntryAutoType = z.infer<typeof EntryAutoTypeSchema>;
+
+export const GroupAutoTypeSchema = z.object({
+  /** Inherited from the parent group when null */
+  enabled: z.boolean().nullable(),
+  /** Inherited from the parent group when null */
+  defaultSequence: z.string().nullable(),
+});
+export type GroupAutoType = z.infer<typeof GroupAutoTypeSchema>;
+
+export const KeyModifiersSchema = z.object({
+  shift: z.boolean(),
+  ctrl: z.boolean(),
+  alt: z.boolean(),
+  meta: z.boolean(),
+});
+export type KeyModifiers = z.infer<typeof KeyModifiersSchema>;
+
+export const KeySchema = z.union([
+  z.object({
+    name: z.enum([
+      "tab",
+      "enter",
+      "space",
+      "backspace",
+      "delete",
+      "insert",
+      "home",
+      "end",
+      "pageUp",
+      "pageDown",
+      "up",
+      "down",
+      "left",
+      "right",
+      "escape",
+      "capsLock",
+      "numLock",
+      "scrollLock",
+      "printScreen",
+      "break",
+      "help",
+      "apps",
+      "win",
+      "leftWin",
+      "rightWin",
+      "add",
+      "subtract",
+      "multiply",
+      "divide",
+    ]),
+  }),
+  z.object({ name: z.literal("numpad"), value: z.number().int() }),
+  z.object({ name: z.literal("function"), value: z.number().int() }),
+  z.object({ name: z.literal("char"), value: z.string() }),
+  z.object({ name: z.literal("virtualKey"), value: z.number().int() }),
+]);
+export type Key = z.infer<typeof KeySchema>;
+
+export const KeyActionSchema = z.discriminatedUnion("type", [
+  z.object({ type: z.literal("text"), text: z.string() }),
+  z.object({
+    type: z.literal("key"),
+    key: KeySchema,
+    modifiers: KeyModifiersSchema,
+  }),
+  z.object({ type: z.literal("placeholder"), name: z.string() }),
+  z.object({ type: z.literal("delay"), milliseconds: z.number().int() }),
+  z.object({ type: z.literal("defaultDelay"), milliseconds: z.number().int() }),
+  z.object({ type: z.literal("clearField") }),
+]);
+export type KeyAction = z.infer<typeof KeyActionSchema>;
+
+export const AutoTypeSequenceSchema = z.object({
+  sequence: z.string(),
+  actions: z.array(KeyActionSchema),
+});
+export type AutoTypeSequence = z.infer<typeof AutoTypeSequenceSchema>;
+
 export const EntrySchema = z.object({
   id: z.string(),
   groupId: z.string(),
@@ -52,15 +165,162 @@ export const EntrySchema = z.object({
   createdAt: z.string(),
   modifiedAt: z.string(),
   accessedAt: z.string(),
+  expires: z.boolean(),
+  expiryTime: z.string().nullable(),
+  /** Fields stored as `{REF:...}` references; values above are resolved */
+  referenceFields: z.array(z.string()),
+  autoType: EntryAutoTypeSchema,
 });
 export type Entry = z.infer<typeof EntrySchema>;
 
+export const ReferenceFieldSchema = z.enum([
+  "title",
+  "username",
+  "password",
+  "url",
+  "notes",
+]);
+export type ReferenceField = z.infer<typeof ReferenceFieldSchema>;
+
+export const CreateReferenceEntryDataSchema = z.object({
+  sourceEntryId: z.string().min(1),
+  fields: z.array(ReferenceFieldSchema).min(1),
+  title: z.string().min(1).optional(),
+});
+export type CreateReferenceEntryData = z.infer<
+  typeof CreateReferenceEntryDataSchema
+>;
+
+export const UrlMatchOptionsSchema = z.object({
+  matchScheme: z.boolean(),
+  matchPort: z.boolean(),
+  /** The entry URL's path must be a prefix of the site's path */
+  matchPath: z.boolean(),
+});
+export type UrlMatchOptions = z.infer<typeof UrlMatchOptionsSchema>;
+
+export const UrlMatchSchema = z.object({
+  id: z.string(),
+  groupId: z.string(),
+  title: z.string(),
+  username: z.string(),
+  matchedUrl: z.string(),
+  matchKind: z.enum(["exactHost", "domain"]),
+});
+export type UrlMatch = z.infer<typeof UrlMatchSchema>;
+
+/** A browser extension associated with the open database. */
+export const BrowserAssociationSchema = z.object({
+  id: z.string(),
+  key: z.string(),
+  createdAt: z.string().nullable(),
+});
+export type BrowserAssociation = z.infer<typeof BrowserAssociationSchema>;
+
+/** What an IPC client may access; a client without groups is denied. */
+export const IpcScopesSchema = z.object({
+  allowedGroups: z.array(z.string()),
+  readOnly: z.boolean(),
+});
+export type IpcScopes = z.infer<typeof IpcScopesSchema>;
+
+export const IpcClientSchema = z.object({
+  id: z.string(),
+  name: z.string(),
+  approved: z.boolean(),
+  scopes: IpcScopesSchema,
+  createdAt: z.string(),
+  lastUsedAt: z.string().nullable(),
+});
+export type IpcClient = z.infer<typeof IpcClientSchema>;
+
+export const IpcStatusSchema = z.object({
+  enabled: z.boolean(),
+  socketPath: z.string().nullable(),
+  clients: z.array(IpcClientSchema),
+});
+export type IpcStatus = z.infer<typeof IpcStatusSchema>;
+
+/** One request received on the IPC socket. Values are never logged. */
+export const IpcAuditRecordSchema = z.object({
+  timestamp: z.string(),
+  clientId: z.string().nullable(),
+  clientName: z.string().nullable(),
+  method: z.string(),
+  target: z.string().nullable(),
+  outcome: z.enum(["allowed", "denied", "failed"]),
+  error: z.string().nullable(),
+});
+export type IpcAuditRecord = z.infer<typeof IpcAuditRecordSchema>;
+
+/** A key the built-in SSH agent currently offers. */
+export const SshAgentKeySchema = z.object({
+  entryId: z.string(),
+  title: z.string(),
+  comment: z.string(),
+  algorithm: z.string(),
+  fingerprint: z.string(),
+  confirm: z.boolean(),
+  expiresAt: z.string().nullable(),
+});
+export type SshAgentKey = z.infer<typeof SshAgentKeySchema>;
+
+export const SshKeyLoadErrorSchema = z.object({
+  entryId: z.string(),
+  title: z.string(),
+  message: z.string(),
+});
+export type SshKeyLoadError = z.infer<typeof SshKeyLoadErrorSchema>;
+
+export const SshAgentStatusSchema = z.object({
+  socketPath: z.string().nullable(),
+  keys: z.array(SshAgentKeySchema),
+  loadErrors: z.array(SshKeyLoadErrorSchema),
+});
+export type SshAgentStatus = z.infer<typeof SshAgentStatusSchema>;
+
+export const QuickUnlockStatusSchema = z.object({
+  enabled: z.boolean(),
+  path: z.string().nullable(),
+  attemptsRemaining: z.number().int(),
+  expiresInSecs: z.number().int().nullable(),
+});
+export type QuickUnlockStatus = z.infer<typeof QuickUnlockStatusSchema>;
+
+export const SecretServiceStatusSchema = z.object({
+  active: z.boolean(),
+  groupId: z.string().nullable(),
+  groupName: z.string().nullable(),
+  approvedClients: z.array(z.string()),
+});
+export type SecretServiceStatus = z.infer<typeof SecretServiceStatusSchema>;
+
+export const ExpiringEntrySchema = z.object({
+  id: z.string(),
+  groupId: z.string(),
+  title: z.string(),
+  username: z.string(),
+  expiryTime: z.string(),
+  expired: z.boolean(),
+});
+export type ExpiringEntry = z.infer<typeof ExpiringEntrySchema>;
+
+/** Payload of the `entries-expiring` event emitted after unlocking. */
+export const ExpirySummarySchema = z.object({
+  withinDays: z.number().int(),
+  expiredCount: z.number().int(),
+  expiringCount: z.number().int(),
+  entries: z.array(ExpiringEntrySchema),
+});
+export type ExpirySummary = z.infer<typeof ExpirySummarySchema>;
+
 export interface Group {
   id: string;
   parentId?: string | undefined;
   name: string;
   icon?: string | undefined;
   children: Group[];
+  autoType: GroupAutoType;
 }
 
 export const GroupSchema: z.ZodType<Group> = z.lazy(() =>
@@ -70,9 +330,18 @@ export const GroupSchema: z.ZodType<Group> = z.lazy(() =>
     name: z.string(),
     icon: z.string().optional(),
     children: z.array(GroupSchema),
+    autoType: GroupAutoTypeSchema,
   })
 );
 
+export const UpdateGroupDataSchema = z.object({
+  name: z.string().min(1).optional(),
+  icon: z.string().optional(),
+  /** Replaces the group's auto-type settings */
+  autoType: GroupAutoTypeSchema.optional(),
+});
+export type UpdateGroupData = z.infer<typeof UpdateGroupDataSchema>;
+
 export const PasswordGeneratorOptionsSchema = z.object({
   length: z.number().int().min(1).max(128),
   uppercase: z.boolean(),
@@ -96,6 +365,8 @@ export const CreateEntryDataSchema = z.object({
   tags: z.array(z.string()).optional(),
   customFields: z.record(z.string(), z.string()).optional(),
   protectedCustomFields: z.record(z.string(), z.string()).optional(),
+  /** RFC 3339 or `YYYY-MM-DD HH:MM:SS` (UTC); enables expiry */
+  expiryTime: z.string().optional(),
 });
 export type CreateEntryData = z.infer<typeof CreateEntryDataSchema>;
 
@@ -109,9 +380,147 @@ export const UpdateEntryDataSchema = z.object({
   tags: z.array(z.string()).optional(),
   customFields: z.record(z.string(), z.string()).optional(),
   protectedCustomFields: z.record(z.string(), z.string()).optional(),
+  /** `false` turns expiry off */
+  expires: z.boolean().optional(),
+  /** Sets the expiry time and turns expiry on unless `expires` is false */
+  expiryTime: z.string().optional(),
+  /** Replaces the entry's auto-type settings */
+  autoType: EntryAutoTypeSchema.optional(),
 });
 export type UpdateEntryData = z.infer<typeof UpdateEntryDataSchema>;
 
+export const ExportFormatSchema = z.enum(["csv", "json"]);
+export type ExportFormat = z.infer<typeof ExportFormatSchema>;
+
+export const ExportFieldSchema = z.discriminatedUnion("field", [
+  z.object({ field: z.literal("title") }),
+  z.object({ field: z.literal("username") }),
+  z.object({ field: z.literal("password") }),
+  z.object({ field: z.literal("url") }),
+  z.object({ field: z.literal("notes") }),
+  z.object({ field: z.literal("tags") }),
+  z.object({ field: z.literal("createdAt") }),
+  z.object({ field: z.literal("modifiedAt") }),
+  z.object({ field: z.literal("custom"), key: z.string().min(1) }),
+]);
+export type ExportField = z.infer<typeof ExportFieldSchema>;
+
+export const ExportOptionsSchema = z.object({
+  format: ExportFormatSchema,
+  fields: z.array(ExportFieldSchema).optional(),
+  groupId: z.uuid().optional(),
+  includeGroupPath: z.boolean().optional(),
+  includeProtected: z.boolean().optional(),
+  acknowledgePlaintext: z.boolean().optional(),
+});
+export type ExportOptions = z.infer<typeof ExportOptionsSchema>;
+
+export const ExportResultSchema = z.object({
+  path: z.string(),
+  entryCount: z.number().int(),
+  includesProtected: z.boolean(),
+});
+export type ExportResult = z.infer<typeof ExportResultSchema>;
+
+export const GroupExportResultSchema = z.object({
+  path: z.string(),
+  groupCount: z.number().int(),
+  entryCount: z.number().int(),
+  attachmentCount: z.number().int(),
+  customIconCount: z.number().int(),
+});
+export type GroupExportResult = z.infer<typeof GroupExportResultSchema>;
+
+export const ShareModeSchema = z.enum(["import", "export", "synchronize"]);
+export type ShareMode = z.infer<typeof ShareModeSchema>;
+
+export const ShareSettingsSchema = z.object({
+  mode: ShareModeSchema,
+  path: z.string().min(1),
+  password: z.string().nullable().optional(),
+  keepGroups: z.boolean().optional(),
+  trustedSigners: z.array(z.string()).optional(),
+  allowUnsigned: z.boolean().optional(),
+});
+export type ShareSettings = z.infer<typeof ShareSettingsSchema>;
+
+export const ShareInfoSchema = z.object({
+  groupId: z.string(),
+  mode: ShareModeSchema,
+  path: z.string(),
+  isSigned: z.boolean(),
+  keepGroups: z.boolean(),
+  trustedSigners: z.array(z.string()),
+  allowUnsigned: z.boolean(),
+});
+export type ShareInfo = z.infer<typeof ShareInfoSchema>;
+
+export const ShareSyncResultSchema = z.object({
+  groupId: z.string(),
+  path: z.string(),
+  status: z.enum(["imported", "exported", "skipped", "failed"]),
+  signature: z.enum(["unsigned", "verified", "unverified"]).nullable(),
+  message: z.string().nullable(),
+});
+export type ShareSyncResult = z.infer<typeof ShareSyncResultSchema>;
+
+export const SaveResultSchema = z.object({
+  shareFailures: z.array(ShareSyncResultSchema),
+});
+export type SaveResult = z.infer<typeof SaveResultSchema>;
+
+export const RecycleBinItemSchema = z.object({
+  id: z.string(),
+  kind: z.enum(["entry", "group"]),
+  name: z.string(),
+  previousParentId: z.string().nullable(),
+  deletedAt: z.string().nullable(),
+  entryCount: z.number().int(),
+});
+export type RecycleBinItem = z.infer<typeof RecycleBinItemSchema>;
+
+export const RecycleBinSettingsSchema = z.object({
+  enabled: z.boolean(),
+  retentionDays: z.number().int().nonnegative().nullable(),
+});
+export type RecycleBinSettings = z.infer<typeof RecycleBinSettingsSchema>;
+
+export const BreachReportSchema = z.object({
+  checkedCount: z.number().int(),
+  breached: z.array(
+    z.object({
+      entryId: z.string(),
+      title: z.string(),
+      count: z.number().int(),
+    })
+  ),
+});
+export type BreachReport = z.infer<typeof BreachReportSchema>;
+
+export const HealthReportOptionsSchema = z.object({
+  maxPasswordAgeDays: z.number().int().nonnegative(),
+});
+export type HealthReportOptions = z.infer<typeof HealthReportOptionsSchema>;
+
+export const EntryHealthSchema = z.object({
+  entryId: z.string(),
+  groupId: z.string(),
+  title: z.string(),
+  entropy: z.number(),
+  quality: z.enum(["poor", "weak", "good", "excellent"]),
+  passwordChangedAt: z.string().nullable(),
+  reuseCount: z.number().int(),
+  issues: z.array(z.enum(["empty", "weak", "reused", "old", "expired"])),
+});
+export type EntryHealth = z.infer<typeof EntryHealthSchema>;
+
+export const HealthReportSchema = z.object({
+  entries: z.array(EntryHealthSchema),
+  reused: z.array(z.object({ entryIds: z.array(z.string()) })),
+  excludedEntryIds: z.array(z.string()),
+});
+export type HealthReport = z.infer<typeof HealthReportSchema>;
+
 export const CustomFieldValueSchema = z.object({
   key: z.string(),
   value: z.string(),
@@ -129,6 +538,22 @@ export type DatabaseCreationOptions = z.infer<
   typeof DatabaseCreationOptionsSchema
 >;
 
+export const KdbImportSourceSchema = z.object({
+  path: z.string().min(1),
+  password: z.string().optional(),
+  keyfilePath: z.string().min(1).optional(),
+});
+export type KdbImportSource = z.infer<typeof KdbImportSourceSchema>;
+
+export const KdbImportResultSchema = z.object({
+  database: DatabaseInfoSchema,
+  groupCount: z.number().int(),
+  entryCount: z.number().int(),
+  attachmentCount: z.number().int(),
+  skippedMetaStreams: z.array(z.string()),
+});
+export type KdbImportResult = z.infer<typeof KdbImportResultSchema>;
+
 export const DatabaseHeaderInfoSchema = z.object({
   version: z.string(),
   isValidKdbx: z.boolean(),
@@ -190,5 +615,9 @@ export const AppSettingsSchema = z.object({
   startMinimized: z.boolean(),
   theme: z.string(),
   recentDatabases: z.array(RecentDatabaseSchema),
+  /** Serve SSH keys on the agent socket; applied at startup */
+  sshAgentEnabled: z.boolean(),
+  /** Serve the database as the system keyring; applied at startup */
+  secretServiceEnabled: z.boolean(),
 });
 export type AppSettings = z.infer<typeof AppSettingsSchema>;